@@ -0,0 +1,764 @@
+// 命令行工具，与桌面应用使用同一个数据库和服务，供终端和脚本切换供应商、工作模式
+
+mod output;
+
+use ai_tools_core::models::config::ConfigHistory;
+use ai_tools_core::models::mcp_template::{CreateMcpTemplateRequest, McpTemplate};
+use ai_tools_core::models::mode::{WorkModeConfig, WorkModeSwitchRequest};
+use ai_tools_core::models::supplier::{CreateSupplierRequest, Supplier};
+use ai_tools_core::models::{ApiResponse, AppError, ConflictStrategy, ResultExt};
+use ai_tools_core::services::backup::{DataExport, DataImportMode};
+use ai_tools_core::services::data_dir::DataDirResolver;
+use ai_tools_core::services::platform::Platform;
+use ai_tools_core::utils::logger;
+use ai_tools_core::{t, AppCore};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use output::{optional, print_fields, print_table, yes_no, Output};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(name = "ai-tools", version, about = "AI Tools 命令行工具")]
+struct Cli {
+    /// 以 JSON 输出结果，格式与桌面应用收到的响应相同
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// 数据目录，默认与桌面应用相同
+    #[arg(long, global = true, value_name = "DIR")]
+    data_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 管理供应商
+    #[command(subcommand)]
+    Supplier(SupplierCommand),
+    /// 切换工作模式
+    #[command(subcommand)]
+    Mode(ModeCommand),
+    /// 管理MCP模板
+    #[command(subcommand)]
+    Template(TemplateCommand),
+    /// 供应商健康检查
+    #[command(subcommand)]
+    Health(HealthCommand),
+    /// 配置文件备份
+    #[command(subcommand)]
+    Backup(BackupCommand),
+    /// 导出全部数据
+    Export {
+        /// 写入的文件，默认输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 导入 export 导出的数据
+    Import {
+        file: PathBuf,
+        /// 清空现有数据后按原ID导入，默认合并
+        #[arg(long)]
+        replace: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SupplierCommand {
+    /// 列出供应商
+    List {
+        #[arg(long = "type", value_enum)]
+        supplier_type: Option<AiType>,
+    },
+    /// 添加供应商
+    Add(AddSupplierArgs),
+    /// 切换到指定供应商并写入配置文件
+    Use {
+        id: i64,
+        /// 跳过切换前的连通性检查
+        #[arg(long)]
+        force: bool,
+        /// 不备份当前配置文件
+        #[arg(long)]
+        no_backup: bool,
+    },
+    /// 测试供应商连接
+    Test { id: i64 },
+}
+
+#[derive(Args)]
+struct AddSupplierArgs {
+    #[arg(long = "type", value_enum)]
+    supplier_type: AiType,
+    #[arg(long)]
+    name: String,
+    #[arg(long)]
+    base_url: String,
+    /// 访问令牌，也可以通过环境变量传入，避免出现在进程列表中
+    #[arg(long, env = "AI_TOOLS_AUTH_TOKEN", hide_env_values = true)]
+    auth_token: String,
+    #[arg(long)]
+    timeout_ms: Option<i64>,
+    #[arg(long)]
+    opus_model: Option<String>,
+    #[arg(long)]
+    sonnet_model: Option<String>,
+    #[arg(long)]
+    haiku_model: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum ModeCommand {
+    /// 切换工作模式，未指定的供应商和模板沿用该模式上次的设置
+    Use {
+        name: String,
+        #[arg(long)]
+        claude: Option<i64>,
+        #[arg(long)]
+        codex: Option<i64>,
+        /// MCP模板ID，可以重复指定
+        #[arg(long = "template")]
+        templates: Vec<i64>,
+    },
+    /// 查看当前工作模式
+    Status,
+    /// 回到上一次切换之前的工作模式
+    Rollback,
+}
+
+#[derive(Subcommand)]
+enum TemplateCommand {
+    /// 列出MCP模板
+    List {
+        #[arg(long = "type", value_enum)]
+        ai_type: Option<AiType>,
+    },
+    /// 渲染模板并写入对应工具的用户级 MCP 配置
+    Apply {
+        id: i64,
+        /// 目标平台，默认为当前平台
+        #[arg(long)]
+        platform: Option<String>,
+        /// 参数取值，格式为 KEY=VALUE，可以重复指定
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        values: Vec<(String, String)>,
+    },
+    /// 从 JSON 文件导入模板，文件内容为模板数组或单个模板
+    Import {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = Strategy::Skip)]
+        strategy: Strategy,
+        /// 只检查不写入
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HealthCommand {
+    /// 检查供应商健康状态，有不健康的供应商时以 5 退出
+    Check {
+        /// 只检查指定供应商，默认检查全部
+        id: Option<i64>,
+    },
+}
+
+#[derive(Subcommand)]
+enum BackupCommand {
+    /// 列出配置文件备份
+    List {
+        /// 配置类型，例如 settings、codex、auth、claude_mcp
+        #[arg(long = "type")]
+        config_type: Option<String>,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// 把配置文件恢复为备份时的内容
+    Restore { id: i64 },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum AiType {
+    Claude,
+    Codex,
+}
+
+impl AiType {
+    fn as_str(self) -> &'static str {
+        match self {
+            AiType::Claude => "claude",
+            AiType::Codex => "codex",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Strategy {
+    Skip,
+    Overwrite,
+    Rename,
+    BumpVersion,
+}
+
+impl From<Strategy> for ConflictStrategy {
+    fn from(strategy: Strategy) -> Self {
+        match strategy {
+            Strategy::Skip => ConflictStrategy::Skip,
+            Strategy::Overwrite => ConflictStrategy::Overwrite,
+            Strategy::Rename => ConflictStrategy::Rename,
+            Strategy::BumpVersion => ConflictStrategy::BumpVersion,
+        }
+    }
+}
+
+fn parse_key_value(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(t!("cli.key_value_invalid", value = value)),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let output = Output { json: cli.json };
+
+    let mut resolver = DataDirResolver::from_environment();
+    resolver.cli_arg = cli.data_dir.clone();
+    let data_dir = resolver.resolve().dir;
+
+    // 日志只写入文件，标准输出只留给命令结果
+    let _log_guard = logger::init_with_console(&logger::log_dir(&data_dir), false);
+
+    // 命令行工具不退回内存数据库，打不开时直接失败
+    let core = match AppCore::open(&data_dir).await {
+        Ok(core) => core,
+        Err(e) => {
+            tracing::error!("打开数据库失败: {:?}", e);
+            let error = AppError::from(e).context(t!("cli.db_open_failed"));
+            return output.report::<()>(Err(error), |_| {});
+        }
+    };
+
+    // 数据库可能由命令行工具创建，内置模板和桌面应用启动时一样同步
+    core.prepare().await;
+
+    let context = Context { core, output };
+    context.run(cli.command).await
+}
+
+struct Context {
+    core: AppCore,
+    output: Output,
+}
+
+impl Context {
+    async fn run(&self, command: Command) -> ExitCode {
+        match command {
+            Command::Supplier(command) => self.supplier(command).await,
+            Command::Mode(command) => self.mode(command).await,
+            Command::Template(command) => self.template(command).await,
+            Command::Health(HealthCommand::Check { id }) => self.health_check(id).await,
+            Command::Backup(command) => self.backup(command).await,
+            Command::Export { output } => self.export(output).await,
+            Command::Import { file, replace } => self.import(&file, replace).await,
+        }
+    }
+
+    async fn supplier(&self, command: SupplierCommand) -> ExitCode {
+        let pool = self.core.pool();
+        match command {
+            SupplierCommand::List { supplier_type } => {
+                let suppliers = match supplier_type {
+                    Some(supplier_type) => {
+                        Supplier::get_by_type(pool, supplier_type.as_str()).await
+                    }
+                    None => Supplier::get_all(pool).await,
+                }
+                .context(t!("supplier.list_failed"))
+                .map(|suppliers| {
+                    // 输出可能出现在 CI 日志中，令牌只保留前几位
+                    ApiResponse::success(
+                        suppliers
+                            .into_iter()
+                            .map(|mut supplier| {
+                                supplier.auth_token = mask_token(&supplier.auth_token);
+                                supplier
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                });
+
+                self.output.report(suppliers, |suppliers| {
+                    print_table(
+                        &["ID", "TYPE", "NAME", "BASE_URL", "ACTIVE", "HEALTHY"],
+                        suppliers
+                            .iter()
+                            .map(|supplier| {
+                                vec![
+                                    optional(supplier.id),
+                                    supplier.r#type.clone(),
+                                    supplier.name.clone(),
+                                    supplier.base_url.clone(),
+                                    yes_no(supplier.is_active == Some(1)),
+                                    supplier
+                                        .is_healthy
+                                        .map(|healthy| yes_no(healthy == 1))
+                                        .unwrap_or_else(|| "-".to_string()),
+                                ]
+                            })
+                            .collect(),
+                    )
+                })
+            }
+            SupplierCommand::Add(args) => {
+                let request = CreateSupplierRequest {
+                    r#type: args.supplier_type.as_str().to_string(),
+                    name: args.name,
+                    base_url: args.base_url,
+                    auth_token: args.auth_token,
+                    timeout_ms: args.timeout_ms,
+                    auto_update: None,
+                    opus_model: args.opus_model,
+                    sonnet_model: args.sonnet_model,
+                    haiku_model: args.haiku_model,
+                };
+
+                let result =
+                    self.core
+                        .suppliers()
+                        .create(request)
+                        .await
+                        .map(|response| ApiResponse {
+                            data: response.data.map(|mut supplier| {
+                                supplier.auth_token = mask_token(&supplier.auth_token);
+                                supplier
+                            }),
+                            ..response
+                        });
+
+                self.output.report(result, |supplier| {
+                    println!(
+                        "{}",
+                        t!(
+                            "cli.supplier_created",
+                            name = supplier.name,
+                            id = optional(supplier.id)
+                        )
+                    )
+                })
+            }
+            SupplierCommand::Use {
+                id,
+                force,
+                no_backup,
+            } => {
+                let result = self.core.suppliers().switch_to(id, force, !no_backup).await;
+                self.output
+                    .report(result, |result| println!("{}", result.message))
+            }
+            SupplierCommand::Test { id } => {
+                let result = self.core.suppliers().test_connection(id).await;
+
+                self.output.report_check(
+                    result,
+                    |result| result.success,
+                    |result| {
+                        if result.success {
+                            println!(
+                                "{}",
+                                t!("cli.connection_ok", ms = optional(result.response_time))
+                            );
+                        } else {
+                            println!(
+                                "{}",
+                                t!(
+                                    "cli.connection_failed",
+                                    error = result
+                                        .error
+                                        .clone()
+                                        .unwrap_or_else(|| t!("common.unknown_error"))
+                                )
+                            );
+                        }
+                    },
+                )
+            }
+        }
+    }
+
+    async fn mode(&self, command: ModeCommand) -> ExitCode {
+        let service = self.core.modes();
+        match command {
+            ModeCommand::Use {
+                name,
+                claude,
+                codex,
+                templates,
+            } => {
+                let result = match WorkModeConfig::get_by_name(self.core.pool(), &name)
+                    .await
+                    .context(t!("mode.load_failed"))
+                {
+                    Ok(saved) => {
+                        let pins = saved
+                            .as_ref()
+                            .map(|config| config.get_pinned_revisions())
+                            .filter(|pins| !pins.is_empty());
+                        let saved_templates =
+                            saved.as_ref().map(|config| config.get_mcp_template_ids());
+                        let request = WorkModeSwitchRequest {
+                            target_mode: name,
+                            claude_supplier_id: claude.or_else(|| {
+                                saved
+                                    .as_ref()
+                                    .and_then(|config| config.active_claude_supplier_id)
+                            }),
+                            codex_supplier_id: codex.or_else(|| {
+                                saved
+                                    .as_ref()
+                                    .and_then(|config| config.active_codex_supplier_id)
+                            }),
+                            mcp_template_ids: if templates.is_empty() {
+                                saved_templates
+                            } else {
+                                Some(templates)
+                            },
+                            pinned_template_revisions: pins,
+                            create_backup: false,
+                        };
+                        service.switch(request).await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                self.output
+                    .report(result, |result| println!("{}", result.message))
+            }
+            ModeCommand::Status => {
+                let result = service.status().await.map(ApiResponse::success);
+                self.output.report(result, |status| {
+                    print_fields(vec![
+                        ("current_mode", status.current_mode.clone()),
+                        (
+                            "claude_supplier",
+                            optional(status.active_claude_supplier.clone()),
+                        ),
+                        (
+                            "codex_supplier",
+                            optional(status.active_codex_supplier.clone()),
+                        ),
+                        ("mcp_templates", status.active_mcp_templates.join(", ")),
+                        (
+                            "last_switch_time",
+                            optional(status.last_switch_time.map(|time| time.to_rfc3339())),
+                        ),
+                    ])
+                })
+            }
+            ModeCommand::Rollback => {
+                let result = service.rollback().await;
+                self.output
+                    .report(result, |result| println!("{}", result.message))
+            }
+        }
+    }
+
+    async fn template(&self, command: TemplateCommand) -> ExitCode {
+        let pool = self.core.pool();
+        match command {
+            TemplateCommand::List { ai_type } => {
+                let templates = McpTemplate::get_all(pool)
+                    .await
+                    .context(t!("template.load_failed"))
+                    .map(|templates| {
+                        ApiResponse::success(
+                            templates
+                                .into_iter()
+                                .filter(|template| {
+                                    ai_type
+                                        .is_none_or(|ai_type| template.ai_type == ai_type.as_str())
+                                })
+                                .collect::<Vec<_>>(),
+                        )
+                    });
+
+                self.output.report(templates, |templates| {
+                    print_table(
+                        &["ID", "TYPE", "PLATFORM", "NAME", "VERSION", "CATEGORY"],
+                        templates
+                            .iter()
+                            .map(|template| {
+                                vec![
+                                    optional(template.id),
+                                    template.ai_type.clone(),
+                                    template.platform_type.clone(),
+                                    template.name.clone(),
+                                    template.version.clone(),
+                                    optional(template.category.clone()),
+                                ]
+                            })
+                            .collect(),
+                    )
+                })
+            }
+            TemplateCommand::Apply {
+                id,
+                platform,
+                values,
+            } => {
+                let result = match self.core.secret_cipher() {
+                    Ok(cipher) => {
+                        let platform = platform.unwrap_or_else(Platform::get_platform_type);
+                        self.core
+                            .config()
+                            .apply_template(id, &cipher, &platform, values.into_iter().collect())
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+
+                self.output.report(result, |result| {
+                    println!(
+                        "{}",
+                        t!(
+                            "cli.template_applied",
+                            name = result.template_name,
+                            path = result.config_path,
+                            servers = result.server_ids.join(", ")
+                        )
+                    )
+                })
+            }
+            TemplateCommand::Import {
+                file,
+                strategy,
+                dry_run,
+            } => {
+                let result = match read_json::<TemplateFile>(&file) {
+                    Ok(templates) => {
+                        McpTemplate::import(pool, templates.into_vec(), strategy.into(), dry_run)
+                            .await
+                            .context(t!("template.import_failed"))
+                            .map(|report| report.into_response())
+                    }
+                    Err(e) => Err(e),
+                };
+
+                self.output.report(result, |report| {
+                    print_table(
+                        &["#", "NAME", "ACTION", "ID", "MESSAGE"],
+                        report
+                            .items
+                            .iter()
+                            .map(|item| {
+                                vec![
+                                    item.index.to_string(),
+                                    item.name.clone(),
+                                    format!("{:?}", item.action).to_lowercase(),
+                                    optional(item.id),
+                                    optional(item.message.clone()),
+                                ]
+                            })
+                            .collect(),
+                    )
+                })
+            }
+        }
+    }
+
+    async fn health_check(&self, id: Option<i64>) -> ExitCode {
+        let service = self.core.suppliers();
+        let result = match id {
+            Some(id) => match service.check_health(id).await {
+                Ok(Some(health)) => Ok(ApiResponse::success(vec![health])),
+                Ok(None) => Ok(AppError::not_found_with_id(t!("resource.supplier"), id).into()),
+                Err(e) => Err(e),
+            },
+            None => service.check_all_health().await.map(ApiResponse::success),
+        };
+
+        self.output.report_check(
+            result,
+            |results| results.iter().all(|health| health.is_healthy),
+            |results| {
+                print_table(
+                    &["ID", "HEALTHY", "STATUS", "RESPONSE_MS", "CIRCUIT", "ERROR"],
+                    results
+                        .iter()
+                        .map(|health| {
+                            vec![
+                                health.supplier_id.to_string(),
+                                yes_no(health.is_healthy),
+                                format!("{:?}", health.status).to_lowercase(),
+                                health.response_time.to_string(),
+                                format!("{:?}", health.circuit_state).to_lowercase(),
+                                optional(health.error_message.clone()),
+                            ]
+                        })
+                        .collect(),
+                )
+            },
+        )
+    }
+
+    async fn backup(&self, command: BackupCommand) -> ExitCode {
+        match command {
+            BackupCommand::List { config_type, limit } => {
+                let backups = match &config_type {
+                    Some(config_type) => {
+                        ConfigHistory::get_by_type(self.core.pool(), config_type, Some(limit)).await
+                    }
+                    None => ConfigHistory::get_recent(self.core.pool(), limit).await,
+                }
+                .context(t!("config.history_load_failed"))
+                .map(|backups| {
+                    // 备份内容可能包含令牌，只输出元数据
+                    ApiResponse::success(
+                        backups
+                            .into_iter()
+                            .map(|mut backup| {
+                                backup.backup_content = String::new();
+                                backup
+                            })
+                            .collect::<Vec<_>>(),
+                    )
+                });
+
+                self.output.report(backups, |backups| {
+                    print_table(
+                        &["ID", "TYPE", "OPERATION", "TIME", "PATH", "DESCRIPTION"],
+                        backups
+                            .iter()
+                            .map(|backup| {
+                                vec![
+                                    optional(backup.id),
+                                    backup.config_type.clone(),
+                                    backup.operation_type.clone(),
+                                    optional(backup.operation_time.map(|time| time.to_rfc3339())),
+                                    backup.config_path.clone(),
+                                    optional(backup.description.clone()),
+                                ]
+                            })
+                            .collect(),
+                    )
+                })
+            }
+            BackupCommand::Restore { id } => {
+                let result =
+                    self.core
+                        .config()
+                        .restore_backup(id)
+                        .await
+                        .map(|response| ApiResponse {
+                            data: response.data.map(|mut history| {
+                                history.backup_content = String::new();
+                                history
+                            }),
+                            ..response
+                        });
+
+                self.output.report(result, |history| {
+                    println!("{}", t!("cli.backup_restored", path = history.config_path))
+                })
+            }
+        }
+    }
+
+    async fn export(&self, output: Option<PathBuf>) -> ExitCode {
+        let export = match DataExport::collect(self.core.pool())
+            .await
+            .context(t!("backup.export_failed"))
+        {
+            Ok(export) => export,
+            Err(e) => return self.output.report::<()>(Err(e), |_| {}),
+        };
+        let content = match serde_json::to_string_pretty(&export) {
+            Ok(content) => content,
+            Err(e) => return self.output.report::<()>(Err(e.into()), |_| {}),
+        };
+
+        // 没有指定文件时直接输出导出内容，便于重定向后再用 import 导入
+        let Some(path) = output else {
+            println!("{}", content);
+            return ExitCode::SUCCESS;
+        };
+
+        let result = std::fs::write(&path, content)
+            .context(t!("cli.write_file_failed", path = path.display()))
+            .map(|_| ApiResponse::success(path.display().to_string()));
+        self.output.report(result, |path| {
+            println!("{}", t!("cli.exported", path = path))
+        })
+    }
+
+    async fn import(&self, file: &Path, replace: bool) -> ExitCode {
+        let mode = if replace {
+            DataImportMode::Replace
+        } else {
+            DataImportMode::Merge
+        };
+
+        let result = match read_json::<DataExport>(file) {
+            Ok(export) => match export.verify() {
+                Ok(()) => export
+                    .import(self.core.pool(), mode)
+                    .await
+                    .context(t!("backup.import_failed"))
+                    .map(ApiResponse::success),
+                Err(e) => Err(AppError::validation(e.to_string())),
+            },
+            Err(e) => Err(e),
+        };
+
+        self.output.report(result, |result| {
+            print_table(
+                &["TABLE", "IMPORTED", "SKIPPED"],
+                result
+                    .tables
+                    .iter()
+                    .map(|(table, count)| {
+                        vec![
+                            table.clone(),
+                            count.imported.to_string(),
+                            count.skipped.to_string(),
+                        ]
+                    })
+                    .collect(),
+            )
+        })
+    }
+}
+
+/// 模板导入文件可以是模板数组，也可以是单个模板
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum TemplateFile {
+    Many(Vec<CreateMcpTemplateRequest>),
+    One(Box<CreateMcpTemplateRequest>),
+}
+
+impl TemplateFile {
+    fn into_vec(self) -> Vec<CreateMcpTemplateRequest> {
+        match self {
+            TemplateFile::Many(templates) => templates,
+            TemplateFile::One(template) => vec![*template],
+        }
+    }
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, AppError> {
+    let content =
+        std::fs::read_to_string(path).context(t!("cli.read_file_failed", path = path.display()))?;
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::validation(t!("cli.invalid_json", path = path.display(), error = e)))
+}
+
+/// 只保留令牌前四位
+fn mask_token(token: &str) -> String {
+    let prefix: String = token.chars().take(4).collect();
+    format!("{}****", prefix)
+}
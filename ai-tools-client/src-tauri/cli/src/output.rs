@@ -0,0 +1,237 @@
+// 命令结果的输出和退出码
+
+use ai_tools_core::models::{ApiResponse, AppError, ErrorCode};
+use serde::Serialize;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+/// 退出码，脚本按退出码判断失败原因。参数错误由 clap 以 2 退出
+const EXIT_SUCCESS: u8 = 0;
+pub const EXIT_FAILURE: u8 = 1;
+pub const EXIT_NOT_FOUND: u8 = 3;
+/// 验证失败、与已有数据冲突或当前状态不允许该操作
+pub const EXIT_REJECTED: u8 = 4;
+/// 连接测试或健康检查没有通过
+pub const EXIT_UNHEALTHY: u8 = 5;
+
+fn exit_code(code: Option<ErrorCode>) -> u8 {
+    match code {
+        Some(ErrorCode::NotFound) => EXIT_NOT_FOUND,
+        Some(ErrorCode::ValidationFailed | ErrorCode::Conflict | ErrorCode::PreconditionFailed) => {
+            EXIT_REJECTED
+        }
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// 按 --json 选择输出方式。JSON 输出与桌面应用收到的 ApiResponse 相同
+#[derive(Clone, Copy)]
+pub struct Output {
+    pub json: bool,
+}
+
+impl Output {
+    /// 输出命令结果，失败时按错误码退出
+    pub fn report<T: Serialize>(
+        self,
+        result: Result<ApiResponse<T>, AppError>,
+        print: impl FnOnce(&T),
+    ) -> ExitCode {
+        self.report_check(result, |_| true, print)
+    }
+
+    /// 同 report，passed 返回 false 时以 EXIT_UNHEALTHY 退出
+    pub fn report_check<T: Serialize>(
+        self,
+        result: Result<ApiResponse<T>, AppError>,
+        passed: impl FnOnce(&T) -> bool,
+        print: impl FnOnce(&T),
+    ) -> ExitCode {
+        let code = self.write_check(&mut io::stdout(), &mut io::stderr(), result, passed, print);
+        ExitCode::from(code)
+    }
+
+    /// report_check 的实现，JSON 和消息写入 out/err，返回退出码。print 输出的表格等内容直接写到标准输出
+    fn write_check<T: Serialize>(
+        self,
+        out: &mut impl Write,
+        err: &mut impl Write,
+        result: Result<ApiResponse<T>, AppError>,
+        passed: impl FnOnce(&T) -> bool,
+        print: impl FnOnce(&T),
+    ) -> u8 {
+        let response = result.unwrap_or_else(ApiResponse::error);
+
+        if self.json {
+            match serde_json::to_string_pretty(&response) {
+                Ok(json) => {
+                    let _ = writeln!(out, "{}", json);
+                }
+                Err(e) => {
+                    let _ = writeln!(err, "{}", e);
+                    return EXIT_FAILURE;
+                }
+            }
+        }
+
+        match &response.data {
+            Some(data) if response.success => {
+                let passed = passed(data);
+                if !self.json {
+                    print(data);
+                    // 成功时附带的说明，例如导入汇总
+                    if let Some(message) = &response.message {
+                        let _ = writeln!(out, "{}", message);
+                    }
+                }
+                if passed {
+                    EXIT_SUCCESS
+                } else {
+                    EXIT_UNHEALTHY
+                }
+            }
+            data => {
+                if !self.json {
+                    // 失败时附带的数据（例如导入的逐项结果）也要输出
+                    if let Some(data) = data {
+                        print(data);
+                    }
+                    if let Some(message) = &response.message {
+                        let _ = writeln!(err, "{}", message);
+                    }
+                }
+                exit_code(response.code)
+            }
+        }
+    }
+}
+
+/// 按列对齐输出表格，中日韩字符按两列宽计算
+pub fn print_table(headers: &[&str], rows: Vec<Vec<String>>) {
+    let mut widths: Vec<usize> = headers.iter().map(|header| display_width(header)).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(display_width(cell));
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - display_width(cell))))
+            .collect::<Vec<_>>()
+            .join("  ");
+        line.trim_end().to_string()
+    };
+
+    println!("{}", format_row(headers.to_vec()));
+    for row in &rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
+/// 输出键值对，用于单条记录
+pub fn print_fields(fields: Vec<(&str, String)>) {
+    let width = fields
+        .iter()
+        .map(|(key, _)| display_width(key))
+        .max()
+        .unwrap_or(0);
+    for (key, value) in fields {
+        println!(
+            "{}{}  {}",
+            key,
+            " ".repeat(width - display_width(key)),
+            value
+        );
+    }
+}
+
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if c.len_utf8() > 1 { 2 } else { 1 })
+        .sum()
+}
+
+pub fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+pub fn optional<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai_tools_core::models::supplier::ConnectionTestResult;
+    use serde_json::Value;
+
+    #[test]
+    fn test_exit_code_for_each_error_code() {
+        let cases = [
+            (None, 1),
+            (Some(ErrorCode::NotFound), 3),
+            (Some(ErrorCode::ValidationFailed), 4),
+            (Some(ErrorCode::Conflict), 4),
+            (Some(ErrorCode::PreconditionFailed), 4),
+            (Some(ErrorCode::DbBusy), 1),
+            (Some(ErrorCode::DbError), 1),
+            (Some(ErrorCode::IoError), 1),
+            (Some(ErrorCode::Internal), 1),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(exit_code(code), expected, "{:?}", code);
+        }
+    }
+
+    fn connection(success: bool) -> ConnectionTestResult {
+        ConnectionTestResult {
+            success,
+            response_time: Some(120),
+            error: (!success).then(|| "timeout".to_string()),
+            status_code: success.then_some(200),
+            auth_error: false,
+        }
+    }
+
+    /// 以 --json 输出时返回退出码和解析后的输出
+    fn check_json(result: Result<ApiResponse<ConnectionTestResult>, AppError>) -> (u8, Value) {
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        let code = Output { json: true }.write_check(
+            &mut out,
+            &mut err,
+            result,
+            |result| result.success,
+            |_| panic!("--json 时不输出表格"),
+        );
+        assert!(err.is_empty());
+        (code, serde_json::from_slice(&out).unwrap())
+    }
+
+    #[test]
+    fn test_report_check_json_healthy_and_unhealthy() {
+        let (code, json) = check_json(Ok(ApiResponse::success(connection(true))));
+        assert_eq!(code, EXIT_SUCCESS);
+        assert_eq!(json["success"], true);
+        assert_eq!(json["data"]["success"], true);
+        assert_eq!(json["data"]["response_time"], 120);
+        assert!(json.get("code").is_none());
+
+        // 检查没有通过时响应本身仍是成功的，只有退出码不同
+        let (code, json) = check_json(Ok(ApiResponse::success(connection(false))));
+        assert_eq!(code, EXIT_UNHEALTHY);
+        assert_eq!(json["success"], true);
+        assert_eq!(json["data"]["success"], false);
+        assert_eq!(json["data"]["error"], "timeout");
+
+        let (code, json) = check_json(Ok(AppError::not_found("supplier").into()));
+        assert_eq!(code, EXIT_NOT_FOUND);
+        assert_eq!(json["success"], false);
+        assert_eq!(json["code"], "NOT_FOUND");
+        assert!(json["data"].is_null());
+    }
+}
@@ -0,0 +1,139 @@
+use crate::models::{AppError, ResultExt};
+use crate::services::builtin_templates::BuiltinTemplates;
+use crate::services::config_service::ConfigService;
+use crate::services::config_writer::ConfigPaths;
+use crate::services::data_dir::{build_sqlite_url, DB_FILE_NAME};
+use crate::services::database::Database;
+use crate::services::i18n::{set_current_locale, Locale};
+use crate::services::mode_service::WorkModeService;
+use crate::services::secret_store::SecretCipher;
+use crate::services::supplier_service::SupplierService;
+use crate::services::template_service::TemplateService;
+use crate::t;
+use crate::utils::logger;
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+/// 应用核心：数据库连接池、数据目录和配置文件位置。桌面应用的命令和命令行工具都从这里
+/// 取得服务，服务只依赖连接池和文件系统，可以在没有 Tauri 的情况下使用和测试
+#[derive(Clone)]
+pub struct AppCore {
+    pool: SqlitePool,
+    data_dir: PathBuf,
+    config_paths: Option<ConfigPaths>,
+    /// 是否有正在进行的工作模式切换
+    mode_switching: Arc<AtomicBool>,
+}
+
+impl AppCore {
+    pub fn new(pool: SqlitePool, data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            pool,
+            data_dir: data_dir.into(),
+            config_paths: None,
+            mode_switching: Arc::default(),
+        }
+    }
+
+    /// 打开数据目录中的数据库，不存在时创建并执行迁移
+    pub async fn open(data_dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(data_dir)?;
+        let database = Database::new(&build_sqlite_url(&data_dir.join(DB_FILE_NAME))).await?;
+        Ok(Self::new(database.pool, data_dir))
+    }
+
+    /// 使用指定的 Claude/Codex 配置文件位置，默认使用当前用户主目录下的配置
+    pub fn with_config_paths(mut self, paths: ConfigPaths) -> Self {
+        self.config_paths = Some(paths);
+        self
+    }
+
+    /// 启动时的准备：读取保存的语言和日志级别，同步内置MCP模板。失败只记录日志
+    pub async fn prepare(&self) {
+        match Locale::load(&self.pool).await {
+            Ok(locale) => set_current_locale(locale),
+            Err(e) => tracing::warn!("读取语言设置失败，使用默认语言: {:?}", e),
+        }
+
+        // 环境变量没有指定日志级别时使用保存的级别
+        if logger::env_override().is_none() {
+            match logger::LogLevel::load(&self.pool).await {
+                Ok(level) => {
+                    if let Err(e) = logger::set_level(level) {
+                        tracing::warn!("{:#}", e);
+                    }
+                }
+                Err(e) => tracing::warn!("读取日志级别失败，使用默认级别: {:?}", e),
+            }
+        }
+
+        match BuiltinTemplates::initialize_builtin_templates(&self.pool).await {
+            Ok(summary) => {
+                tracing::info!(
+                    "内置模板同步完成: 新增 {}，升级 {}，未变化 {}",
+                    summary.created,
+                    summary.upgraded,
+                    summary.unchanged
+                );
+                if !summary.skipped.is_empty() {
+                    tracing::warn!(
+                        "以下内置模板与用户模板冲突，已跳过: {}",
+                        summary.skipped.join(", ")
+                    );
+                }
+            }
+            Err(e) => tracing::error!("内置模板同步失败: {:?}", e),
+        }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    /// 模板密钥参数的加解密，密钥文件保存在数据目录中
+    pub fn secret_cipher(&self) -> Result<SecretCipher, AppError> {
+        SecretCipher::for_data_dir(&self.data_dir).context(t!("template.secret_key_load_failed"))
+    }
+
+    pub fn suppliers(&self) -> SupplierService {
+        let service = SupplierService::new(self.pool.clone());
+        match &self.config_paths {
+            Some(paths) => service.with_config_paths(paths.clone()),
+            None => service,
+        }
+    }
+
+    pub fn modes(&self) -> WorkModeService {
+        let service = WorkModeService::new(
+            self.pool.clone(),
+            self.data_dir.clone(),
+            self.mode_switching.clone(),
+        );
+        match &self.config_paths {
+            Some(paths) => service.with_config_paths(paths.clone()),
+            None => service,
+        }
+    }
+
+    pub fn config(&self) -> ConfigService {
+        let service = ConfigService::new(self.pool.clone());
+        match &self.config_paths {
+            Some(paths) => service.with_config_paths(paths.clone()),
+            None => service,
+        }
+    }
+
+    pub fn templates(&self) -> TemplateService {
+        let service = TemplateService::new(self.pool.clone(), self.data_dir.clone());
+        match &self.config_paths {
+            Some(paths) => service.with_config_paths(paths.clone()),
+            None => service,
+        }
+    }
+}
@@ -0,0 +1,7 @@
+// 应用核心：模型、服务、配置文件写入和健康检查，不依赖 Tauri，桌面应用和命令行工具共用
+pub mod app;
+pub mod models;
+pub mod services;
+pub mod utils;
+
+pub use app::AppCore;
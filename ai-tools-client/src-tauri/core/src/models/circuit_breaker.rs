@@ -0,0 +1,510 @@
+// 供应商熔断器相关模型
+
+use crate::models::error::AppError;
+use crate::models::supplier::FailoverConfig;
+use crate::t;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+
+/// 熔断器状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// 正常，探测照常进行
+    #[default]
+    Closed,
+    /// 熔断中，冷却结束前跳过该供应商
+    Open,
+    /// 冷却结束，允许有限次数的试探
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "closed",
+            CircuitState::Open => "open",
+            CircuitState::HalfOpen => "half_open",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "open" => CircuitState::Open,
+            "half_open" => CircuitState::HalfOpen,
+            _ => CircuitState::Closed,
+        }
+    }
+}
+
+/// 熔断器参数，连续失败阈值使用 FailoverConfig.max_consecutive_failures
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CircuitBreakerSettings {
+    /// 熔断后的冷却时间（秒）
+    pub open_duration_seconds: u32,
+    /// 半开状态下允许的试探次数
+    pub half_open_max_probes: u32,
+    /// 半开状态下恢复所需的成功次数
+    pub half_open_success_threshold: u32,
+}
+
+impl Default for CircuitBreakerSettings {
+    fn default() -> Self {
+        Self {
+            open_duration_seconds: 60,
+            half_open_max_probes: 1,
+            half_open_success_threshold: 1,
+        }
+    }
+}
+
+impl CircuitBreakerSettings {
+    /// 验证熔断器参数
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.half_open_max_probes == 0 {
+            return Err(AppError::validation(t!("circuit.half_open_probes_invalid")));
+        }
+
+        if self.half_open_success_threshold == 0
+            || self.half_open_success_threshold > self.half_open_max_probes
+        {
+            return Err(AppError::validation(t!(
+                "circuit.half_open_threshold_invalid"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CircuitBreaker {
+    pub supplier_id: i64,
+    pub state: String, // "closed", "open" or "half_open"
+    pub consecutive_failures: i64,
+    pub half_open_probes: i64,
+    pub half_open_successes: i64,
+    pub opened_at: Option<DateTime<Utc>>,
+    /// 最近一次发放半开试探许可的时间
+    pub probe_acquired_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 熔断器状态变化事件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CircuitStateChange {
+    pub supplier_id: i64,
+    pub supplier_name: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub consecutive_failures: i64,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// 前端监听的熔断器状态变化事件名
+pub const CIRCUIT_STATE_EVENT: &str = "supplier-circuit-state-changed";
+
+impl CircuitBreaker {
+    pub fn new(supplier_id: i64) -> Self {
+        Self {
+            supplier_id,
+            state: CircuitState::Closed.as_str().to_string(),
+            consecutive_failures: 0,
+            half_open_probes: 0,
+            half_open_successes: 0,
+            opened_at: None,
+            probe_acquired_at: None,
+            updated_at: None,
+        }
+    }
+
+    pub fn circuit_state(&self) -> CircuitState {
+        CircuitState::parse(&self.state)
+    }
+
+    /// 熔断中且冷却尚未结束，此时应跳过该供应商
+    pub fn is_blocking(&self, config: &FailoverConfig, now: DateTime<Utc>) -> bool {
+        self.circuit_state() == CircuitState::Open && !self.cooldown_elapsed(config, now)
+    }
+
+    /// 申请一次探测许可。
+    ///
+    /// 熔断冷却结束后转为半开；半开状态下超过试探次数的申请会被拒绝。
+    /// 许可的有效期与冷却时间相同，试探次数用完后如果超过有效期仍没有记录结果
+    /// （例如探测过程中进程退出），视为许可已丢失，再发放一次。
+    pub fn try_acquire(&mut self, config: &FailoverConfig, now: DateTime<Utc>) -> bool {
+        match self.circuit_state() {
+            CircuitState::Closed => true,
+            CircuitState::Open => {
+                if !self.cooldown_elapsed(config, now) {
+                    return false;
+                }
+                self.set_state(CircuitState::HalfOpen);
+                self.half_open_probes = 1;
+                self.probe_acquired_at = Some(now);
+                true
+            }
+            CircuitState::HalfOpen => {
+                let max_probes = config.circuit_breaker.half_open_max_probes as i64;
+                if self.half_open_probes >= max_probes {
+                    if !self.probe_lease_expired(config, now) {
+                        return false;
+                    }
+                } else {
+                    self.half_open_probes += 1;
+                }
+                self.probe_acquired_at = Some(now);
+                true
+            }
+        }
+    }
+
+    /// 记录一次成功的探测
+    pub fn record_success(&mut self, config: &FailoverConfig) {
+        self.consecutive_failures = 0;
+
+        if self.circuit_state() == CircuitState::HalfOpen {
+            self.half_open_successes += 1;
+            let threshold = config.circuit_breaker.half_open_success_threshold as i64;
+            if self.half_open_successes >= threshold {
+                self.set_state(CircuitState::Closed);
+            }
+        }
+    }
+
+    /// 记录一次失败的探测
+    pub fn record_failure(&mut self, config: &FailoverConfig, now: DateTime<Utc>) {
+        self.consecutive_failures += 1;
+
+        let should_open = match self.circuit_state() {
+            CircuitState::Closed => {
+                self.consecutive_failures >= config.max_consecutive_failures.max(1) as i64
+            }
+            // 半开状态下任何失败都重新熔断
+            CircuitState::HalfOpen => true,
+            CircuitState::Open => false,
+        };
+
+        if should_open {
+            self.set_state(CircuitState::Open);
+            self.opened_at = Some(now);
+        }
+    }
+
+    fn cooldown_elapsed(&self, config: &FailoverConfig, now: DateTime<Utc>) -> bool {
+        let cooldown = Duration::seconds(config.circuit_breaker.open_duration_seconds as i64);
+        match self.opened_at {
+            Some(opened_at) => now - opened_at >= cooldown,
+            None => true,
+        }
+    }
+
+    fn probe_lease_expired(&self, config: &FailoverConfig, now: DateTime<Utc>) -> bool {
+        let lease = Duration::seconds(config.circuit_breaker.open_duration_seconds as i64);
+        match self.probe_acquired_at {
+            Some(acquired_at) => now - acquired_at >= lease,
+            None => true,
+        }
+    }
+
+    fn set_state(&mut self, state: CircuitState) {
+        self.state = state.as_str().to_string();
+        self.half_open_probes = 0;
+        self.half_open_successes = 0;
+        self.probe_acquired_at = None;
+        if state == CircuitState::Closed {
+            self.opened_at = None;
+        }
+    }
+
+    /// 获取供应商的熔断器，没有记录时返回关闭状态
+    pub async fn get(pool: &SqlitePool, supplier_id: i64) -> Result<Self, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        Self::fetch(&mut conn, supplier_id).await
+    }
+
+    async fn fetch(conn: &mut SqliteConnection, supplier_id: i64) -> Result<Self, sqlx::Error> {
+        let breaker = sqlx::query_as::<_, CircuitBreaker>(
+            "SELECT * FROM circuit_breakers WHERE supplier_id = ?",
+        )
+        .bind(supplier_id)
+        .fetch_optional(conn)
+        .await?;
+
+        Ok(breaker.unwrap_or_else(|| Self::new(supplier_id)))
+    }
+
+    /// 读取、修改并保存熔断器，整个过程在一个 BEGIN IMMEDIATE 事务中进行。
+    /// 写锁在读取前取得，同一供应商的并发检查（包括其他进程）依次修改，计数和半开试探次数
+    /// 不会被后保存的一方覆盖。返回修改前的状态、修改后的熔断器和 apply 的结果
+    pub async fn update<R>(
+        pool: &SqlitePool,
+        supplier_id: i64,
+        apply: impl FnOnce(&mut Self) -> R,
+    ) -> Result<(CircuitState, Self, R), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+
+        let result = async {
+            let mut breaker = Self::fetch(&mut conn, supplier_id).await?;
+            let previous_state = breaker.circuit_state();
+            let output = apply(&mut breaker);
+            breaker.write(&mut conn).await?;
+            Ok((previous_state, breaker, output))
+        }
+        .await;
+
+        let finish = if result.is_ok() { "COMMIT" } else { "ROLLBACK" };
+        sqlx::query(finish).execute(&mut *conn).await?;
+        result
+    }
+
+    /// 获取某类供应商的全部熔断器记录
+    pub async fn get_by_type(
+        pool: &SqlitePool,
+        supplier_type: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, CircuitBreaker>(
+            r#"
+            SELECT cb.* FROM circuit_breakers cb
+            JOIN suppliers s ON s.id = cb.supplier_id
+            WHERE s.type = ?
+            "#,
+        )
+        .bind(supplier_type)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 保存熔断器状态
+    pub async fn save(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        self.write(&mut conn).await
+    }
+
+    async fn write(&self, conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO circuit_breakers (
+                supplier_id, state, consecutive_failures, half_open_probes,
+                half_open_successes, opened_at, probe_acquired_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(supplier_id) DO UPDATE SET
+                state = excluded.state,
+                consecutive_failures = excluded.consecutive_failures,
+                half_open_probes = excluded.half_open_probes,
+                half_open_successes = excluded.half_open_successes,
+                opened_at = excluded.opened_at,
+                probe_acquired_at = excluded.probe_acquired_at,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(self.supplier_id)
+        .bind(&self.state)
+        .bind(self.consecutive_failures)
+        .bind(self.half_open_probes)
+        .bind(self.half_open_successes)
+        .bind(self.opened_at)
+        .bind(self.probe_acquired_at)
+        .bind(Utc::now())
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::supplier::{CreateSupplierRequest, Supplier};
+    use crate::services::database::Database;
+    use tempfile::{tempdir, TempDir};
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn create_test_pool() -> TestDb {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+
+        TestDb {
+            _dir: temp_dir,
+            pool,
+        }
+    }
+
+    fn config() -> FailoverConfig {
+        FailoverConfig {
+            max_consecutive_failures: 2,
+            circuit_breaker: CircuitBreakerSettings {
+                open_duration_seconds: 30,
+                half_open_max_probes: 2,
+                half_open_success_threshold: 2,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_opens_after_threshold_and_blocks_until_cooldown() {
+        let config = config();
+        let now = Utc::now();
+        let mut breaker = CircuitBreaker::new(1);
+
+        breaker.record_failure(&config, now);
+        assert_eq!(breaker.circuit_state(), CircuitState::Closed);
+        breaker.record_failure(&config, now);
+        assert_eq!(breaker.circuit_state(), CircuitState::Open);
+
+        assert!(breaker.is_blocking(&config, now + Duration::seconds(10)));
+        assert!(!breaker.try_acquire(&config, now + Duration::seconds(10)));
+
+        let later = now + Duration::seconds(31);
+        assert!(breaker.try_acquire(&config, later));
+        assert_eq!(breaker.circuit_state(), CircuitState::HalfOpen);
+    }
+
+    #[test]
+    fn test_half_open_limits_probes_and_recovers() {
+        let config = config();
+        let now = Utc::now();
+        let mut breaker = CircuitBreaker::new(1);
+        breaker.record_failure(&config, now);
+        breaker.record_failure(&config, now);
+
+        let later = now + Duration::seconds(31);
+        assert!(breaker.try_acquire(&config, later));
+        breaker.record_success(&config);
+        assert!(breaker.try_acquire(&config, later));
+        // 试探次数已用完
+        assert!(!breaker.try_acquire(&config, later));
+
+        breaker.record_success(&config);
+        assert_eq!(breaker.circuit_state(), CircuitState::Closed);
+        assert!(breaker.try_acquire(&config, later));
+    }
+
+    #[test]
+    fn test_half_open_failure_reopens() {
+        let config = config();
+        let now = Utc::now();
+        let mut breaker = CircuitBreaker::new(1);
+        breaker.record_failure(&config, now);
+        breaker.record_failure(&config, now);
+
+        let later = now + Duration::seconds(31);
+        assert!(breaker.try_acquire(&config, later));
+        breaker.record_failure(&config, later);
+
+        assert_eq!(breaker.circuit_state(), CircuitState::Open);
+        assert_eq!(breaker.opened_at, Some(later));
+        assert!(breaker.is_blocking(&config, later + Duration::seconds(1)));
+    }
+
+    #[test]
+    fn test_unreported_probe_permit_expires() {
+        let config = config();
+        let now = Utc::now();
+        let mut breaker = CircuitBreaker::new(1);
+        breaker.record_failure(&config, now);
+        breaker.record_failure(&config, now);
+
+        // 两次许可都发出去了，但结果一直没有记录
+        let later = now + Duration::seconds(31);
+        assert!(breaker.try_acquire(&config, later));
+        assert!(breaker.try_acquire(&config, later + Duration::seconds(1)));
+        assert!(!breaker.try_acquire(&config, later + Duration::seconds(30)));
+
+        // 最近一次许可过期后重新发放，新的许可同样有有效期
+        let expired = later + Duration::seconds(31);
+        assert!(breaker.try_acquire(&config, expired));
+        assert_eq!(breaker.circuit_state(), CircuitState::HalfOpen);
+        assert_eq!(breaker.half_open_probes, 2);
+        assert!(!breaker.try_acquire(&config, expired + Duration::seconds(1)));
+
+        breaker.record_success(&config);
+        breaker.record_success(&config);
+        assert_eq!(breaker.circuit_state(), CircuitState::Closed);
+        assert_eq!(breaker.probe_acquired_at, None);
+    }
+
+    #[tokio::test]
+    async fn test_state_is_persisted() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let supplier = Supplier::create(
+            pool,
+            CreateSupplierRequest {
+                r#type: "claude".to_string(),
+                name: "flaky".to_string(),
+                base_url: "https://api.example.com".to_string(),
+                auth_token: "token".to_string(),
+                timeout_ms: None,
+                auto_update: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+            },
+        )
+        .await
+        .unwrap();
+        let supplier_id = supplier.id.unwrap();
+
+        let config = config();
+        let mut breaker = CircuitBreaker::get(pool, supplier_id).await.unwrap();
+        assert_eq!(breaker.circuit_state(), CircuitState::Closed);
+
+        breaker.record_failure(&config, Utc::now());
+        breaker.record_failure(&config, Utc::now());
+        breaker.save(pool).await.unwrap();
+
+        let loaded = CircuitBreaker::get(pool, supplier_id).await.unwrap();
+        assert_eq!(loaded.circuit_state(), CircuitState::Open);
+        assert_eq!(loaded.consecutive_failures, 2);
+        assert!(loaded.opened_at.is_some());
+
+        let by_type = CircuitBreaker::get_by_type(pool, "claude").await.unwrap();
+        assert_eq!(by_type.len(), 1);
+
+        // 申请到许可后没有记录结果，保存的发放时间让许可在冷却时间后过期
+        let acquired_at = Utc::now() + Duration::seconds(31);
+        let (_, _, acquired) = CircuitBreaker::update(pool, supplier_id, |breaker| {
+            breaker.try_acquire(&config, acquired_at)
+        })
+        .await
+        .unwrap();
+        assert!(acquired);
+        let (_, _, acquired) = CircuitBreaker::update(pool, supplier_id, |breaker| {
+            breaker.try_acquire(&config, acquired_at)
+        })
+        .await
+        .unwrap();
+        assert!(acquired);
+
+        let loaded = CircuitBreaker::get(pool, supplier_id).await.unwrap();
+        assert_eq!(loaded.circuit_state(), CircuitState::HalfOpen);
+        assert_eq!(loaded.probe_acquired_at, Some(acquired_at));
+        let (_, _, acquired) = CircuitBreaker::update(pool, supplier_id, |breaker| {
+            breaker.try_acquire(&config, acquired_at + Duration::seconds(10))
+        })
+        .await
+        .unwrap();
+        assert!(!acquired);
+        let (_, _, acquired) = CircuitBreaker::update(pool, supplier_id, |breaker| {
+            breaker.try_acquire(&config, acquired_at + Duration::seconds(30))
+        })
+        .await
+        .unwrap();
+        assert!(acquired);
+    }
+}
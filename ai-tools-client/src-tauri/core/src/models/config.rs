@@ -1,3 +1,6 @@
+use crate::models::error::AppError;
+use crate::models::returned_row;
+use crate::t;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::{FromRow, SqlitePool};
@@ -61,8 +64,9 @@ impl ConfigHistory {
         .bind(backup_content)
         .bind(operation_type)
         .bind(description)
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await
+        .and_then(returned_row)
     }
 
     /// 获取配置历史列表
@@ -87,6 +91,16 @@ impl ConfigHistory {
         query.fetch_all(pool).await
     }
 
+    /// 获取所有类型中最近的配置历史
+    pub async fn get_recent(pool: &SqlitePool, limit: i64) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, ConfigHistory>(
+            "SELECT * FROM config_history ORDER BY operation_time DESC, id DESC LIMIT ?",
+        )
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+    }
+
     /// 获取最近的配置历史
     pub async fn get_latest(
         pool: &SqlitePool,
@@ -192,19 +206,17 @@ impl WorkModeConfig {
         .bind(&templates_json)
         .bind(now)
         .bind(now)
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await
+        .and_then(returned_row)
     }
 
     /// 获取MCP模板ID列表
     pub fn get_mcp_template_ids(&self) -> Vec<i64> {
-        match &self.mcp_template_ids {
-            Some(json) => match serde_json::from_str::<Vec<i64>>(json) {
-                Ok(ids) => ids,
-                Err(_) => Vec::new(),
-            },
-            None => Vec::new(),
-        }
+        self.mcp_template_ids
+            .as_deref()
+            .and_then(|json| serde_json::from_str::<Vec<i64>>(json).ok())
+            .unwrap_or_default()
     }
 
     /// 设置MCP模板ID列表
@@ -216,10 +228,10 @@ impl WorkModeConfig {
     }
 
     /// 验证工作模式配置
-    pub fn validate(&self) -> Result<(), String> {
+    pub fn validate(&self) -> Result<(), AppError> {
         match self.mode_name.as_str() {
             "claude_only" | "codex_only" | "claude_codex" => Ok(()),
-            _ => Err("无效的工作模式名称".to_string()),
+            _ => Err(AppError::validation(t!("mode.name_invalid"))),
         }
     }
 }
@@ -250,8 +262,9 @@ impl AppState {
         .bind(key)
         .bind(value)
         .bind(now)
-        .fetch_one(pool)
+        .fetch_all(pool)
         .await
+        .and_then(returned_row)
     }
 
     /// 删除应用状态
@@ -334,24 +347,22 @@ mod tests {
         let pool = &test_db.pool;
 
         // 测试设置和获取应用状态
-        let state = AppState::set(&pool, "test_key", "test_value")
-            .await
-            .unwrap();
+        let state = AppState::set(pool, "test_key", "test_value").await.unwrap();
         assert_eq!(state.key, "test_key");
         assert_eq!(state.value, "test_value");
 
-        let retrieved = AppState::get(&pool, "test_key").await.unwrap();
+        let retrieved = AppState::get(pool, "test_key").await.unwrap();
         assert!(retrieved.is_some());
         assert_eq!(retrieved.unwrap().value, "test_value");
 
         // 测试当前工作模式
-        let mode = AppState::get_current_mode(&pool).await.unwrap();
+        let mode = AppState::get_current_mode(pool).await.unwrap();
         assert_eq!(mode, "claude_only");
 
-        AppState::set_current_mode(&pool, "codex_only")
+        AppState::set_current_mode(pool, "codex_only")
             .await
             .unwrap();
-        let updated_mode = AppState::get_current_mode(&pool).await.unwrap();
+        let updated_mode = AppState::get_current_mode(pool).await.unwrap();
         assert_eq!(updated_mode, "codex_only");
     }
 
@@ -377,7 +388,7 @@ mod tests {
             mcp_template_ids: Some(vec![1, 2, 3]),
         };
 
-        let config = WorkModeConfig::update(&pool, request).await.unwrap();
+        let config = WorkModeConfig::update(pool, request).await.unwrap();
         assert_eq!(config.mode_name, "claude_only");
         assert_eq!(config.active_claude_supplier_id, Some(1));
         assert_eq!(config.active_codex_supplier_id, None);
@@ -389,13 +400,59 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[tokio::test]
+    async fn test_returning_writes_are_visible_to_other_connections() {
+        let test_db = create_test_pool().await;
+        let db_url = format!(
+            "sqlite://{}",
+            test_db._dir.path().join("test.db").to_string_lossy()
+        );
+
+        // 新连接池只有一个连接，写入后立即读取时通常要再打开一个连接
+        for index in 0..30 {
+            let pool = SqlitePool::connect(&db_url).await.unwrap();
+            let path = format!("/tmp/{}.json", index);
+            let history = ConfigHistory::create(&pool, "claude", &path, "{}", "backup", None)
+                .await
+                .unwrap();
+            let retrieved = ConfigHistory::get_by_id(&pool, history.id.unwrap())
+                .await
+                .unwrap();
+            assert_eq!(retrieved.map(|history| history.config_path), Some(path));
+
+            let pool = SqlitePool::connect(&db_url).await.unwrap();
+            let mode_name = format!("mode_{}", index);
+            WorkModeConfig::update(
+                &pool,
+                UpdateWorkModeRequest {
+                    mode_name: mode_name.clone(),
+                    active_claude_supplier_id: None,
+                    active_codex_supplier_id: None,
+                    mcp_template_ids: None,
+                },
+            )
+            .await
+            .unwrap();
+            assert!(WorkModeConfig::get_by_mode(&pool, &mode_name)
+                .await
+                .unwrap()
+                .is_some());
+
+            let pool = SqlitePool::connect(&db_url).await.unwrap();
+            let value = index.to_string();
+            AppState::set(&pool, "test_key", &value).await.unwrap();
+            let state = AppState::get(&pool, "test_key").await.unwrap();
+            assert_eq!(state.map(|state| state.value), Some(value));
+        }
+    }
+
     #[tokio::test]
     async fn test_config_history() {
         let test_db = create_test_pool().await;
         let pool = &test_db.pool;
 
         let history = ConfigHistory::create(
-            &pool,
+            pool,
             "claude",
             "/home/user/.claude.json",
             "{\"test\": true}",
@@ -408,7 +465,7 @@ mod tests {
         assert_eq!(history.config_type, "claude");
         assert_eq!(history.operation_type, "backup");
 
-        let retrieved = ConfigHistory::get_by_id(&pool, history.id.unwrap())
+        let retrieved = ConfigHistory::get_by_id(pool, history.id.unwrap())
             .await
             .unwrap();
         assert!(retrieved.is_some());
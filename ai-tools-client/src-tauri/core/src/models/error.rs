@@ -0,0 +1,289 @@
+// 统一错误类型，模型层、服务层和命令层共用
+
+use crate::models::ApiResponse;
+use crate::t;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Value};
+
+/// 错误码，前端按错误码判断错误类型并选择本地化文案。
+/// 错误码会随响应返回给前端，已经发布的错误码不能修改
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// 请求的记录不存在
+    NotFound,
+    /// 输入没有通过验证，details.errors 是全部验证错误
+    ValidationFailed,
+    /// 与已有记录冲突，例如名称重复
+    Conflict,
+    /// 当前状态不允许执行该操作，例如内置模板不允许修改
+    PreconditionFailed,
+    /// 数据库被其他连接占用，稍后重试即可
+    DbBusy,
+    /// 其他数据库错误
+    DbError,
+    /// 文件读写失败
+    IoError,
+    /// 其他内部错误
+    Internal,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    /// 记录不存在。resource 是资源名称，id 是可选的记录标识
+    #[error("{}", not_found_message(.resource, .id.as_deref()))]
+    NotFound {
+        resource: String,
+        id: Option<String>,
+    },
+    /// 验证失败，保留全部验证错误
+    #[error("{}", .0.join(&t!("common.list_separator")))]
+    Validation(Vec<String>),
+    #[error("{0}")]
+    Conflict(String),
+    #[error("{0}")]
+    PreconditionFailed(String),
+    #[error("{}", t!("error.db_busy"))]
+    DbBusy,
+    #[error("{0}")]
+    Database(sqlx::Error),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Internal(String),
+    /// 给下层错误加上说明，错误码和详情沿用下层错误
+    #[error("{message}: {source}")]
+    Context {
+        message: String,
+        #[source]
+        source: Box<AppError>,
+    },
+}
+
+fn not_found_message(resource: &str, id: Option<&str>) -> String {
+    match id {
+        Some(id) => t!("error.not_found_with_id", resource = resource, id = id),
+        None => t!("error.not_found", resource = resource),
+    }
+}
+
+impl AppError {
+    pub fn not_found(resource: impl Into<String>) -> Self {
+        AppError::NotFound {
+            resource: resource.into(),
+            id: None,
+        }
+    }
+
+    pub fn not_found_with_id(resource: impl Into<String>, id: impl ToString) -> Self {
+        AppError::NotFound {
+            resource: resource.into(),
+            id: Some(id.to_string()),
+        }
+    }
+
+    /// 只有一条验证错误时使用
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation(vec![message.into()])
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        AppError::Conflict(message.into())
+    }
+
+    pub fn precondition(message: impl Into<String>) -> Self {
+        AppError::PreconditionFailed(message.into())
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        AppError::Internal(message.into())
+    }
+
+    /// 给错误加上说明，错误码和详情沿用原错误
+    pub fn context(self, message: impl Into<String>) -> Self {
+        AppError::Context {
+            message: message.into(),
+            source: Box::new(self),
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::NotFound { .. } => ErrorCode::NotFound,
+            AppError::Validation(_) => ErrorCode::ValidationFailed,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::PreconditionFailed(_) => ErrorCode::PreconditionFailed,
+            AppError::DbBusy => ErrorCode::DbBusy,
+            AppError::Database(_) => ErrorCode::DbError,
+            AppError::Io(_) => ErrorCode::IoError,
+            AppError::Internal(_) => ErrorCode::Internal,
+            AppError::Context { source, .. } => source.code(),
+        }
+    }
+
+    /// 结构化的错误详情，前端可以用来展示字段级错误或拼接本地化文案
+    pub fn details(&self) -> Option<Value> {
+        match self {
+            AppError::NotFound { resource, id } => Some(json!({ "resource": resource, "id": id })),
+            AppError::Validation(errors) => Some(json!({ "errors": errors })),
+            AppError::Context { source, .. } => source.details(),
+            _ => None,
+        }
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::RowNotFound => AppError::not_found(t!("resource.record")),
+            sqlx::Error::PoolTimedOut => AppError::DbBusy,
+            sqlx::Error::Database(db_error) => {
+                // SQLITE_BUSY = 5，SQLITE_LOCKED = 6，扩展错误码的低 8 位是主错误码
+                let primary = db_error
+                    .code()
+                    .and_then(|code| code.parse::<i32>().ok())
+                    .map(|code| code & 0xff);
+                match primary {
+                    Some(5) | Some(6) => AppError::DbBusy,
+                    _ => AppError::Database(error),
+                }
+            }
+            _ => AppError::Database(error),
+        }
+    }
+}
+
+impl From<anyhow::Error> for AppError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<AppError>() {
+            Ok(error) => return error,
+            Err(error) => error,
+        };
+        let error = match error.downcast::<sqlx::Error>() {
+            Ok(error) => return error.into(),
+            Err(error) => error,
+        };
+        match error.downcast::<std::io::Error>() {
+            Ok(error) => AppError::Io(error),
+            Err(error) => AppError::Internal(format!("{:#}", error)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::Internal(error.to_string())
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl<T> From<AppError> for ApiResponse<T> {
+    fn from(error: AppError) -> Self {
+        ApiResponse::error(error)
+    }
+}
+
+/// 命令以失败返回时也序列化成 ApiResponse 的形状，前端统一按 success/code 处理
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ApiResponse::<()> {
+            success: false,
+            data: None,
+            message: Some(self.to_string()),
+            code: Some(self.code()),
+            details: self.details(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// 命令的返回类型
+pub type CommandResult<T> = Result<ApiResponse<T>, AppError>;
+
+pub trait ResultExt<T> {
+    /// 给错误加上说明，替代 `map_err(|e| format!("说明: {}", e))`
+    fn context(self, message: impl Into<String>) -> Result<T, AppError>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, message: impl Into<String>) -> Result<T, AppError> {
+        self.map_err(|error| error.into().context(message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_keeps_code_and_details() {
+        let error: Result<(), AppError> = Err(AppError::Validation(vec![
+            "供应商名称不能为空".to_string(),
+            "访问URL不能为空".to_string(),
+        ]));
+        let error = error.context(t!("supplier.create_failed")).unwrap_err();
+
+        assert_eq!(error.code(), ErrorCode::ValidationFailed);
+        assert_eq!(
+            error.to_string(),
+            "创建供应商失败: 供应商名称不能为空；访问URL不能为空"
+        );
+
+        let value = serde_json::to_value(&error).unwrap();
+        assert_eq!(value["success"], false);
+        assert_eq!(value["code"], "VALIDATION_FAILED");
+        assert_eq!(value["details"]["errors"][1], "访问URL不能为空");
+    }
+
+    #[test]
+    fn test_error_codes_are_stable() {
+        let cases = [
+            (AppError::not_found("供应商"), "NOT_FOUND", "供应商不存在"),
+            (
+                AppError::not_found_with_id("工作模式", "claude_only"),
+                "NOT_FOUND",
+                "工作模式 claude_only 不存在",
+            ),
+            (
+                AppError::conflict("模板名称已存在"),
+                "CONFLICT",
+                "模板名称已存在",
+            ),
+            (AppError::DbBusy, "DB_BUSY", "数据库繁忙，请稍后重试"),
+            (sqlx::Error::RowNotFound.into(), "NOT_FOUND", "记录不存在"),
+            (
+                sqlx::Error::PoolTimedOut.into(),
+                "DB_BUSY",
+                "数据库繁忙，请稍后重试",
+            ),
+            (
+                anyhow::anyhow!("写入配置文件失败").into(),
+                "INTERNAL",
+                "写入配置文件失败",
+            ),
+        ];
+
+        for (error, code, message) in cases {
+            assert_eq!(serde_json::to_value(error.code()).unwrap(), code);
+            assert_eq!(error.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn test_success_response_omits_error_fields() {
+        let value = serde_json::to_value(ApiResponse::success(1)).unwrap();
+        assert!(value.get("code").is_none());
+        assert!(value.get("details").is_none());
+
+        let response: ApiResponse<i64> = AppError::not_found("供应商").into();
+        let value = serde_json::to_value(response).unwrap();
+        assert_eq!(value["code"], "NOT_FOUND");
+        assert_eq!(value["details"]["resource"], "供应商");
+        assert_eq!(value["message"], "供应商不存在");
+    }
+}
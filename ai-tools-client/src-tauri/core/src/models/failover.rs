@@ -0,0 +1,386 @@
+// 故障转移链相关模型
+
+use crate::models::error::AppError;
+use crate::models::supplier::Supplier;
+use crate::t;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+use std::collections::HashMap;
+
+/// 自动故障转移时选择目标供应商的策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailoverStrategy {
+    /// 按链顺序选择当前供应商之后第一个健康的供应商
+    #[default]
+    NextInChain,
+    /// 选择综合评分最高的健康供应商
+    BestScore,
+    /// 选择响应时间最短的健康供应商
+    LowestLatency,
+}
+
+/// 供应商综合评分的权重配置，各项权重即该项的满分
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringWeights {
+    pub success_rate: f64,
+    pub latency: f64,
+    pub failures: f64,
+    pub stability: f64,
+    pub cost: f64,
+    /// 目标响应时间 = max_response_time_ms * target_latency_factor，不超过目标时延迟项得满分
+    pub target_latency_factor: f64,
+    /// 各供应商的相对成本（按供应商ID），1.0 为基准，未配置的按 1.0 计算
+    pub supplier_costs: HashMap<i64, f64>,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            success_rate: 40.0,
+            latency: 30.0,
+            failures: 20.0,
+            stability: 10.0,
+            cost: 0.0,
+            target_latency_factor: 0.5,
+            supplier_costs: HashMap::new(),
+        }
+    }
+}
+
+impl ScoringWeights {
+    /// 验证权重配置
+    pub fn validate(&self) -> Result<(), AppError> {
+        let weights = [
+            ("scoring.weight_success_rate", self.success_rate),
+            ("scoring.weight_latency", self.latency),
+            ("scoring.weight_failures", self.failures),
+            ("scoring.weight_stability", self.stability),
+            ("scoring.weight_cost", self.cost),
+        ];
+        for (name, weight) in weights {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(AppError::validation(t!(
+                    "scoring.weight_negative",
+                    name = t!(name)
+                )));
+            }
+        }
+
+        if weights.iter().all(|(_, weight)| *weight == 0.0) {
+            return Err(AppError::validation(t!("scoring.weights_all_zero")));
+        }
+
+        if !self.target_latency_factor.is_finite() || self.target_latency_factor <= 0.0 {
+            return Err(AppError::validation(t!("scoring.latency_factor_invalid")));
+        }
+
+        if self
+            .supplier_costs
+            .values()
+            .any(|cost| !cost.is_finite() || *cost <= 0.0)
+        {
+            return Err(AppError::validation(t!("scoring.cost_invalid")));
+        }
+
+        Ok(())
+    }
+
+    /// 供应商的相对成本
+    pub fn cost_of(&self, supplier_id: i64) -> f64 {
+        self.supplier_costs
+            .get(&supplier_id)
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
+
+/// 单个供应商的评分明细
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub success_rate: f64,
+    pub latency: f64,
+    pub failures: f64,
+    pub stability: f64,
+    pub cost: f64,
+    pub total: f64,
+}
+
+/// 故障转移候选的评分说明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplierScoreExplanation {
+    pub supplier_id: i64,
+    pub supplier_name: String,
+    pub position: i64,
+    pub is_current: bool,
+    pub is_excluded: bool,
+    pub is_healthy: bool,
+    pub response_time: i64,
+    pub relative_cost: f64,
+    pub breakdown: ScoreBreakdown,
+    pub selected: bool, // 按当前策略自动故障转移会选择该供应商
+    pub error_message: Option<String>,
+}
+
+/// 故障转移链中的一项，position 越小优先级越高
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FailoverChainEntry {
+    pub id: Option<i64>,
+    pub supplier_type: String,
+    pub supplier_id: i64,
+    pub position: i64,
+    pub is_excluded: Option<i64>, // SQLite uses INTEGER for boolean
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailoverChainEntryRequest {
+    pub supplier_id: i64,
+    #[serde(default)]
+    pub is_excluded: bool,
+}
+
+/// 前端展示用的故障转移链条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverChainItem {
+    pub supplier_id: i64,
+    pub supplier_name: String,
+    pub position: i64,
+    pub is_excluded: bool,
+    pub is_active: bool,
+}
+
+impl FailoverChainEntry {
+    pub fn is_excluded(&self) -> bool {
+        self.is_excluded.unwrap_or(0) == 1
+    }
+
+    /// 获取某类供应商的故障转移链
+    pub async fn get_by_type(
+        pool: &SqlitePool,
+        supplier_type: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, FailoverChainEntry>(
+            "SELECT * FROM failover_chains WHERE supplier_type = ? ORDER BY position ASC, id ASC",
+        )
+        .bind(supplier_type)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 用新的顺序整体替换某类供应商的故障转移链
+    pub async fn replace_for_type(
+        pool: &SqlitePool,
+        supplier_type: &str,
+        entries: &[FailoverChainEntryRequest],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let now = Utc::now();
+        let mut tx = pool.begin().await?;
+
+        sqlx::query("DELETE FROM failover_chains WHERE supplier_type = ?")
+            .bind(supplier_type)
+            .execute(&mut *tx)
+            .await?;
+
+        for (position, entry) in entries.iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO failover_chains (
+                    supplier_type, supplier_id, position, is_excluded, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(supplier_type)
+            .bind(entry.supplier_id)
+            .bind(position as i64)
+            .bind(if entry.is_excluded { 1 } else { 0 })
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Self::get_by_type(pool, supplier_type).await
+    }
+}
+
+/// 按故障转移链排列供应商。
+///
+/// 链中的供应商按 position 排在前面，未加入链的供应商按原有顺序排在后面。配置了链之后，
+/// 未加入链的供应商视为排除，新添加的供应商不会自动成为备用；没有配置链时全部供应商都参与。
+/// 返回值中包含被排除的供应商，由调用方决定如何处理。
+pub fn order_by_chain(
+    suppliers: Vec<Supplier>,
+    entries: &[FailoverChainEntry],
+) -> Vec<(Supplier, bool)> {
+    let mut ordered: Vec<(Supplier, bool)> = Vec::with_capacity(suppliers.len());
+    let mut remaining = suppliers;
+
+    for entry in entries {
+        if let Some(index) = remaining
+            .iter()
+            .position(|supplier| supplier.id == Some(entry.supplier_id))
+        {
+            let supplier = remaining.remove(index);
+            ordered.push((supplier, entry.is_excluded()));
+        }
+    }
+
+    let unlisted_excluded = !entries.is_empty();
+    ordered.extend(
+        remaining
+            .into_iter()
+            .map(|supplier| (supplier, unlisted_excluded)),
+    );
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::supplier::CreateSupplierRequest;
+    use crate::services::database::Database;
+    use tempfile::{tempdir, TempDir};
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn create_test_pool() -> TestDb {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+
+        TestDb {
+            _dir: temp_dir,
+            pool,
+        }
+    }
+
+    fn supplier(id: i64, name: &str) -> Supplier {
+        Supplier {
+            id: Some(id),
+            r#type: "claude".to_string(),
+            name: name.to_string(),
+            base_url: "https://api.example.com".to_string(),
+            auth_token: "token".to_string(),
+            timeout_ms: None,
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            is_active: Some(0),
+            sort_order: Some(id),
+            is_healthy: None,
+            last_check_time: None,
+            response_time: None,
+            consecutive_failures: None,
+            uptime_percentage: None,
+            total_requests: None,
+            failed_requests: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replace_chain_keeps_order() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let mut ids = Vec::new();
+        for name in ["primary", "backup"] {
+            let created = Supplier::create(
+                pool,
+                CreateSupplierRequest {
+                    r#type: "claude".to_string(),
+                    name: name.to_string(),
+                    base_url: "https://api.example.com".to_string(),
+                    auth_token: "token".to_string(),
+                    timeout_ms: None,
+                    auto_update: None,
+                    opus_model: None,
+                    sonnet_model: None,
+                    haiku_model: None,
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(created.id.unwrap());
+        }
+
+        let entries = vec![
+            FailoverChainEntryRequest {
+                supplier_id: ids[1],
+                is_excluded: false,
+            },
+            FailoverChainEntryRequest {
+                supplier_id: ids[0],
+                is_excluded: true,
+            },
+        ];
+        FailoverChainEntry::replace_for_type(pool, "claude", &entries)
+            .await
+            .unwrap();
+
+        // 再次替换会覆盖旧链
+        let chain = FailoverChainEntry::replace_for_type(pool, "claude", &entries[..1])
+            .await
+            .unwrap();
+        assert_eq!(chain.len(), 1);
+        assert_eq!(chain[0].supplier_id, ids[1]);
+        assert!(!chain[0].is_excluded());
+
+        let codex_chain = FailoverChainEntry::get_by_type(pool, "codex")
+            .await
+            .unwrap();
+        assert!(codex_chain.is_empty());
+    }
+
+    #[test]
+    fn test_order_by_chain_excludes_unlisted_suppliers() {
+        let suppliers = vec![supplier(1, "a"), supplier(2, "b"), supplier(3, "c")];
+        let entries = vec![
+            FailoverChainEntry {
+                id: Some(1),
+                supplier_type: "claude".to_string(),
+                supplier_id: 3,
+                position: 0,
+                is_excluded: Some(0),
+                created_at: None,
+                updated_at: None,
+            },
+            FailoverChainEntry {
+                id: Some(2),
+                supplier_type: "claude".to_string(),
+                supplier_id: 1,
+                position: 1,
+                is_excluded: Some(1),
+                created_at: None,
+                updated_at: None,
+            },
+        ];
+
+        let ordered = order_by_chain(suppliers, &entries);
+        let names: Vec<(&str, bool)> = ordered
+            .iter()
+            .map(|(supplier, excluded)| (supplier.name.as_str(), *excluded))
+            .collect();
+        assert_eq!(names, vec![("c", false), ("a", true), ("b", true)]);
+
+        // 没有配置链时全部供应商都参与
+        let suppliers = vec![supplier(1, "a"), supplier(2, "b")];
+        assert!(order_by_chain(suppliers, &[])
+            .iter()
+            .all(|(_, excluded)| !excluded));
+    }
+}
@@ -0,0 +1,525 @@
+// 切换钩子相关模型
+
+use crate::models::error::AppError;
+use crate::models::returned_row;
+use crate::t;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqlitePool};
+
+/// 钩子默认超时时间（毫秒）
+pub const DEFAULT_HOOK_TIMEOUT_MS: i64 = 30_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SwitchHook {
+    pub id: Option<i64>,
+    pub scope_type: String,  // "work_mode" or "supplier_type"
+    pub scope_value: String, // 工作模式名称或供应商类型
+    pub stage: String,       // "pre" or "post"
+    pub name: String,
+    pub command: String,
+    pub timeout_ms: Option<i64>,
+    pub is_enabled: Option<i64>, // SQLite uses INTEGER for boolean
+    pub sort_order: Option<i64>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSwitchHookRequest {
+    pub scope_type: String,
+    pub scope_value: String,
+    pub stage: String,
+    pub name: String,
+    pub command: String,
+    pub timeout_ms: Option<i64>,
+    pub is_enabled: Option<bool>,
+    pub sort_order: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSwitchHookRequest {
+    pub id: i64,
+    pub stage: Option<String>,
+    pub name: Option<String>,
+    pub command: Option<String>,
+    pub timeout_ms: Option<i64>,
+    pub is_enabled: Option<bool>,
+    pub sort_order: Option<i64>,
+}
+
+/// 单个钩子的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookExecutionResult {
+    pub hook_id: Option<i64>,
+    pub name: String,
+    pub stage: String,
+    pub command: String,
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: i64,
+    pub timed_out: bool,
+    pub error: Option<String>,
+}
+
+/// 切换记录（供应商切换或工作模式切换）
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SwitchRecord {
+    pub id: Option<i64>,
+    pub switch_type: String, // "supplier" or "work_mode"
+    pub supplier_type: Option<String>,
+    pub from_supplier_id: Option<i64>,
+    pub to_supplier_id: Option<i64>,
+    pub from_mode: Option<String>,
+    pub to_mode: Option<String>,
+    pub backup_id: Option<i64>,
+    pub success: i64, // SQLite uses INTEGER for boolean
+    pub message: Option<String>,
+    pub hook_results: Option<String>, // JSON数组
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSwitchRecordRequest {
+    pub switch_type: String,
+    pub supplier_type: Option<String>,
+    pub from_supplier_id: Option<i64>,
+    pub to_supplier_id: Option<i64>,
+    pub from_mode: Option<String>,
+    pub to_mode: Option<String>,
+    pub backup_id: Option<i64>,
+    pub success: bool,
+    pub message: Option<String>,
+    pub hook_results: Vec<HookExecutionResult>,
+}
+
+impl SwitchHook {
+    /// 创建钩子
+    pub async fn create(
+        pool: &SqlitePool,
+        request: CreateSwitchHookRequest,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query_as::<_, SwitchHook>(
+            r#"
+            INSERT INTO switch_hooks (
+                scope_type, scope_value, stage, name, command, timeout_ms,
+                is_enabled, sort_order, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(&request.scope_type)
+        .bind(&request.scope_value)
+        .bind(&request.stage)
+        .bind(&request.name)
+        .bind(&request.command)
+        .bind(request.timeout_ms.unwrap_or(DEFAULT_HOOK_TIMEOUT_MS))
+        .bind(if request.is_enabled.unwrap_or(true) {
+            1
+        } else {
+            0
+        })
+        .bind(request.sort_order.unwrap_or(0))
+        .bind(now)
+        .bind(now)
+        .fetch_all(pool)
+        .await
+        .and_then(returned_row)
+    }
+
+    /// 获取所有钩子
+    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, SwitchHook>(
+            "SELECT * FROM switch_hooks ORDER BY scope_type ASC, scope_value ASC, stage ASC, sort_order ASC, id ASC",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 根据作用域获取钩子
+    pub async fn get_by_scope(
+        pool: &SqlitePool,
+        scope_type: &str,
+        scope_value: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, SwitchHook>(
+            "SELECT * FROM switch_hooks WHERE scope_type = ? AND scope_value = ? ORDER BY stage ASC, sort_order ASC, id ASC",
+        )
+        .bind(scope_type)
+        .bind(scope_value)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 获取某个作用域在指定阶段启用的钩子（按执行顺序）
+    pub async fn get_enabled(
+        pool: &SqlitePool,
+        scope_type: &str,
+        scope_value: &str,
+        stage: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, SwitchHook>(
+            r#"
+            SELECT * FROM switch_hooks
+            WHERE scope_type = ? AND scope_value = ? AND stage = ? AND is_enabled = 1
+            ORDER BY sort_order ASC, id ASC
+            "#,
+        )
+        .bind(scope_type)
+        .bind(scope_value)
+        .bind(stage)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 根据ID获取钩子
+    pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, SwitchHook>("SELECT * FROM switch_hooks WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// 更新钩子
+    pub async fn update(
+        pool: &SqlitePool,
+        request: UpdateSwitchHookRequest,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query_as::<_, SwitchHook>(
+            r#"
+            UPDATE switch_hooks SET
+                stage = COALESCE(?, stage),
+                name = COALESCE(?, name),
+                command = COALESCE(?, command),
+                timeout_ms = COALESCE(?, timeout_ms),
+                is_enabled = COALESCE(?, is_enabled),
+                sort_order = COALESCE(?, sort_order),
+                updated_at = ?
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&request.stage)
+        .bind(&request.name)
+        .bind(&request.command)
+        .bind(request.timeout_ms)
+        .bind(request.is_enabled.map(|b| if b { 1 } else { 0 }))
+        .bind(request.sort_order)
+        .bind(now)
+        .bind(request.id)
+        .fetch_all(pool)
+        .await
+        .map(|mut rows| rows.pop())
+    }
+
+    /// 删除钩子
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM switch_hooks WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 由创建请求构造尚未保存的钩子，用于保存前的验证
+    pub fn from_request(request: &CreateSwitchHookRequest) -> Self {
+        Self {
+            id: None,
+            scope_type: request.scope_type.clone(),
+            scope_value: request.scope_value.clone(),
+            stage: request.stage.clone(),
+            name: request.name.clone(),
+            command: request.command.clone(),
+            timeout_ms: request.timeout_ms,
+            is_enabled: request.is_enabled.map(|b| if b { 1 } else { 0 }),
+            sort_order: request.sort_order,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// 把更新请求合并到当前钩子上，用于保存前的验证
+    pub fn apply_update(&self, request: &UpdateSwitchHookRequest) -> Self {
+        Self {
+            stage: request.stage.clone().unwrap_or_else(|| self.stage.clone()),
+            name: request.name.clone().unwrap_or_else(|| self.name.clone()),
+            command: request
+                .command
+                .clone()
+                .unwrap_or_else(|| self.command.clone()),
+            timeout_ms: request.timeout_ms.or(self.timeout_ms),
+            ..self.clone()
+        }
+    }
+
+    /// 获取超时时间（毫秒）
+    pub fn timeout_ms(&self) -> u64 {
+        self.timeout_ms
+            .filter(|ms| *ms > 0)
+            .unwrap_or(DEFAULT_HOOK_TIMEOUT_MS) as u64
+    }
+
+    /// 验证钩子配置，返回全部验证错误
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push(t!("hook.name_empty"));
+        }
+
+        if self.command.trim().is_empty() {
+            errors.push(t!("hook.command_empty"));
+        }
+
+        match self.scope_type.as_str() {
+            "work_mode" => {
+                if !matches!(
+                    self.scope_value.as_str(),
+                    "claude_only" | "codex_only" | "claude_codex"
+                ) {
+                    errors.push(t!("mode.name_invalid"));
+                }
+            }
+            "supplier_type" => {
+                if self.scope_value != "claude" && self.scope_value != "codex" {
+                    errors.push(t!("supplier.type_invalid"));
+                }
+            }
+            _ => errors.push(t!("hook.scope_invalid")),
+        }
+
+        if self.stage != "pre" && self.stage != "post" {
+            errors.push(t!("hook.stage_invalid"));
+        }
+
+        if self.timeout_ms.is_some_and(|timeout_ms| timeout_ms <= 0) {
+            errors.push(t!("hook.timeout_invalid"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(errors))
+        }
+    }
+}
+
+impl SwitchRecord {
+    /// 保存切换记录
+    pub async fn create(
+        pool: &SqlitePool,
+        request: CreateSwitchRecordRequest,
+    ) -> Result<Self, sqlx::Error> {
+        let hook_results_json = serde_json::to_string(&request.hook_results).unwrap_or_default();
+
+        sqlx::query_as::<_, SwitchRecord>(
+            r#"
+            INSERT INTO switch_history (
+                switch_type, supplier_type, from_supplier_id, to_supplier_id,
+                from_mode, to_mode, backup_id, success, message, hook_results, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(&request.switch_type)
+        .bind(&request.supplier_type)
+        .bind(request.from_supplier_id)
+        .bind(request.to_supplier_id)
+        .bind(&request.from_mode)
+        .bind(&request.to_mode)
+        .bind(request.backup_id)
+        .bind(if request.success { 1 } else { 0 })
+        .bind(&request.message)
+        .bind(hook_results_json)
+        .bind(Utc::now())
+        .fetch_all(pool)
+        .await
+        .and_then(returned_row)
+    }
+
+    /// 获取最近的切换记录
+    pub async fn get_recent(
+        pool: &SqlitePool,
+        switch_type: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        if let Some(switch_type) = switch_type {
+            sqlx::query_as::<_, SwitchRecord>(
+                "SELECT * FROM switch_history WHERE switch_type = ? ORDER BY created_at DESC, id DESC LIMIT ?",
+            )
+            .bind(switch_type)
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        } else {
+            sqlx::query_as::<_, SwitchRecord>(
+                "SELECT * FROM switch_history ORDER BY created_at DESC, id DESC LIMIT ?",
+            )
+            .bind(limit)
+            .fetch_all(pool)
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use sqlx::SqlitePool;
+    use tempfile::{tempdir, TempDir};
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn create_test_pool() -> TestDb {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+
+        TestDb {
+            _dir: temp_dir,
+            pool,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_enabled_hooks_are_ordered_by_stage_and_sort_order() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        for (name, stage, sort_order, enabled) in [
+            ("second", "pre", 2, true),
+            ("first", "pre", 1, true),
+            ("disabled", "pre", 0, false),
+            ("after", "post", 0, true),
+        ] {
+            SwitchHook::create(
+                pool,
+                CreateSwitchHookRequest {
+                    scope_type: "supplier_type".to_string(),
+                    scope_value: "claude".to_string(),
+                    stage: stage.to_string(),
+                    name: name.to_string(),
+                    command: "true".to_string(),
+                    timeout_ms: None,
+                    is_enabled: Some(enabled),
+                    sort_order: Some(sort_order),
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let pre_hooks = SwitchHook::get_enabled(pool, "supplier_type", "claude", "pre")
+            .await
+            .unwrap();
+        let names: Vec<&str> = pre_hooks.iter().map(|h| h.name.as_str()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+        assert_eq!(pre_hooks[0].timeout_ms(), DEFAULT_HOOK_TIMEOUT_MS as u64);
+
+        let codex_hooks = SwitchHook::get_enabled(pool, "supplier_type", "codex", "pre")
+            .await
+            .unwrap();
+        assert!(codex_hooks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_switch_record_keeps_hook_output() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let record = SwitchRecord::create(
+            pool,
+            CreateSwitchRecordRequest {
+                switch_type: "supplier".to_string(),
+                supplier_type: Some("claude".to_string()),
+                from_supplier_id: Some(1),
+                to_supplier_id: Some(2),
+                from_mode: None,
+                to_mode: None,
+                backup_id: None,
+                success: false,
+                message: Some("前置钩子执行失败".to_string()),
+                hook_results: vec![HookExecutionResult {
+                    hook_id: Some(1),
+                    name: "notify".to_string(),
+                    stage: "pre".to_string(),
+                    command: "exit 3".to_string(),
+                    success: false,
+                    exit_code: Some(3),
+                    stdout: String::new(),
+                    stderr: "boom".to_string(),
+                    duration_ms: 5,
+                    timed_out: false,
+                    error: None,
+                }],
+            },
+        )
+        .await
+        .unwrap();
+
+        let stored = SwitchRecord::get_recent(pool, Some("supplier"), 10)
+            .await
+            .unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].id, record.id);
+        assert_eq!(stored[0].success, 0);
+
+        let hook_results: Vec<HookExecutionResult> =
+            serde_json::from_str(stored[0].hook_results.as_deref().unwrap()).unwrap();
+        assert_eq!(hook_results.len(), 1);
+        assert_eq!(hook_results[0].exit_code, Some(3));
+        assert_eq!(hook_results[0].stderr, "boom");
+    }
+
+    #[test]
+    fn test_validate_hook() {
+        let mut hook = SwitchHook {
+            id: None,
+            scope_type: "work_mode".to_string(),
+            scope_value: "claude_only".to_string(),
+            stage: "post".to_string(),
+            name: "tmux".to_string(),
+            command: "tmux refresh-client -S".to_string(),
+            timeout_ms: Some(5000),
+            is_enabled: Some(1),
+            sort_order: Some(0),
+            created_at: None,
+            updated_at: None,
+        };
+        assert!(hook.validate().is_ok());
+
+        hook.stage = "during".to_string();
+        assert!(hook.validate().is_err());
+
+        hook.stage = "pre".to_string();
+        hook.scope_value = "unknown_mode".to_string();
+        assert!(hook.validate().is_err());
+
+        // 一次返回全部验证错误
+        hook.name = " ".to_string();
+        hook.timeout_ms = Some(0);
+        let error = hook.validate().unwrap_err();
+        assert_eq!(
+            error.details().unwrap()["errors"],
+            serde_json::json!([
+                "钩子名称不能为空",
+                "无效的工作模式名称",
+                "钩子超时时间必须大于0"
+            ])
+        );
+    }
+}
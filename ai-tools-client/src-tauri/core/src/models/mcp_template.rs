@@ -0,0 +1,2523 @@
+use crate::models::{
+    next_available_name, returned_row, AppError, ConflictStrategy, ImportAction, ImportReport,
+    ResultExt,
+};
+use crate::services::mcp_probe::{McpProbe, McpProbeResult};
+use crate::services::mcp_validator::McpServerValidator;
+use crate::services::platform_transform::PlatformTransformer;
+use crate::services::secret_store::SecretCipher;
+use crate::services::template_converter::claude_servers;
+use crate::services::template_renderer::TemplateRenderer;
+use crate::services::text_diff::{unified_diff, TextDiff};
+use crate::t;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite, SqliteConnection, SqlitePool};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// 模板支持的平台类型，any 表示按 unix 写法保存、在其他平台上自动转换
+pub const PLATFORM_TYPES: [&str; 3] = ["unix", "windows", "any"];
+
+/// 界面上展示密钥参数时使用的掩码
+pub const SECRET_MASK: &str = "******";
+
+/// 模板列表支持的排序方式
+pub const TEMPLATE_SORT_OPTIONS: [&str; 5] = ["default", "relevance", "usage", "recent", "name"];
+
+/// 分页查询时每页最多返回的模板数
+pub const MAX_PAGE_SIZE: i64 = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct McpTemplate {
+    pub id: Option<i64>,
+    pub name: String,
+    pub version: String,
+    pub ai_type: String,       // "claude" or "codex"
+    pub platform_type: String, // "unix", "windows" or "any"
+    pub config_content: String,
+    pub description: Option<String>,
+    pub is_builtin: Option<i64>, // SQLite uses INTEGER for boolean
+    pub category: Option<String>,
+    pub tags: Option<String>, // JSON string
+    pub usage_count: Option<i64>,
+    pub parameters: Option<String>, // JSON array of TemplateParameter
+    pub platform_overrides: Option<String>, // JSON object: platform -> config_content
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 模板参数定义，config_content 中通过 `${name}` 引用
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateParameter {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub secret: bool, // 密钥参数加密保存在 mcp_template_values 中，不随模板导出
+    pub default: Option<String>,
+}
+
+/// 模板参数的已保存取值，密钥参数的 value 为密文
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct McpTemplateValue {
+    pub id: Option<i64>,
+    pub template_id: i64,
+    pub name: String,
+    pub value: String,
+    pub is_secret: Option<i64>, // SQLite uses INTEGER for boolean
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// 握手测试发现的工具，每个服务器保留最近一次成功的结果
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct McpTemplateTools {
+    pub id: Option<i64>,
+    pub template_id: i64,
+    pub server_id: String,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub tools: String, // JSON array of McpToolInfo
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+/// 模板的一次修订，保存修改后的完整内容
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct McpTemplateRevision {
+    pub id: Option<i64>,
+    pub template_id: i64,
+    pub revision: i64,
+    pub name: String,
+    pub version: String,
+    pub config_content: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<String>,
+    pub parameters: Option<String>,
+    pub platform_overrides: Option<String>,
+    pub note: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// 两个修订之间的差异
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpTemplateRevisionDiff {
+    pub template_id: i64,
+    pub from_revision: i64,
+    pub to_revision: i64,
+    pub from_version: String,
+    pub to_version: String,
+    pub changed_fields: Vec<String>,
+    pub content: TextDiff,
+}
+
+/// 自动升级版本号的级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionBump {
+    Patch,
+    Minor,
+    Major,
+}
+
+/// 前端展示用的参数取值，密钥参数只返回掩码
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpTemplateValueView {
+    pub name: String,
+    pub value: Option<String>,
+    pub is_secret: bool,
+    pub is_set: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateMcpTemplateRequest {
+    pub name: String,
+    pub version: Option<String>,
+    pub ai_type: String,
+    pub platform_type: String,
+    pub config_content: String,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub parameters: Option<Vec<TemplateParameter>>,
+    #[serde(default)]
+    pub platform_overrides: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateMcpTemplateRequest {
+    pub id: i64,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub config_content: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub parameters: Option<Vec<TemplateParameter>>,
+    #[serde(default)]
+    pub platform_overrides: Option<HashMap<String, String>>,
+    /// 记录在修订历史中的说明
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpTemplateValidationResult {
+    pub valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl McpTemplateValidationResult {
+    /// 未通过验证时返回包含全部验证错误的错误，警告不算错误
+    pub fn to_error(&self) -> Option<AppError> {
+        if self.valid {
+            None
+        } else {
+            Some(AppError::Validation(self.errors.clone()))
+        }
+    }
+}
+
+/// 生成另一种AI类型的对应模板的结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpTemplateConversion {
+    pub template: McpTemplate,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct McpTemplateCategory {
+    pub name: String,
+    pub count: i64,
+    pub description: Option<String>,
+    pub sort_order: i64,
+}
+
+/// 模板列表查询条件，各条件之间为“且”的关系
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct McpTemplateQuery {
+    pub ai_type: Option<String>,
+    pub platform_type: Option<String>,
+    /// 全文搜索关键字，匹配名称、描述、标签和服务器命令
+    pub keyword: Option<String>,
+    /// 模板必须同时带有的标签
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub is_builtin: Option<bool>,
+    /// 取值见 TEMPLATE_SORT_OPTIONS，有关键字时默认按相关度排序
+    pub sort_by: Option<String>,
+    /// 从 1 开始的页码
+    pub page: Option<i64>,
+    /// 未指定时返回全部结果
+    pub page_size: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpTemplatePage {
+    pub items: Vec<McpTemplate>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: Option<i64>,
+}
+
+impl McpTemplate {
+    /// 创建新MCP模板
+    pub async fn create(
+        pool: &SqlitePool,
+        request: CreateMcpTemplateRequest,
+    ) -> Result<Self, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let created = Self::insert(&mut tx, &request, &t!("template.revision_created")).await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// 在调用方的事务内写入用户模板，并记录第一条修订
+    async fn insert(
+        conn: &mut SqliteConnection,
+        request: &CreateMcpTemplateRequest,
+        note: &str,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+        let template = Self::from_request(request);
+
+        let created = sqlx::query_as::<_, McpTemplate>(
+            r#"
+            INSERT INTO mcp_templates (
+                name, version, ai_type, platform_type, config_content,
+                description, is_builtin, category, tags, usage_count,
+                parameters, platform_overrides, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, 0, ?, ?, 0, ?, ?, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(&template.name)
+        .bind(&template.version)
+        .bind(&template.ai_type)
+        .bind(&template.platform_type)
+        .bind(&template.config_content)
+        .bind(&template.description)
+        .bind(&template.category)
+        .bind(&template.tags)
+        .bind(&template.parameters)
+        .bind(&template.platform_overrides)
+        .bind(now)
+        .bind(now)
+        .fetch_all(&mut *conn)
+        .await
+        .and_then(returned_row)?;
+
+        McpTemplateRevision::record(&mut *conn, &created, Some(note)).await?;
+        Self::index(&mut *conn, &created).await?;
+        Ok(created)
+    }
+
+    /// 由创建请求构造尚未保存的模板，用于保存前的验证
+    pub fn from_request(request: &CreateMcpTemplateRequest) -> Self {
+        Self {
+            id: None,
+            name: request.name.clone(),
+            version: request
+                .version
+                .clone()
+                .unwrap_or_else(|| "1.0.0".to_string()),
+            ai_type: request.ai_type.clone(),
+            platform_type: request.platform_type.clone(),
+            config_content: request.config_content.clone(),
+            description: request.description.clone(),
+            is_builtin: Some(0),
+            category: request.category.clone(),
+            tags: request
+                .tags
+                .as_ref()
+                .map(|tags| serde_json::to_string(tags).unwrap_or_default()),
+            usage_count: Some(0),
+            parameters: parameters_to_json(&request.parameters),
+            platform_overrides: overrides_to_json(&request.platform_overrides),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// 按名称、AI类型和平台获取内置模板
+    pub async fn get_builtin(
+        pool: &SqlitePool,
+        name: &str,
+        ai_type: &str,
+        platform_type: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplate>(
+            r#"
+            SELECT * FROM mcp_templates
+            WHERE is_builtin = 1 AND name = ? AND ai_type = ? AND platform_type = ?
+            ORDER BY id ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(name)
+        .bind(ai_type)
+        .bind(platform_type)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// 写入内置模板；同名同版本的用户模板已存在时不写入并返回 None
+    pub async fn create_builtin(
+        pool: &SqlitePool,
+        request: CreateMcpTemplateRequest,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+        let version = request.version.unwrap_or_else(|| "1.0.0".to_string());
+        let tags_json = request
+            .tags
+            .as_ref()
+            .map(|tags| serde_json::to_string(tags).unwrap_or_default());
+        let parameters_json = parameters_to_json(&request.parameters);
+        let overrides_json = overrides_to_json(&request.platform_overrides);
+
+        let mut tx = pool.begin().await?;
+        let created = sqlx::query_as::<_, McpTemplate>(
+            r#"
+            INSERT INTO mcp_templates (
+                name, version, ai_type, platform_type, config_content,
+                description, is_builtin, category, tags, usage_count,
+                parameters, platform_overrides, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, 1, ?, ?, 0, ?, ?, ?, ?)
+            ON CONFLICT(name, version, ai_type, platform_type) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(&request.name)
+        .bind(&version)
+        .bind(&request.ai_type)
+        .bind(&request.platform_type)
+        .bind(&request.config_content)
+        .bind(&request.description)
+        .bind(&request.category)
+        .bind(&tags_json)
+        .bind(&parameters_json)
+        .bind(&overrides_json)
+        .bind(now)
+        .bind(now)
+        .fetch_all(&mut *tx)
+        .await?
+        .pop();
+
+        if let Some(template) = &created {
+            let note = t!("template.revision_builtin_created");
+            McpTemplateRevision::record(&mut tx, template, Some(&note)).await?;
+            Self::index(&mut tx, template).await?;
+        }
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    /// 用新版本内容覆盖内置模板，保留ID、使用计数和已保存的参数取值
+    pub async fn upgrade_builtin(
+        pool: &SqlitePool,
+        id: i64,
+        request: CreateMcpTemplateRequest,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+        let version = request.version.unwrap_or_else(|| "1.0.0".to_string());
+        let tags_json = request
+            .tags
+            .as_ref()
+            .map(|tags| serde_json::to_string(tags).unwrap_or_default());
+        let parameters_json = parameters_to_json(&request.parameters);
+        let overrides_json = overrides_to_json(&request.platform_overrides);
+
+        let mut tx = pool.begin().await?;
+        // 升级前的内容作为第一个修订保留下来
+        McpTemplateRevision::ensure_baseline(&mut tx, id).await?;
+        let upgraded = sqlx::query_as::<_, McpTemplate>(
+            r#"
+            UPDATE mcp_templates SET
+                version = ?,
+                config_content = ?,
+                description = ?,
+                category = ?,
+                tags = ?,
+                parameters = ?,
+                platform_overrides = ?,
+                updated_at = ?
+            WHERE id = ? AND is_builtin = 1
+            RETURNING *
+            "#,
+        )
+        .bind(&version)
+        .bind(&request.config_content)
+        .bind(&request.description)
+        .bind(&request.category)
+        .bind(&tags_json)
+        .bind(&parameters_json)
+        .bind(&overrides_json)
+        .bind(now)
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await?
+        .pop();
+
+        if let Some(template) = &upgraded {
+            let note = t!("template.revision_builtin_upgraded", version = version);
+            McpTemplateRevision::record(&mut tx, template, Some(&note)).await?;
+            Self::index(&mut tx, template).await?;
+        }
+        tx.commit().await?;
+        Ok(upgraded)
+    }
+
+    /// 获取所有MCP模板
+    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplate>(
+            "SELECT * FROM mcp_templates ORDER BY is_builtin DESC, category ASC, name ASC",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 按查询条件分页获取模板，返回当前页和满足条件的总数
+    pub async fn search(
+        pool: &SqlitePool,
+        query: &McpTemplateQuery,
+    ) -> Result<McpTemplatePage, sqlx::Error> {
+        let keyword = SearchKeyword::parse(query.keyword.as_deref().unwrap_or_default());
+
+        let mut count_builder = QueryBuilder::<Sqlite>::new("SELECT COUNT(*) FROM mcp_templates t");
+        Self::push_search_filters(&mut count_builder, query, &keyword);
+        let total: i64 = count_builder.build_query_scalar().fetch_one(pool).await?;
+
+        let mut builder = QueryBuilder::<Sqlite>::new("SELECT t.* FROM mcp_templates t");
+        Self::push_search_filters(&mut builder, query, &keyword);
+
+        let sort_by = query.sort_by.as_deref().unwrap_or(if keyword.is_empty() {
+            "default"
+        } else {
+            "relevance"
+        });
+        builder.push(match sort_by {
+            "usage" => " ORDER BY t.usage_count DESC, t.name ASC",
+            "recent" => " ORDER BY t.updated_at DESC, t.id DESC",
+            "name" => " ORDER BY t.name ASC, t.version DESC",
+            // 只有短关键字时没有 MATCH 条件，无法计算相关度
+            "relevance" if keyword.match_expression.is_some() => {
+                " ORDER BY bm25(mcp_templates_fts) ASC, t.usage_count DESC"
+            }
+            _ => " ORDER BY t.is_builtin DESC, t.category ASC, t.name ASC",
+        });
+
+        let page = query.page.unwrap_or(1).max(1);
+        let page_size = query.page_size.map(|size| size.clamp(1, MAX_PAGE_SIZE));
+        if let Some(page_size) = page_size {
+            builder
+                .push(" LIMIT ")
+                .push_bind(page_size)
+                .push(" OFFSET ")
+                .push_bind((page - 1) * page_size);
+        }
+
+        let items = builder
+            .build_query_as::<McpTemplate>()
+            .fetch_all(pool)
+            .await?;
+
+        Ok(McpTemplatePage {
+            items,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    fn push_search_filters<'a>(
+        builder: &mut QueryBuilder<'a, Sqlite>,
+        query: &'a McpTemplateQuery,
+        keyword: &SearchKeyword,
+    ) {
+        if !keyword.is_empty() {
+            builder.push(" JOIN mcp_templates_fts ON mcp_templates_fts.rowid = t.id");
+        }
+        builder.push(" WHERE 1 = 1");
+
+        if let Some(ai_type) = &query.ai_type {
+            builder.push(" AND t.ai_type = ").push_bind(ai_type);
+        }
+        if let Some(platform_type) = &query.platform_type {
+            // 适用于所有平台的模板也一并返回
+            builder
+                .push(" AND t.platform_type IN (")
+                .push_bind(platform_type)
+                .push(", 'any')");
+        }
+        if let Some(category) = &query.category {
+            builder.push(" AND t.category = ").push_bind(category);
+        }
+        if let Some(is_builtin) = query.is_builtin {
+            builder
+                .push(" AND COALESCE(t.is_builtin, 0) = ")
+                .push_bind(i64::from(is_builtin));
+        }
+        for tag in &query.tags {
+            builder
+                .push(
+                    " AND EXISTS (SELECT 1 FROM json_each(CASE WHEN json_valid(t.tags) THEN t.tags ELSE '[]' END) WHERE value = ",
+                )
+                .push_bind(tag)
+                .push(")");
+        }
+
+        if let Some(expression) = &keyword.match_expression {
+            builder
+                .push(" AND mcp_templates_fts MATCH ")
+                .push_bind(expression.clone());
+        }
+        for term in &keyword.short_terms {
+            let pattern = format!("%{}%", term);
+            builder.push(" AND (");
+            let mut columns = builder.separated(" OR ");
+            for column in ["name", "description", "tags", "commands"] {
+                columns
+                    .push(format!("mcp_templates_fts.{} LIKE ", column))
+                    .push_bind_unseparated(pattern.clone())
+                    .push_unseparated(" ESCAPE '\\'");
+            }
+            builder.push(")");
+        }
+    }
+
+    /// 根据ID获取模板
+    pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplate>("SELECT * FROM mcp_templates WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// 更新MCP模板并追加一条修订。未指定版本号时按改动自动升级版本：
+    /// 删除服务器或参数、参数改为必填为主版本，配置和参数的其他改动为次版本，只改元数据为修订版本
+    pub async fn update(
+        pool: &SqlitePool,
+        request: UpdateMcpTemplateRequest,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let note = request.note.clone();
+        let version = request.version.clone();
+        Self::save_change(
+            pool,
+            request.id,
+            |existing| existing.apply_update(&request),
+            version,
+            note,
+        )
+        .await
+    }
+
+    /// 把模板内容恢复为指定修订，恢复本身也作为一条新修订记录
+    pub async fn revert_to(
+        pool: &SqlitePool,
+        revision: &McpTemplateRevision,
+        note: Option<String>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let note = note.or_else(|| {
+            Some(t!(
+                "template.revision_reverted",
+                revision = revision.revision
+            ))
+        });
+        Self::save_change(
+            pool,
+            revision.template_id,
+            |existing| revision.apply_to(existing),
+            None,
+            note,
+        )
+        .await
+    }
+
+    /// 在同一个事务中导入一批模板，同名同版本（且AI类型、平台相同）的模板按冲突策略处理。
+    /// 试运行或有失败项时回滚，结果中仍会列出每一项的处理方式
+    pub async fn import(
+        pool: &SqlitePool,
+        requests: Vec<CreateMcpTemplateRequest>,
+        strategy: ConflictStrategy,
+        dry_run: bool,
+    ) -> Result<ImportReport, sqlx::Error> {
+        let mut report = ImportReport::new(dry_run);
+        let mut tx = pool.begin().await?;
+
+        for (index, mut request) in requests.into_iter().enumerate() {
+            let incoming = Self::from_request(&request);
+            let validation = incoming.validate_config();
+            if !validation.valid {
+                report.push(
+                    index,
+                    &request.name,
+                    ImportAction::Failed,
+                    None,
+                    Some(t!(
+                        "import.validation_failed",
+                        error = validation.errors.join(&t!("common.list_separator"))
+                    )),
+                );
+                continue;
+            }
+
+            let existing = sqlx::query_as::<_, McpTemplate>(
+                "SELECT * FROM mcp_templates WHERE name = ? AND version = ? AND ai_type = ? AND platform_type = ?",
+            )
+            .bind(&incoming.name)
+            .bind(&incoming.version)
+            .bind(&incoming.ai_type)
+            .bind(&incoming.platform_type)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let existing = match existing {
+                Some(existing) => existing,
+                None => {
+                    let created =
+                        Self::insert(&mut tx, &request, &t!("template.revision_imported")).await?;
+                    report.push(
+                        index,
+                        &created.name,
+                        ImportAction::Created,
+                        created.id,
+                        None,
+                    );
+                    continue;
+                }
+            };
+
+            match strategy {
+                ConflictStrategy::Skip => report.push(
+                    index,
+                    &existing.name,
+                    ImportAction::Skipped,
+                    existing.id,
+                    Some(t!("import.template_exists")),
+                ),
+                ConflictStrategy::Overwrite => {
+                    if existing.is_builtin == Some(1) {
+                        report.push(
+                            index,
+                            &existing.name,
+                            ImportAction::Failed,
+                            existing.id,
+                            Some(t!("import.builtin_protected")),
+                        );
+                        continue;
+                    }
+
+                    let overwritten = Self {
+                        id: existing.id,
+                        is_builtin: existing.is_builtin,
+                        usage_count: existing.usage_count,
+                        created_at: existing.created_at,
+                        updated_at: existing.updated_at,
+                        ..incoming
+                    };
+                    if classify_change(&existing, &overwritten).is_none() {
+                        report.push(
+                            index,
+                            &existing.name,
+                            ImportAction::Skipped,
+                            existing.id,
+                            Some(t!("import.identical")),
+                        );
+                        continue;
+                    }
+
+                    let id = existing.id.unwrap_or_default();
+                    let version = Some(overwritten.version.clone());
+                    let saved = Self::save_change_in(
+                        &mut tx,
+                        id,
+                        |_| overwritten,
+                        version,
+                        Some(t!("template.revision_overwritten")),
+                    )
+                    .await?;
+                    report.push(
+                        index,
+                        &existing.name,
+                        ImportAction::Updated,
+                        saved.and_then(|t| t.id),
+                        None,
+                    );
+                }
+                ConflictStrategy::Rename => {
+                    let taken: HashSet<String> = sqlx::query_scalar(
+                        "SELECT name FROM mcp_templates WHERE version = ? AND ai_type = ? AND platform_type = ?",
+                    )
+                    .bind(&incoming.version)
+                    .bind(&incoming.ai_type)
+                    .bind(&incoming.platform_type)
+                    .fetch_all(&mut *tx)
+                    .await?
+                    .into_iter()
+                    .collect();
+                    request.name = next_available_name(&request.name, |name| taken.contains(name));
+
+                    let created =
+                        Self::insert(&mut tx, &request, &t!("template.revision_imported")).await?;
+                    report.push(
+                        index,
+                        &created.name,
+                        ImportAction::Created,
+                        created.id,
+                        Some(t!("import.renamed", name = created.name)),
+                    );
+                }
+                ConflictStrategy::BumpVersion => {
+                    let versions: Vec<McpTemplate> = sqlx::query_as(
+                        "SELECT * FROM mcp_templates WHERE name = ? AND ai_type = ? AND platform_type = ?",
+                    )
+                    .bind(&incoming.name)
+                    .bind(&incoming.ai_type)
+                    .bind(&incoming.platform_type)
+                    .fetch_all(&mut *tx)
+                    .await?;
+                    let latest = versions
+                        .iter()
+                        .max_by(|a, b| compare_versions(&a.version, &b.version))
+                        .unwrap_or(&existing);
+                    let bump = classify_change(latest, &incoming).unwrap_or(VersionBump::Patch);
+                    let mut version = bump_version(&latest.version, bump);
+                    while versions.iter().any(|t| t.version == version) {
+                        version = bump_version(&version, VersionBump::Patch);
+                    }
+                    request.version = Some(version.clone());
+
+                    let created =
+                        Self::insert(&mut tx, &request, &t!("template.revision_imported")).await?;
+                    report.push(
+                        index,
+                        &created.name,
+                        ImportAction::Created,
+                        created.id,
+                        Some(t!("import.version_bumped", version = version)),
+                    );
+                }
+            }
+        }
+
+        report.committed = report.should_commit();
+        if report.committed {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+        Ok(report)
+    }
+
+    async fn save_change(
+        pool: &SqlitePool,
+        id: i64,
+        build: impl FnOnce(&Self) -> Self,
+        version: Option<String>,
+        note: Option<String>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let saved = Self::save_change_in(&mut tx, id, build, version, note).await?;
+        tx.commit().await?;
+        Ok(saved)
+    }
+
+    /// 在调用方的事务内保存改动，内容没有变化时不写入并返回原模板
+    async fn save_change_in(
+        conn: &mut SqliteConnection,
+        id: i64,
+        build: impl FnOnce(&Self) -> Self,
+        version: Option<String>,
+        note: Option<String>,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let existing =
+            match sqlx::query_as::<_, McpTemplate>("SELECT * FROM mcp_templates WHERE id = ?")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await?
+            {
+                Some(existing) => existing,
+                None => return Ok(None),
+            };
+
+        let mut updated = build(&existing);
+        let bump = classify_change(&existing, &updated);
+        updated.version = match version {
+            Some(version) => version,
+            None => match bump {
+                Some(bump) => bump_version(&existing.version, bump),
+                None => existing.version.clone(),
+            },
+        };
+        if bump.is_none() && updated.version == existing.version {
+            return Ok(Some(existing));
+        }
+
+        McpTemplateRevision::ensure_baseline(&mut *conn, id).await?;
+        let saved = sqlx::query_as::<_, McpTemplate>(
+            r#"
+            UPDATE mcp_templates SET
+                name = ?,
+                version = ?,
+                config_content = ?,
+                description = ?,
+                category = ?,
+                tags = ?,
+                parameters = ?,
+                platform_overrides = ?,
+                updated_at = ?
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&updated.name)
+        .bind(&updated.version)
+        .bind(&updated.config_content)
+        .bind(&updated.description)
+        .bind(&updated.category)
+        .bind(&updated.tags)
+        .bind(&updated.parameters)
+        .bind(&updated.platform_overrides)
+        .bind(Utc::now())
+        .bind(id)
+        .fetch_all(&mut *conn)
+        .await
+        .and_then(returned_row)?;
+
+        McpTemplateRevision::record(&mut *conn, &saved, note.as_deref()).await?;
+        Self::index(&mut *conn, &saved).await?;
+        Ok(Some(saved))
+    }
+
+    /// 把更新请求合并到当前模板上，未提供的字段保持不变
+    pub fn apply_update(&self, request: &UpdateMcpTemplateRequest) -> Self {
+        let mut updated = self.clone();
+        if let Some(name) = &request.name {
+            updated.name = name.clone();
+        }
+        if let Some(version) = &request.version {
+            updated.version = version.clone();
+        }
+        if let Some(config_content) = &request.config_content {
+            updated.config_content = config_content.clone();
+        }
+        if request.description.is_some() {
+            updated.description = request.description.clone();
+        }
+        if request.category.is_some() {
+            updated.category = request.category.clone();
+        }
+        if let Some(tags) = &request.tags {
+            updated.tags = Some(serde_json::to_string(tags).unwrap_or_default());
+        }
+        if request.parameters.is_some() {
+            updated.parameters = parameters_to_json(&request.parameters);
+        }
+        if request.platform_overrides.is_some() {
+            updated.platform_overrides = overrides_to_json(&request.platform_overrides);
+        }
+        updated
+    }
+
+    /// 删除MCP模板
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM mcp_templates WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 增加使用计数
+    pub async fn increment_usage_count(pool: &SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+        let result =
+            sqlx::query("UPDATE mcp_templates SET usage_count = usage_count + 1 WHERE id = ?")
+                .bind(id)
+                .execute(pool)
+                .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 同步模板的全文索引并登记分类，在写入模板的事务内调用
+    async fn index(conn: &mut SqliteConnection, template: &McpTemplate) -> Result<(), sqlx::Error> {
+        let id = match template.id {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        sqlx::query("DELETE FROM mcp_templates_fts WHERE rowid = ?")
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            "INSERT INTO mcp_templates_fts (rowid, name, description, tags, commands) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(template.get_tags().join(" "))
+        .bind(template.server_commands().join("\n"))
+        .execute(&mut *conn)
+        .await?;
+
+        if let Some(category) = template.category.as_deref().filter(|c| !c.is_empty()) {
+            McpTemplateCategory::ensure(&mut *conn, category, None).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 重建全部模板的全文索引，返回索引的模板数
+    pub async fn rebuild_search_index(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+        let templates = Self::get_all(pool).await?;
+
+        let mut tx = pool.begin().await?;
+        sqlx::query("DELETE FROM mcp_templates_fts")
+            .execute(&mut *tx)
+            .await?;
+        for template in &templates {
+            Self::index(&mut tx, template).await?;
+        }
+        tx.commit().await?;
+
+        Ok(templates.len())
+    }
+
+    /// 各服务器的启动命令行或远程地址，配置无法解析时返回空列表
+    pub fn server_commands(&self) -> Vec<String> {
+        let content = TemplateRenderer::render_for_validation(&self.config_content)
+            .unwrap_or_else(|_| self.config_content.clone());
+        let servers =
+            McpProbe::servers_from_config(&content, &self.ai_type, "(default)").unwrap_or_default();
+
+        servers
+            .into_iter()
+            .filter_map(|(_, server)| {
+                if let Some(url) = server.get("url").and_then(|url| url.as_str()) {
+                    return Some(url.to_string());
+                }
+                let command = server.get("command").and_then(|c| c.as_str())?;
+                let args = server
+                    .get("args")
+                    .and_then(|args| args.as_array())
+                    .map(|args| {
+                        args.iter()
+                            .filter_map(|arg| arg.as_str())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                Some(
+                    std::iter::once(command)
+                        .chain(args)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+            })
+            .collect()
+    }
+
+    /// 验证模板配置
+    pub fn validate_config(&self) -> McpTemplateValidationResult {
+        let mut result = McpTemplateValidationResult {
+            valid: true,
+            errors: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        // 基本验证
+        if self.name.trim().is_empty() {
+            result.errors.push(t!("template.name_empty"));
+            result.valid = false;
+        }
+
+        if self.config_content.trim().is_empty() {
+            result.errors.push(t!("template.content_empty"));
+            result.valid = false;
+        }
+
+        if self.ai_type != "claude" && self.ai_type != "codex" {
+            result.errors.push(t!("template.ai_type_invalid"));
+            result.valid = false;
+        }
+
+        if !PLATFORM_TYPES.contains(&self.platform_type.as_str()) {
+            result.errors.push(t!("template.platform_type_invalid"));
+            result.valid = false;
+        }
+
+        // 参数验证
+        let parameters = match self.get_parameters() {
+            Ok(parameters) => parameters,
+            Err(e) => {
+                result.errors.push(e);
+                result.valid = false;
+                Vec::new()
+            }
+        };
+        let (parameter_errors, parameter_warnings) =
+            TemplateRenderer::check(&self.config_content, &parameters);
+        if !parameter_errors.is_empty() {
+            result.errors.extend(parameter_errors);
+            result.valid = false;
+        }
+        result.warnings.extend(parameter_warnings);
+
+        // 格式验证
+        let (structure_errors, structure_warnings) =
+            check_content(&self.ai_type, &self.platform_type, &self.config_content);
+        if !structure_errors.is_empty() {
+            result.errors.extend(structure_errors);
+            result.valid = false;
+        }
+        result.warnings.extend(structure_warnings);
+
+        // 平台覆盖配置验证
+        match self.get_platform_overrides() {
+            Ok(overrides) => {
+                for (platform, content) in overrides {
+                    if platform != "unix" && platform != "windows" {
+                        result.errors.push(t!(
+                            "template.override_platform_invalid",
+                            platform = platform
+                        ));
+                        result.valid = false;
+                        continue;
+                    }
+                    if platform == self.platform_type {
+                        result.warnings.push(t!(
+                            "template.override_replaces_content",
+                            platform = platform
+                        ));
+                    }
+
+                    let (errors, warnings) = check_content(&self.ai_type, &platform, &content);
+                    if !errors.is_empty() {
+                        result.errors.extend(errors.into_iter().map(|e| {
+                            t!("template.override_issue", platform = platform, message = e)
+                        }));
+                        result.valid = false;
+                    }
+                    result.warnings.extend(
+                        warnings.into_iter().map(|w| {
+                            t!("template.override_issue", platform = platform, message = w)
+                        }),
+                    );
+                }
+            }
+            Err(e) => {
+                result.errors.push(e);
+                result.valid = false;
+            }
+        }
+
+        result
+    }
+
+    /// 获取标签列表
+    pub fn get_tags(&self) -> Vec<String> {
+        self.tags
+            .as_deref()
+            .and_then(|tags_json| serde_json::from_str::<Vec<String>>(tags_json).ok())
+            .unwrap_or_default()
+    }
+
+    /// 设置标签列表
+    pub fn set_tags(&mut self, tags: Vec<String>) {
+        match serde_json::to_string(&tags) {
+            Ok(tags_json) => self.tags = Some(tags_json),
+            Err(_) => self.tags = None,
+        }
+    }
+
+    /// 获取参数定义
+    pub fn get_parameters(&self) -> Result<Vec<TemplateParameter>, String> {
+        match &self.parameters {
+            Some(json) if !json.trim().is_empty() => {
+                serde_json::from_str(json).map_err(|e| t!("template.parameters_invalid", error = e))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// 获取平台覆盖配置
+    pub fn get_platform_overrides(&self) -> Result<HashMap<String, String>, String> {
+        match &self.platform_overrides {
+            Some(json) if !json.trim().is_empty() => {
+                serde_json::from_str(json).map_err(|e| t!("template.overrides_invalid", error = e))
+            }
+            _ => Ok(HashMap::new()),
+        }
+    }
+
+    /// 取得目标平台的配置内容：优先使用该平台的覆盖配置，其次是同平台的模板内容，
+    /// 否则由模板内容转换得到
+    pub fn content_for_platform(&self, platform_type: &str) -> Result<String, String> {
+        if let Some(content) = self.get_platform_overrides()?.remove(platform_type) {
+            return Ok(content);
+        }
+
+        PlatformTransformer::transform(
+            &self.config_content,
+            &self.ai_type,
+            &self.platform_type,
+            platform_type,
+        )
+    }
+
+    /// 用传入的值和已保存的值渲染目标平台的配置内容，传入的值优先。
+    /// home_dir 为空时 ${home_dir} 使用当前用户的主目录
+    pub async fn render(
+        &self,
+        pool: &SqlitePool,
+        cipher: &SecretCipher,
+        platform_type: &str,
+        home_dir: Option<&Path>,
+        overrides: HashMap<String, String>,
+    ) -> Result<String, String> {
+        let parameters = self.get_parameters()?;
+        let template_id = self.id.unwrap_or_default();
+
+        let mut values = McpTemplateValue::load_decrypted(pool, cipher, template_id)
+            .await
+            .map_err(|e| t!("template.values_read_failed", error = e))?;
+        values.extend(overrides);
+
+        if !values.contains_key("home_dir") {
+            if let Some(home) = home_dir.map(Path::to_path_buf).or_else(dirs::home_dir) {
+                values.insert("home_dir".to_string(), home.to_string_lossy().to_string());
+            }
+        }
+
+        let content = self.content_for_platform(platform_type)?;
+        let rendered = TemplateRenderer::render(&content, &self.ai_type, &parameters, &values)?;
+
+        // 渲染结果必须仍是有效的配置
+        let parsed = match self.ai_type.as_str() {
+            "claude" => serde_json::from_str::<serde_json::Value>(&rendered).is_ok(),
+            "codex" => toml::from_str::<toml::Value>(&rendered).is_ok(),
+            _ => true,
+        };
+        if !parsed {
+            return Err(t!("template.rendered_invalid"));
+        }
+
+        Ok(rendered)
+    }
+
+    /// 获取预览配置（用于显示）
+    pub fn get_preview_config(&self) -> String {
+        // 截取前200个字符作为预览
+        if self.config_content.len() > 200 {
+            format!("{}...", &self.config_content[..200])
+        } else {
+            self.config_content.clone()
+        }
+    }
+
+    /// 检查是否为内置模板
+    pub fn is_builtin(&self) -> bool {
+        self.is_builtin.unwrap_or(0) == 1
+    }
+
+    /// 克隆模板
+    pub async fn clone_template(
+        pool: &SqlitePool,
+        id: i64,
+        new_name: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        if let Some(original) = Self::get_by_id(pool, id).await? {
+            let request = CreateMcpTemplateRequest {
+                name: new_name.to_string(),
+                version: Some("1.0.0".to_string()),
+                ai_type: original.ai_type.clone(),
+                platform_type: original.platform_type.clone(),
+                config_content: original.config_content.clone(),
+                description: Some(t!("template.cloned_from", name = original.name)),
+                category: original.category.clone(),
+                tags: Some(original.get_tags()),
+                parameters: original.get_parameters().ok(),
+                platform_overrides: original.get_platform_overrides().ok(),
+            };
+
+            Self::create(pool, request).await.map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl McpTemplateValue {
+    pub fn is_secret(&self) -> bool {
+        self.is_secret.unwrap_or(0) == 1
+    }
+
+    /// 获取模板已保存的参数取值
+    pub async fn get_by_template(
+        pool: &SqlitePool,
+        template_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplateValue>(
+            "SELECT * FROM mcp_template_values WHERE template_id = ? ORDER BY name ASC",
+        )
+        .bind(template_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 保存参数取值，密钥参数加密后保存；空字符串表示清除该参数
+    pub async fn set_values(
+        pool: &SqlitePool,
+        cipher: &SecretCipher,
+        template_id: i64,
+        parameters: &[TemplateParameter],
+        values: HashMap<String, String>,
+    ) -> Result<(), AppError> {
+        let now = Utc::now();
+        let mut tx = pool
+            .begin()
+            .await
+            .context(t!("database.transaction_begin_failed"))?;
+
+        for (name, value) in values {
+            let secret = parameters
+                .iter()
+                .any(|parameter| parameter.name == name && parameter.secret);
+
+            if value.is_empty() {
+                sqlx::query("DELETE FROM mcp_template_values WHERE template_id = ? AND name = ?")
+                    .bind(template_id)
+                    .bind(&name)
+                    .execute(&mut *tx)
+                    .await
+                    .context(t!("template.value_clear_failed", name = name))?;
+                continue;
+            }
+
+            let stored = if secret {
+                cipher.encrypt(&value)?
+            } else {
+                value
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO mcp_template_values (
+                    template_id, name, value, is_secret, created_at, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT(template_id, name) DO UPDATE SET
+                    value = excluded.value,
+                    is_secret = excluded.is_secret,
+                    updated_at = excluded.updated_at
+                "#,
+            )
+            .bind(template_id)
+            .bind(&name)
+            .bind(&stored)
+            .bind(if secret { 1 } else { 0 })
+            .bind(now)
+            .bind(now)
+            .execute(&mut *tx)
+            .await
+            .context(t!("template.value_save_failed", name = name))?;
+        }
+
+        tx.commit()
+            .await
+            .context(t!("database.transaction_commit_failed"))
+    }
+
+    /// 读取并解密模板已保存的参数取值
+    pub async fn load_decrypted(
+        pool: &SqlitePool,
+        cipher: &SecretCipher,
+        template_id: i64,
+    ) -> Result<HashMap<String, String>, String> {
+        let rows = Self::get_by_template(pool, template_id)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        let mut values = HashMap::new();
+        for row in rows {
+            let value = if row.is_secret() {
+                cipher
+                    .decrypt(&row.value)
+                    .map_err(|e| t!("template.value_decrypt_failed", name = row.name, error = e))?
+            } else {
+                row.value
+            };
+            values.insert(row.name, value);
+        }
+
+        Ok(values)
+    }
+
+    /// 按参数定义列出取值，密钥参数只返回掩码
+    pub fn to_views(rows: &[Self], parameters: &[TemplateParameter]) -> Vec<McpTemplateValueView> {
+        let mut views: Vec<McpTemplateValueView> = parameters
+            .iter()
+            .map(|parameter| {
+                let row = rows.iter().find(|row| row.name == parameter.name);
+                McpTemplateValueView {
+                    name: parameter.name.clone(),
+                    value: row.map(|row| {
+                        if row.is_secret() {
+                            SECRET_MASK.to_string()
+                        } else {
+                            row.value.clone()
+                        }
+                    }),
+                    is_secret: parameter.secret,
+                    is_set: row.is_some(),
+                }
+            })
+            .collect();
+
+        // 参数定义之外的取值（例如内置变量）
+        for row in rows {
+            if !parameters
+                .iter()
+                .any(|parameter| parameter.name == row.name)
+            {
+                views.push(McpTemplateValueView {
+                    name: row.name.clone(),
+                    value: Some(if row.is_secret() {
+                        SECRET_MASK.to_string()
+                    } else {
+                        row.value.clone()
+                    }),
+                    is_secret: row.is_secret(),
+                    is_set: true,
+                });
+            }
+        }
+
+        views
+    }
+}
+
+impl McpTemplateTools {
+    /// 获取模板缓存的工具列表
+    pub async fn get_by_template(
+        pool: &SqlitePool,
+        template_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplateTools>(
+            "SELECT * FROM mcp_template_tools WHERE template_id = ? ORDER BY server_id ASC",
+        )
+        .bind(template_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 保存一次成功的握手结果，覆盖该服务器之前的记录
+    pub async fn save(
+        pool: &SqlitePool,
+        template_id: i64,
+        result: &McpProbeResult,
+    ) -> Result<Self, sqlx::Error> {
+        let tools = serde_json::to_string(&result.tools).unwrap_or_else(|_| "[]".to_string());
+
+        sqlx::query_as::<_, McpTemplateTools>(
+            r#"
+            INSERT INTO mcp_template_tools (
+                template_id, server_id, server_name, server_version,
+                protocol_version, tools, checked_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(template_id, server_id) DO UPDATE SET
+                server_name = excluded.server_name,
+                server_version = excluded.server_version,
+                protocol_version = excluded.protocol_version,
+                tools = excluded.tools,
+                checked_at = excluded.checked_at
+            RETURNING *
+            "#,
+        )
+        .bind(template_id)
+        .bind(&result.server_id)
+        .bind(&result.server_name)
+        .bind(&result.server_version)
+        .bind(&result.protocol_version)
+        .bind(&tools)
+        .bind(Utc::now())
+        .fetch_all(pool)
+        .await
+        .and_then(returned_row)
+    }
+}
+
+impl McpTemplateRevision {
+    /// 追加一条修订，修订号在模板内递增
+    pub async fn record(
+        conn: &mut SqliteConnection,
+        template: &McpTemplate,
+        note: Option<&str>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplateRevision>(
+            r#"
+            INSERT INTO mcp_template_revisions (
+                template_id, revision, name, version, config_content, description,
+                category, tags, parameters, platform_overrides, note, created_at
+            ) VALUES (
+                ?,
+                (SELECT COALESCE(MAX(revision), 0) + 1 FROM mcp_template_revisions WHERE template_id = ?),
+                ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
+            )
+            RETURNING *
+            "#,
+        )
+        .bind(template.id)
+        .bind(template.id)
+        .bind(&template.name)
+        .bind(&template.version)
+        .bind(&template.config_content)
+        .bind(&template.description)
+        .bind(&template.category)
+        .bind(&template.tags)
+        .bind(&template.parameters)
+        .bind(&template.platform_overrides)
+        .bind(note)
+        .bind(Utc::now())
+        .fetch_all(&mut *conn)
+        .await
+        .and_then(returned_row)
+    }
+
+    /// 早于修订历史创建的模板没有修订，修改前先把当前内容记为第一个修订
+    pub async fn ensure_baseline(
+        conn: &mut SqliteConnection,
+        template_id: i64,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO mcp_template_revisions (
+                template_id, revision, name, version, config_content, description,
+                category, tags, parameters, platform_overrides, note, created_at
+            )
+            SELECT id, 1, name, version, config_content, description,
+                category, tags, parameters, platform_overrides, ?,
+                COALESCE(updated_at, created_at, CURRENT_TIMESTAMP)
+            FROM mcp_templates
+            WHERE id = ?
+              AND NOT EXISTS (SELECT 1 FROM mcp_template_revisions WHERE template_id = ?)
+            "#,
+        )
+        .bind(t!("template.revision_baseline"))
+        .bind(template_id)
+        .bind(template_id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 获取模板的全部修订，最新的在前
+    pub async fn get_by_template(
+        pool: &SqlitePool,
+        template_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplateRevision>(
+            "SELECT * FROM mcp_template_revisions WHERE template_id = ? ORDER BY revision DESC",
+        )
+        .bind(template_id)
+        .fetch_all(pool)
+        .await
+    }
+
+    pub async fn get(
+        pool: &SqlitePool,
+        template_id: i64,
+        revision: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplateRevision>(
+            "SELECT * FROM mcp_template_revisions WHERE template_id = ? AND revision = ?",
+        )
+        .bind(template_id)
+        .bind(revision)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// 以修订内容替换模板内容，ID、类型、平台和使用计数保持不变
+    pub fn apply_to(&self, template: &McpTemplate) -> McpTemplate {
+        McpTemplate {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            config_content: self.config_content.clone(),
+            description: self.description.clone(),
+            category: self.category.clone(),
+            tags: self.tags.clone(),
+            parameters: self.parameters.clone(),
+            platform_overrides: self.platform_overrides.clone(),
+            ..template.clone()
+        }
+    }
+
+    /// 与另一条修订比较，self 为旧修订
+    pub fn diff(&self, other: &Self) -> McpTemplateRevisionDiff {
+        let fields = [
+            ("name", Some(&self.name), Some(&other.name)),
+            ("version", Some(&self.version), Some(&other.version)),
+            (
+                "config_content",
+                Some(&self.config_content),
+                Some(&other.config_content),
+            ),
+            (
+                "description",
+                self.description.as_ref(),
+                other.description.as_ref(),
+            ),
+            ("category", self.category.as_ref(), other.category.as_ref()),
+            ("tags", self.tags.as_ref(), other.tags.as_ref()),
+            (
+                "parameters",
+                self.parameters.as_ref(),
+                other.parameters.as_ref(),
+            ),
+            (
+                "platform_overrides",
+                self.platform_overrides.as_ref(),
+                other.platform_overrides.as_ref(),
+            ),
+        ];
+        let changed_fields = fields
+            .iter()
+            .filter(|(_, old, new)| old != new)
+            .map(|(name, _, _)| name.to_string())
+            .collect();
+
+        McpTemplateRevisionDiff {
+            template_id: self.template_id,
+            from_revision: self.revision,
+            to_revision: other.revision,
+            from_version: self.version.clone(),
+            to_version: other.version.clone(),
+            changed_fields,
+            content: unified_diff(
+                &self.config_content,
+                &other.config_content,
+                &format!("revision {} ({})", self.revision, self.version),
+                &format!("revision {} ({})", other.revision, other.version),
+                3,
+            ),
+        }
+    }
+}
+
+impl McpTemplateCategory {
+    /// 获取全部分类及其模板数
+    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, McpTemplateCategory>(
+            r#"
+            SELECT
+                c.name as name,
+                COUNT(t.id) as count,
+                c.description as description,
+                c.sort_order as sort_order
+            FROM mcp_template_categories c
+            LEFT JOIN mcp_templates t ON t.category = c.name
+            GROUP BY c.id
+            ORDER BY c.sort_order ASC, count DESC, c.name ASC
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 登记分类，已存在时只补全缺失的描述
+    pub async fn ensure(
+        conn: &mut SqliteConnection,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO mcp_template_categories (name, description)
+            VALUES (?, ?)
+            ON CONFLICT(name) DO UPDATE SET
+                description = COALESCE(mcp_template_categories.description, excluded.description)
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .execute(conn)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 创建或修改分类
+    pub async fn save(
+        pool: &SqlitePool,
+        name: &str,
+        description: Option<&str>,
+        sort_order: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO mcp_template_categories (name, description, sort_order)
+            VALUES (?, ?, COALESCE(?, 0))
+            ON CONFLICT(name) DO UPDATE SET
+                description = excluded.description,
+                sort_order = COALESCE(?, mcp_template_categories.sort_order),
+                updated_at = CURRENT_TIMESTAMP
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(sort_order)
+        .bind(sort_order)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 删除分类，仍有模板使用时不删除并返回 false
+    pub async fn delete(pool: &SqlitePool, name: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM mcp_template_categories
+            WHERE name = ? AND NOT EXISTS (SELECT 1 FROM mcp_templates WHERE category = ?)
+            "#,
+        )
+        .bind(name)
+        .bind(name)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+/// 拆分后的搜索关键字。trigram 分词至少需要三个字符，更短的词改用 LIKE 匹配
+struct SearchKeyword {
+    match_expression: Option<String>,
+    short_terms: Vec<String>,
+}
+
+impl SearchKeyword {
+    fn parse(keyword: &str) -> Self {
+        let mut phrases = Vec::new();
+        let mut short_terms = Vec::new();
+        for term in keyword.split_whitespace() {
+            if term.chars().count() >= 3 {
+                phrases.push(format!("\"{}\"", term.replace('"', "\"\"")));
+            } else {
+                short_terms.push(
+                    term.replace('\\', "\\\\")
+                        .replace('%', "\\%")
+                        .replace('_', "\\_"),
+                );
+            }
+        }
+
+        Self {
+            match_expression: if phrases.is_empty() {
+                None
+            } else {
+                Some(phrases.join(" AND "))
+            },
+            short_terms,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.match_expression.is_none() && self.short_terms.is_empty()
+    }
+}
+
+/// 判断两次模板内容之间的改动级别，没有改动时返回 None
+pub fn classify_change(old: &McpTemplate, new: &McpTemplate) -> Option<VersionBump> {
+    let old_parameters = old.get_parameters().unwrap_or_default();
+    let new_parameters = new.get_parameters().unwrap_or_default();
+
+    let servers_removed = {
+        let new_ids = server_ids(&new.ai_type, &new.config_content);
+        server_ids(&old.ai_type, &old.config_content)
+            .iter()
+            .any(|id| !new_ids.contains(id))
+    };
+    let parameters_broken = old_parameters.iter().any(|old_parameter| {
+        match new_parameters.iter().find(|p| p.name == old_parameter.name) {
+            Some(new_parameter) => new_parameter.required && !old_parameter.required,
+            None => true,
+        }
+    }) || new_parameters.iter().any(|new_parameter| {
+        new_parameter.required
+            && new_parameter.default.is_none()
+            && !old_parameters.iter().any(|p| p.name == new_parameter.name)
+    });
+
+    if servers_removed || parameters_broken {
+        Some(VersionBump::Major)
+    } else if old.config_content != new.config_content
+        || old_parameters != new_parameters
+        || old.get_platform_overrides().unwrap_or_default()
+            != new.get_platform_overrides().unwrap_or_default()
+    {
+        Some(VersionBump::Minor)
+    } else if old.name != new.name
+        || old.description != new.description
+        || old.category != new.category
+        || old.get_tags() != new.get_tags()
+    {
+        Some(VersionBump::Patch)
+    } else {
+        None
+    }
+}
+
+/// 按级别升级版本号，例如 1.2.3 升级次版本得到 1.3.0；无法解析的段按 0 处理
+pub fn bump_version(version: &str, bump: VersionBump) -> String {
+    let trimmed = version.trim();
+    let prefix = if trimmed.starts_with('v') { "v" } else { "" };
+    let core = trimmed
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default();
+    let mut parts = core
+        .split('.')
+        .map(|part| part.parse::<u64>().unwrap_or(0))
+        .chain(std::iter::repeat(0));
+    let (major, minor, patch) = (
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+        parts.next().unwrap_or(0),
+    );
+
+    match bump {
+        VersionBump::Major => format!("{}{}.0.0", prefix, major + 1),
+        VersionBump::Minor => format!("{}{}.{}.0", prefix, major, minor + 1),
+        VersionBump::Patch => format!("{}{}.{}.{}", prefix, major, minor, patch + 1),
+    }
+}
+
+/// 配置中定义的服务器ID，无法解析时返回空列表
+fn server_ids(ai_type: &str, content: &str) -> Vec<String> {
+    let content =
+        TemplateRenderer::render_for_validation(content).unwrap_or_else(|_| content.to_string());
+    match ai_type {
+        "claude" => serde_json::from_str::<serde_json::Value>(&content)
+            .ok()
+            .and_then(|root| claude_servers(root, "(default)").ok())
+            .map(|servers| servers.into_iter().map(|(id, _)| id).collect())
+            .unwrap_or_default(),
+        "codex" => toml::from_str::<toml::Value>(&content)
+            .ok()
+            .and_then(|root| {
+                root.get("mcp_servers")
+                    .and_then(|servers| servers.as_table())
+                    .map(|servers| servers.keys().cloned().collect())
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+/// 比较点分数字版本号，缺失的段按 0 处理，无法解析的段按字符串比较
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<String> {
+        version
+            .trim()
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map(|part| part.to_string())
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+
+    for index in 0..a.len().max(b.len()) {
+        let left = a.get(index).map(String::as_str).unwrap_or("0");
+        let right = b.get(index).map(String::as_str).unwrap_or("0");
+        let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+            (Ok(left), Ok(right)) => left.cmp(&right),
+            _ => left.cmp(right),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// 检查配置格式和服务器定义。占位符应位于字符串中，原文无法解析时再用占位值替换后解析
+fn check_content(ai_type: &str, platform_type: &str, content: &str) -> (Vec<String>, Vec<String>) {
+    let substituted =
+        TemplateRenderer::render_for_validation(content).unwrap_or_else(|_| content.to_string());
+
+    match ai_type {
+        "claude" => {
+            // Claude使用JSON格式
+            match serde_json::from_str::<serde_json::Value>(content)
+                .or_else(|_| serde_json::from_str::<serde_json::Value>(&substituted))
+            {
+                Ok(content) => McpServerValidator::validate_claude(&content, platform_type),
+                Err(_) => (vec![t!("template.claude_json_invalid")], Vec::new()),
+            }
+        }
+        "codex" => {
+            // Codex使用TOML格式
+            match toml::from_str::<toml::Value>(content)
+                .or_else(|_| toml::from_str::<toml::Value>(&substituted))
+            {
+                Ok(content) => McpServerValidator::validate_codex(&content, platform_type),
+                Err(_) => (vec![t!("template.codex_toml_invalid")], Vec::new()),
+            }
+        }
+        _ => (Vec::new(), Vec::new()),
+    }
+}
+
+fn overrides_to_json(overrides: &Option<HashMap<String, String>>) -> Option<String> {
+    overrides
+        .as_ref()
+        .map(|overrides| serde_json::to_string(overrides).unwrap_or_default())
+}
+
+fn parameters_to_json(parameters: &Option<Vec<TemplateParameter>>) -> Option<String> {
+    parameters
+        .as_ref()
+        .map(|parameters| serde_json::to_string(parameters).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use crate::services::mcp_probe::McpToolInfo;
+    use sqlx::SqlitePool;
+    use tempfile::{tempdir, TempDir};
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn create_test_pool() -> TestDb {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+
+        TestDb {
+            _dir: temp_dir,
+            pool,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_create_mcp_template() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let request = CreateMcpTemplateRequest {
+            name: "Test Template".to_string(),
+            version: Some("1.0.0".to_string()),
+            ai_type: "claude".to_string(),
+            platform_type: "unix".to_string(),
+            config_content: r#"{"type": "stdio", "command": "npx", "args": ["-y", "example"]}"#
+                .to_string(),
+            description: Some("Test template".to_string()),
+            category: Some("test".to_string()),
+            tags: Some(vec!["test".to_string(), "example".to_string()]),
+            parameters: None,
+            platform_overrides: None,
+        };
+
+        let template = McpTemplate::create(pool, request).await.unwrap();
+        assert!(template.id.is_some());
+        assert_eq!(template.name, "Test Template");
+        assert_eq!(template.ai_type, "claude");
+        assert_eq!(template.platform_type, "unix");
+    }
+
+    #[tokio::test]
+    async fn test_validate_claude_template() {
+        let template = McpTemplate {
+            id: None,
+            name: "Test".to_string(),
+            version: "1.0.0".to_string(),
+            ai_type: "claude".to_string(),
+            platform_type: "unix".to_string(),
+            config_content: r#"{"type": "stdio", "command": "npx"}"#.to_string(),
+            description: None,
+            is_builtin: Some(0),
+            category: None,
+            tags: None,
+            usage_count: Some(0),
+            parameters: None,
+            platform_overrides: None,
+            created_at: None,
+            updated_at: None,
+        };
+
+        let result = template.validate_config();
+        assert!(result.valid);
+        assert!(result.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_invalid_json() {
+        let template = McpTemplate {
+            id: None,
+            name: "Test".to_string(),
+            version: "1.0.0".to_string(),
+            ai_type: "claude".to_string(),
+            platform_type: "unix".to_string(),
+            config_content: r#"{"type": "stdio", "command": "npx""#.to_string(), // Invalid JSON
+            description: None,
+            is_builtin: Some(0),
+            category: None,
+            tags: None,
+            usage_count: Some(0),
+            parameters: None,
+            platform_overrides: None,
+            created_at: None,
+            updated_at: None,
+        };
+
+        let result = template.validate_config();
+        assert!(!result.valid);
+        assert!(!result.errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_secret_values_are_encrypted_and_rendered() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+        let cipher = SecretCipher::load_or_create(&test_db._dir.path().join("secret.key")).unwrap();
+
+        let parameters = vec![TemplateParameter {
+            name: "EXA_API_KEY".to_string(),
+            description: Some("Exa API Key".to_string()),
+            required: true,
+            secret: true,
+            default: None,
+        }];
+        let template = McpTemplate::create(
+            pool,
+            CreateMcpTemplateRequest {
+                name: "Exa".to_string(),
+                version: None,
+                ai_type: "codex".to_string(),
+                platform_type: "unix".to_string(),
+                config_content: r#"[mcp_servers.exa]
+command = "npx"
+args = ["-y", "exa-mcp", "--key", "${EXA_API_KEY}"]"#
+                    .to_string(),
+                description: None,
+                category: None,
+                tags: None,
+                parameters: Some(parameters.clone()),
+                platform_overrides: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(template.get_parameters().unwrap(), parameters);
+
+        // 缺少必填参数时无法渲染
+        assert!(template
+            .render(pool, &cipher, "unix", None, HashMap::new())
+            .await
+            .is_err());
+
+        let template_id = template.id.unwrap();
+        McpTemplateValue::set_values(
+            pool,
+            &cipher,
+            template_id,
+            &parameters,
+            HashMap::from([("EXA_API_KEY".to_string(), "sk-exa".to_string())]),
+        )
+        .await
+        .unwrap();
+
+        let rows = McpTemplateValue::get_by_template(pool, template_id)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].is_secret());
+        assert!(!rows[0].value.contains("sk-exa"));
+
+        let views = McpTemplateValue::to_views(&rows, &parameters);
+        assert_eq!(views[0].value.as_deref(), Some(SECRET_MASK));
+
+        let rendered = template
+            .render(pool, &cipher, "unix", None, HashMap::new())
+            .await
+            .unwrap();
+        assert!(rendered.contains(r#""--key", "sk-exa""#));
+    }
+
+    #[tokio::test]
+    async fn test_discovered_tools_are_cached_per_server() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let template =
+            McpTemplate::create(
+                pool,
+                CreateMcpTemplateRequest {
+                    name: "Memory".to_string(),
+                    version: None,
+                    ai_type: "claude".to_string(),
+                    platform_type: "any".to_string(),
+                    config_content:
+                        r#"{"memory": {"command": "npx", "args": ["-y", "server-memory"]}}"#
+                            .to_string(),
+                    description: None,
+                    category: None,
+                    tags: None,
+                    parameters: None,
+                    platform_overrides: None,
+                },
+            )
+            .await
+            .unwrap();
+        let template_id = template.id.unwrap();
+
+        let mut result = McpProbeResult {
+            server_id: "memory".to_string(),
+            success: true,
+            server_name: Some("memory-server".to_string()),
+            server_version: Some("1.0.0".to_string()),
+            protocol_version: Some("2025-06-18".to_string()),
+            tools: vec![McpToolInfo {
+                name: "read_graph".to_string(),
+                description: None,
+            }],
+            error: None,
+            stderr: None,
+            elapsed_ms: 12,
+        };
+        McpTemplateTools::save(pool, template_id, &result)
+            .await
+            .unwrap();
+
+        // 再次测试覆盖之前的结果
+        result.server_version = Some("1.1.0".to_string());
+        result.tools.push(McpToolInfo {
+            name: "create_entities".to_string(),
+            description: Some("Create entities".to_string()),
+        });
+        let saved = McpTemplateTools::save(pool, template_id, &result)
+            .await
+            .unwrap();
+        assert_eq!(saved.server_version.as_deref(), Some("1.1.0"));
+
+        let cached = McpTemplateTools::get_by_template(pool, template_id)
+            .await
+            .unwrap();
+        assert_eq!(cached.len(), 1);
+        let tools: Vec<McpToolInfo> = serde_json::from_str(&cached[0].tools).unwrap();
+        assert_eq!(tools, result.tools);
+
+        // 删除模板时一并删除缓存
+        McpTemplate::delete(pool, template_id).await.unwrap();
+        assert!(McpTemplateTools::get_by_template(pool, template_id)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_update_records_revisions_and_bumps_version() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let template = McpTemplate::create(
+            pool,
+            CreateMcpTemplateRequest {
+                name: "Servers".to_string(),
+                version: Some("1.0.0".to_string()),
+                ai_type: "claude".to_string(),
+                platform_type: "unix".to_string(),
+                config_content: r#"{"memory": {"command": "npx", "args": ["-y", "server-memory"]}, "fetch": {"command": "uvx", "args": ["mcp-server-fetch"]}}"#
+                    .to_string(),
+                description: None,
+                category: None,
+                tags: None,
+                parameters: None,
+                platform_overrides: None,
+            },
+        )
+        .await
+        .unwrap();
+        let template_id = template.id.unwrap();
+
+        let update =
+            |config_content: Option<&str>, description: Option<&str>| UpdateMcpTemplateRequest {
+                id: template_id,
+                name: None,
+                version: None,
+                config_content: config_content.map(|c| c.to_string()),
+                description: description.map(|d| d.to_string()),
+                category: None,
+                tags: None,
+                parameters: None,
+                platform_overrides: None,
+                note: None,
+            };
+
+        // 修改配置为次版本
+        let updated = McpTemplate::update(
+            pool,
+            update(
+                Some(r#"{"memory": {"command": "npx", "args": ["-y", "server-memory@1.2.0"]}, "fetch": {"command": "uvx", "args": ["mcp-server-fetch"]}}"#),
+                None,
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(updated.version, "1.1.0");
+
+        // 只改描述为修订版本，没有改动时不追加修订
+        let updated = McpTemplate::update(pool, update(None, Some("常用服务器")))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.version, "1.1.1");
+        McpTemplate::update(pool, update(None, Some("常用服务器")))
+            .await
+            .unwrap();
+
+        // 删除服务器为主版本
+        let updated = McpTemplate::update(
+            pool,
+            update(
+                Some(r#"{"memory": {"command": "npx", "args": ["-y", "server-memory@1.2.0"]}}"#),
+                None,
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(updated.version, "2.0.0");
+
+        let revisions = McpTemplateRevision::get_by_template(pool, template_id)
+            .await
+            .unwrap();
+        let versions: Vec<&str> = revisions.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, vec!["2.0.0", "1.1.1", "1.1.0", "1.0.0"]);
+
+        let diff = revisions[3].diff(&revisions[0]);
+        assert!(diff.changed_fields.contains(&"config_content".to_string()));
+        assert!(diff.changed_fields.contains(&"description".to_string()));
+        assert!(diff.content.removed > 0);
+
+        // 恢复到第一条修订，恢复本身也是一条新修订
+        let reverted = McpTemplate::revert_to(pool, &revisions[3], None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reverted.config_content, template.config_content);
+        assert_eq!(reverted.description, None);
+        // 版本号不会倒退，恢复内容按改动继续升级
+        assert_eq!(reverted.version, "2.1.0");
+        let latest = McpTemplateRevision::get_by_template(pool, template_id)
+            .await
+            .unwrap();
+        assert_eq!(latest.len(), 5);
+        assert_eq!(latest[0].note.as_deref(), Some("恢复到修订 1"));
+    }
+
+    #[tokio::test]
+    async fn test_search_templates() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let request =
+            |name: &str, content: &str, category: &str, tags: &[&str]| CreateMcpTemplateRequest {
+                name: name.to_string(),
+                version: None,
+                ai_type: "claude".to_string(),
+                platform_type: "any".to_string(),
+                config_content: content.to_string(),
+                description: Some(format!("{} 服务器", name)),
+                category: Some(category.to_string()),
+                tags: Some(tags.iter().map(|tag| tag.to_string()).collect()),
+                parameters: None,
+                platform_overrides: None,
+            };
+        let memory = McpTemplate::create(
+            pool,
+            request(
+                "Memory",
+                r#"{"memory": {"command": "npx", "args": ["-y", "@modelcontextprotocol/server-memory"]}}"#,
+                "tools",
+                &["memory", "graph"],
+            ),
+        )
+        .await
+        .unwrap();
+        let fetch = McpTemplate::create(
+            pool,
+            request(
+                "Fetch",
+                r#"{"fetch": {"command": "uvx", "args": ["mcp-server-fetch"]}}"#,
+                "web",
+                &["http"],
+            ),
+        )
+        .await
+        .unwrap();
+        McpTemplate::create(
+            pool,
+            request(
+                "Docs",
+                r#"{"docs": {"type": "http", "url": "https://mcp.example.com/docs"}}"#,
+                "web",
+                &["http", "docs"],
+            ),
+        )
+        .await
+        .unwrap();
+        McpTemplate::increment_usage_count(pool, fetch.id.unwrap())
+            .await
+            .unwrap();
+
+        let search = |query: McpTemplateQuery| async move {
+            McpTemplate::search(pool, &query)
+                .await
+                .unwrap()
+                .items
+                .into_iter()
+                .map(|t| t.name)
+                .collect::<Vec<_>>()
+        };
+
+        // 按服务器命令、地址和描述搜索
+        let by_command = search(McpTemplateQuery {
+            keyword: Some("server-memory".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(by_command, vec!["Memory"]);
+        let by_url = search(McpTemplateQuery {
+            keyword: Some("example.com".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(by_url, vec!["Docs"]);
+        let short = search(McpTemplateQuery {
+            keyword: Some("uv".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(short, vec!["Fetch"]);
+
+        // 标签需要全部匹配
+        let tagged = search(McpTemplateQuery {
+            tags: vec!["http".to_string(), "docs".to_string()],
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(tagged, vec!["Docs"]);
+
+        let by_usage = search(McpTemplateQuery {
+            category: Some("web".to_string()),
+            sort_by: Some("usage".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(by_usage, vec!["Fetch", "Docs"]);
+
+        let page = McpTemplate::search(
+            pool,
+            &McpTemplateQuery {
+                sort_by: Some("name".to_string()),
+                page: Some(2),
+                page_size: Some(2),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].name, "Memory");
+
+        // 修改后重新索引，删除后从索引中移除
+        McpTemplate::update(
+            pool,
+            UpdateMcpTemplateRequest {
+                id: memory.id.unwrap(),
+                name: None,
+                version: None,
+                config_content: None,
+                description: Some("知识图谱记忆".to_string()),
+                category: None,
+                tags: None,
+                parameters: None,
+                platform_overrides: None,
+                note: None,
+            },
+        )
+        .await
+        .unwrap();
+        let renamed = search(McpTemplateQuery {
+            keyword: Some("知识图谱".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(renamed, vec!["Memory"]);
+        McpTemplate::delete(pool, memory.id.unwrap()).await.unwrap();
+        let indexed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates_fts")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(indexed, 2);
+
+        // 模板使用的分类自动登记
+        McpTemplateCategory::save(pool, "web", Some("网络访问"), Some(1))
+            .await
+            .unwrap();
+        let categories = McpTemplateCategory::get_all(pool).await.unwrap();
+        let web = categories.iter().find(|c| c.name == "web").unwrap();
+        assert_eq!(web.count, 2);
+        assert_eq!(web.description.as_deref(), Some("网络访问"));
+        assert!(!McpTemplateCategory::delete(pool, "web").await.unwrap());
+        assert!(McpTemplateCategory::delete(pool, "tools").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_import_templates_with_conflict_strategies() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let request = |args: &str| CreateMcpTemplateRequest {
+            name: "Memory".to_string(),
+            version: Some("1.0.0".to_string()),
+            ai_type: "claude".to_string(),
+            platform_type: "unix".to_string(),
+            config_content: format!(r#"{{"memory": {{"command": "npx", "args": {}}}}}"#, args),
+            description: None,
+            category: None,
+            tags: None,
+            parameters: None,
+            platform_overrides: None,
+        };
+        let original = McpTemplate::create(pool, request(r#"["-y", "server-memory"]"#))
+            .await
+            .unwrap();
+        let incoming = || vec![request(r#"["-y", "server-memory@2.0.0"]"#)];
+
+        let skipped = McpTemplate::import(pool, incoming(), ConflictStrategy::Skip, false)
+            .await
+            .unwrap();
+        assert_eq!(skipped.skipped, 1);
+        assert!(skipped.committed);
+
+        // 试运行只报告结果，不写入
+        let preview = McpTemplate::import(pool, incoming(), ConflictStrategy::Overwrite, true)
+            .await
+            .unwrap();
+        assert_eq!(preview.updated, 1);
+        assert!(!preview.committed);
+        let unchanged = McpTemplate::get_by_id(pool, original.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(unchanged.config_content, original.config_content);
+
+        let overwritten = McpTemplate::import(pool, incoming(), ConflictStrategy::Overwrite, false)
+            .await
+            .unwrap();
+        assert_eq!(overwritten.items[0].action, ImportAction::Updated);
+        assert_eq!(overwritten.items[0].id, original.id);
+
+        let renamed = McpTemplate::import(pool, incoming(), ConflictStrategy::Rename, false)
+            .await
+            .unwrap();
+        assert_eq!(renamed.items[0].name, "Memory (2)");
+
+        let bumped = McpTemplate::import(pool, incoming(), ConflictStrategy::BumpVersion, false)
+            .await
+            .unwrap();
+        let bumped_id = bumped.items[0].id.unwrap();
+        let bumped_template = McpTemplate::get_by_id(pool, bumped_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(bumped_template.version, "1.0.1");
+
+        // 有失败项时整批回滚，先前的有效项也不会写入
+        let mut batch = vec![CreateMcpTemplateRequest {
+            name: "Fetch".to_string(),
+            ..request(r#"["mcp-server-fetch"]"#)
+        }];
+        batch.push(CreateMcpTemplateRequest {
+            name: "Broken".to_string(),
+            config_content: "{not json".to_string(),
+            ..request("[]")
+        });
+        let failed = McpTemplate::import(pool, batch, ConflictStrategy::Skip, false)
+            .await
+            .unwrap();
+        assert_eq!(failed.created, 1);
+        assert_eq!(failed.failed, 1);
+        assert!(!failed.committed);
+        let names: Vec<String> = McpTemplate::get_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert!(!names.contains(&"Fetch".to_string()));
+    }
+
+    #[test]
+    fn test_bump_version() {
+        assert_eq!(bump_version("1.2.3", VersionBump::Patch), "1.2.4");
+        assert_eq!(bump_version("v1.2.3", VersionBump::Minor), "v1.3.0");
+        assert_eq!(bump_version("1.2.3-beta", VersionBump::Major), "2.0.0");
+        assert_eq!(bump_version("2", VersionBump::Minor), "2.1.0");
+    }
+
+    #[test]
+    fn test_validate_template_with_placeholders() {
+        let template = McpTemplate {
+            id: None,
+            name: "Test".to_string(),
+            version: "1.0.0".to_string(),
+            ai_type: "claude".to_string(),
+            platform_type: "unix".to_string(),
+            config_content:
+                r#"{"type": "stdio", "command": "npx", "args": ["${workspace_dir}", "${TOKEN}"]}"#
+                    .to_string(),
+            description: None,
+            is_builtin: Some(0),
+            category: None,
+            tags: None,
+            usage_count: Some(0),
+            parameters: Some(
+                r#"[{"name": "TOKEN", "secret": true, "default": "abc"}]"#.to_string(),
+            ),
+            platform_overrides: None,
+            created_at: None,
+            updated_at: None,
+        };
+
+        let result = template.validate_config();
+        assert!(!result.valid);
+        assert!(result.errors.iter().any(|e| e.contains("TOKEN")));
+    }
+
+    #[test]
+    fn test_any_platform_template_is_derived_or_overridden() {
+        let mut template = McpTemplate {
+            id: None,
+            name: "Context7".to_string(),
+            version: "1.0.0".to_string(),
+            ai_type: "codex".to_string(),
+            platform_type: "any".to_string(),
+            config_content: r#"[mcp_servers.context7]
+command = "npx"
+args = ["-y", "@upstash/context7-mcp"]"#
+                .to_string(),
+            description: None,
+            is_builtin: Some(0),
+            category: None,
+            tags: None,
+            usage_count: Some(0),
+            parameters: None,
+            platform_overrides: None,
+            created_at: None,
+            updated_at: None,
+        };
+        assert!(template.validate_config().valid);
+        assert_eq!(
+            template.content_for_platform("unix").unwrap(),
+            template.config_content
+        );
+        assert!(template
+            .content_for_platform("windows")
+            .unwrap()
+            .contains(r#"args = ["/c", "npx", "-y", "@upstash/context7-mcp"]"#));
+
+        let windows = r#"[mcp_servers.context7]
+command = "C:\\tools\\context7.exe""#;
+        template.platform_overrides = Some(serde_json::json!({ "windows": windows }).to_string());
+        assert!(template.validate_config().valid);
+        assert_eq!(template.content_for_platform("windows").unwrap(), windows);
+
+        template.platform_overrides = Some(r#"{"macos": "x"}"#.to_string());
+        assert!(!template.validate_config().valid);
+    }
+}
@@ -0,0 +1,189 @@
+// 模型模块声明
+pub mod circuit_breaker;
+pub mod config;
+pub mod error;
+pub mod failover;
+pub mod hook;
+pub mod mcp_template;
+pub mod mode;
+pub mod supplier;
+
+// 通用类型定义
+use crate::t;
+use serde::{Deserialize, Serialize};
+
+pub use error::{AppError, CommandResult, ErrorCode, ResultExt};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiResponse<T> {
+    pub success: bool,
+    pub data: Option<T>,
+    pub message: Option<String>,
+    /// 失败时的错误码，见 ErrorCode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<ErrorCode>,
+    /// 失败时的结构化详情，例如验证错误列表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl<T> ApiResponse<T> {
+    pub fn success(data: T) -> Self {
+        Self {
+            success: true,
+            data: Some(data),
+            message: None,
+            code: None,
+            details: None,
+        }
+    }
+
+    pub fn error(error: AppError) -> Self {
+        tracing::warn!(code = ?error.code(), "{}", error);
+        Self {
+            success: false,
+            data: None,
+            message: Some(error.to_string()),
+            code: Some(error.code()),
+            details: error.details(),
+        }
+    }
+}
+
+/// 取出 INSERT/UPDATE ... RETURNING 写入的行。
+///
+/// SQLite 驱动的 fetch_one/fetch_optional 取到第一行就停止，语句没有执行完，写入要等语句
+/// 重置后才提交，在此之前连接池中的其他连接读到的还是旧数据。这类语句一律用 fetch_all
+/// 读完结果，再用这个函数取出写入的行
+pub(crate) fn returned_row<T>(rows: Vec<T>) -> Result<T, sqlx::Error> {
+    rows.into_iter().next().ok_or(sqlx::Error::RowNotFound)
+}
+
+/// 导入时遇到已存在记录的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictStrategy {
+    /// 保留已有记录，跳过导入项
+    #[default]
+    Skip,
+    /// 用导入项覆盖已有记录
+    Overwrite,
+    /// 给导入项的名称加上序号后作为新记录导入
+    Rename,
+    /// 升级导入项的版本号后作为新记录导入，只适用于有版本号的模板
+    BumpVersion,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Created,
+    Updated,
+    Skipped,
+    Failed,
+}
+
+/// 单个导入项的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportItemResult {
+    /// 在导入列表中的位置
+    pub index: usize,
+    /// 导入后的名称，重命名时与导入项不同
+    pub name: String,
+    pub action: ImportAction,
+    pub id: Option<i64>,
+    pub message: Option<String>,
+}
+
+/// 导入结果。所有导入项在同一个事务中写入，有失败项或试运行时整体回滚
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub committed: bool,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub items: Vec<ImportItemResult>,
+}
+
+impl ImportReport {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            ..Default::default()
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        index: usize,
+        name: &str,
+        action: ImportAction,
+        id: Option<i64>,
+        message: Option<String>,
+    ) {
+        match action {
+            ImportAction::Created => self.created += 1,
+            ImportAction::Updated => self.updated += 1,
+            ImportAction::Skipped => self.skipped += 1,
+            ImportAction::Failed => self.failed += 1,
+        }
+        self.items.push(ImportItemResult {
+            index,
+            name: name.to_string(),
+            action,
+            id,
+            message,
+        });
+    }
+
+    /// 没有失败项且不是试运行时才提交
+    pub fn should_commit(&self) -> bool {
+        !self.dry_run && self.failed == 0
+    }
+
+    /// 汇总说明，用于界面提示
+    pub fn summary(&self) -> String {
+        let id = if self.dry_run {
+            "import.summary_dry_run"
+        } else {
+            "import.summary"
+        };
+        let mut summary = t!(
+            id,
+            created = self.created,
+            updated = self.updated,
+            skipped = self.skipped,
+            failed = self.failed
+        );
+        if !self.dry_run && !self.committed {
+            summary.push_str(&t!("import.rolled_back"));
+        }
+        summary
+    }
+
+    /// 回滚时以失败返回，但仍附带逐项结果
+    pub fn into_response(self) -> ApiResponse<ImportReport> {
+        let success = self.committed || self.dry_run;
+        ApiResponse {
+            success,
+            message: Some(self.summary()),
+            data: Some(self),
+            code: (!success).then_some(ErrorCode::ValidationFailed),
+            details: None,
+        }
+    }
+}
+
+/// 生成不与已有名称冲突的新名称，例如 "Memory (2)"
+pub fn next_available_name(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut index = 2;
+    loop {
+        let candidate = format!("{} ({})", name, index);
+        if !taken(&candidate) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
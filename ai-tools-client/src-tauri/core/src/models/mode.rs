@@ -1,9 +1,12 @@
 // 工作模式相关模型
 
+use crate::models::hook::HookExecutionResult;
+use crate::models::returned_row;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct WorkModeConfig {
@@ -11,7 +14,8 @@ pub struct WorkModeConfig {
     pub mode_name: String,
     pub active_claude_supplier_id: Option<i64>,
     pub active_codex_supplier_id: Option<i64>,
-    pub mcp_template_ids: Option<String>, // JSON数组
+    pub mcp_template_ids: Option<String>,          // JSON数组
+    pub pinned_template_revisions: Option<String>, // JSON对象：模板ID -> 修订号
     pub created_at: Option<DateTime<Utc>>,
     pub updated_at: Option<DateTime<Utc>>,
 }
@@ -22,6 +26,9 @@ pub struct CreateWorkModeRequest {
     pub active_claude_supplier_id: Option<i64>,
     pub active_codex_supplier_id: Option<i64>,
     pub mcp_template_ids: Option<Vec<i64>>,
+    /// 固定使用的模板修订，未固定的模板使用最新内容
+    #[serde(default)]
+    pub pinned_template_revisions: Option<HashMap<i64, i64>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +38,9 @@ pub struct UpdateWorkModeRequest {
     pub active_claude_supplier_id: Option<i64>,
     pub active_codex_supplier_id: Option<i64>,
     pub mcp_template_ids: Option<Vec<i64>>,
+    /// 固定使用的模板修订，未固定的模板使用最新内容
+    #[serde(default)]
+    pub pinned_template_revisions: Option<HashMap<i64, i64>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +49,9 @@ pub struct WorkModeSwitchRequest {
     pub claude_supplier_id: Option<i64>,
     pub codex_supplier_id: Option<i64>,
     pub mcp_template_ids: Option<Vec<i64>>,
+    /// 固定使用的模板修订，未固定的模板使用最新内容
+    #[serde(default)]
+    pub pinned_template_revisions: Option<HashMap<i64, i64>>,
     pub create_backup: bool,
 }
 
@@ -59,6 +72,8 @@ pub struct WorkModeSwitchResult {
     pub backup_id: Option<i64>,
     pub applied_at: Option<DateTime<Utc>>,
     pub steps_completed: Vec<String>,
+    #[serde(default)]
+    pub hook_results: Vec<HookExecutionResult>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -93,24 +108,54 @@ impl WorkModeConfig {
         Ok(mode)
     }
 
-    pub async fn create(pool: &sqlx::SqlitePool, request: CreateWorkModeRequest) -> Result<Self> {
+    /// 在一个事务中保存模式配置并设为当前模式，同名模式已存在时覆盖其配置
+    pub async fn save_and_activate(
+        pool: &sqlx::SqlitePool,
+        request: CreateWorkModeRequest,
+    ) -> Result<Self> {
         let mcp_template_ids_json = request
             .mcp_template_ids
             .map(|ids| serde_json::to_string(&ids).unwrap_or_default());
+        let pinned_json = request
+            .pinned_template_revisions
+            .map(|pins| serde_json::to_string(&pins).unwrap_or_default());
 
+        let mut tx = pool.begin().await?;
         let mode = sqlx::query_as::<_, WorkModeConfig>(
             r#"
-            INSERT INTO work_mode_configs (mode_name, active_claude_supplier_id, active_codex_supplier_id, mcp_template_ids)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO work_mode_configs (mode_name, active_claude_supplier_id, active_codex_supplier_id, mcp_template_ids, pinned_template_revisions)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(mode_name) DO UPDATE SET
+                active_claude_supplier_id = excluded.active_claude_supplier_id,
+                active_codex_supplier_id = excluded.active_codex_supplier_id,
+                mcp_template_ids = excluded.mcp_template_ids,
+                pinned_template_revisions = excluded.pinned_template_revisions,
+                updated_at = CURRENT_TIMESTAMP
             RETURNING *
-            "#
+            "#,
         )
         .bind(&request.mode_name)
         .bind(request.active_claude_supplier_id)
         .bind(request.active_codex_supplier_id)
         .bind(mcp_template_ids_json)
-        .fetch_one(pool)
+        .bind(pinned_json)
+        .fetch_all(&mut *tx)
+        .await
+        .and_then(returned_row)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO app_state (key, value, updated_at)
+            VALUES ('current_mode', ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(key) DO UPDATE SET
+                value = excluded.value,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&request.mode_name)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         Ok(mode)
     }
@@ -137,25 +182,32 @@ impl WorkModeConfig {
         let mcp_template_ids_json = if let Some(ids) = request.mcp_template_ids {
             Some(serde_json::to_string(&ids).unwrap_or_default())
         } else {
-            existing.and_then(|m| m.mcp_template_ids)
+            existing.clone().and_then(|m| m.mcp_template_ids)
+        };
+        let pinned_json = if let Some(pins) = request.pinned_template_revisions {
+            Some(serde_json::to_string(&pins).unwrap_or_default())
+        } else {
+            existing.and_then(|m| m.pinned_template_revisions)
         };
 
         let updated = sqlx::query_as::<_, WorkModeConfig>(
             r#"
             UPDATE work_mode_configs 
             SET mode_name = ?, active_claude_supplier_id = ?, active_codex_supplier_id = ?, 
-                mcp_template_ids = ?, updated_at = CURRENT_TIMESTAMP
+                mcp_template_ids = ?, pinned_template_revisions = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             RETURNING *
             "#,
         )
-        .bind(&mode_name.unwrap())
+        .bind(mode_name.unwrap())
         .bind(claude_id)
         .bind(codex_id)
         .bind(mcp_template_ids_json)
+        .bind(pinned_json)
         .bind(request.id)
-        .fetch_one(pool)
-        .await?;
+        .fetch_all(pool)
+        .await
+        .and_then(returned_row)?;
 
         Ok(Some(updated))
     }
@@ -186,4 +238,51 @@ impl WorkModeConfig {
             vec![]
         }
     }
+
+    pub fn get_pinned_revisions(&self) -> HashMap<i64, i64> {
+        if let Some(pins_json) = &self.pinned_template_revisions {
+            serde_json::from_str(pins_json).unwrap_or_default()
+        } else {
+            HashMap::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_save_and_activate_overwrites_existing_mode() {
+        let dir = TempDir::new().unwrap();
+        let db_url = format!("sqlite://{}", dir.path().join("test.db").to_string_lossy());
+        let db = Database::new(&db_url).await.unwrap();
+        let pool = db.pool();
+
+        let request = |template_ids: Vec<i64>| CreateWorkModeRequest {
+            mode_name: "claude_only".to_string(),
+            active_claude_supplier_id: None,
+            active_codex_supplier_id: None,
+            mcp_template_ids: Some(template_ids),
+            pinned_template_revisions: None,
+        };
+
+        let first = WorkModeConfig::save_and_activate(pool, request(vec![1]))
+            .await
+            .unwrap();
+        let second = WorkModeConfig::save_and_activate(pool, request(vec![2, 3]))
+            .await
+            .unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.get_mcp_template_ids(), vec![2, 3]);
+
+        let current: String =
+            sqlx::query_scalar("SELECT value FROM app_state WHERE key = 'current_mode'")
+                .fetch_one(pool)
+                .await
+                .unwrap();
+        assert_eq!(current, "claude_only");
+    }
 }
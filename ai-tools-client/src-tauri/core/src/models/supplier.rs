@@ -0,0 +1,906 @@
+use crate::models::circuit_breaker::{CircuitBreakerSettings, CircuitState};
+use crate::models::config::AppState;
+use crate::models::failover::{FailoverStrategy, ScoringWeights};
+use crate::models::hook::HookExecutionResult;
+use crate::models::{
+    next_available_name, returned_row, AppError, ConflictStrategy, ImportAction, ImportReport,
+};
+use crate::t;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use std::time::Duration;
+
+/// 连通性探测的默认超时时间（毫秒）
+pub const DEFAULT_PROBE_TIMEOUT_MS: i64 = 10_000;
+/// 连通性探测的最长超时时间，供应商的 timeout_ms 往往是给长请求用的
+const MAX_PROBE_TIMEOUT_MS: i64 = 30_000;
+
+// 健康状态枚举
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+}
+
+// 故障转移触发条件类型枚举
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConditionType {
+    ConsecutiveFailures,
+    ResponseTime,
+    SuccessRate,
+}
+
+// 供应商切换原因枚举
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SwitchReason {
+    Manual,
+    AutoFailover,
+    HealthCheck,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Supplier {
+    pub id: Option<i64>,
+    pub r#type: String, // "claude" or "codex"
+    pub name: String,
+    pub base_url: String,
+    pub auth_token: String,
+    pub timeout_ms: Option<i64>,
+    pub auto_update: Option<i64>, // SQLite uses INTEGER for boolean
+    pub opus_model: Option<String>,
+    pub sonnet_model: Option<String>,
+    pub haiku_model: Option<String>,
+    pub is_active: Option<i64>, // SQLite uses INTEGER for boolean
+    pub sort_order: Option<i64>,
+    // 健康检查相关字段
+    pub is_healthy: Option<i64>, // SQLite uses INTEGER for boolean
+    pub last_check_time: Option<DateTime<Utc>>,
+    pub response_time: Option<i64>,        // 毫秒
+    pub consecutive_failures: Option<i64>, // 连续失败次数
+    pub uptime_percentage: Option<f64>,    // 运行时间百分比
+    pub total_requests: Option<i64>,       // 总请求数
+    pub failed_requests: Option<i64>,      // 失败请求数
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSupplierRequest {
+    pub r#type: String,
+    pub name: String,
+    pub base_url: String,
+    pub auth_token: String,
+    pub timeout_ms: Option<i64>,
+    pub auto_update: Option<bool>,
+    pub opus_model: Option<String>,
+    pub sonnet_model: Option<String>,
+    pub haiku_model: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSupplierRequest {
+    pub id: i64,
+    pub name: Option<String>,
+    pub base_url: Option<String>,
+    pub auth_token: Option<String>,
+    pub timeout_ms: Option<i64>,
+    pub auto_update: Option<bool>,
+    pub opus_model: Option<String>,
+    pub sonnet_model: Option<String>,
+    pub haiku_model: Option<String>,
+    pub is_active: Option<bool>,
+    pub sort_order: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConnectionTestResult {
+    pub success: bool,
+    pub response_time: Option<i64>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub status_code: Option<u16>,
+    #[serde(default)]
+    pub auth_error: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplierHealth {
+    pub supplier_id: i64,
+    pub is_healthy: bool,
+    pub last_check_time: DateTime<Utc>,
+    pub response_time: i64,
+    pub consecutive_failures: i64,
+    pub uptime_percentage: f64,
+    pub total_requests: i64,
+    pub failed_requests: i64,
+    pub status: HealthStatus,
+    pub error_message: Option<String>,
+    #[serde(default)]
+    pub circuit_state: CircuitState,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierSwitchProgress {
+    pub total_steps: u8,
+    pub completed_steps: u8,
+    pub overall_progress: u8, // 0-100
+    pub current_step: String,
+    pub from_supplier: i64,
+    pub to_supplier: i64,
+    pub start_time: DateTime<Utc>,
+    pub estimated_completion: Option<DateTime<Utc>>,
+    pub rollback_available: bool,
+    pub is_completed: bool,
+    pub has_error: bool,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverConfig {
+    pub enabled: bool,
+    pub trigger_conditions: Vec<FailoverTrigger>,
+    pub auto_rollback: bool,
+    pub rollback_delay_seconds: u32,
+    pub max_consecutive_failures: u32,
+    pub max_response_time_ms: u32,
+    pub min_success_rate: f64,
+    #[serde(default)]
+    pub strategy: FailoverStrategy,
+    #[serde(default)]
+    pub scoring: ScoringWeights,
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerSettings,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            trigger_conditions: vec![],
+            auto_rollback: true,
+            rollback_delay_seconds: 300,
+            max_consecutive_failures: 3,
+            max_response_time_ms: 5000,
+            min_success_rate: 95.0,
+            strategy: FailoverStrategy::default(),
+            scoring: ScoringWeights::default(),
+            circuit_breaker: CircuitBreakerSettings::default(),
+        }
+    }
+}
+
+impl FailoverConfig {
+    /// 验证故障转移配置
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.max_consecutive_failures == 0 {
+            return Err(AppError::validation(t!("failover.max_failures_invalid")));
+        }
+
+        if self.max_response_time_ms == 0 {
+            return Err(AppError::validation(t!(
+                "failover.max_response_time_invalid"
+            )));
+        }
+
+        self.scoring.validate()?;
+        self.circuit_breaker.validate()
+    }
+
+    /// app_state 中故障转移配置键的前缀，后接供应商类型
+    pub const STATE_KEY_PREFIX: &'static str = "failover_config:";
+
+    fn state_key(supplier_type: &str) -> String {
+        format!("{}{}", Self::STATE_KEY_PREFIX, supplier_type)
+    }
+
+    /// 读取某类供应商的故障转移配置，未保存过时返回默认配置
+    pub async fn load(pool: &SqlitePool, supplier_type: &str) -> Result<Self, sqlx::Error> {
+        let config = AppState::get(pool, &Self::state_key(supplier_type))
+            .await?
+            .and_then(|state| serde_json::from_str(&state.value).ok())
+            .unwrap_or_default();
+
+        Ok(config)
+    }
+
+    /// 保存某类供应商的故障转移配置
+    pub async fn save(&self, pool: &SqlitePool, supplier_type: &str) -> Result<(), sqlx::Error> {
+        let value = serde_json::to_string(self).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        AppState::set(pool, &Self::state_key(supplier_type), &value).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverTrigger {
+    pub condition_type: ConditionType,
+    pub threshold: f64,
+    pub evaluation_window_minutes: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierSwitchRequest {
+    pub from_supplier_id: i64,
+    pub to_supplier_id: i64,
+    pub switch_reason: SwitchReason,
+    pub create_backup: bool,
+    pub rollback_on_failure: bool,
+    #[serde(default)]
+    pub force: bool, // 跳过切换前的连通性检查
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SupplierSwitchResult {
+    pub success: bool,
+    pub message: String,
+    pub from_supplier_id: i64,
+    pub to_supplier_id: i64,
+    pub switch_time: DateTime<Utc>,
+    pub rollback_available: bool,
+    pub backup_id: Option<i64>,
+    pub error: Option<String>,
+    #[serde(default)]
+    pub hook_results: Vec<HookExecutionResult>,
+}
+
+impl Supplier {
+    /// 创建新供应商
+    pub async fn create(
+        pool: &SqlitePool,
+        request: CreateSupplierRequest,
+    ) -> Result<Self, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        Self::insert(&mut conn, &request).await
+    }
+
+    async fn insert(
+        conn: &mut SqliteConnection,
+        request: &CreateSupplierRequest,
+    ) -> Result<Self, sqlx::Error> {
+        let now = Utc::now();
+
+        sqlx::query_as::<_, Supplier>(
+            r#"
+            INSERT INTO suppliers (
+                type, name, base_url, auth_token, timeout_ms, auto_update,
+                opus_model, sonnet_model, haiku_model, is_active, sort_order,
+                created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, 1, 0, ?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(&request.r#type)
+        .bind(&request.name)
+        .bind(&request.base_url)
+        .bind(&request.auth_token)
+        .bind(request.timeout_ms)
+        .bind(request.auto_update.map(|b| if b { 1 } else { 0 }))
+        .bind(&request.opus_model)
+        .bind(&request.sonnet_model)
+        .bind(&request.haiku_model)
+        .bind(now)
+        .bind(now)
+        .fetch_all(conn)
+        .await
+        .and_then(returned_row)
+    }
+
+    /// 由创建请求构造尚未保存的供应商，用于保存前的验证
+    pub fn from_request(request: &CreateSupplierRequest) -> Self {
+        Self {
+            id: None,
+            r#type: request.r#type.clone(),
+            name: request.name.clone(),
+            base_url: request.base_url.clone(),
+            auth_token: request.auth_token.clone(),
+            timeout_ms: request.timeout_ms,
+            auto_update: request.auto_update.map(|b| if b { 1 } else { 0 }),
+            opus_model: request.opus_model.clone(),
+            sonnet_model: request.sonnet_model.clone(),
+            haiku_model: request.haiku_model.clone(),
+            is_active: Some(0),
+            sort_order: Some(0),
+            // 健康检查字段（初始值）
+            is_healthy: None,
+            last_check_time: None,
+            response_time: None,
+            consecutive_failures: None,
+            uptime_percentage: None,
+            total_requests: None,
+            failed_requests: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    /// 在同一个事务中导入一批供应商，类型和名称都相同的视为已存在。
+    /// 供应商没有版本号，不支持 BumpVersion 策略；试运行或有失败项时回滚
+    pub async fn import(
+        pool: &SqlitePool,
+        requests: Vec<CreateSupplierRequest>,
+        strategy: ConflictStrategy,
+        dry_run: bool,
+    ) -> Result<ImportReport, sqlx::Error> {
+        let mut report = ImportReport::new(dry_run);
+        let mut tx = pool.begin().await?;
+
+        for (index, mut request) in requests.into_iter().enumerate() {
+            if let Err(e) = Self::from_request(&request).validate() {
+                report.push(
+                    index,
+                    &request.name,
+                    ImportAction::Failed,
+                    None,
+                    Some(t!("import.validation_failed", error = e)),
+                );
+                continue;
+            }
+
+            let existing = sqlx::query_as::<_, Supplier>(
+                "SELECT * FROM suppliers WHERE type = ? AND name = ? ORDER BY id ASC LIMIT 1",
+            )
+            .bind(&request.r#type)
+            .bind(&request.name)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+            let existing = match existing {
+                Some(existing) => existing,
+                None => {
+                    let created = Self::insert(&mut tx, &request).await?;
+                    report.push(
+                        index,
+                        &created.name,
+                        ImportAction::Created,
+                        created.id,
+                        None,
+                    );
+                    continue;
+                }
+            };
+
+            match strategy {
+                ConflictStrategy::Skip => report.push(
+                    index,
+                    &existing.name,
+                    ImportAction::Skipped,
+                    existing.id,
+                    Some(t!("import.supplier_exists")),
+                ),
+                ConflictStrategy::Overwrite => {
+                    // 只覆盖连接配置，保留启用状态、排序和健康统计
+                    let updated = sqlx::query_as::<_, Supplier>(
+                        r#"
+                        UPDATE suppliers SET
+                            base_url = ?, auth_token = ?, timeout_ms = ?, auto_update = ?,
+                            opus_model = ?, sonnet_model = ?, haiku_model = ?, updated_at = ?
+                        WHERE id = ?
+                        RETURNING *
+                        "#,
+                    )
+                    .bind(&request.base_url)
+                    .bind(&request.auth_token)
+                    .bind(request.timeout_ms)
+                    .bind(request.auto_update.map(|b| if b { 1 } else { 0 }))
+                    .bind(&request.opus_model)
+                    .bind(&request.sonnet_model)
+                    .bind(&request.haiku_model)
+                    .bind(Utc::now())
+                    .bind(existing.id)
+                    .fetch_all(&mut *tx)
+                    .await
+                    .and_then(returned_row)?;
+                    report.push(
+                        index,
+                        &updated.name,
+                        ImportAction::Updated,
+                        updated.id,
+                        None,
+                    );
+                }
+                ConflictStrategy::Rename => {
+                    let taken: HashSet<String> =
+                        sqlx::query_scalar("SELECT name FROM suppliers WHERE type = ?")
+                            .bind(&request.r#type)
+                            .fetch_all(&mut *tx)
+                            .await?
+                            .into_iter()
+                            .collect();
+                    request.name = next_available_name(&request.name, |name| taken.contains(name));
+
+                    let created = Self::insert(&mut tx, &request).await?;
+                    report.push(
+                        index,
+                        &created.name,
+                        ImportAction::Created,
+                        created.id,
+                        Some(t!("import.renamed", name = created.name)),
+                    );
+                }
+                ConflictStrategy::BumpVersion => report.push(
+                    index,
+                    &existing.name,
+                    ImportAction::Failed,
+                    existing.id,
+                    Some(t!("import.supplier_unversioned")),
+                ),
+            }
+        }
+
+        report.committed = report.should_commit();
+        if report.committed {
+            tx.commit().await?;
+        } else {
+            tx.rollback().await?;
+        }
+        Ok(report)
+    }
+
+    /// 获取所有供应商
+    pub async fn get_all(pool: &SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Supplier>("SELECT * FROM suppliers ORDER BY sort_order ASC, name ASC")
+            .fetch_all(pool)
+            .await
+    }
+
+    /// 根据类型获取供应商
+    pub async fn get_by_type(
+        pool: &SqlitePool,
+        supplier_type: &str,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Supplier>(
+            "SELECT * FROM suppliers WHERE type = ? ORDER BY sort_order ASC, name ASC",
+        )
+        .bind(supplier_type)
+        .fetch_all(pool)
+        .await
+    }
+
+    /// 获取激活的供应商
+    pub async fn get_active(
+        pool: &SqlitePool,
+        supplier_type: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Supplier>(
+            "SELECT * FROM suppliers WHERE type = ? AND is_active = 1 LIMIT 1",
+        )
+        .bind(supplier_type)
+        .fetch_optional(pool)
+        .await
+    }
+
+    /// 根据ID获取供应商
+    pub async fn get_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Supplier>("SELECT * FROM suppliers WHERE id = ?")
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+    }
+
+    /// 更新供应商
+    pub async fn update(
+        pool: &SqlitePool,
+        request: UpdateSupplierRequest,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let now = Utc::now();
+
+        let result = sqlx::query_as::<_, Supplier>(
+            r#"
+            UPDATE suppliers SET
+                name = COALESCE(?, name),
+                base_url = COALESCE(?, base_url),
+                auth_token = COALESCE(?, auth_token),
+                timeout_ms = COALESCE(?, timeout_ms),
+                auto_update = COALESCE(?, auto_update),
+                opus_model = COALESCE(?, opus_model),
+                sonnet_model = COALESCE(?, sonnet_model),
+                haiku_model = COALESCE(?, haiku_model),
+                is_active = COALESCE(?, is_active),
+                sort_order = COALESCE(?, sort_order),
+                updated_at = ?
+            WHERE id = ?
+            RETURNING *
+            "#,
+        )
+        .bind(&request.name)
+        .bind(&request.base_url)
+        .bind(&request.auth_token)
+        .bind(request.timeout_ms)
+        .bind(request.is_active.map(|b| if b { 1 } else { 0 }))
+        .bind(&request.opus_model)
+        .bind(&request.sonnet_model)
+        .bind(&request.haiku_model)
+        .bind(request.is_active.map(|b| if b { 1 } else { 0 }))
+        .bind(request.sort_order)
+        .bind(now)
+        .bind(request.id)
+        .fetch_all(pool)
+        .await?
+        .pop();
+
+        Ok(result)
+    }
+
+    /// 删除供应商
+    pub async fn delete(pool: &SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM suppliers WHERE id = ?")
+            .bind(id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 设置激活状态
+    pub async fn set_active(
+        pool: &SqlitePool,
+        id: i64,
+        is_active: bool,
+    ) -> Result<bool, sqlx::Error> {
+        // 两次更新放在同一个事务中，其他连接不会看到没有激活供应商的中间状态
+        let mut tx = pool.begin().await?;
+
+        // 先将该类型的所有供应商设为非激活
+        sqlx::query("UPDATE suppliers SET is_active = 0 WHERE type = (SELECT type FROM suppliers WHERE id = ?)")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        // 设置指定供应商为激活
+        let result = sqlx::query("UPDATE suppliers SET is_active = ? WHERE id = ?")
+            .bind(if is_active { 1 } else { 0 })
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 测试供应商连接
+    ///
+    /// 请求供应商的模型列表接口：2xx 视为可用，401/403 视为认证失败，
+    /// 404/405 说明服务可达但不提供该接口，也视为可用；其余状态和网络错误视为不可用。
+    pub async fn test_connection(&self) -> ConnectionTestResult {
+        let start = std::time::Instant::now();
+        let timeout_ms = self
+            .timeout_ms
+            .filter(|ms| *ms > 0)
+            .unwrap_or(DEFAULT_PROBE_TIMEOUT_MS)
+            .min(MAX_PROBE_TIMEOUT_MS);
+
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_millis(timeout_ms as u64))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                return ConnectionTestResult {
+                    success: false,
+                    response_time: None,
+                    error: Some(t!("probe.client_create_failed", error = e)),
+                    status_code: None,
+                    auth_error: false,
+                }
+            }
+        };
+
+        let mut request = client.get(self.probe_url()).bearer_auth(&self.auth_token);
+        if self.r#type == "claude" {
+            request = request
+                .header("x-api-key", &self.auth_token)
+                .header("anthropic-version", "2023-06-01");
+        }
+
+        let response = request.send().await;
+        let response_time = Some(start.elapsed().as_millis() as i64);
+
+        match response {
+            Ok(response) => {
+                let status = response.status().as_u16();
+                let (success, auth_error, error) = classify_probe_status(status);
+                ConnectionTestResult {
+                    success,
+                    response_time,
+                    error,
+                    status_code: Some(status),
+                    auth_error,
+                }
+            }
+            Err(e) => ConnectionTestResult {
+                success: false,
+                response_time,
+                error: Some(if e.is_timeout() {
+                    t!("probe.timeout", timeout_ms = timeout_ms)
+                } else {
+                    t!("probe.connect_failed", error = e)
+                }),
+                status_code: None,
+                auth_error: false,
+            },
+        }
+    }
+
+    /// 连通性探测使用的地址
+    fn probe_url(&self) -> String {
+        let base = self.base_url.trim_end_matches('/');
+        if self.r#type == "claude" && !base.ends_with("/v1") {
+            format!("{}/v1/models", base)
+        } else {
+            format!("{}/models", base)
+        }
+    }
+
+    /// 验证供应商配置，返回全部验证错误
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut errors = Vec::new();
+
+        if self.name.trim().is_empty() {
+            errors.push(t!("supplier.name_empty"));
+        }
+
+        if self.base_url.trim().is_empty() {
+            errors.push(t!("supplier.base_url_empty"));
+        } else if !self.base_url.starts_with("http://") && !self.base_url.starts_with("https://") {
+            // 验证URL格式
+            errors.push(t!("supplier.base_url_invalid"));
+        }
+
+        if self.auth_token.trim().is_empty() {
+            errors.push(t!("supplier.auth_token_empty"));
+        }
+
+        // 验证类型
+        if self.r#type != "claude" && self.r#type != "codex" {
+            errors.push(t!("supplier.type_invalid"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::Validation(errors))
+        }
+    }
+}
+
+/// 根据探测接口的状态码判断供应商是否可用（是否可用, 是否认证失败, 错误信息）
+fn classify_probe_status(status: u16) -> (bool, bool, Option<String>) {
+    match status {
+        200..=299 | 404 | 405 => (true, false, None),
+        401 | 403 => (false, true, Some(t!("probe.auth_failed", status = status))),
+        429 => (false, false, Some(t!("probe.rate_limited"))),
+        _ => (
+            false,
+            false,
+            Some(t!("probe.unexpected_status", status = status)),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::database::Database;
+    use sqlx::SqlitePool;
+    use tempfile::{tempdir, TempDir};
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn create_test_pool() -> TestDb {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+
+        TestDb {
+            _dir: temp_dir,
+            pool,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_suppliers_in_one_transaction() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let request = |name: &str, base_url: &str| CreateSupplierRequest {
+            r#type: "claude".to_string(),
+            name: name.to_string(),
+            base_url: base_url.to_string(),
+            auth_token: "token".to_string(),
+            timeout_ms: None,
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+        };
+        let existing = Supplier::create(pool, request("Primary", "https://a.example.com"))
+            .await
+            .unwrap();
+
+        let report = Supplier::import(
+            pool,
+            vec![
+                request("Primary", "https://b.example.com"),
+                request("Backup", "https://c.example.com"),
+            ],
+            ConflictStrategy::Overwrite,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(report.committed);
+        assert_eq!((report.created, report.updated), (1, 1));
+        let updated = Supplier::get_by_id(pool, existing.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.base_url, "https://b.example.com");
+
+        let renamed = Supplier::import(
+            pool,
+            vec![request("Primary", "https://d.example.com")],
+            ConflictStrategy::Rename,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(renamed.items[0].name, "Primary (2)");
+
+        // 版本号策略不适用于供应商，整批回滚
+        let rejected = Supplier::import(
+            pool,
+            vec![
+                request("Third", "https://e.example.com"),
+                request("Primary", "https://f.example.com"),
+            ],
+            ConflictStrategy::BumpVersion,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(rejected.failed, 1);
+        assert!(!rejected.committed);
+        assert_eq!(Supplier::get_all(pool).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_create_supplier() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let request = CreateSupplierRequest {
+            r#type: "claude".to_string(),
+            name: "Test Supplier".to_string(),
+            base_url: "https://api.example.com".to_string(),
+            auth_token: "test_token".to_string(),
+            timeout_ms: Some(30000),
+            auto_update: Some(true),
+            opus_model: Some("claude-3-opus-20240229".to_string()),
+            sonnet_model: Some("claude-3-sonnet-20240229".to_string()),
+            haiku_model: Some("claude-3-haiku-20240307".to_string()),
+        };
+
+        let supplier = Supplier::create(pool, request).await.unwrap();
+        assert!(supplier.id.is_some());
+        assert_eq!(supplier.name, "Test Supplier");
+        assert_eq!(supplier.r#type, "claude");
+    }
+
+    #[tokio::test]
+    async fn test_get_suppliers() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+
+        let request = CreateSupplierRequest {
+            r#type: "claude".to_string(),
+            name: "Test Supplier".to_string(),
+            base_url: "https://api.example.com".to_string(),
+            auth_token: "test_token".to_string(),
+            timeout_ms: None,
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+        };
+
+        Supplier::create(pool, request).await.unwrap();
+
+        let suppliers = Supplier::get_all(pool).await.unwrap();
+        assert_eq!(suppliers.len(), 1);
+
+        let claude_suppliers = Supplier::get_by_type(pool, "claude").await.unwrap();
+        assert_eq!(claude_suppliers.len(), 1);
+
+        let codex_suppliers = Supplier::get_by_type(pool, "codex").await.unwrap();
+        assert_eq!(codex_suppliers.len(), 0);
+    }
+
+    /// 启动只响应一次的本地 HTTP 服务，返回其地址
+    async fn serve_once(status_line: &'static str) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = socket.read(&mut buf).await;
+            let response = format!("HTTP/1.1 {}\r\ncontent-length: 0\r\n\r\n", status_line);
+            socket.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        format!("http://{}", addr)
+    }
+
+    fn probe_supplier(base_url: String) -> Supplier {
+        Supplier {
+            id: Some(1),
+            r#type: "claude".to_string(),
+            name: "probe".to_string(),
+            base_url,
+            auth_token: "test_token".to_string(),
+            timeout_ms: Some(3_000_000),
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            is_active: None,
+            sort_order: None,
+            is_healthy: None,
+            last_check_time: None,
+            response_time: None,
+            consecutive_failures: None,
+            uptime_percentage: None,
+            total_requests: None,
+            failed_requests: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_reports_auth_error() {
+        let base_url = serve_once("401 Unauthorized").await;
+        let result = probe_supplier(base_url).test_connection().await;
+
+        assert!(!result.success);
+        assert!(result.auth_error);
+        assert_eq!(result.status_code, Some(401));
+    }
+
+    #[tokio::test]
+    async fn test_connection_succeeds_on_ok_response() {
+        let base_url = serve_once("200 OK").await;
+        let result = probe_supplier(base_url).test_connection().await;
+
+        assert!(result.success);
+        assert!(!result.auth_error);
+        assert!(result.error.is_none());
+    }
+
+    #[test]
+    fn test_probe_url_and_status_classification() {
+        let mut supplier = probe_supplier("https://api.example.com/".to_string());
+        assert_eq!(supplier.probe_url(), "https://api.example.com/v1/models");
+
+        supplier.r#type = "codex".to_string();
+        supplier.base_url = "https://api.example.com/v1".to_string();
+        assert_eq!(supplier.probe_url(), "https://api.example.com/v1/models");
+
+        assert!(classify_probe_status(404).0);
+        assert!(!classify_probe_status(502).0);
+        assert!(classify_probe_status(403).1);
+    }
+}
@@ -0,0 +1,993 @@
+use crate::models::config::AppState;
+use crate::models::error::AppError;
+use crate::models::mcp_template::McpTemplate;
+use crate::models::supplier::FailoverConfig;
+use crate::services::database::SCHEMA_VERSION;
+use crate::t;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::sqlite::{SqliteArguments, SqliteRow};
+use sqlx::{Arguments, Column, Row, SqliteConnection, SqlitePool, TypeInfo, ValueRef};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 导出文件格式版本，格式发生不兼容的变化时递增
+pub const EXPORT_FORMAT_VERSION: i64 = 1;
+
+/// 自动快照检查间隔
+const SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+const SNAPSHOT_PREFIX: &str = "ai-tools-";
+
+type TableRows = Vec<Map<String, Value>>;
+
+/// 数据表的导入规则
+struct TableSpec {
+    name: &'static str,
+    /// 自增主键，合并导入时丢弃并重新分配
+    id_column: Option<&'static str>,
+    /// 判断记录是否已存在的列，合并导入时已存在的记录保留本地版本
+    key_columns: &'static [&'static str],
+    /// 引用其他表ID的列及被引用的表
+    references: &'static [(&'static str, &'static str)],
+}
+
+/// 全部数据表，按依赖顺序排列，被引用的表在前
+const TABLES: [TableSpec; 13] = [
+    TableSpec {
+        name: "suppliers",
+        id_column: Some("id"),
+        key_columns: &["type", "name"],
+        references: &[],
+    },
+    TableSpec {
+        name: "mcp_template_categories",
+        id_column: Some("id"),
+        key_columns: &["name"],
+        references: &[],
+    },
+    TableSpec {
+        name: "mcp_templates",
+        id_column: Some("id"),
+        key_columns: &["name", "version", "ai_type", "platform_type"],
+        references: &[],
+    },
+    TableSpec {
+        name: "mcp_template_values",
+        id_column: Some("id"),
+        key_columns: &["template_id", "name"],
+        references: &[("template_id", "mcp_templates")],
+    },
+    TableSpec {
+        name: "mcp_template_revisions",
+        id_column: Some("id"),
+        key_columns: &["template_id", "revision"],
+        references: &[("template_id", "mcp_templates")],
+    },
+    TableSpec {
+        name: "mcp_template_tools",
+        id_column: Some("id"),
+        key_columns: &["template_id", "server_id"],
+        references: &[("template_id", "mcp_templates")],
+    },
+    TableSpec {
+        name: "config_history",
+        id_column: Some("id"),
+        key_columns: &["config_type", "config_path", "operation_time"],
+        references: &[],
+    },
+    TableSpec {
+        name: "work_mode_configs",
+        id_column: Some("id"),
+        key_columns: &["mode_name"],
+        references: &[
+            ("active_claude_supplier_id", "suppliers"),
+            ("active_codex_supplier_id", "suppliers"),
+        ],
+    },
+    TableSpec {
+        name: "failover_chains",
+        id_column: Some("id"),
+        key_columns: &["supplier_type", "supplier_id"],
+        references: &[("supplier_id", "suppliers")],
+    },
+    TableSpec {
+        name: "circuit_breakers",
+        id_column: None,
+        key_columns: &["supplier_id"],
+        references: &[("supplier_id", "suppliers")],
+    },
+    TableSpec {
+        name: "switch_hooks",
+        id_column: Some("id"),
+        key_columns: &["scope_type", "scope_value", "stage", "name"],
+        references: &[],
+    },
+    TableSpec {
+        name: "switch_history",
+        id_column: Some("id"),
+        key_columns: &["switch_type", "created_at"],
+        references: &[
+            ("from_supplier_id", "suppliers"),
+            ("to_supplier_id", "suppliers"),
+            ("backup_id", "config_history"),
+        ],
+    },
+    TableSpec {
+        name: "app_state",
+        id_column: None,
+        key_columns: &["key"],
+        references: &[],
+    },
+];
+
+/// 完整数据导出。模板密钥参数保持加密形式，只能在持有同一密钥文件的环境中解密
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataExport {
+    pub format_version: i64,
+    pub schema_version: i64,
+    pub exported_at: DateTime<Utc>,
+    /// 格式版本、结构版本和全部数据的 SHA-256
+    pub checksum: String,
+    pub tables: BTreeMap<String, TableRows>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataImportMode {
+    /// 清空现有数据后按原ID写入
+    Replace,
+    /// 保留现有数据，导入的记录重新分配ID
+    #[default]
+    Merge,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TableImportCount {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataImportResult {
+    pub mode: DataImportMode,
+    pub tables: BTreeMap<String, TableImportCount>,
+}
+
+impl DataExport {
+    /// 在一个读事务中导出全部数据表，保证各表数据一致
+    pub async fn collect(pool: &SqlitePool) -> Result<Self> {
+        let mut tx = pool.begin().await?;
+        let mut tables = BTreeMap::new();
+        for spec in &TABLES {
+            let rows = sqlx::query(&format!("SELECT * FROM {}", spec.name))
+                .fetch_all(&mut *tx)
+                .await?;
+            let rows = rows
+                .iter()
+                .map(row_to_json)
+                .collect::<Result<TableRows>>()?;
+            tables.insert(spec.name.to_string(), rows);
+        }
+        tx.commit().await?;
+
+        let checksum = Self::compute_checksum(EXPORT_FORMAT_VERSION, SCHEMA_VERSION, &tables)?;
+        Ok(Self {
+            format_version: EXPORT_FORMAT_VERSION,
+            schema_version: SCHEMA_VERSION,
+            exported_at: Utc::now(),
+            checksum,
+            tables,
+        })
+    }
+
+    fn compute_checksum(
+        format_version: i64,
+        schema_version: i64,
+        tables: &BTreeMap<String, TableRows>,
+    ) -> Result<String> {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}:{}:", format_version, schema_version));
+        hasher.update(serde_json::to_vec(tables)?);
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect())
+    }
+
+    /// 检查格式版本、结构版本、校验和以及数据表名称
+    pub fn verify(&self) -> Result<()> {
+        if self.format_version > EXPORT_FORMAT_VERSION {
+            return Err(anyhow!(t!(
+                "backup.format_too_new",
+                version = self.format_version,
+                supported = EXPORT_FORMAT_VERSION
+            )));
+        }
+        if self.schema_version > SCHEMA_VERSION {
+            return Err(anyhow!(t!(
+                "backup.schema_too_new",
+                version = self.schema_version,
+                supported = SCHEMA_VERSION
+            )));
+        }
+
+        let checksum =
+            Self::compute_checksum(self.format_version, self.schema_version, &self.tables)?;
+        if checksum != self.checksum {
+            return Err(anyhow!(t!("backup.checksum_mismatch")));
+        }
+
+        if let Some(name) = self
+            .tables
+            .keys()
+            .find(|name| !TABLES.iter().any(|spec| spec.name == name.as_str()))
+        {
+            return Err(anyhow!(t!("backup.unknown_table", name = name)));
+        }
+
+        Ok(())
+    }
+
+    /// 在一个事务中导入，任何一步失败都不会改动现有数据
+    pub async fn import(
+        &self,
+        pool: &SqlitePool,
+        mode: DataImportMode,
+    ) -> Result<DataImportResult> {
+        self.verify()?;
+
+        let mut tx = pool.begin().await?;
+        // 外键检查推迟到提交时，替换导入时允许先清空被引用的表
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await?;
+
+        if mode == DataImportMode::Replace {
+            for spec in TABLES.iter().rev() {
+                sqlx::query(&format!("DELETE FROM {}", spec.name))
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        // 旧ID -> 新ID，按表记录
+        let mut id_maps: HashMap<&str, HashMap<i64, i64>> = HashMap::new();
+        // 合并到本地已有记录的ID，按表记录
+        let mut kept_ids: HashMap<&str, HashSet<i64>> = HashMap::new();
+        let mut tables = BTreeMap::new();
+        for spec in &TABLES {
+            let rows = match self.tables.get(spec.name) {
+                Some(rows) => rows,
+                None => continue,
+            };
+            let columns = table_columns(&mut tx, spec.name).await?;
+
+            let mut count = TableImportCount::default();
+            for row in rows {
+                let mut row: Map<String, Value> = row
+                    .iter()
+                    .filter(|(column, _)| columns.contains(column.as_str()))
+                    .map(|(column, value)| (column.clone(), value.clone()))
+                    .collect();
+
+                if mode == DataImportMode::Replace {
+                    insert_row(&mut tx, spec.name, &row).await?;
+                    count.imported += 1;
+                    continue;
+                }
+
+                for (column, parent) in spec.references {
+                    if let Some(old_id) = row.get(*column).and_then(Value::as_i64) {
+                        let new_id = id_maps
+                            .get(parent)
+                            .and_then(|ids| ids.get(&old_id))
+                            .copied();
+                        row.insert(
+                            column.to_string(),
+                            new_id.map(Value::from).unwrap_or_default(),
+                        );
+                    }
+                }
+                if spec.name == "work_mode_configs" {
+                    remap_mode_templates(&mut row, id_maps.get("mcp_templates"));
+                }
+                if spec.name == "app_state" {
+                    remap_state_suppliers(&mut row, id_maps.get("suppliers"));
+                }
+                // 模板已存在时保留本地的修订历史，导入的修订号会和本地历史交错
+                if spec.name == "mcp_template_revisions"
+                    && row
+                        .get("template_id")
+                        .and_then(Value::as_i64)
+                        .is_some_and(|id| {
+                            kept_ids
+                                .get("mcp_templates")
+                                .is_some_and(|ids| ids.contains(&id))
+                        })
+                {
+                    count.skipped += 1;
+                    continue;
+                }
+
+                let old_id = spec
+                    .id_column
+                    .and_then(|column| row.remove(column))
+                    .and_then(|id| id.as_i64());
+                let new_id = match find_existing(&mut tx, spec, &row).await? {
+                    Some(existing_id) => {
+                        count.skipped += 1;
+                        kept_ids.entry(spec.name).or_default().insert(existing_id);
+                        existing_id
+                    }
+                    None => {
+                        count.imported += 1;
+                        insert_row(&mut tx, spec.name, &row).await?
+                    }
+                };
+                if let Some(old_id) = old_id {
+                    id_maps.entry(spec.name).or_default().insert(old_id, new_id);
+                }
+            }
+            tables.insert(spec.name.to_string(), count);
+        }
+
+        tx.commit().await?;
+
+        McpTemplate::rebuild_search_index(pool).await?;
+
+        Ok(DataImportResult { mode, tables })
+    }
+}
+
+/// 按值的实际存储类型转换为 JSON
+fn row_to_json(row: &SqliteRow) -> Result<Map<String, Value>> {
+    let mut object = Map::new();
+    for column in row.columns() {
+        let index = column.ordinal();
+        let raw = row.try_get_raw(index)?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => Value::from(row.try_get_unchecked::<i64, _>(index)?),
+                "REAL" => Value::from(row.try_get_unchecked::<f64, _>(index)?),
+                "TEXT" => Value::from(row.try_get_unchecked::<String, _>(index)?),
+                other => {
+                    return Err(anyhow!(t!(
+                        "backup.column_type_unsupported",
+                        column_type = other,
+                        column = column.name()
+                    )))
+                }
+            }
+        };
+        object.insert(column.name().to_string(), value);
+    }
+
+    Ok(object)
+}
+
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<HashSet<String>> {
+    let columns = sqlx::query(&format!("PRAGMA table_info({})", table))
+        .fetch_all(&mut *conn)
+        .await?
+        .iter()
+        .map(|row| row.get::<String, _>("name"))
+        .collect();
+    Ok(columns)
+}
+
+fn bind_value(args: &mut SqliteArguments<'_>, column: &str, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => args.add(Option::<i64>::None),
+        Value::Bool(flag) => args.add(*flag as i64),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => args.add(integer),
+            None => args.add(number.as_f64().unwrap_or_default()),
+        },
+        Value::String(text) => args.add(text.clone()),
+        _ => return Err(anyhow!(t!("backup.value_not_scalar", column = column))),
+    }
+    Ok(())
+}
+
+async fn insert_row(
+    conn: &mut SqliteConnection,
+    table: &str,
+    row: &Map<String, Value>,
+) -> Result<i64> {
+    let mut args = SqliteArguments::default();
+    for (column, value) in row {
+        bind_value(&mut args, column, value)?;
+    }
+
+    let sql = if row.is_empty() {
+        format!("INSERT INTO {} DEFAULT VALUES", table)
+    } else {
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            row.keys()
+                .map(|column| format!("\"{}\"", column))
+                .collect::<Vec<_>>()
+                .join(", "),
+            vec!["?"; row.len()].join(", ")
+        )
+    };
+    let result = sqlx::query_with(&sql, args).execute(&mut *conn).await?;
+    Ok(result.last_insert_rowid())
+}
+
+/// 按判重列查找已存在的记录，返回其ID
+async fn find_existing(
+    conn: &mut SqliteConnection,
+    spec: &TableSpec,
+    row: &Map<String, Value>,
+) -> Result<Option<i64>> {
+    let mut args = SqliteArguments::default();
+    let mut conditions = Vec::new();
+    for column in spec.key_columns {
+        conditions.push(format!("\"{}\" IS ?", column));
+        bind_value(&mut args, column, row.get(*column).unwrap_or(&Value::Null))?;
+    }
+
+    let sql = format!(
+        "SELECT {} FROM {} WHERE {} LIMIT 1",
+        spec.id_column.unwrap_or("rowid"),
+        spec.name,
+        conditions.join(" AND ")
+    );
+    let id: Option<i64> = sqlx::query_scalar_with(&sql, args)
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(id)
+}
+
+/// 工作模式中以 JSON 保存的模板ID和固定修订也要换成新ID，找不到对应模板的项丢弃
+fn remap_mode_templates(row: &mut Map<String, Value>, template_ids: Option<&HashMap<i64, i64>>) {
+    let empty = HashMap::new();
+    let template_ids = template_ids.unwrap_or(&empty);
+
+    if let Some(Value::String(json)) = row.get("mcp_template_ids") {
+        let ids: Vec<i64> = serde_json::from_str(json).unwrap_or_default();
+        let ids: Vec<i64> = ids
+            .iter()
+            .filter_map(|id| template_ids.get(id).copied())
+            .collect();
+        row.insert(
+            "mcp_template_ids".to_string(),
+            Value::from(serde_json::to_string(&ids).unwrap_or_default()),
+        );
+    }
+
+    if let Some(Value::String(json)) = row.get("pinned_template_revisions") {
+        let pins: HashMap<i64, i64> = serde_json::from_str(json).unwrap_or_default();
+        let pins: HashMap<i64, i64> = pins
+            .iter()
+            .filter_map(|(id, revision)| template_ids.get(id).map(|new_id| (*new_id, *revision)))
+            .collect();
+        row.insert(
+            "pinned_template_revisions".to_string(),
+            Value::from(serde_json::to_string(&pins).unwrap_or_default()),
+        );
+    }
+}
+
+/// 故障转移配置中按供应商ID保存的成本换成新ID，对应供应商未导入的项丢弃
+fn remap_state_suppliers(row: &mut Map<String, Value>, supplier_ids: Option<&HashMap<i64, i64>>) {
+    let is_failover_config = row
+        .get("key")
+        .and_then(Value::as_str)
+        .is_some_and(|key| key.starts_with(FailoverConfig::STATE_KEY_PREFIX));
+    if !is_failover_config {
+        return;
+    }
+    let mut config: Value = match row.get("value").and_then(Value::as_str) {
+        Some(json) => match serde_json::from_str(json) {
+            Ok(config) => config,
+            Err(_) => return,
+        },
+        None => return,
+    };
+
+    let empty = HashMap::new();
+    let supplier_ids = supplier_ids.unwrap_or(&empty);
+    if let Some(costs) = config
+        .pointer_mut("/scoring/supplier_costs")
+        .and_then(Value::as_object_mut)
+    {
+        *costs = costs
+            .iter()
+            .filter_map(|(id, cost)| {
+                let new_id = supplier_ids.get(&id.parse().ok()?)?;
+                Some((new_id.to_string(), cost.clone()))
+            })
+            .collect();
+    }
+    row.insert("value".to_string(), Value::from(config.to_string()));
+}
+
+/// 自动快照策略，保存在 app_state 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotPolicy {
+    pub enabled: bool,
+    /// 两次快照之间的最短间隔（小时）
+    pub interval_hours: i64,
+    /// 保留的快照数量，超出的旧快照会被删除
+    pub keep: usize,
+}
+
+impl Default for SnapshotPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+impl SnapshotPolicy {
+    const STATE_KEY: &'static str = "snapshot_policy";
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.interval_hours < 1 {
+            return Err(AppError::validation(t!("snapshot.interval_invalid")));
+        }
+        if self.keep < 1 {
+            return Err(AppError::validation(t!("snapshot.keep_invalid")));
+        }
+        Ok(())
+    }
+
+    /// 读取快照策略，未保存过时返回默认策略
+    pub async fn load(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let policy = AppState::get(pool, Self::STATE_KEY)
+            .await?
+            .and_then(|state| serde_json::from_str(&state.value).ok())
+            .unwrap_or_default();
+
+        Ok(policy)
+    }
+
+    pub async fn save(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let value = serde_json::to_string(self).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+        AppState::set(pool, Self::STATE_KEY, &value).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    pub file_name: String,
+    pub path: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 数据库快照目录，快照文件名包含生成时间，按文件名排序即按时间排序
+pub struct SnapshotStore {
+    dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            dir: data_dir.join("snapshots"),
+        }
+    }
+
+    /// 用 VACUUM INTO 生成一致的数据库副本，不阻塞其他连接的读写
+    pub async fn create(&self, pool: &SqlitePool) -> Result<SnapshotInfo> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!(
+            "{}{}.db",
+            SNAPSHOT_PREFIX,
+            Utc::now().format("%Y%m%d%H%M%S%3f")
+        ));
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(pool)
+            .await?;
+
+        Self::info(&path)
+    }
+
+    /// 列出全部快照，最新的在前
+    pub fn list(&self) -> Result<Vec<SnapshotInfo>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut paths: Vec<PathBuf> = std::fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy())
+                    .is_some_and(|name| name.starts_with(SNAPSHOT_PREFIX) && name.ends_with(".db"))
+            })
+            .collect();
+        paths.sort();
+        paths.reverse();
+
+        paths.iter().map(|path| Self::info(path)).collect()
+    }
+
+    /// 只保留最新的 keep 个快照，返回删除的数量
+    pub fn prune(&self, keep: usize) -> Result<usize> {
+        let snapshots = self.list()?;
+        let mut removed = 0;
+        for snapshot in snapshots.iter().skip(keep) {
+            std::fs::remove_file(&snapshot.path)?;
+            removed += 1;
+        }
+        Ok(removed)
+    }
+
+    /// 距离最近一次快照超过策略间隔时生成新快照并清理旧快照
+    pub async fn run_if_due(
+        &self,
+        pool: &SqlitePool,
+        policy: &SnapshotPolicy,
+    ) -> Result<Option<SnapshotInfo>> {
+        if !policy.enabled {
+            return Ok(None);
+        }
+
+        let latest = self.list()?.into_iter().next();
+        let due = latest.is_none_or(|snapshot| {
+            Utc::now() - snapshot.created_at >= chrono::Duration::hours(policy.interval_hours)
+        });
+        if !due {
+            return Ok(None);
+        }
+
+        let snapshot = self.create(pool).await?;
+        self.prune(policy.keep)?;
+        Ok(Some(snapshot))
+    }
+
+    /// 后台定期检查是否需要生成快照，策略每次检查时重新读取
+    pub async fn run_periodically(self, pool: SqlitePool) {
+        let mut interval = tokio::time::interval(SNAPSHOT_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let result = match SnapshotPolicy::load(&pool).await {
+                Ok(policy) => self.run_if_due(&pool, &policy).await,
+                Err(e) => Err(e.into()),
+            };
+            match result {
+                Ok(Some(snapshot)) => tracing::info!("已生成数据库快照: {}", snapshot.path),
+                Ok(None) => {}
+                Err(e) => tracing::error!("生成数据库快照失败: {:#}", e),
+            }
+        }
+    }
+
+    fn info(path: &Path) -> Result<SnapshotInfo> {
+        let metadata = std::fs::metadata(path)?;
+        Ok(SnapshotInfo {
+            file_name: path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            path: path.to_string_lossy().to_string(),
+            size: metadata.len(),
+            created_at: metadata.modified().map(DateTime::<Utc>::from)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::mcp_template::{
+        CreateMcpTemplateRequest, McpTemplate, UpdateMcpTemplateRequest,
+    };
+    use crate::models::supplier::{CreateSupplierRequest, Supplier};
+    use crate::services::database::Database;
+    use tempfile::TempDir;
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn test_db() -> TestDb {
+        let dir = TempDir::new().unwrap();
+        let db_path = dir.path().join("test.db");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+        TestDb { _dir: dir, pool }
+    }
+
+    fn supplier_request(name: &str) -> CreateSupplierRequest {
+        CreateSupplierRequest {
+            r#type: "claude".to_string(),
+            name: name.to_string(),
+            base_url: "https://api.example.com".to_string(),
+            auth_token: "token".to_string(),
+            timeout_ms: None,
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+        }
+    }
+
+    fn template_request(name: &str) -> CreateMcpTemplateRequest {
+        CreateMcpTemplateRequest {
+            name: name.to_string(),
+            version: None,
+            ai_type: "claude".to_string(),
+            platform_type: "unix".to_string(),
+            config_content: format!(r#"{{"{}": {{"command": "npx"}}}}"#, name),
+            description: None,
+            category: Some("工具".to_string()),
+            tags: None,
+            parameters: None,
+            platform_overrides: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_and_merge_import_remaps_ids() {
+        let source = test_db().await;
+        let supplier = Supplier::create(&source.pool, supplier_request("源供应商"))
+            .await
+            .unwrap();
+        let template = McpTemplate::create(&source.pool, template_request("source-memory"))
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO work_mode_configs (mode_name, active_claude_supplier_id, mcp_template_ids, pinned_template_revisions) VALUES ('claude_only', ?, ?, ?)",
+        )
+        .bind(supplier.id)
+        .bind(format!("[{}]", template.id.unwrap()))
+        .bind(format!(r#"{{"{}": 1}}"#, template.id.unwrap()))
+        .execute(&source.pool)
+        .await
+        .unwrap();
+
+        let export = DataExport::collect(&source.pool).await.unwrap();
+        assert_eq!(export.tables.len(), TABLES.len());
+        export.verify().unwrap();
+
+        // 目标库已有数据，导入的记录需要换成新ID
+        let target = test_db().await;
+        for name in ["占位一", "占位二"] {
+            Supplier::create(&target.pool, supplier_request(name))
+                .await
+                .unwrap();
+        }
+        McpTemplate::create(&target.pool, template_request("target-only"))
+            .await
+            .unwrap();
+
+        let result = export
+            .import(&target.pool, DataImportMode::Merge)
+            .await
+            .unwrap();
+        assert_eq!(result.tables["suppliers"].imported, 1);
+        assert_eq!(result.tables["work_mode_configs"].imported, 1);
+
+        let new_supplier: i64 =
+            sqlx::query_scalar("SELECT id FROM suppliers WHERE name = '源供应商'")
+                .fetch_one(&target.pool)
+                .await
+                .unwrap();
+        let new_template: i64 =
+            sqlx::query_scalar("SELECT id FROM mcp_templates WHERE name = 'source-memory'")
+                .fetch_one(&target.pool)
+                .await
+                .unwrap();
+        assert_ne!(Some(new_supplier), supplier.id);
+        assert_ne!(Some(new_template), template.id);
+
+        let (claude_id, template_ids, pins): (Option<i64>, String, String) = sqlx::query_as(
+            "SELECT active_claude_supplier_id, mcp_template_ids, pinned_template_revisions FROM work_mode_configs WHERE mode_name = 'claude_only'",
+        )
+        .fetch_one(&target.pool)
+        .await
+        .unwrap();
+        assert_eq!(claude_id, Some(new_supplier));
+        assert_eq!(template_ids, format!("[{}]", new_template));
+        assert_eq!(pins, format!(r#"{{"{}":1}}"#, new_template));
+
+        // 修订记录跟随模板换成新ID，全文索引同步重建
+        let revisions: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM mcp_template_revisions WHERE template_id = ?")
+                .bind(new_template)
+                .fetch_one(&target.pool)
+                .await
+                .unwrap();
+        assert_eq!(revisions, 1);
+        let indexed: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates_fts WHERE rowid = ?")
+                .bind(new_template)
+                .fetch_one(&target.pool)
+                .await
+                .unwrap();
+        assert_eq!(indexed, 1);
+
+        // 再次合并时已存在的记录全部跳过
+        let again = export
+            .import(&target.pool, DataImportMode::Merge)
+            .await
+            .unwrap();
+        assert!(again.tables.values().all(|count| count.imported == 0));
+    }
+
+    #[tokio::test]
+    async fn test_merge_import_remaps_failover_costs_and_keeps_local_revisions() {
+        let source = test_db().await;
+        let supplier = Supplier::create(&source.pool, supplier_request("源供应商"))
+            .await
+            .unwrap();
+        let mut config = FailoverConfig::default();
+        config
+            .scoring
+            .supplier_costs
+            .insert(supplier.id.unwrap(), 2.5);
+        // 已删除供应商的成本没有对应记录，合并时丢弃
+        config.scoring.supplier_costs.insert(99, 4.0);
+        config.save(&source.pool, "claude").await.unwrap();
+
+        let shared = McpTemplate::create(&source.pool, template_request("shared"))
+            .await
+            .unwrap();
+        McpTemplate::update(
+            &source.pool,
+            UpdateMcpTemplateRequest {
+                id: shared.id.unwrap(),
+                name: None,
+                version: Some(shared.version.clone()),
+                config_content: None,
+                description: Some("源端改动".to_string()),
+                category: None,
+                tags: None,
+                parameters: None,
+                platform_overrides: None,
+                note: None,
+            },
+        )
+        .await
+        .unwrap();
+        let export = DataExport::collect(&source.pool).await.unwrap();
+
+        let target = test_db().await;
+        for name in ["占位一", "占位二"] {
+            Supplier::create(&target.pool, supplier_request(name))
+                .await
+                .unwrap();
+        }
+        let local = McpTemplate::create(&target.pool, template_request("shared"))
+            .await
+            .unwrap();
+
+        export
+            .import(&target.pool, DataImportMode::Merge)
+            .await
+            .unwrap();
+
+        let new_supplier: i64 =
+            sqlx::query_scalar("SELECT id FROM suppliers WHERE name = '源供应商'")
+                .fetch_one(&target.pool)
+                .await
+                .unwrap();
+        assert_ne!(Some(new_supplier), supplier.id);
+        let merged = FailoverConfig::load(&target.pool, "claude").await.unwrap();
+        assert_eq!(
+            merged.scoring.supplier_costs,
+            HashMap::from([(new_supplier, 2.5)])
+        );
+
+        // 本地已有同一模板时只保留本地的修订历史
+        let revisions: Vec<(i64, Option<String>)> = sqlx::query_as(
+            "SELECT revision, description FROM mcp_template_revisions WHERE template_id = ? ORDER BY revision",
+        )
+        .bind(local.id)
+        .fetch_all(&target.pool)
+        .await
+        .unwrap();
+        assert_eq!(revisions, vec![(1, local.description.clone())]);
+    }
+
+    #[tokio::test]
+    async fn test_replace_import_and_checksum() {
+        let source = test_db().await;
+        let supplier = Supplier::create(&source.pool, supplier_request("源供应商"))
+            .await
+            .unwrap();
+        let export = DataExport::collect(&source.pool).await.unwrap();
+
+        let target = test_db().await;
+        Supplier::create(&target.pool, supplier_request("将被替换"))
+            .await
+            .unwrap();
+
+        let mut tampered = export.clone();
+        tampered.tables.get_mut("suppliers").unwrap()[0]
+            .insert("name".to_string(), Value::from("篡改"));
+        let error = tampered
+            .import(&target.pool, DataImportMode::Replace)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("校验和不匹配"));
+
+        // 经过 JSON 序列化往返后校验和仍然一致
+        let json = serde_json::to_string(&export).unwrap();
+        let parsed: DataExport = serde_json::from_str(&json).unwrap();
+        parsed
+            .import(&target.pool, DataImportMode::Replace)
+            .await
+            .unwrap();
+
+        let suppliers = Supplier::get_all(&target.pool).await.unwrap();
+        assert_eq!(suppliers.len(), 1);
+        assert_eq!(suppliers[0].id, supplier.id);
+        assert_eq!(suppliers[0].name, "源供应商");
+    }
+
+    #[tokio::test]
+    async fn test_tables_cover_every_data_table() {
+        let db = test_db().await;
+        let names: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT name FROM sqlite_master
+            WHERE type = 'table' AND name NOT LIKE 'sqlite_%'
+              AND name != 'schema_version' AND name NOT LIKE 'mcp_templates_fts%'
+            "#,
+        )
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+
+        for name in names {
+            assert!(
+                TABLES.iter().any(|spec| spec.name == name),
+                "数据表 {} 没有导出规则",
+                name
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_follow_retention_policy() {
+        let db = test_db().await;
+        let dir = TempDir::new().unwrap();
+        let store = SnapshotStore::new(dir.path());
+        let policy = SnapshotPolicy {
+            enabled: true,
+            interval_hours: 1,
+            keep: 2,
+        };
+
+        assert!(store.run_if_due(&db.pool, &policy).await.unwrap().is_some());
+        // 间隔未到时不生成新快照
+        assert!(store.run_if_due(&db.pool, &policy).await.unwrap().is_none());
+
+        for _ in 0..2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            store.create(&db.pool).await.unwrap();
+        }
+        assert_eq!(store.list().unwrap().len(), 3);
+        assert_eq!(store.prune(policy.keep).unwrap(), 1);
+
+        let snapshots = store.list().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(snapshots[0].file_name > snapshots[1].file_name);
+
+        // 快照是可以直接打开的数据库文件
+        let snapshot_url = format!("sqlite://{}", snapshots[0].path);
+        let snapshot = Database::new(&snapshot_url).await.unwrap();
+        assert!(Database::test_db_connection(snapshot.pool()).await.unwrap());
+    }
+}
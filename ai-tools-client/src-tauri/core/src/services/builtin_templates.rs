@@ -1,5 +1,20 @@
-use crate::models::mcp_template::CreateMcpTemplateRequest;
+use crate::models::mcp_template::{
+    compare_versions, CreateMcpTemplateRequest, McpTemplate, McpTemplateCategory, TemplateParameter,
+};
+use crate::t;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::cmp::Ordering;
+
+/// 内置模板同步结果
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct BuiltinSeedSummary {
+    pub created: usize,
+    pub upgraded: usize,
+    pub unchanged: usize,
+    pub skipped: Vec<String>, // 与用户模板冲突而未写入的内置模板
+}
 
 pub struct BuiltinTemplates;
 
@@ -23,10 +38,13 @@ impl BuiltinTemplates {
     ],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Context7 - 编程库文档聚合服务".to_string()),
                 category: Some("documentation".to_string()),
                 tags: Some(vec!["docs".to_string(), "search".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Sequential Thinking".to_string(),
@@ -43,10 +61,13 @@ impl BuiltinTemplates {
     ],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Sequential Thinking - 结构化思维工具".to_string()),
                 category: Some("tools".to_string()),
                 tags: Some(vec!["thinking".to_string(), "analysis".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Memory".to_string(),
@@ -63,12 +84,15 @@ impl BuiltinTemplates {
     ],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Memory - 记忆管理服务".to_string()),
                 category: Some("tools".to_string()),
                 tags: Some(vec!["memory".to_string(), "storage".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
-            CreateMTemplateRequest {
+            CreateMcpTemplateRequest {
                 name: "Playwright".to_string(),
                 version: Some("1.0.0".to_string()),
                 ai_type: "claude".to_string(),
@@ -83,10 +107,13 @@ impl BuiltinTemplates {
     ],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Playwright - Web自动化测试工具".to_string()),
                 category: Some("testing".to_string()),
                 tags: Some(vec!["testing".to_string(), "automation".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Shrimp Task Manager".to_string(),
@@ -106,10 +133,13 @@ impl BuiltinTemplates {
       "ENABLE_GUI": "false"
     }
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Shrimp Task Manager - 任务管理工具".to_string()),
                 category: Some("productivity".to_string()),
                 tags: Some(vec!["task".to_string(), "management".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Desktop Commander".to_string(),
@@ -126,12 +156,14 @@ impl BuiltinTemplates {
     ],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Desktop Commander - 桌面文件和进程管理".to_string()),
                 category: Some("productivity".to_string()),
                 tags: Some(vec!["desktop".to_string(), "files".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
-
             // Claude模板 - Windows平台
             CreateMcpTemplateRequest {
                 name: "Context7".to_string(),
@@ -145,10 +177,13 @@ impl BuiltinTemplates {
     "args": [],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Context7 - 编程库文档聚合服务 (Windows)".to_string()),
                 category: Some("documentation".to_string()),
                 tags: Some(vec!["docs".to_string(), "search".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Sequential Thinking".to_string(),
@@ -162,10 +197,13 @@ impl BuiltinTemplates {
     "args": [],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Sequential Thinking - 结构化思维工具 (Windows)".to_string()),
                 category: Some("tools".to_string()),
                 tags: Some(vec!["thinking".to_string(), "analysis".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Memory".to_string(),
@@ -179,12 +217,14 @@ impl BuiltinTemplates {
     "args": [],
     "env": {}
   }
-}"#.to_string(),
+}"#
+                .to_string(),
                 description: Some("Memory - 记忆管理服务 (Windows)".to_string()),
                 category: Some("tools".to_string()),
                 tags: Some(vec!["memory".to_string(), "storage".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
-
             // Codex模板 - Unix平台
             CreateMcpTemplateRequest {
                 name: "Context7".to_string(),
@@ -195,10 +235,13 @@ impl BuiltinTemplates {
 type = "stdio"
 command = "npx"
 args = [ "-y", "@upstash/context7-mcp" ]
-env = {}"#.to_string(),
+env = {}"#
+                    .to_string(),
                 description: Some("Context7 - 编程库文档聚合服务".to_string()),
                 category: Some("documentation".to_string()),
                 tags: Some(vec!["docs".to_string(), "search".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Sequential Thinking".to_string(),
@@ -208,10 +251,13 @@ env = {}"#.to_string(),
                 config_content: r#"[mcp_servers.sequential-thinking]
 type = "stdio"
 command = "npx"
-args = [ "-y", "@modelcontextprotocol/server-sequential-thinking" ]"#.to_string(),
+args = [ "-y", "@modelcontextprotocol/server-sequential-thinking" ]"#
+                    .to_string(),
                 description: Some("Sequential Thinking - 结构化思维工具".to_string()),
                 category: Some("tools".to_string()),
                 tags: Some(vec!["thinking".to_string(), "analysis".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Chrome DevTools".to_string(),
@@ -221,10 +267,13 @@ args = [ "-y", "@modelcontextprotocol/server-sequential-thinking" ]"#.to_string(
                 config_content: r#"[mcp_servers.chrome-devtools]
 type = "stdio"
 command = "npx"
-args = [ "chrome-devtools-mcp@latest" ]"#.to_string(),
+args = [ "chrome-devtools-mcp@latest" ]"#
+                    .to_string(),
                 description: Some("Chrome DevTools - Chrome开发者工具".to_string()),
                 category: Some("development".to_string()),
                 tags: Some(vec!["chrome".to_string(), "debugging".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Exa".to_string(),
@@ -234,11 +283,20 @@ args = [ "chrome-devtools-mcp@latest" ]"#.to_string(),
                 config_content: r#"[mcp_servers.exa]
 type = "stdio"
 command = "npx"
-args = [ "-y", "@smithery/cli@latest", "run", "exa", "--key", "1778bbf9-08f1-4d2e-9999-4cb4ba664dd9" ]
-env = {}"#.to_string(),
+args = [ "-y", "@smithery/cli@latest", "run", "exa", "--key", "${EXA_API_KEY}" ]
+env = {}"#
+                    .to_string(),
                 description: Some("Exa - 搜索和网络信息获取".to_string()),
                 category: Some("search".to_string()),
                 tags: Some(vec!["search".to_string(), "web".to_string()]),
+                parameters: Some(vec![TemplateParameter {
+                    name: "EXA_API_KEY".to_string(),
+                    description: Some("Exa API Key".to_string()),
+                    required: true,
+                    secret: true,
+                    default: None,
+                }]),
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "MCP DeepWiki".to_string(),
@@ -249,10 +307,13 @@ env = {}"#.to_string(),
 startup_timeout_ms = 20000
 type = "stdio"
 command = "npx"
-args = [ "-y", "mcp-deepwiki@latest" ]"#.to_string(),
+args = [ "-y", "mcp-deepwiki@latest" ]"#
+                    .to_string(),
                 description: Some("MCP DeepWiki - 深度知识聚合".to_string()),
                 category: Some("knowledge".to_string()),
                 tags: Some(vec!["wiki".to_string(), "research".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Spec Workflow".to_string(),
@@ -263,12 +324,15 @@ args = [ "-y", "mcp-deepwiki@latest" ]"#.to_string(),
 startup_timeout_ms = 20000
 type = "stdio"
 command = "npx"
-args = [ "-y", "@pimzino/spec-workflow-mcp@latest" ]"#.to_string(),
+args = [ "-y", "@pimzino/spec-workflow-mcp@latest" ]"#
+                    .to_string(),
                 description: Some("Spec Workflow - 规范工作流工具".to_string()),
                 category: Some("workflow".to_string()),
                 tags: Some(vec!["workflow".to_string(), "spec".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
-            CreateMcpRequest {
+            CreateMcpTemplateRequest {
                 name: "Serena".to_string(),
                 version: Some("1.0.0".to_string()),
                 ai_type: "codex".to_string(),
@@ -286,12 +350,14 @@ args = [
     "False",
 ]
 command = "uvx"
-type = "stdio""#.to_string(),
+type = "stdio""#
+                    .to_string(),
                 description: Some("Serena - 代码语义检索工具".to_string()),
                 category: Some("development".to_string()),
                 tags: Some(vec!["code".to_string(), "search".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
-
             // Codex模板 - Windows平台
             CreateMcpTemplateRequest {
                 name: "Context7".to_string(),
@@ -301,10 +367,13 @@ type = "stdio""#.to_string(),
                 config_content: r#"[mcp_servers.context7]
 type = "stdio"
 command = "/usr/bin/context7-mcp"
-args = []"#.to_string(),
+args = []"#
+                    .to_string(),
                 description: Some("Context7 - 编程库文档聚合服务 (Windows)".to_string()),
                 category: Some("documentation".to_string()),
                 tags: Some(vec!["docs".to_string(), "search".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Sequential Thinking".to_string(),
@@ -314,10 +383,13 @@ args = []"#.to_string(),
                 config_content: r#"[mcp_servers.sequential-thinking]
 type = "stdio"
 command = "/usr/bin/sequential-thinking"
-args = []"#.to_string(),
+args = []"#
+                    .to_string(),
                 description: Some("Sequential Thinking - 结构化思维工具 (Windows)".to_string()),
                 category: Some("tools".to_string()),
                 tags: Some(vec!["thinking".to_string(), "analysis".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Chrome DevTools".to_string(),
@@ -327,10 +399,13 @@ args = []"#.to_string(),
                 config_content: r#"[mcp_servers.chrome-devtools]
 type = "stdio"
 command = "/usr/bin/npx"
-args = [ "chrome-devtools-mcp@latest" ]"#.to_string(),
+args = [ "chrome-devtools-mcp@latest" ]"#
+                    .to_string(),
                 description: Some("Chrome DevTools - Chrome开发者工具 (Windows)".to_string()),
                 category: Some("development".to_string()),
                 tags: Some(vec!["chrome".to_string(), "debugging".to_string()]),
+                parameters: None,
+                platform_overrides: None,
             },
             CreateMcpTemplateRequest {
                 name: "Exa".to_string(),
@@ -340,104 +415,279 @@ args = [ "chrome-devtools-mcp@latest" ]"#.to_string(),
                 config_content: r#"[mcp_servers.exa]
 type = "stdio"
 command = "/usr/bin/npx"
-args = [ "-y", "@smithery/cli@latest", "run", "exa", "--key", "1778bbf9-08f1-4d2e-9999-4cb4ba664dd9" ]
-"#.to_string(),
+args = [ "-y", "@smithery/cli@latest", "run", "exa", "--key", "${EXA_API_KEY}" ]
+"#
+                .to_string(),
                 description: Some("Exa - 搜索和网络信息获取 (Windows)".to_string()),
                 category: Some("search".to_string()),
                 tags: Some(vec!["search".to_string(), "web".to_string()]),
+                parameters: Some(vec![TemplateParameter {
+                    name: "EXA_API_KEY".to_string(),
+                    description: Some("Exa API Key".to_string()),
+                    required: true,
+                    secret: true,
+                    default: None,
+                }]),
+                platform_overrides: None,
             },
         ]
     }
 
-    /// 初始化内置模板到数据库
-    pub async fn initialize_builtin_templates(pool: &sqlx::SqlitePool) -> Result<()> {
-        let templates = Self::get_all_templates();
+    /// 内置模板使用的分类及其描述
+    pub fn get_all_categories() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("development", "开发辅助：浏览器调试和代码语义检索"),
+            ("documentation", "文档与资料查询"),
+            ("knowledge", "开源仓库知识库问答"),
+            ("productivity", "本地操作与任务管理"),
+            ("search", "网络搜索"),
+            ("testing", "浏览器自动化与测试"),
+            ("tools", "通用工具：记忆与结构化思考"),
+            ("workflow", "规格驱动的开发流程"),
+        ]
+    }
 
-        for template in templates {
-            // 检查是否已存在
-            let existing = sqlx::query_scalar(
-                "SELECT COUNT(*) FROM mcp_templates WHERE name = ? AND version = ?"
-            )
-            .bind(&template.name)
-            .bind(template.version.as_ref().unwrap_or(&"1.0.0".to_string()))
-            .fetch_one(pool)
-            .await
-            .unwrap_or(0);
+    /// 启动时同步内置模板。
+    ///
+    /// 按名称、AI类型和平台匹配 is_builtin = 1 的行：不存在则创建，版本较旧则升级，
+    /// 用户克隆或自建的模板（is_builtin = 0）不受影响。
+    pub async fn initialize_builtin_templates(pool: &SqlitePool) -> Result<BuiltinSeedSummary> {
+        let mut summary = BuiltinSeedSummary::default();
+
+        // 分类描述只在缺失时补全，保留用户修改过的描述
+        let mut conn = pool.acquire().await?;
+        for (name, description) in Self::get_all_categories() {
+            McpTemplateCategory::ensure(&mut conn, name, Some(description))
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(t!("builtin.category_save_failed", name = name, error = e))
+                })?;
+        }
+        drop(conn);
 
-            if existing == 0 {
-                // 标记为内置模板
-                let request = CreateMcpTemplateRequest {
-                    name: template.name,
-                    version: template.version,
-                    ai_type: template.ai_type,
-                    platform_type: template.platform_type,
-                    config_content: template.config_content,
-                    description: template.description,
-                    category: template.category,
-                    tags: template.tags,
-                };
+        for template in Self::get_all_templates() {
+            let label = format!(
+                "{} ({}/{})",
+                template.name, template.ai_type, template.platform_type
+            );
+            let version = template
+                .version
+                .clone()
+                .unwrap_or_else(|| "1.0.0".to_string());
 
-                // 创建模板
-                let created = crate::models::mcp_template::McpTemplate::create(pool, request)
-                    .await
-                    .map_err(|e| anyhow::anyhow!("创建内置模板 '{}' 失败: {}", template.name, e))?;
+            let existing = McpTemplate::get_builtin(
+                pool,
+                &template.name,
+                &template.ai_type,
+                &template.platform_type,
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!(t!("builtin.query_failed", label = label, error = e)))?;
 
-                if let Some(created) = {
-                    log::info!("创建内置模板: {} (ID: {})", created.name, created.id.unwrap_or(0));
+            match existing {
+                Some(existing) => {
+                    if compare_versions(&version, &existing.version) == Ordering::Greater {
+                        let id = existing.id.unwrap_or_default();
+                        McpTemplate::upgrade_builtin(pool, id, template)
+                            .await
+                            .map_err(|e| {
+                                anyhow::anyhow!(t!(
+                                    "builtin.upgrade_failed",
+                                    label = label,
+                                    error = e
+                                ))
+                            })?;
+                        summary.upgraded += 1;
+                    } else {
+                        summary.unchanged += 1;
+                    }
+                }
+                None => {
+                    let created =
+                        McpTemplate::create_builtin(pool, template)
+                            .await
+                            .map_err(|e| {
+                                anyhow::anyhow!(t!(
+                                    "builtin.create_failed",
+                                    label = label,
+                                    error = e
+                                ))
+                            })?;
+                    match created {
+                        Some(_) => summary.created += 1,
+                        None => summary.skipped.push(label),
+                    }
                 }
             }
         }
 
-        Ok(())
+        Ok(summary)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sqlx::SqlitePool;
-    use tempfile::tempdir;
+    use crate::models::mcp_template::CreateMcpTemplateRequest;
+    use crate::services::database::Database;
+    use std::collections::HashSet;
+    use tempfile::{tempdir, TempDir};
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
 
-    async fn create_test_pool() -> SqlitePool {
+    async fn create_test_pool() -> TestDb {
         let temp_dir = tempdir().unwrap();
         let db_path = temp_dir.path().join("test.db");
-        let db_url = format!("sqlite:{}", db_path.display());
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
 
-        crate::database::Database::new(&db_url).await.unwrap();
-        SqlitePool::connect(&db_url).await.unwrap()
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+
+        TestDb {
+            _dir: temp_dir,
+            pool,
+        }
     }
 
-    #[tokio::test]
-    async fn test_builtin_templates() {
+    #[test]
+    fn test_builtin_templates_are_valid_and_unique() {
         let templates = BuiltinTemplates::get_all_templates();
-        assert!(!templates.is_empty());
+        assert!(templates.len() >= 15);
 
-        // 验证模板配置格式
+        let mut keys = HashSet::new();
         for template in &templates {
-            if template.ai_type == "claude" {
-                assert!(serde_json::from_str::<serde_json::Value>(&template.config_content).is_ok());
-            } else if template.ai_type == "codex" {
-                assert!(toml::from_str::<toml::Value>(&template.config_content).is_ok());
-            }
+            assert!(keys.insert((
+                template.name.clone(),
+                template.ai_type.clone(),
+                template.platform_type.clone(),
+            )));
+
+            let parameters = template
+                .parameters
+                .as_ref()
+                .map(|parameters| serde_json::to_string(parameters).unwrap());
+            let model = McpTemplate {
+                id: None,
+                name: template.name.clone(),
+                version: template.version.clone().unwrap_or_default(),
+                ai_type: template.ai_type.clone(),
+                platform_type: template.platform_type.clone(),
+                config_content: template.config_content.clone(),
+                description: template.description.clone(),
+                is_builtin: Some(1),
+                category: template.category.clone(),
+                tags: None,
+                usage_count: Some(0),
+                parameters,
+                platform_overrides: None,
+                created_at: None,
+                updated_at: None,
+            };
+            let result = model.validate_config();
+            assert!(result.valid, "{}: {:?}", template.name, result.errors);
         }
     }
 
     #[tokio::test]
-    async fn test_initialize_builtin_templates() {
-        let pool = create_test_pool().await;
+    async fn test_seed_is_builtin_and_idempotent() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+        let total = BuiltinTemplates::get_all_templates().len();
 
-        BuiltinTemplates::initialize_builtin_templates(&pool).await.unwrap();
+        let first = BuiltinTemplates::initialize_builtin_templates(pool)
+            .await
+            .unwrap();
+        assert_eq!(first.created, total);
 
-        // 验证模板已创建
-        for template in BuiltinTemplates::get_all_templates() {
-            let count = sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates WHERE name = ? AND version = ?")
-                .bind(&template.name)
-                .bind(template.version.as_ref().unwrap_or(&"1.0.0".to_string()))
-                .fetch_one(&pool)
+        let second = BuiltinTemplates::initialize_builtin_templates(pool)
+            .await
+            .unwrap();
+        assert_eq!(second.created, 0);
+        assert_eq!(second.unchanged, total);
+
+        let builtin: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates WHERE is_builtin = 1")
+                .fetch_one(pool)
                 .await
-                .unwrap_or(0);
+                .unwrap();
+        assert_eq!(builtin as usize, total);
+    }
 
-            assert!(count > 0, "模板 {} 应该已创建", template.name);
-        }
+    #[tokio::test]
+    async fn test_seed_upgrades_builtin_and_keeps_clones() {
+        let test_db = create_test_pool().await;
+        let pool = &test_db.pool;
+        BuiltinTemplates::initialize_builtin_templates(pool)
+            .await
+            .unwrap();
+
+        let builtin = McpTemplate::get_builtin(pool, "Context7", "claude", "unix")
+            .await
+            .unwrap()
+            .unwrap();
+        let builtin_id = builtin.id.unwrap();
+        let clone = McpTemplate::clone_template(pool, builtin_id, "My Context7")
+            .await
+            .unwrap()
+            .unwrap();
+
+        // 模拟旧版本应用写入的内置模板
+        sqlx::query(
+            "UPDATE mcp_templates SET version = '0.9.0', config_content = '{}' WHERE id = ?",
+        )
+        .bind(builtin_id)
+        .execute(pool)
+        .await
+        .unwrap();
+
+        let summary = BuiltinTemplates::initialize_builtin_templates(pool)
+            .await
+            .unwrap();
+        assert_eq!(summary.upgraded, 1);
+
+        let upgraded = McpTemplate::get_by_id(pool, builtin_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(upgraded.version, builtin.version);
+        assert_eq!(upgraded.config_content, builtin.config_content);
+
+        let untouched = McpTemplate::get_by_id(pool, clone.id.unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!untouched.is_builtin());
+        assert_eq!(untouched.name, "My Context7");
+
+        // 与用户模板冲突时跳过
+        sqlx::query("DELETE FROM mcp_templates WHERE id = ?")
+            .bind(builtin_id)
+            .execute(pool)
+            .await
+            .unwrap();
+        McpTemplate::create(
+            pool,
+            CreateMcpTemplateRequest {
+                name: "Context7".to_string(),
+                version: Some(builtin.version.clone()),
+                ai_type: "claude".to_string(),
+                platform_type: "unix".to_string(),
+                config_content: builtin.config_content.clone(),
+                description: None,
+                category: None,
+                tags: None,
+                parameters: None,
+                platform_overrides: None,
+            },
+        )
+        .await
+        .unwrap();
+        let summary = BuiltinTemplates::initialize_builtin_templates(pool)
+            .await
+            .unwrap();
+        assert_eq!(summary.skipped.len(), 1);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,463 @@
+use crate::models::mcp_template::{CreateMcpTemplateRequest, McpTemplate};
+use crate::t;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 可以导入MCP服务器的配置文件位置
+#[derive(Debug, Clone)]
+pub struct ImportPaths {
+    /// Claude Code 用户配置 ~/.claude.json，包含用户级和各项目的本地服务器
+    pub claude_user: PathBuf,
+    /// Claude Desktop 的 claude_desktop_config.json
+    pub claude_desktop: PathBuf,
+    pub codex_config: PathBuf,
+}
+
+impl ImportPaths {
+    /// 以指定目录作为用户主目录
+    pub fn from_home(home: &Path) -> Self {
+        Self {
+            claude_user: home.join(".claude.json"),
+            claude_desktop: claude_desktop_dir(home).join("claude_desktop_config.json"),
+            codex_config: home.join(".codex").join("config.toml"),
+        }
+    }
+
+    /// 使用当前用户的主目录
+    pub fn detect() -> Result<Self> {
+        let home = dirs::home_dir().ok_or_else(|| anyhow!(t!("config.home_dir_unavailable")))?;
+        Ok(Self::from_home(&home))
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn claude_desktop_dir(home: &Path) -> PathBuf {
+    home.join("Library")
+        .join("Application Support")
+        .join("Claude")
+}
+
+#[cfg(target_os = "windows")]
+fn claude_desktop_dir(home: &Path) -> PathBuf {
+    home.join("AppData").join("Roaming").join("Claude")
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn claude_desktop_dir(home: &Path) -> PathBuf {
+    home.join(".config").join("Claude")
+}
+
+/// 扫描过的配置文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportSource {
+    pub kind: String,
+    pub path: String,
+    pub exists: bool,
+    pub server_count: usize,
+    pub error: Option<String>,
+}
+
+/// 可导入的服务器，确认后把 template 交给 import_mcp_templates
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpImportCandidate {
+    pub source_kind: String,
+    pub source_path: String,
+    pub server_id: String,
+    pub template: CreateMcpTemplateRequest,
+    /// 内容相同的已有模板
+    pub duplicate_of: Option<i64>,
+    pub duplicate_name: Option<String>,
+}
+
+/// 导入预览
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpImportPreview {
+    pub sources: Vec<ImportSource>,
+    pub candidates: Vec<McpImportCandidate>,
+}
+
+/// 从 Claude Code、Claude Desktop 和 Codex 的现有配置中收集MCP服务器
+pub struct ConfigImporter;
+
+impl ConfigImporter {
+    /// 扫描所有已知位置和指定的项目目录，只读取文件，不写入数据库
+    pub fn scan(paths: &ImportPaths, project_dirs: &[PathBuf]) -> McpImportPreview {
+        let mut files = vec![
+            ("claude_user", paths.claude_user.clone()),
+            ("claude_desktop", paths.claude_desktop.clone()),
+            ("codex", paths.codex_config.clone()),
+        ];
+        for dir in project_dirs {
+            files.push(("claude_project", dir.join(".mcp.json")));
+        }
+
+        let mut sources = Vec::new();
+        let mut candidates: Vec<McpImportCandidate> = Vec::new();
+        let mut seen = HashSet::new();
+
+        for (kind, path) in files {
+            let mut source = ImportSource {
+                kind: kind.to_string(),
+                path: path.display().to_string(),
+                exists: path.exists(),
+                server_count: 0,
+                error: None,
+            };
+
+            if source.exists {
+                let parsed = fs::read_to_string(&path)
+                    .map_err(|e| t!("import.file_read_failed", error = e))
+                    .and_then(|content| Self::parse(kind, &source.path, &content));
+                match parsed {
+                    Ok(found) => {
+                        source.server_count = found.len();
+                        // 同一个服务器可能同时出现在用户级和项目级配置中，只保留第一个
+                        for candidate in found {
+                            let key = normalized_servers(
+                                &candidate.template.ai_type,
+                                &candidate.template.config_content,
+                            );
+                            if seen.insert((candidate.template.ai_type.clone(), key)) {
+                                candidates.push(candidate);
+                            }
+                        }
+                    }
+                    Err(e) => source.error = Some(e),
+                }
+            }
+            sources.push(source);
+        }
+
+        McpImportPreview {
+            sources,
+            candidates,
+        }
+    }
+
+    /// 解析一个配置文件的内容，source_kind 决定文件格式
+    pub fn parse(
+        source_kind: &str,
+        source_path: &str,
+        content: &str,
+    ) -> Result<Vec<McpImportCandidate>, String> {
+        let servers = match source_kind {
+            "codex" => {
+                let root: toml::Value =
+                    toml::from_str(content).map_err(|e| t!("mcp.codex_toml_invalid", error = e))?;
+                let root = serde_json::to_value(root).map_err(|e| e.to_string())?;
+                object_entries(root.get("mcp_servers"))
+            }
+            "claude_user" | "claude_desktop" | "claude_project" => {
+                let root: Value = serde_json::from_str(content)
+                    .map_err(|e| t!("mcp.claude_json_invalid", error = e))?;
+                let mut servers = object_entries(root.get("mcpServers"));
+
+                // ~/.claude.json 中每个项目还有自己的本地服务器
+                if let Some(projects) = root.get("projects").and_then(Value::as_object) {
+                    for project in projects.values() {
+                        servers.extend(object_entries(project.get("mcpServers")));
+                    }
+                }
+                servers
+            }
+            other => return Err(t!("import.source_unsupported", source = other)),
+        };
+
+        let ai_type = if source_kind == "codex" {
+            "codex"
+        } else {
+            "claude"
+        };
+
+        servers
+            .into_iter()
+            .map(|(id, server)| {
+                let platform_type = detect_platform(&server);
+                let config_content = server_content(ai_type, &id, server)?;
+                Ok(McpImportCandidate {
+                    source_kind: source_kind.to_string(),
+                    source_path: source_path.to_string(),
+                    server_id: id.clone(),
+                    template: CreateMcpTemplateRequest {
+                        name: id,
+                        version: None,
+                        ai_type: ai_type.to_string(),
+                        platform_type: platform_type.to_string(),
+                        config_content,
+                        description: Some(t!("import.description", path = source_path)),
+                        category: Some("imported".to_string()),
+                        tags: Some(vec!["imported".to_string(), source_kind.to_string()]),
+                        parameters: None,
+                        platform_overrides: None,
+                    },
+                    duplicate_of: None,
+                    duplicate_name: None,
+                })
+            })
+            .collect()
+    }
+
+    /// 标记与已有模板内容相同的候选项，比较时忽略服务器ID、键顺序和格式
+    pub fn mark_duplicates(candidates: &mut [McpImportCandidate], existing: &[McpTemplate]) {
+        let mut known = Vec::new();
+        for template in existing {
+            let mut contents = vec![template.config_content.clone()];
+            if let Ok(overrides) = template.get_platform_overrides() {
+                contents.extend(overrides.into_values());
+            }
+            for content in contents {
+                if let Some(key) = normalized_servers(&template.ai_type, &content) {
+                    known.push((template.ai_type.as_str(), key, template));
+                }
+            }
+        }
+
+        for candidate in candidates.iter_mut() {
+            let key = normalized_servers(
+                &candidate.template.ai_type,
+                &candidate.template.config_content,
+            );
+            if let Some((_, _, template)) = known.iter().find(|(ai_type, existing_key, _)| {
+                *ai_type == candidate.template.ai_type && Some(existing_key) == key.as_ref()
+            }) {
+                candidate.duplicate_of = template.id;
+                candidate.duplicate_name = Some(template.name.clone());
+            }
+        }
+    }
+}
+
+fn object_entries(value: Option<&Value>) -> Vec<(String, Map<String, Value>)> {
+    value
+        .and_then(Value::as_object)
+        .map(|servers| {
+            servers
+                .iter()
+                .filter_map(|(id, server)| server.as_object().map(|s| (id.clone(), s.clone())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// `cmd /c`、`.exe` 或盘符路径说明是 Windows 写法，绝对路径说明是 unix 写法，其余两边通用
+pub(crate) fn detect_platform(server: &Map<String, Value>) -> &'static str {
+    let command = match server.get("command").and_then(Value::as_str) {
+        Some(command) => command,
+        None => return "any",
+    };
+
+    let lower = command.to_lowercase();
+    let has_drive = lower.len() > 2 && lower.as_bytes()[1] == b':';
+    if lower == "cmd" || has_drive || [".exe", ".cmd", ".bat"].iter().any(|s| lower.ends_with(s)) {
+        "windows"
+    } else if command.starts_with('/') {
+        "unix"
+    } else {
+        "any"
+    }
+}
+
+/// 生成单个服务器的模板内容：Claude 为以ID为键的JSON，Codex 为 `[mcp_servers.<id>]` 表
+fn server_content(ai_type: &str, id: &str, server: Map<String, Value>) -> Result<String, String> {
+    let mut root = Map::new();
+    root.insert(id.to_string(), Value::Object(server));
+
+    if ai_type == "codex" {
+        let mut config = Map::new();
+        config.insert("mcp_servers".to_string(), Value::Object(root));
+        toml::to_string(&Value::Object(config))
+            .map(|content| content.trim_end().to_string())
+            .map_err(|e| t!("mcp.codex_generate_failed", error = e))
+    } else {
+        serde_json::to_string_pretty(&Value::Object(root))
+            .map_err(|e| t!("mcp.claude_generate_failed", error = e))
+    }
+}
+
+/// 规范化后的服务器定义列表，用于判断内容是否相同
+fn normalized_servers(ai_type: &str, content: &str) -> Option<Vec<String>> {
+    let root = if ai_type == "codex" {
+        let root: toml::Value = toml::from_str(content).ok()?;
+        serde_json::to_value(root).ok()?.get("mcp_servers")?.clone()
+    } else {
+        let root: Value = serde_json::from_str(content).ok()?;
+        match root.get("mcpServers") {
+            Some(servers) => servers.clone(),
+            None => root,
+        }
+    };
+
+    let root = root.as_object()?;
+    let servers: Vec<Map<String, Value>> = if ["type", "command", "url"]
+        .iter()
+        .any(|key| root.contains_key(*key))
+    {
+        vec![root.clone()]
+    } else {
+        root.values()
+            .filter_map(Value::as_object)
+            .cloned()
+            .collect()
+    };
+
+    let mut normalized: Vec<String> = servers
+        .into_iter()
+        .map(|mut server| {
+            // 省略默认值，避免写法不同导致误判
+            if server.get("type").and_then(Value::as_str) == Some("stdio") {
+                server.remove("type");
+            }
+            if server.get("enabled").and_then(Value::as_bool) == Some(true) {
+                server.remove("enabled");
+            }
+            for key in ["env", "args", "headers", "http_headers"] {
+                let empty = match server.get(key) {
+                    Some(Value::Object(map)) => map.is_empty(),
+                    Some(Value::Array(items)) => items.is_empty(),
+                    _ => false,
+                };
+                if empty {
+                    server.remove(key);
+                }
+            }
+            canonical(&Value::Object(server)).to_string()
+        })
+        .collect();
+    normalized.sort();
+    Some(normalized)
+}
+
+/// 递归按键排序，序列化结果与原始键顺序无关
+fn canonical(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<&String, Value> = map
+                .iter()
+                .map(|(key, value)| (key, canonical(value)))
+                .collect();
+            Value::Object(
+                sorted
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), value))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonical).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_scan_claude_and_codex_configs() {
+        let home = tempdir().unwrap();
+        let project = tempdir().unwrap();
+        let paths = ImportPaths::from_home(home.path());
+
+        fs::write(
+            &paths.claude_user,
+            r#"{
+  "numStartups": 3,
+  "mcpServers": {
+    "memory": {"type": "stdio", "command": "npx", "args": ["-y", "@modelcontextprotocol/server-memory"]}
+  },
+  "projects": {
+    "/work/app": {
+      "mcpServers": {
+        "local-db": {"command": "C:\\tools\\db-mcp.exe", "args": []}
+      }
+    }
+  }
+}"#,
+        )
+        .unwrap();
+        // 与用户级配置重复的服务器只保留一个
+        fs::write(
+            project.path().join(".mcp.json"),
+            r#"{"mcpServers": {"memory": {"args": ["-y", "@modelcontextprotocol/server-memory"], "command": "npx"}}}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(paths.codex_config.parent().unwrap()).unwrap();
+        fs::write(
+            &paths.codex_config,
+            r#"model = "gpt-5"
+
+[mcp_servers.serena]
+command = "/usr/local/bin/uvx"
+args = ["serena"]"#,
+        )
+        .unwrap();
+
+        let preview = ConfigImporter::scan(&paths, &[project.path().to_path_buf()]);
+        assert_eq!(preview.sources.len(), 4);
+        assert!(!preview.sources[1].exists);
+        assert_eq!(preview.sources[3].server_count, 1);
+
+        let ids: Vec<&str> = preview
+            .candidates
+            .iter()
+            .map(|c| c.server_id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["memory", "local-db", "serena"]);
+
+        let local = &preview.candidates[1].template;
+        assert_eq!(local.platform_type, "windows");
+        assert_eq!(preview.candidates[0].template.platform_type, "any");
+
+        let serena = &preview.candidates[2].template;
+        assert_eq!(serena.ai_type, "codex");
+        assert_eq!(serena.platform_type, "unix");
+        let parsed: toml::Value = toml::from_str(&serena.config_content).unwrap();
+        assert_eq!(
+            parsed["mcp_servers"]["serena"]["args"][0].as_str(),
+            Some("serena")
+        );
+    }
+
+    #[test]
+    fn test_mark_duplicates_ignores_id_and_formatting() {
+        let mut candidates = ConfigImporter::parse(
+            "claude_desktop",
+            "claude_desktop_config.json",
+            r#"{"mcpServers": {"my-memory": {"command": "npx", "args": ["-y", "@modelcontextprotocol/server-memory"], "env": {}}}}"#,
+        )
+        .unwrap();
+
+        let existing = McpTemplate {
+            id: Some(7),
+            name: "Memory".to_string(),
+            version: "1.0.0".to_string(),
+            ai_type: "claude".to_string(),
+            platform_type: "any".to_string(),
+            config_content: r#"{
+  "memory": {
+    "type": "stdio",
+    "command": "npx",
+    "args": ["-y", "@modelcontextprotocol/server-memory"]
+  }
+}"#
+            .to_string(),
+            description: None,
+            is_builtin: Some(1),
+            category: None,
+            tags: None,
+            usage_count: Some(0),
+            parameters: None,
+            platform_overrides: None,
+            created_at: None,
+            updated_at: None,
+        };
+
+        ConfigImporter::mark_duplicates(&mut candidates, &[existing]);
+        assert_eq!(candidates[0].duplicate_of, Some(7));
+        assert_eq!(candidates[0].duplicate_name.as_deref(), Some("Memory"));
+    }
+}
@@ -0,0 +1,179 @@
+use crate::models::config::ConfigHistory;
+use crate::models::mcp_template::McpTemplate;
+use crate::models::{ApiResponse, AppError, ResultExt};
+use crate::services::config_writer::{ConfigFileSnapshot, ConfigPaths, ConfigWriter};
+use crate::services::secret_store::SecretCipher;
+use crate::services::template_converter::server_id;
+use crate::t;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+
+/// 模板写入配置文件的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateApplyResult {
+    pub template_id: i64,
+    pub template_name: String,
+    pub ai_type: String,
+    pub config_path: String,
+    /// 写入的服务器ID，同名服务器已被覆盖
+    pub server_ids: Vec<String>,
+    /// 写入前配置文件的备份
+    pub backup_id: Option<i64>,
+}
+
+/// 配置文件的备份恢复和MCP模板写入，桌面应用和命令行工具共用
+#[derive(Clone)]
+pub struct ConfigService {
+    pool: SqlitePool,
+    paths: Option<ConfigPaths>,
+}
+
+impl ConfigService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool, paths: None }
+    }
+
+    /// 使用指定的配置文件位置，默认使用当前用户主目录下的配置
+    pub fn with_config_paths(mut self, paths: ConfigPaths) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    fn writer(&self) -> Result<ConfigWriter, AppError> {
+        let paths = match &self.paths {
+            Some(paths) => paths.clone(),
+            None => ConfigPaths::detect().context(t!("config.locate_failed"))?,
+        };
+        Ok(ConfigWriter::new(paths))
+    }
+
+    /// 把配置文件恢复为备份时的内容，并记录一条恢复历史
+    pub async fn restore_backup(
+        &self,
+        backup_id: i64,
+    ) -> Result<ApiResponse<ConfigHistory>, AppError> {
+        let pool = &self.pool;
+
+        let backup = match ConfigHistory::get_by_id(pool, backup_id)
+            .await
+            .context(t!("config.backup_load_failed"))?
+        {
+            Some(backup) => backup,
+            None => return Ok(AppError::not_found(t!("resource.backup_record")).into()),
+        };
+
+        self.writer()?
+            .restore(&[ConfigFileSnapshot::from_history(&backup)])
+            .context(t!("config.restore_file_failed"))?;
+
+        let restore_history = ConfigHistory::create(
+            pool,
+            &backup.config_type,
+            &backup.config_path,
+            &backup.backup_content,
+            "restore",
+            Some(&t!("config.restore_description", backup_id = backup_id)),
+        )
+        .await
+        .context(t!("config.restore_history_create_failed"))?;
+
+        Ok(ApiResponse::success(restore_history))
+    }
+
+    /// 渲染模板并写入对应 AI 工具的用户级 MCP 配置，写入前备份原文件
+    pub async fn apply_template(
+        &self,
+        template_id: i64,
+        cipher: &SecretCipher,
+        platform_type: &str,
+        values: HashMap<String, String>,
+    ) -> Result<ApiResponse<TemplateApplyResult>, AppError> {
+        let pool = &self.pool;
+
+        let template = match McpTemplate::get_by_id(pool, template_id)
+            .await
+            .context(t!("template.query_failed"))?
+        {
+            Some(template) => template,
+            None => {
+                return Ok(
+                    AppError::not_found_with_id(t!("resource.mcp_template"), template_id).into(),
+                )
+            }
+        };
+
+        let home_dir = self.paths.as_ref().map(|paths| paths.home.as_path());
+        let rendered = match template
+            .render(pool, cipher, platform_type, home_dir, values)
+            .await
+        {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                return Ok(AppError::validation(e)
+                    .context(t!("template.render_failed"))
+                    .into())
+            }
+        };
+
+        let writer = self.writer()?;
+        let snapshots = writer
+            .snapshot_mcp(&template.ai_type)
+            .context(t!("switch.snapshot_failed"))?;
+        let config_path = match snapshots.first() {
+            Some(snapshot) => snapshot.path.to_string_lossy().to_string(),
+            None => {
+                return Ok(AppError::validation(t!(
+                    "template.apply_unsupported",
+                    ai_type = template.ai_type
+                ))
+                .into())
+            }
+        };
+
+        let description = t!("template.apply_backup_description", name = template.name);
+        let mut backup_id = None;
+        for snapshot in &snapshots {
+            let history = ConfigHistory::create(
+                pool,
+                &snapshot.config_type,
+                &snapshot.path.to_string_lossy(),
+                snapshot.content.as_deref().unwrap_or_default(),
+                "backup",
+                Some(&description),
+            )
+            .await
+            .context(t!("config.backup_failed"))?;
+            backup_id = backup_id.or(history.id);
+        }
+
+        let server_ids = match writer.apply_mcp_servers(
+            &template.ai_type,
+            &rendered,
+            &server_id(&template.name),
+        ) {
+            Ok(server_ids) => server_ids,
+            Err(e) => {
+                return Ok(AppError::internal(t!(
+                    "template.apply_failed",
+                    error = format!("{:#}", e)
+                ))
+                .into())
+            }
+        };
+
+        McpTemplate::increment_usage_count(pool, template_id)
+            .await
+            .context(t!("template.usage_increment_failed"))?;
+        tracing::info!(template_id, ?server_ids, "MCP模板已写入 {}", config_path);
+
+        Ok(ApiResponse::success(TemplateApplyResult {
+            template_id,
+            template_name: template.name,
+            ai_type: template.ai_type,
+            config_path,
+            server_ids,
+            backup_id,
+        }))
+    }
+}
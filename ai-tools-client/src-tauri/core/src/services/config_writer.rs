@@ -0,0 +1,613 @@
+use crate::models::config::ConfigHistory;
+use crate::models::supplier::Supplier;
+use crate::services::template_converter::claude_servers;
+use crate::t;
+use anyhow::{anyhow, bail, Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 各 AI 工具配置文件的位置
+#[derive(Debug, Clone)]
+pub struct ConfigPaths {
+    /// 用户主目录，模板中的 ${home_dir} 渲染为这个目录
+    pub home: PathBuf,
+    pub claude_settings: PathBuf,
+    /// Claude Code 用户配置，用户级 MCP 服务器写在 mcpServers 中
+    pub claude_user: PathBuf,
+    pub codex_config: PathBuf,
+    pub codex_auth: PathBuf,
+}
+
+impl ConfigPaths {
+    /// 以指定目录作为用户主目录
+    pub fn from_home(home: &Path) -> Self {
+        Self {
+            home: home.to_path_buf(),
+            claude_settings: home.join(".claude").join("settings.json"),
+            claude_user: home.join(".claude.json"),
+            codex_config: home.join(".codex").join("config.toml"),
+            codex_auth: home.join(".codex").join("auth.json"),
+        }
+    }
+
+    /// 使用当前用户的主目录
+    pub fn detect() -> Result<Self> {
+        let home = dirs::home_dir().ok_or_else(|| anyhow!(t!("config.home_dir_unavailable")))?;
+        Ok(Self::from_home(&home))
+    }
+}
+
+/// 切换前配置文件的快照，文件不存在时 content 为 None
+#[derive(Debug, Clone)]
+pub struct ConfigFileSnapshot {
+    pub config_type: String,
+    pub path: PathBuf,
+    pub content: Option<String>,
+}
+
+impl ConfigFileSnapshot {
+    /// 由配置备份记录生成快照。备份时文件不存在的记录内容为空，恢复时会删除文件
+    pub fn from_history(history: &ConfigHistory) -> Self {
+        Self {
+            config_type: history.config_type.clone(),
+            path: PathBuf::from(&history.config_path),
+            content: (!history.backup_content.is_empty()).then(|| history.backup_content.clone()),
+        }
+    }
+}
+
+/// 把供应商配置写入 Claude Code / Codex 的配置文件
+pub struct ConfigWriter {
+    paths: ConfigPaths,
+}
+
+impl ConfigWriter {
+    pub fn new(paths: ConfigPaths) -> Self {
+        Self { paths }
+    }
+
+    /// 切换某类供应商会改动的文件（配置类型, 路径）
+    pub fn target_files(&self, supplier_type: &str) -> Vec<(&'static str, PathBuf)> {
+        match supplier_type {
+            "claude" => vec![("settings", self.paths.claude_settings.clone())],
+            "codex" => vec![
+                ("codex", self.paths.codex_config.clone()),
+                ("auth", self.paths.codex_auth.clone()),
+            ],
+            _ => Vec::new(),
+        }
+    }
+
+    /// 写入某类 MCP 服务器配置的文件（配置类型, 路径）
+    pub fn mcp_target_file(&self, ai_type: &str) -> Option<(&'static str, PathBuf)> {
+        match ai_type {
+            "claude" => Some(("claude_mcp", self.paths.claude_user.clone())),
+            "codex" => Some(("codex", self.paths.codex_config.clone())),
+            _ => None,
+        }
+    }
+
+    /// 读取当前配置文件内容，用于备份和回滚
+    pub fn snapshot(&self, supplier_type: &str) -> Result<Vec<ConfigFileSnapshot>> {
+        Self::snapshot_files(self.target_files(supplier_type))
+    }
+
+    /// 读取 MCP 服务器配置文件的内容
+    pub fn snapshot_mcp(&self, ai_type: &str) -> Result<Vec<ConfigFileSnapshot>> {
+        Self::snapshot_files(self.mcp_target_file(ai_type).into_iter().collect())
+    }
+
+    fn snapshot_files(files: Vec<(&'static str, PathBuf)>) -> Result<Vec<ConfigFileSnapshot>> {
+        files
+            .into_iter()
+            .map(|(config_type, path)| {
+                let content =
+                    if path.exists() {
+                        Some(fs::read_to_string(&path).with_context(|| {
+                            t!("config.file_read_failed", path = path.display())
+                        })?)
+                    } else {
+                        None
+                    };
+
+                Ok(ConfigFileSnapshot {
+                    config_type: config_type.to_string(),
+                    path,
+                    content,
+                })
+            })
+            .collect()
+    }
+
+    /// 恢复快照；快照时不存在的文件会被删除
+    pub fn restore(&self, snapshots: &[ConfigFileSnapshot]) -> Result<()> {
+        for snapshot in snapshots {
+            match &snapshot.content {
+                Some(content) => write_atomic(&snapshot.path, content)?,
+                None => {
+                    if snapshot.path.exists() {
+                        fs::remove_file(&snapshot.path).with_context(|| {
+                            t!("config.file_delete_failed", path = snapshot.path.display())
+                        })?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 写入供应商配置，保留文件中与供应商无关的其他设置
+    pub fn apply_supplier(&self, supplier: &Supplier) -> Result<()> {
+        match supplier.r#type.as_str() {
+            "claude" => self.apply_claude(supplier),
+            "codex" => self.apply_codex(supplier),
+            other => bail!(t!(
+                "config.supplier_type_unsupported",
+                supplier_type = other
+            )),
+        }
+    }
+
+    /// 重新读取写入的文件，确认能够解析且内容指向目标供应商
+    pub fn verify_supplier(&self, supplier: &Supplier) -> Result<()> {
+        match supplier.r#type.as_str() {
+            "claude" => self.verify_claude(supplier),
+            "codex" => self.verify_codex(supplier),
+            other => bail!(t!(
+                "config.supplier_type_unsupported",
+                supplier_type = other
+            )),
+        }
+    }
+
+    /// 把渲染后的模板配置合并到用户级 MCP 服务器配置中，同名服务器会被覆盖，
+    /// 返回写入的服务器ID
+    pub fn apply_mcp_servers(
+        &self,
+        ai_type: &str,
+        rendered: &str,
+        fallback_id: &str,
+    ) -> Result<Vec<String>> {
+        match ai_type {
+            "claude" => self.apply_claude_mcp(rendered, fallback_id),
+            "codex" => self.apply_codex_mcp(rendered),
+            other => bail!(t!("template.unsupported_ai_type", ai_type = other)),
+        }
+    }
+
+    fn apply_claude_mcp(&self, rendered: &str, fallback_id: &str) -> Result<Vec<String>> {
+        let root: Value =
+            serde_json::from_str(rendered).with_context(|| t!("config.template_json_invalid"))?;
+        let servers = claude_servers(root, fallback_id).map_err(|e| anyhow!(e))?;
+
+        let path = &self.paths.claude_user;
+        let mut config = read_json_object(path)?;
+        let target = config
+            .entry("mcpServers")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        let target = target.as_object_mut().expect("mcpServers 已确保为对象");
+
+        let ids = servers.iter().map(|(id, _)| id.clone()).collect();
+        for (id, server) in servers {
+            target.insert(id, Value::Object(server));
+        }
+
+        write_atomic(path, &serde_json::to_string_pretty(&Value::Object(config))?)?;
+        Ok(ids)
+    }
+
+    fn apply_codex_mcp(&self, rendered: &str) -> Result<Vec<String>> {
+        let rendered: toml::Table = rendered
+            .parse()
+            .with_context(|| t!("config.template_toml_invalid"))?;
+        let servers = match rendered.get("mcp_servers") {
+            Some(toml::Value::Table(servers)) if !servers.is_empty() => servers.clone(),
+            _ => bail!(t!("config.template_missing_table")),
+        };
+
+        let path = &self.paths.codex_config;
+        let mut config = read_toml_table(path)?;
+        let target = config
+            .entry("mcp_servers")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !target.is_table() {
+            *target = toml::Value::Table(toml::Table::new());
+        }
+        let target = target.as_table_mut().expect("mcp_servers 已确保为表");
+
+        let ids = servers.keys().cloned().collect();
+        for (id, server) in servers {
+            target.insert(id, server);
+        }
+
+        write_atomic(path, &toml::to_string(&config)?)?;
+        Ok(ids)
+    }
+
+    fn apply_claude(&self, supplier: &Supplier) -> Result<()> {
+        let path = &self.paths.claude_settings;
+        let mut settings = read_json_object(path)?;
+
+        let env = settings
+            .entry("env")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if !env.is_object() {
+            *env = Value::Object(Map::new());
+        }
+        let env = env.as_object_mut().expect("env 已确保为对象");
+
+        env.insert(
+            "ANTHROPIC_BASE_URL".to_string(),
+            Value::String(supplier.base_url.clone()),
+        );
+        env.insert(
+            "ANTHROPIC_AUTH_TOKEN".to_string(),
+            Value::String(supplier.auth_token.clone()),
+        );
+
+        // 可选项没有配置时移除，避免沿用上一个供应商的值
+        let optional = [
+            (
+                "API_TIMEOUT_MS",
+                supplier.timeout_ms.map(|ms| ms.to_string()),
+            ),
+            ("ANTHROPIC_DEFAULT_OPUS_MODEL", supplier.opus_model.clone()),
+            (
+                "ANTHROPIC_DEFAULT_SONNET_MODEL",
+                supplier.sonnet_model.clone(),
+            ),
+            (
+                "ANTHROPIC_DEFAULT_HAIKU_MODEL",
+                supplier.haiku_model.clone(),
+            ),
+        ];
+        for (key, value) in optional {
+            match value.filter(|v| !v.trim().is_empty()) {
+                Some(value) => {
+                    env.insert(key.to_string(), Value::String(value));
+                }
+                None => {
+                    env.remove(key);
+                }
+            }
+        }
+
+        let content = serde_json::to_string_pretty(&Value::Object(settings))?;
+        write_atomic(path, &content)
+    }
+
+    fn verify_claude(&self, supplier: &Supplier) -> Result<()> {
+        let path = &self.paths.claude_settings;
+        let settings = read_json_object(path)?;
+        let env = settings
+            .get("env")
+            .and_then(Value::as_object)
+            .ok_or_else(|| anyhow!(t!("config.env_missing", path = path.display())))?;
+
+        expect_value(
+            path,
+            "env.ANTHROPIC_BASE_URL",
+            env.get("ANTHROPIC_BASE_URL").and_then(Value::as_str),
+            &supplier.base_url,
+        )?;
+        expect_value(
+            path,
+            "env.ANTHROPIC_AUTH_TOKEN",
+            env.get("ANTHROPIC_AUTH_TOKEN").and_then(Value::as_str),
+            &supplier.auth_token,
+        )
+    }
+
+    fn apply_codex(&self, supplier: &Supplier) -> Result<()> {
+        let config_path = &self.paths.codex_config;
+        let mut config = read_toml_table(config_path)?;
+
+        config.insert(
+            "model_provider".to_string(),
+            toml::Value::String(supplier.name.clone()),
+        );
+
+        let providers = config
+            .entry("model_providers")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        if !providers.is_table() {
+            *providers = toml::Value::Table(toml::Table::new());
+        }
+        let providers = providers
+            .as_table_mut()
+            .expect("model_providers 已确保为表");
+
+        let mut provider = toml::Table::new();
+        provider.insert(
+            "name".to_string(),
+            toml::Value::String(supplier.name.clone()),
+        );
+        provider.insert(
+            "base_url".to_string(),
+            toml::Value::String(supplier.base_url.clone()),
+        );
+        provider.insert(
+            "wire_api".to_string(),
+            toml::Value::String("responses".to_string()),
+        );
+        provider.insert(
+            "requires_openai_auth".to_string(),
+            toml::Value::Boolean(true),
+        );
+        providers.insert(supplier.name.clone(), toml::Value::Table(provider));
+
+        write_atomic(config_path, &toml::to_string(&config)?)?;
+
+        let auth_path = &self.paths.codex_auth;
+        let mut auth = read_json_object(auth_path)?;
+        auth.insert(
+            "OPENAI_API_KEY".to_string(),
+            Value::String(supplier.auth_token.clone()),
+        );
+        write_atomic(
+            auth_path,
+            &serde_json::to_string_pretty(&Value::Object(auth))?,
+        )
+    }
+
+    fn verify_codex(&self, supplier: &Supplier) -> Result<()> {
+        let config_path = &self.paths.codex_config;
+        let config = read_toml_table(config_path)?;
+
+        expect_value(
+            config_path,
+            "model_provider",
+            config.get("model_provider").and_then(toml::Value::as_str),
+            &supplier.name,
+        )?;
+
+        let base_url = config
+            .get("model_providers")
+            .and_then(|providers| providers.get(&supplier.name))
+            .and_then(|provider| provider.get("base_url"))
+            .and_then(toml::Value::as_str);
+        expect_value(
+            config_path,
+            &format!("model_providers.{}.base_url", supplier.name),
+            base_url,
+            &supplier.base_url,
+        )?;
+
+        let auth_path = &self.paths.codex_auth;
+        let auth = read_json_object(auth_path)?;
+        expect_value(
+            auth_path,
+            "OPENAI_API_KEY",
+            auth.get("OPENAI_API_KEY").and_then(Value::as_str),
+            &supplier.auth_token,
+        )
+    }
+}
+
+/// 读取 JSON 对象，文件不存在或为空时返回空对象
+fn read_json_object(path: &Path) -> Result<Map<String, Value>> {
+    if !path.exists() {
+        return Ok(Map::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| t!("config.file_read_failed", path = path.display()))?;
+    if content.trim().is_empty() {
+        return Ok(Map::new());
+    }
+
+    match serde_json::from_str(&content)
+        .with_context(|| t!("config.json_parse_failed", path = path.display()))?
+    {
+        Value::Object(map) => Ok(map),
+        _ => bail!(t!("config.json_not_object", path = path.display())),
+    }
+}
+
+/// 读取 TOML 表，文件不存在时返回空表
+fn read_toml_table(path: &Path) -> Result<toml::Table> {
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .with_context(|| t!("config.file_read_failed", path = path.display()))?;
+    content
+        .parse::<toml::Table>()
+        .with_context(|| t!("config.toml_parse_failed", path = path.display()))
+}
+
+fn expect_value(path: &Path, key: &str, actual: Option<&str>, expected: &str) -> Result<()> {
+    if actual == Some(expected) {
+        Ok(())
+    } else {
+        bail!(t!(
+            "config.supplier_mismatch",
+            path = path.display(),
+            key = key
+        ))
+    }
+}
+
+/// 先写临时文件再重命名，避免写到一半留下损坏的配置
+fn write_atomic(path: &Path, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| t!("config.dir_create_failed", path = parent.display()))?;
+    }
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| anyhow!(t!("config.path_invalid", path = path.display())))?;
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name.to_string_lossy()));
+
+    fs::write(&tmp_path, content)
+        .with_context(|| t!("config.file_write_failed", path = tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| t!("config.file_replace_failed", path = path.display()))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn supplier(supplier_type: &str, name: &str) -> Supplier {
+        Supplier {
+            id: Some(1),
+            r#type: supplier_type.to_string(),
+            name: name.to_string(),
+            base_url: "https://api.example.com".to_string(),
+            auth_token: "sk-test".to_string(),
+            timeout_ms: Some(600000),
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: Some("claude-sonnet".to_string()),
+            haiku_model: None,
+            is_active: Some(0),
+            sort_order: Some(0),
+            is_healthy: None,
+            last_check_time: None,
+            response_time: None,
+            consecutive_failures: None,
+            uptime_percentage: None,
+            total_requests: None,
+            failed_requests: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_claude_settings_keep_unrelated_keys() {
+        let home = tempdir().unwrap();
+        let paths = ConfigPaths::from_home(home.path());
+        fs::create_dir_all(paths.claude_settings.parent().unwrap()).unwrap();
+        fs::write(
+            &paths.claude_settings,
+            r#"{"permissions":{"allow":["Bash"]},"env":{"FOO":"bar","ANTHROPIC_DEFAULT_OPUS_MODEL":"old"}}"#,
+        )
+        .unwrap();
+
+        let writer = ConfigWriter::new(paths.clone());
+        let target = supplier("claude", "relay");
+        writer.apply_supplier(&target).unwrap();
+        writer.verify_supplier(&target).unwrap();
+
+        let settings: Value =
+            serde_json::from_str(&fs::read_to_string(&paths.claude_settings).unwrap()).unwrap();
+        assert_eq!(settings["permissions"]["allow"][0], "Bash");
+        assert_eq!(settings["env"]["FOO"], "bar");
+        assert_eq!(
+            settings["env"]["ANTHROPIC_BASE_URL"],
+            "https://api.example.com"
+        );
+        assert_eq!(
+            settings["env"]["ANTHROPIC_DEFAULT_SONNET_MODEL"],
+            "claude-sonnet"
+        );
+        assert!(settings["env"]
+            .get("ANTHROPIC_DEFAULT_OPUS_MODEL")
+            .is_none());
+    }
+
+    #[test]
+    fn test_codex_config_and_restore() {
+        let home = tempdir().unwrap();
+        let paths = ConfigPaths::from_home(home.path());
+        fs::create_dir_all(paths.codex_config.parent().unwrap()).unwrap();
+        fs::write(&paths.codex_config, "model = \"gpt-5-codex\"\n").unwrap();
+
+        let writer = ConfigWriter::new(paths.clone());
+        let snapshots = writer.snapshot("codex").unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert!(snapshots[1].content.is_none());
+
+        let target = supplier("codex", "my relay");
+        writer.apply_supplier(&target).unwrap();
+        writer.verify_supplier(&target).unwrap();
+
+        let config: toml::Table = fs::read_to_string(&paths.codex_config)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(config["model"].as_str(), Some("gpt-5-codex"));
+        assert_eq!(config["model_provider"].as_str(), Some("my relay"));
+        assert!(paths.codex_auth.exists());
+
+        writer.restore(&snapshots).unwrap();
+        assert_eq!(
+            fs::read_to_string(&paths.codex_config).unwrap(),
+            "model = \"gpt-5-codex\"\n"
+        );
+        assert!(!paths.codex_auth.exists());
+    }
+
+    #[test]
+    fn test_apply_mcp_servers_merges_user_config() {
+        let home = tempdir().unwrap();
+        let paths = ConfigPaths::from_home(home.path());
+        fs::write(
+            &paths.claude_user,
+            r#"{"numStartups":3,"mcpServers":{"old":{"command":"old"}}}"#,
+        )
+        .unwrap();
+
+        let writer = ConfigWriter::new(paths.clone());
+        let ids = writer
+            .apply_mcp_servers(
+                "claude",
+                r#"{"mcpServers":{"fetch":{"command":"uvx","args":["mcp-server-fetch"]}}}"#,
+                "fetch",
+            )
+            .unwrap();
+        assert_eq!(ids, vec!["fetch".to_string()]);
+
+        let config: Value =
+            serde_json::from_str(&fs::read_to_string(&paths.claude_user).unwrap()).unwrap();
+        assert_eq!(config["numStartups"], 3);
+        assert_eq!(config["mcpServers"]["old"]["command"], "old");
+        assert_eq!(config["mcpServers"]["fetch"]["command"], "uvx");
+
+        fs::create_dir_all(paths.codex_config.parent().unwrap()).unwrap();
+        fs::write(&paths.codex_config, "model = \"gpt-5-codex\"\n").unwrap();
+        let ids = writer
+            .apply_mcp_servers(
+                "codex",
+                "[mcp_servers.fetch]\ncommand = \"uvx\"\nargs = [\"mcp-server-fetch\"]\n",
+                "fetch",
+            )
+            .unwrap();
+        assert_eq!(ids, vec!["fetch".to_string()]);
+
+        let config: toml::Table = fs::read_to_string(&paths.codex_config)
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(config["model"].as_str(), Some("gpt-5-codex"));
+        assert_eq!(
+            config["mcp_servers"]["fetch"]["command"].as_str(),
+            Some("uvx")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_unparseable_file() {
+        let home = tempdir().unwrap();
+        let paths = ConfigPaths::from_home(home.path());
+        let writer = ConfigWriter::new(paths.clone());
+        let target = supplier("claude", "relay");
+
+        writer.apply_supplier(&target).unwrap();
+        fs::write(&paths.claude_settings, "{ broken").unwrap();
+
+        assert!(writer.verify_supplier(&target).is_err());
+    }
+}
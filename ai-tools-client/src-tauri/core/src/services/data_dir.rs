@@ -0,0 +1,589 @@
+use crate::services::database::Database;
+use crate::t;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// 指定数据目录的环境变量
+pub const DATA_DIR_ENV: &str = "AI_TOOLS_DATA_DIR";
+
+/// 指定数据目录的命令行参数，支持 `--data-dir <路径>` 和 `--data-dir=<路径>`
+pub const DATA_DIR_ARG: &str = "--data-dir";
+
+pub const DB_FILE_NAME: &str = "ai-tools.db";
+
+/// 迁移数据目录时不复制的文件，数据库由 VACUUM INTO 单独生成
+const SKIPPED_FILES: [&str; 4] = [
+    DB_FILE_NAME,
+    "ai-tools.db-wal",
+    "ai-tools.db-shm",
+    ".write_test",
+];
+
+/// 数据目录的来源，按优先级排列
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDirSource {
+    CliArg,
+    EnvVar,
+    Saved,
+    Home,
+    ProjectData,
+    SystemData,
+    Temp,
+    CurrentDir,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageHealth {
+    /// 数据保存在首选目录
+    Ok,
+    /// 指定的目录不可用或使用了临时目录，数据可能丢失
+    Degraded,
+    /// 使用内存数据库，退出后数据全部丢失；或数据已迁移、重启前无法保存修改
+    Critical,
+}
+
+/// 数据目录选择结果
+#[derive(Debug, Clone)]
+pub struct DataDirSelection {
+    pub dir: PathBuf,
+    pub source: DataDirSource,
+    /// 被跳过的候选目录及原因
+    pub fallback_reasons: Vec<String>,
+}
+
+/// 数据存储状态，启动时确定，供界面提示
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StorageStatus {
+    pub health: StorageHealth,
+    pub message: String,
+    pub data_dir: String,
+    pub db_path: String,
+    pub source: DataDirSource,
+    pub fallback_reasons: Vec<String>,
+    pub in_memory: bool,
+    pub db_error: Option<String>,
+    /// 已保存但要重启后才生效的数据目录
+    pub pending_data_dir: Option<String>,
+    /// 数据已迁移，当前数据库不再接受写入
+    pub restart_required: bool,
+    /// 数据目录由命令行参数或环境变量指定，已保存的目录重启后不会生效
+    pub pending_overridden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MoveDataDirResult {
+    pub from: String,
+    pub to: String,
+    pub copied_files: usize,
+    pub restart_required: bool,
+}
+
+/// 按 命令行参数 > 环境变量 > 已保存的目录 > 默认候选目录 的顺序选择数据目录
+#[derive(Debug, Clone, Default)]
+pub struct DataDirResolver {
+    pub cli_arg: Option<PathBuf>,
+    pub env_var: Option<PathBuf>,
+    pub saved: Option<PathBuf>,
+}
+
+impl DataDirResolver {
+    pub fn from_environment() -> Self {
+        Self {
+            cli_arg: parse_data_dir_arg(std::env::args().skip(1)),
+            env_var: std::env::var_os(DATA_DIR_ENV)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from),
+            saved: Self::load_saved(),
+        }
+    }
+
+    /// 保存数据目录位置的文件，放在系统配置目录下，不随数据目录移动
+    fn location_file() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("ai-tools-client").join("data-dir"))
+    }
+
+    pub fn load_saved() -> Option<PathBuf> {
+        let content = std::fs::read_to_string(Self::location_file()?).ok()?;
+        let path = content.trim();
+        if path.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(path))
+        }
+    }
+
+    /// 保存新的数据目录，下次启动时生效
+    pub fn save_location(dir: &Path) -> Result<()> {
+        let file =
+            Self::location_file().ok_or_else(|| anyhow!(t!("storage.config_dir_unavailable")))?;
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&file, dir.to_string_lossy().as_bytes())
+            .with_context(|| t!("storage.location_save_failed", path = file.display()))
+    }
+
+    pub fn resolve(&self) -> DataDirSelection {
+        let mut fallback_reasons = Vec::new();
+
+        let overrides = [
+            (DataDirSource::CliArg, &self.cli_arg),
+            (DataDirSource::EnvVar, &self.env_var),
+            (DataDirSource::Saved, &self.saved),
+        ];
+        for (source, dir) in overrides {
+            if let Some(dir) = dir {
+                match ensure_writable_dir(dir) {
+                    Ok(()) => {
+                        tracing::info!("使用指定的数据目录: {:?}", dir);
+                        return DataDirSelection {
+                            dir: dir.clone(),
+                            source,
+                            fallback_reasons,
+                        };
+                    }
+                    Err(reason) => fallback_reasons.push(reason),
+                }
+            }
+        }
+
+        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let candidates = [
+            (
+                DataDirSource::Home,
+                dirs::home_dir().map(|dir| dir.join(".ai-tools")),
+            ),
+            (DataDirSource::ProjectData, Some(current_dir.join("data"))),
+            (
+                DataDirSource::SystemData,
+                dirs::data_dir().map(|dir| dir.join("ai-tools-client")),
+            ),
+            (
+                DataDirSource::Temp,
+                Some(std::env::temp_dir().join("ai-tools-client")),
+            ),
+        ];
+        for (source, dir) in candidates {
+            if let Some(dir) = dir {
+                tracing::debug!("尝试数据目录: {:?}", dir);
+                match ensure_writable_dir(&dir) {
+                    Ok(()) => {
+                        tracing::info!("使用数据目录: {:?}", dir);
+                        return DataDirSelection {
+                            dir,
+                            source,
+                            fallback_reasons,
+                        };
+                    }
+                    Err(reason) => fallback_reasons.push(reason),
+                }
+            }
+        }
+
+        // 如果都失败，使用当前目录
+        tracing::warn!("所有路径都失败，使用当前目录");
+        DataDirSelection {
+            dir: PathBuf::from("."),
+            source: DataDirSource::CurrentDir,
+            fallback_reasons,
+        }
+    }
+}
+
+impl StorageStatus {
+    pub fn new(selection: &DataDirSelection, db_error: Option<String>) -> Self {
+        let in_memory = db_error.is_some();
+        let temporary = matches!(
+            selection.source,
+            DataDirSource::Temp | DataDirSource::CurrentDir
+        );
+
+        let (health, message) = if let Some(error) = &db_error {
+            (
+                StorageHealth::Critical,
+                t!("storage.in_memory", error = error),
+            )
+        } else if temporary {
+            (
+                StorageHealth::Degraded,
+                t!("storage.temporary", dir = selection.dir.display()),
+            )
+        } else if !selection.fallback_reasons.is_empty() {
+            (
+                StorageHealth::Degraded,
+                t!("storage.fallback", dir = selection.dir.display()),
+            )
+        } else {
+            (
+                StorageHealth::Ok,
+                t!("storage.ok", dir = selection.dir.display()),
+            )
+        };
+
+        Self {
+            health,
+            message,
+            data_dir: selection.dir.to_string_lossy().to_string(),
+            db_path: selection
+                .dir
+                .join(DB_FILE_NAME)
+                .to_string_lossy()
+                .to_string(),
+            source: selection.source,
+            fallback_reasons: selection.fallback_reasons.clone(),
+            in_memory,
+            db_error,
+            pending_data_dir: None,
+            restart_required: false,
+            pending_overridden: false,
+        }
+    }
+
+    /// 记录已保存、重启后才生效的数据目录。moved 表示数据已迁移且旧数据库已停止写入，
+    /// 重启前界面上的修改都不会保存。当前数据目录由命令行参数或环境变量指定时，
+    /// 重启后仍会使用指定的目录，需要提示用户先移除该设置
+    pub fn set_pending(&mut self, pending: &str, moved: bool) {
+        let setting = match self.source {
+            DataDirSource::CliArg => Some(t!("storage.setting_cli_arg", arg = DATA_DIR_ARG)),
+            DataDirSource::EnvVar => Some(t!("storage.setting_env_var", name = DATA_DIR_ENV)),
+            _ => None,
+        };
+
+        self.pending_data_dir = Some(pending.to_string());
+        self.pending_overridden = setting.is_some();
+        self.restart_required = moved;
+        match (moved, setting) {
+            (true, None) => {
+                self.health = StorageHealth::Critical;
+                self.message = t!("storage.moved_restart_required", dir = pending);
+            }
+            (true, Some(setting)) => {
+                self.health = StorageHealth::Critical;
+                self.message = t!(
+                    "storage.moved_overridden",
+                    dir = pending,
+                    setting = setting,
+                    current = self.data_dir
+                );
+            }
+            (false, Some(setting)) => {
+                if self.health == StorageHealth::Ok {
+                    self.health = StorageHealth::Degraded;
+                }
+                self.message = t!(
+                    "storage.pending_overridden",
+                    dir = pending,
+                    setting = setting,
+                    current = self.data_dir
+                );
+            }
+            (false, None) => {}
+        }
+    }
+}
+
+fn parse_data_dir_arg(args: impl IntoIterator<Item = String>) -> Option<PathBuf> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == DATA_DIR_ARG {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(value) = arg.strip_prefix(&format!("{}=", DATA_DIR_ARG)) {
+            return Some(PathBuf::from(value));
+        }
+    }
+    None
+}
+
+/// 创建目录并确认可以写入，失败时返回原因
+pub fn ensure_writable_dir(dir: &Path) -> Result<(), String> {
+    if let Err(err) = std::fs::create_dir_all(dir) {
+        return Err(t!(
+            "storage.dir_create_failed",
+            dir = dir.display(),
+            error = err
+        ));
+    }
+
+    let test_file = dir.join(".write_test");
+    match std::fs::write(&test_file, "test") {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&test_file);
+            Ok(())
+        }
+        Err(err) => Err(t!(
+            "storage.dir_not_writable",
+            dir = dir.display(),
+            error = err
+        )),
+    }
+}
+
+/// 根据数据库文件路径生成 sqlx 可识别的 sqlite 连接字符串
+pub fn build_sqlite_url(db_path: &Path) -> String {
+    if db_path.is_absolute() {
+        if let Ok(url) = Url::from_file_path(db_path) {
+            return url.as_str().replacen("file://", "sqlite://", 1);
+        }
+    }
+
+    let normalized = db_path.to_string_lossy().replace('\\', "/");
+    format!("sqlite://{}", normalized)
+}
+
+/// 把数据库和数据目录中的其他文件（备份、快照、密钥）复制到新目录并校验，再由 save_location
+/// 保存新目录的位置。原目录保持不变，新目录在重启后生效。
+/// 位置保存成功后才关闭连接池，避免重启前的修改写入旧数据库后在新目录中丢失；
+/// 保存失败时删除复制的数据库，连接池保持打开，应用继续使用原目录
+pub async fn move_data_dir(
+    pool: &SqlitePool,
+    from: &Path,
+    to: &Path,
+    save_location: impl FnOnce(&Path) -> Result<()>,
+) -> Result<MoveDataDirResult> {
+    ensure_writable_dir(to).map_err(|reason| anyhow!(reason))?;
+
+    let from_real = from.canonicalize().unwrap_or_else(|_| from.to_path_buf());
+    let to_real = to.canonicalize()?;
+    if to_real == from_real {
+        return Err(anyhow!(t!("storage.target_same")));
+    }
+    if to_real.starts_with(&from_real) {
+        return Err(anyhow!(t!("storage.target_inside_current")));
+    }
+    if to.join(DB_FILE_NAME).exists() {
+        return Err(anyhow!(t!(
+            "storage.target_has_database",
+            file = DB_FILE_NAME
+        )));
+    }
+
+    let copied_files = if from.exists() {
+        copy_dir(from, to)?
+    } else {
+        0
+    };
+
+    // VACUUM INTO 生成一致的副本，内存数据库也能借此保存到磁盘
+    let db_path = to.join(DB_FILE_NAME);
+    sqlx::query("VACUUM INTO ?")
+        .bind(db_path.to_string_lossy().to_string())
+        .execute(pool)
+        .await?;
+
+    let copy = Database::new(&build_sqlite_url(&db_path)).await?;
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(copy.pool())
+        .await?;
+    copy.pool().close().await;
+    if integrity != "ok" {
+        let _ = std::fs::remove_file(&db_path);
+        return Err(anyhow!(t!("storage.integrity_failed", result = integrity)));
+    }
+
+    if let Err(e) = save_location(to) {
+        let _ = std::fs::remove_file(&db_path);
+        return Err(e);
+    }
+    pool.close().await;
+
+    Ok(MoveDataDirResult {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        copied_files: copied_files + 1,
+        restart_required: true,
+    })
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<usize> {
+    let mut copied = 0;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if SKIPPED_FILES.iter().any(|skipped| name == *skipped) {
+            continue;
+        }
+
+        let target = to.join(&name);
+        if entry.file_type()?.is_dir() {
+            std::fs::create_dir_all(&target)?;
+            copied += copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| t!("storage.copy_failed", path = entry.path().display()))?;
+            copied += 1;
+        }
+    }
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_override_priority_and_fallback() {
+        let dir = TempDir::new().unwrap();
+        let cli_dir = dir.path().join("cli");
+        let env_dir = dir.path().join("env");
+
+        let resolver = DataDirResolver {
+            cli_arg: Some(cli_dir.clone()),
+            env_var: Some(env_dir.clone()),
+            saved: None,
+        };
+        let selection = resolver.resolve();
+        assert_eq!(selection.dir, cli_dir);
+        assert_eq!(selection.source, DataDirSource::CliArg);
+        assert_eq!(
+            StorageStatus::new(&selection, None).health,
+            StorageHealth::Ok
+        );
+
+        // 指定的目录不可用时记录原因并改用下一个候选目录
+        let blocked = dir.path().join("blocked");
+        std::fs::write(&blocked, "not a directory").unwrap();
+        let resolver = DataDirResolver {
+            cli_arg: Some(blocked),
+            env_var: Some(env_dir.clone()),
+            saved: None,
+        };
+        let selection = resolver.resolve();
+        assert_eq!(selection.dir, env_dir);
+        assert_eq!(selection.source, DataDirSource::EnvVar);
+        assert_eq!(selection.fallback_reasons.len(), 1);
+        assert_eq!(
+            StorageStatus::new(&selection, None).health,
+            StorageHealth::Degraded
+        );
+
+        let status = StorageStatus::new(&selection, Some("disk I/O error".to_string()));
+        assert_eq!(status.health, StorageHealth::Critical);
+        assert!(status.in_memory);
+    }
+
+    #[test]
+    fn test_pending_dir_overridden_by_env_var() {
+        let dir = TempDir::new().unwrap();
+        let saved_dir = dir.path().join("saved");
+        let resolver = DataDirResolver {
+            cli_arg: None,
+            env_var: None,
+            saved: Some(saved_dir.clone()),
+        };
+        let mut status = StorageStatus::new(&resolver.resolve(), None);
+        status.set_pending("/data/next", true);
+        assert_eq!(status.health, StorageHealth::Critical);
+        assert!(status.restart_required);
+        assert!(!status.pending_overridden);
+
+        // 环境变量优先于保存的目录，重启后仍会使用环境变量指定的目录
+        let env_dir = dir.path().join("env");
+        let resolver = DataDirResolver {
+            cli_arg: None,
+            env_var: Some(env_dir.clone()),
+            saved: Some(saved_dir),
+        };
+        let mut status = StorageStatus::new(&resolver.resolve(), None);
+        status.set_pending("/data/next", false);
+        assert_eq!(status.health, StorageHealth::Degraded);
+        assert!(status.pending_overridden);
+        assert!(!status.restart_required);
+        assert!(status.message.contains(DATA_DIR_ENV));
+        assert!(status
+            .message
+            .contains(&env_dir.to_string_lossy().to_string()));
+    }
+
+    #[test]
+    fn test_parse_data_dir_arg() {
+        let args = |items: &[&str]| {
+            items
+                .iter()
+                .map(|item| item.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            parse_data_dir_arg(args(&["--data-dir", "/data/ai"])),
+            Some(PathBuf::from("/data/ai"))
+        );
+        assert_eq!(
+            parse_data_dir_arg(args(&["--verbose", "--data-dir=/data/ai"])),
+            Some(PathBuf::from("/data/ai"))
+        );
+        assert_eq!(parse_data_dir_arg(args(&["--verbose"])), None);
+    }
+
+    #[tokio::test]
+    async fn test_move_data_dir_copies_database_and_files() {
+        let root = TempDir::new().unwrap();
+        let from = root.path().join("old");
+        let to = root.path().join("new");
+        std::fs::create_dir_all(from.join("snapshots")).unwrap();
+        std::fs::write(from.join("template-secrets.key"), [7u8; 32]).unwrap();
+        std::fs::write(from.join("snapshots").join("ai-tools-1.db"), "snapshot").unwrap();
+
+        let db = Database::new(&build_sqlite_url(&from.join(DB_FILE_NAME)))
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO app_state (key, value) VALUES ('moved', 'yes')")
+            .execute(db.pool())
+            .await
+            .unwrap();
+
+        // 位置保存失败时不关闭连接池，删除复制的数据库以便重试
+        let error = move_data_dir(db.pool(), &from, &to, |_| Err(anyhow!("read-only")))
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "read-only");
+        assert!(!db.pool().is_closed());
+        assert!(!to.join(DB_FILE_NAME).exists());
+
+        let mut saved = None;
+        let result = move_data_dir(db.pool(), &from, &to, |dir| {
+            saved = Some(dir.to_path_buf());
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(saved.as_deref(), Some(to.as_path()));
+        assert_eq!(result.copied_files, 3);
+        assert!(result.restart_required);
+        assert!(to.join("template-secrets.key").exists());
+        assert!(to.join("snapshots").join("ai-tools-1.db").exists());
+        // 原目录保持不变，迁移后不再接受写入
+        assert!(from.join(DB_FILE_NAME).exists());
+        assert!(db.pool().is_closed());
+        assert!(
+            sqlx::query("INSERT INTO app_state (key, value) VALUES ('late', 'lost')")
+                .execute(db.pool())
+                .await
+                .is_err()
+        );
+
+        let moved = Database::new(&build_sqlite_url(&to.join(DB_FILE_NAME)))
+            .await
+            .unwrap();
+        let value: String = sqlx::query_scalar("SELECT value FROM app_state WHERE key = 'moved'")
+            .fetch_one(moved.pool())
+            .await
+            .unwrap();
+        assert_eq!(value, "yes");
+
+        // 目标目录已有数据库时拒绝覆盖
+        let error = move_data_dir(db.pool(), &from, &to, |_| Ok(()))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("已存在数据库文件"));
+        let error = move_data_dir(db.pool(), &from, &from.join("nested"), |_| Ok(()))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("当前数据目录之内"));
+    }
+}
@@ -0,0 +1,953 @@
+use crate::models::mcp_template::McpTemplate;
+use crate::t;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Connection, FromRow, Row, SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// 当前应用支持的最新数据库结构版本
+pub const SCHEMA_VERSION: i64 = 3;
+
+/// 按版本号顺序执行的迁移。已发布的迁移不能再修改，结构变化一律追加新版本
+const MIGRATIONS: [(i64, &str); 3] = [
+    (1, "初始表结构"),
+    (2, "为常用查询添加索引"),
+    (3, "记录半开试探许可的发放时间"),
+];
+
+pub struct Database {
+    pub pool: SqlitePool,
+}
+
+/// 已执行的迁移记录
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SchemaVersion {
+    pub version: i64,
+    pub description: String,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+impl Database {
+    pub async fn new(database_url: &str) -> Result<Self> {
+        let connect_options = SqliteConnectOptions::from_str(database_url)?
+            .create_if_missing(true)
+            .foreign_keys(true);
+
+        // 内存数据库每个连接各自独立，只能用单个常驻连接
+        let in_memory = database_url.contains(":memory:") || database_url.contains("mode=memory");
+        let pool_options = if in_memory {
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+        } else {
+            SqlitePoolOptions::new().max_connections(5)
+        };
+        let pool = pool_options.connect_with(connect_options).await?;
+
+        // 运行数据库迁移
+        Self::run_migrations(&pool).await?;
+        Self::ensure_template_search_index(&pool).await?;
+
+        Ok(Database { pool })
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    /// 执行尚未执行的迁移。数据库版本高于应用支持的版本时拒绝打开，
+    /// 执行迁移前先把数据库备份到同目录的 backups 下
+    async fn run_migrations(pool: &SqlitePool) -> Result<()> {
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS schema_version (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        let current = Self::current_schema_version(pool).await?;
+        if current > SCHEMA_VERSION {
+            anyhow::bail!(t!(
+                "database.schema_too_new",
+                version = current,
+                supported = SCHEMA_VERSION
+            ));
+        }
+
+        let pending: Vec<(i64, &str)> = MIGRATIONS
+            .iter()
+            .copied()
+            .filter(|(version, _)| *version > current)
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+
+        if let Some(backup) = Self::backup_before_migration(pool, current).await? {
+            tracing::info!("数据库迁移前已备份到: {:?}", backup);
+        }
+
+        // 外键开关在事务内无效，迁移期间关闭，提交前用 foreign_key_check 检查
+        let mut conn = pool.acquire().await?;
+        sqlx::query("PRAGMA foreign_keys = OFF")
+            .execute(&mut *conn)
+            .await?;
+        let mut result = Ok(());
+        for (version, description) in pending {
+            result = Self::apply_migration(&mut conn, version, description).await;
+            if result.is_err() {
+                break;
+            }
+        }
+        sqlx::query("PRAGMA foreign_keys = ON")
+            .execute(&mut *conn)
+            .await?;
+
+        result
+    }
+
+    async fn apply_migration(
+        conn: &mut SqliteConnection,
+        version: i64,
+        description: &str,
+    ) -> Result<()> {
+        let mut tx = conn.begin().await?;
+        match version {
+            1 => Self::migrate_v1(&mut tx).await,
+            2 => Self::migrate_v2(&mut tx).await,
+            3 => Self::migrate_v3(&mut tx).await,
+            _ => Err(anyhow::anyhow!(t!(
+                "database.migration_undefined",
+                version = version
+            ))),
+        }
+        .map_err(|e| {
+            anyhow::anyhow!(t!(
+                "database.migration_failed",
+                version = version,
+                description = description,
+                error = e
+            ))
+        })?;
+
+        let violations = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(&mut *tx)
+            .await?;
+        if !violations.is_empty() {
+            anyhow::bail!(t!(
+                "database.migration_foreign_keys",
+                version = version,
+                description = description,
+                count = violations.len()
+            ));
+        }
+
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?, ?)")
+            .bind(version)
+            .bind(description)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn current_schema_version(pool: &SqlitePool) -> Result<i64> {
+        let version: Option<i64> = sqlx::query_scalar("SELECT MAX(version) FROM schema_version")
+            .fetch_one(pool)
+            .await?;
+        Ok(version.unwrap_or(0))
+    }
+
+    /// 获取已执行的迁移记录
+    pub async fn get_schema_versions(pool: &SqlitePool) -> Result<Vec<SchemaVersion>> {
+        let versions =
+            sqlx::query_as::<_, SchemaVersion>("SELECT * FROM schema_version ORDER BY version ASC")
+                .fetch_all(pool)
+                .await?;
+        Ok(versions)
+    }
+
+    /// 用 VACUUM INTO 生成一致的备份文件；内存数据库或尚未建表的新数据库不需要备份
+    async fn backup_before_migration(pool: &SqlitePool, current: i64) -> Result<Option<PathBuf>> {
+        let db_file: String = sqlx::query("PRAGMA database_list")
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .find(|row| row.get::<String, _>("name") == "main")
+            .map(|row| row.get::<String, _>("file"))
+            .unwrap_or_default();
+        if db_file.is_empty() {
+            return Ok(None);
+        }
+
+        let user_tables: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND name != 'schema_version'",
+        )
+        .fetch_one(pool)
+        .await?;
+        if user_tables == 0 {
+            return Ok(None);
+        }
+
+        let db_path = Path::new(&db_file);
+        let backup_dir = db_path
+            .parent()
+            .map(|dir| dir.join("backups"))
+            .unwrap_or_else(|| PathBuf::from("backups"));
+        std::fs::create_dir_all(&backup_dir)?;
+
+        let stem = db_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "database".to_string());
+        let backup = backup_dir.join(format!(
+            "{}-v{}-{}.db",
+            stem,
+            current,
+            Utc::now().format("%Y%m%d%H%M%S")
+        ));
+        sqlx::query("VACUUM INTO ?")
+            .bind(backup.to_string_lossy().to_string())
+            .execute(pool)
+            .await?;
+
+        Ok(Some(backup))
+    }
+
+    /// 版本 3：熔断器记录最近一次发放半开试探许可的时间，结果一直没有记录的许可过期后可以重新发放
+    async fn migrate_v3(conn: &mut SqliteConnection) -> Result<()> {
+        let rows = sqlx::query("PRAGMA table_info('circuit_breakers')")
+            .fetch_all(&mut *conn)
+            .await?;
+        let has_column = rows
+            .iter()
+            .any(|row| row.get::<String, _>("name") == "probe_acquired_at");
+
+        if !has_column {
+            sqlx::query("ALTER TABLE circuit_breakers ADD COLUMN probe_acquired_at DATETIME")
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// 版本 2：为按类型筛选和按时间排序的查询添加索引
+    async fn migrate_v2(conn: &mut SqliteConnection) -> Result<()> {
+        let indexes = [
+            "CREATE INDEX IF NOT EXISTS idx_suppliers_type_sort ON suppliers(type, sort_order)",
+            "CREATE INDEX IF NOT EXISTS idx_mcp_templates_filters ON mcp_templates(ai_type, platform_type)",
+            "CREATE INDEX IF NOT EXISTS idx_config_history_type_time ON config_history(config_type, operation_time)",
+            "CREATE INDEX IF NOT EXISTS idx_switch_history_type_time ON switch_history(switch_type, created_at)",
+        ];
+        for sql in indexes {
+            sqlx::query(sql).execute(&mut *conn).await?;
+        }
+
+        Ok(())
+    }
+
+    /// 版本 1：未记录版本之前的全部表结构。旧数据库缺少的列和约束在这里补齐，因此每一步都可以重复执行
+    async fn migrate_v1(conn: &mut SqliteConnection) -> Result<()> {
+        // 创建suppliers表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS suppliers (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                type TEXT NOT NULL,
+                name TEXT NOT NULL,
+                base_url TEXT NOT NULL,
+                auth_token TEXT NOT NULL DEFAULT '',
+                timeout_ms INTEGER,
+                auto_update INTEGER DEFAULT 0,
+                opus_model TEXT,
+                sonnet_model TEXT,
+                haiku_model TEXT,
+                is_active INTEGER DEFAULT 0,
+                sort_order INTEGER DEFAULT 0,
+                is_healthy INTEGER DEFAULT 1,
+                last_check_time DATETIME,
+                response_time INTEGER,
+                consecutive_failures INTEGER DEFAULT 0,
+                uptime_percentage REAL DEFAULT 100.0,
+                total_requests INTEGER DEFAULT 0,
+                failed_requests INTEGER DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Self::ensure_suppliers_schema(&mut *conn).await?;
+
+        // 创建mcp_templates表
+        sqlx::query(&Self::mcp_templates_table_sql("mcp_templates"))
+            .execute(&mut *conn)
+            .await?;
+        Self::ensure_mcp_templates_schema(&mut *conn).await?;
+        Self::ensure_mcp_templates_unique_key(&mut *conn).await?;
+
+        // 创建work_mode_configs表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS work_mode_configs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                mode_name TEXT NOT NULL UNIQUE,
+                active_claude_supplier_id INTEGER,
+                active_codex_supplier_id INTEGER,
+                mcp_template_ids TEXT,
+                pinned_template_revisions TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (active_claude_supplier_id) REFERENCES suppliers(id),
+                FOREIGN KEY (active_codex_supplier_id) REFERENCES suppliers(id)
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Self::ensure_work_mode_configs_schema(&mut *conn).await?;
+
+        // 创建config_history表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS config_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                config_type TEXT NOT NULL,
+                config_path TEXT NOT NULL,
+                backup_content TEXT NOT NULL,
+                operation_type TEXT NOT NULL,
+                operation_time DATETIME DEFAULT CURRENT_TIMESTAMP,
+                description TEXT
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建app_state表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS app_state (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建switch_hooks表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS switch_hooks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                scope_type TEXT NOT NULL,
+                scope_value TEXT NOT NULL,
+                stage TEXT NOT NULL,
+                name TEXT NOT NULL,
+                command TEXT NOT NULL,
+                timeout_ms INTEGER DEFAULT 30000,
+                is_enabled INTEGER DEFAULT 1,
+                sort_order INTEGER DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建switch_history表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS switch_history (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                switch_type TEXT NOT NULL,
+                supplier_type TEXT,
+                from_supplier_id INTEGER,
+                to_supplier_id INTEGER,
+                from_mode TEXT,
+                to_mode TEXT,
+                backup_id INTEGER,
+                success INTEGER NOT NULL DEFAULT 0,
+                message TEXT,
+                hook_results TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建failover_chains表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS failover_chains (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                supplier_type TEXT NOT NULL,
+                supplier_id INTEGER NOT NULL,
+                position INTEGER NOT NULL DEFAULT 0,
+                is_excluded INTEGER DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(supplier_type, supplier_id),
+                FOREIGN KEY (supplier_id) REFERENCES suppliers(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建circuit_breakers表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS circuit_breakers (
+                supplier_id INTEGER PRIMARY KEY,
+                state TEXT NOT NULL DEFAULT 'closed',
+                consecutive_failures INTEGER NOT NULL DEFAULT 0,
+                half_open_probes INTEGER NOT NULL DEFAULT 0,
+                half_open_successes INTEGER NOT NULL DEFAULT 0,
+                opened_at DATETIME,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                FOREIGN KEY (supplier_id) REFERENCES suppliers(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建mcp_template_values表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS mcp_template_values (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                template_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                value TEXT NOT NULL,
+                is_secret INTEGER DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(template_id, name),
+                FOREIGN KEY (template_id) REFERENCES mcp_templates(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建mcp_template_revisions表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS mcp_template_revisions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                template_id INTEGER NOT NULL,
+                revision INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                version TEXT NOT NULL,
+                config_content TEXT NOT NULL,
+                description TEXT,
+                category TEXT,
+                tags TEXT,
+                parameters TEXT,
+                platform_overrides TEXT,
+                note TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(template_id, revision),
+                FOREIGN KEY (template_id) REFERENCES mcp_templates(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建mcp_template_tools表
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS mcp_template_tools (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                template_id INTEGER NOT NULL,
+                server_id TEXT NOT NULL,
+                server_name TEXT,
+                server_version TEXT,
+                protocol_version TEXT,
+                tools TEXT NOT NULL,
+                checked_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(template_id, server_id),
+                FOREIGN KEY (template_id) REFERENCES mcp_templates(id) ON DELETE CASCADE
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建mcp_template_categories表，模板通过分类名称关联
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS mcp_template_categories (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                description TEXT,
+                sort_order INTEGER NOT NULL DEFAULT 0,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(
+            r#"
+            INSERT OR IGNORE INTO mcp_template_categories (name)
+            SELECT DISTINCT category FROM mcp_templates
+            WHERE category IS NOT NULL AND category != ''
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        // 创建模板全文索引，rowid 与模板ID一致
+        sqlx::query(
+            r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS mcp_templates_fts USING fts5(
+                name, description, tags, commands,
+                tokenize = 'trigram'
+            )
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query(
+            r#"
+            CREATE TRIGGER IF NOT EXISTS mcp_templates_fts_delete
+            AFTER DELETE ON mcp_templates
+            BEGIN
+                DELETE FROM mcp_templates_fts WHERE rowid = old.id;
+            END
+            "#,
+        )
+        .execute(&mut *conn)
+        .await?;
+        Ok(())
+    }
+
+    /// 索引由模板写入时同步维护，旧数据库或索引与模板不一致时整体重建
+    async fn ensure_template_search_index(pool: &SqlitePool) -> Result<()> {
+        let out_of_sync: i64 = sqlx::query_scalar(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM mcp_templates WHERE id NOT IN (SELECT rowid FROM mcp_templates_fts))
+                + (SELECT COUNT(*) FROM mcp_templates_fts WHERE rowid NOT IN (SELECT id FROM mcp_templates))
+            "#,
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if out_of_sync > 0 {
+            McpTemplate::rebuild_search_index(pool).await?;
+        }
+
+        Ok(())
+    }
+
+    /// mcp_templates 表结构，同名模板按版本、AI类型和平台区分
+    fn mcp_templates_table_sql(table_name: &str) -> String {
+        format!(
+            r#"
+            CREATE TABLE IF NOT EXISTS {} (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL,
+                version TEXT NOT NULL,
+                ai_type TEXT NOT NULL,
+                platform_type TEXT NOT NULL,
+                config_content TEXT NOT NULL,
+                description TEXT,
+                is_builtin INTEGER DEFAULT 0,
+                category TEXT,
+                tags TEXT,
+                usage_count INTEGER DEFAULT 0,
+                parameters TEXT,
+                platform_overrides TEXT,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                UNIQUE(name, version, ai_type, platform_type)
+            )
+            "#,
+            table_name
+        )
+    }
+
+    /// 旧版本的唯一键只有 (name, version)，内置模板在不同AI类型和平台下同名，需要重建表
+    async fn ensure_mcp_templates_unique_key(conn: &mut SqliteConnection) -> Result<()> {
+        let table_sql: Option<String> = sqlx::query_scalar(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'mcp_templates'",
+        )
+        .fetch_optional(&mut *conn)
+        .await?;
+
+        let needs_rebuild = table_sql
+            .map(|sql| sql.contains("UNIQUE(name, version)"))
+            .unwrap_or(false);
+        if !needs_rebuild {
+            return Ok(());
+        }
+
+        // 迁移期间外键已关闭，删除旧表时不会级联删除模板参数
+        Self::rebuild_mcp_templates(conn).await
+    }
+
+    async fn rebuild_mcp_templates(conn: &mut SqliteConnection) -> Result<()> {
+        let columns = "id, name, version, ai_type, platform_type, config_content, description, \
+                       is_builtin, category, tags, usage_count, parameters, platform_overrides, \
+                       created_at, updated_at";
+
+        sqlx::query(&Self::mcp_templates_table_sql("mcp_templates_new"))
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(&format!(
+            "INSERT INTO mcp_templates_new ({}) SELECT {} FROM mcp_templates",
+            columns, columns
+        ))
+        .execute(&mut *conn)
+        .await?;
+        sqlx::query("DROP TABLE mcp_templates")
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query("ALTER TABLE mcp_templates_new RENAME TO mcp_templates")
+            .execute(&mut *conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn ensure_mcp_templates_schema(conn: &mut SqliteConnection) -> Result<()> {
+        let rows = sqlx::query("PRAGMA table_info('mcp_templates')")
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let existing: HashSet<String> = rows
+            .into_iter()
+            .map(|row| row.get::<String, _>("name"))
+            .collect();
+
+        let required = [("parameters", "TEXT"), ("platform_overrides", "TEXT")];
+
+        for (name, definition) in required {
+            if !existing.contains(name) {
+                let sql = format!(
+                    "ALTER TABLE mcp_templates ADD COLUMN {} {}",
+                    name, definition
+                );
+                sqlx::query(&sql).execute(&mut *conn).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_work_mode_configs_schema(conn: &mut SqliteConnection) -> Result<()> {
+        let rows = sqlx::query("PRAGMA table_info('work_mode_configs')")
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let existing: HashSet<String> = rows
+            .into_iter()
+            .map(|row| row.get::<String, _>("name"))
+            .collect();
+
+        if !existing.contains("pinned_template_revisions") {
+            sqlx::query("ALTER TABLE work_mode_configs ADD COLUMN pinned_template_revisions TEXT")
+                .execute(&mut *conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn ensure_suppliers_schema(conn: &mut SqliteConnection) -> Result<()> {
+        let rows = sqlx::query("PRAGMA table_info('suppliers')")
+            .fetch_all(&mut *conn)
+            .await?;
+
+        let existing: HashSet<String> = rows
+            .into_iter()
+            .map(|row| row.get::<String, _>("name"))
+            .collect();
+
+        let required = [
+            ("auth_token", "TEXT NOT NULL DEFAULT ''"),
+            ("timeout_ms", "INTEGER"),
+            ("auto_update", "INTEGER DEFAULT 0"),
+            ("opus_model", "TEXT"),
+            ("sonnet_model", "TEXT"),
+            ("haiku_model", "TEXT"),
+            ("sort_order", "INTEGER DEFAULT 0"),
+            ("is_healthy", "INTEGER DEFAULT 1"),
+            ("last_check_time", "DATETIME"),
+            ("response_time", "INTEGER"),
+            ("consecutive_failures", "INTEGER DEFAULT 0"),
+            ("uptime_percentage", "REAL DEFAULT 100.0"),
+            ("total_requests", "INTEGER DEFAULT 0"),
+            ("failed_requests", "INTEGER DEFAULT 0"),
+        ];
+
+        for (name, definition) in required {
+            if !existing.contains(name) {
+                let sql = format!("ALTER TABLE suppliers ADD COLUMN {} {}", name, definition);
+                sqlx::query(&sql).execute(&mut *conn).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_stats(&self) -> Result<HashMap<String, i64>> {
+        let mut stats = HashMap::new();
+
+        // 获取各表的记录数
+        let suppliers_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM suppliers")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("suppliers".to_string(), suppliers_count);
+
+        let templates_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("mcp_templates".to_string(), templates_count);
+
+        let work_modes_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM work_mode_configs")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("work_mode_configs".to_string(), work_modes_count);
+
+        let config_history_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM config_history")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("config_history".to_string(), config_history_count);
+
+        let app_state_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_state")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("app_state".to_string(), app_state_count);
+
+        Ok(stats)
+    }
+
+    pub async fn test_connection(&self) -> Result<bool> {
+        // 测试数据库连接
+        let result: i64 = sqlx::query_scalar("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap_or(0);
+
+        Ok(result == 1)
+    }
+
+    /// 静态方法：获取数据库统计信息
+    pub async fn get_db_stats(pool: &SqlitePool) -> Result<HashMap<String, i64>> {
+        let mut stats = HashMap::new();
+
+        // 获取各表的记录数
+        let suppliers_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM suppliers")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("suppliers".to_string(), suppliers_count);
+
+        let templates_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("mcp_templates".to_string(), templates_count);
+
+        let work_modes_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM work_mode_configs")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("work_mode_configs".to_string(), work_modes_count);
+
+        let config_history_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM config_history")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("config_history".to_string(), config_history_count);
+
+        let app_state_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM app_state")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+        stats.insert("app_state".to_string(), app_state_count);
+
+        Ok(stats)
+    }
+
+    /// 静态方法：测试数据库连接
+    pub async fn test_db_connection(pool: &SqlitePool) -> Result<bool> {
+        // 测试数据库连接
+        let result: i64 = sqlx::query_scalar("SELECT 1")
+            .fetch_one(pool)
+            .await
+            .unwrap_or(0);
+
+        Ok(result == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Database, SCHEMA_VERSION};
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::SqlitePool;
+    use std::fs;
+    use std::str::FromStr;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn creates_sqlite_file_if_missing() {
+        let temp_root = std::env::temp_dir().join(format!("ai-tools-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_root).unwrap();
+        let db_path = temp_root.join("test-db.sqlite");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        assert!(!db_path.exists());
+
+        {
+            let db = Database::new(&db_url).await.unwrap();
+            db.test_connection().await.unwrap();
+        }
+
+        assert!(db_path.exists());
+
+        let _ = fs::remove_file(&db_path);
+        let _ = fs::remove_dir_all(&temp_root);
+    }
+
+    #[tokio::test]
+    async fn rebuilds_legacy_mcp_templates_unique_key() {
+        let temp_root = std::env::temp_dir().join(format!("ai-tools-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_root).unwrap();
+        let db_path = temp_root.join("legacy.sqlite");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        {
+            let options = SqliteConnectOptions::from_str(&db_url)
+                .unwrap()
+                .create_if_missing(true);
+            let pool = SqlitePool::connect_with(options).await.unwrap();
+            sqlx::query(
+                r#"
+                CREATE TABLE mcp_templates (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL,
+                    version TEXT NOT NULL,
+                    ai_type TEXT NOT NULL,
+                    platform_type TEXT NOT NULL,
+                    config_content TEXT NOT NULL,
+                    description TEXT,
+                    is_builtin INTEGER DEFAULT 0,
+                    category TEXT,
+                    tags TEXT,
+                    usage_count INTEGER DEFAULT 0,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    UNIQUE(name, version)
+                )
+                "#,
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO mcp_templates (name, version, ai_type, platform_type, config_content) \
+                 VALUES ('Context7', '1.0.0', 'claude', 'unix', '{}')",
+            )
+            .execute(&pool)
+            .await
+            .unwrap();
+            pool.close().await;
+        }
+
+        let db = Database::new(&db_url).await.unwrap();
+        let insert_windows = sqlx::query(
+            "INSERT INTO mcp_templates (name, version, ai_type, platform_type, config_content) \
+             VALUES ('Context7', '1.0.0', 'claude', 'windows', '{}')",
+        )
+        .execute(db.pool())
+        .await;
+        assert!(insert_windows.is_ok());
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM mcp_templates")
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        // 旧数据库在迁移前会先备份
+        let backups: Vec<_> = fs::read_dir(temp_root.join("backups"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].starts_with("legacy-v0-"));
+
+        db.pool().close().await;
+        let _ = fs::remove_dir_all(&temp_root);
+    }
+
+    #[tokio::test]
+    async fn records_schema_version_and_refuses_newer_database() {
+        let temp_root = std::env::temp_dir().join(format!("ai-tools-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&temp_root).unwrap();
+        let db_path = temp_root.join("versioned.sqlite");
+        let db_url = format!("sqlite://{}", db_path.to_string_lossy());
+
+        let db = Database::new(&db_url).await.unwrap();
+        let versions = Database::get_schema_versions(db.pool()).await.unwrap();
+        assert_eq!(
+            versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+            (1..=SCHEMA_VERSION).collect::<Vec<_>>()
+        );
+        // 新建的数据库不需要备份
+        assert!(!temp_root.join("backups").exists());
+        db.pool().close().await;
+
+        // 再次打开时没有待执行的迁移
+        let db = Database::new(&db_url).await.unwrap();
+        assert_eq!(
+            Database::get_schema_versions(db.pool())
+                .await
+                .unwrap()
+                .len() as i64,
+            SCHEMA_VERSION
+        );
+        sqlx::query("INSERT INTO schema_version (version, description) VALUES (?, 'future')")
+            .bind(SCHEMA_VERSION + 1)
+            .execute(db.pool())
+            .await
+            .unwrap();
+        db.pool().close().await;
+
+        let error = Database::new(&db_url).await.err().unwrap();
+        assert!(error.to_string().contains("请升级应用"));
+
+        let _ = fs::remove_dir_all(&temp_root);
+    }
+}
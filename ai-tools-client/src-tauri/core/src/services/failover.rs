@@ -0,0 +1,324 @@
+use crate::models::failover::{FailoverStrategy, ScoreBreakdown};
+use crate::models::supplier::{FailoverConfig, Supplier, SupplierHealth};
+
+/// 自动故障转移的决策逻辑
+pub struct FailoverPlanner;
+
+impl FailoverPlanner {
+    /// 生成候选供应商列表。
+    ///
+    /// 排除当前供应商和被排除在链外的供应商；“按链顺序”策略下，
+    /// 列表从当前供应商的下一位开始并循环回到链首。
+    pub fn candidates(
+        ordered: Vec<(Supplier, bool)>,
+        current_id: i64,
+        strategy: FailoverStrategy,
+    ) -> Vec<Supplier> {
+        let mut ordered = ordered;
+        if strategy == FailoverStrategy::NextInChain {
+            if let Some(index) = ordered
+                .iter()
+                .position(|(supplier, _)| supplier.id == Some(current_id))
+            {
+                ordered.rotate_left(index + 1);
+            }
+        }
+
+        ordered
+            .into_iter()
+            .filter(|(supplier, excluded)| !excluded && supplier.id != Some(current_id))
+            .map(|(supplier, _)| supplier)
+            .collect()
+    }
+
+    /// 按策略从健康的候选中选出目标，候选顺序即链顺序，平分时取靠前的
+    pub fn pick(
+        strategy: FailoverStrategy,
+        healthy: Vec<(Supplier, SupplierHealth)>,
+        config: &FailoverConfig,
+    ) -> Option<(Supplier, SupplierHealth)> {
+        let mut best: Option<(Supplier, SupplierHealth)> = None;
+
+        for (supplier, health) in healthy {
+            let better = match (&best, strategy) {
+                (None, _) => true,
+                (Some(_), FailoverStrategy::NextInChain) => false,
+                (Some((current_supplier, current)), FailoverStrategy::BestScore) => {
+                    Self::score(supplier.id.unwrap_or_default(), &health, config)
+                        > Self::score(current_supplier.id.unwrap_or_default(), current, config)
+                }
+                (Some((_, current)), FailoverStrategy::LowestLatency) => {
+                    health.response_time < current.response_time
+                }
+            };
+
+            if better {
+                best = Some((supplier, health));
+            }
+        }
+
+        best
+    }
+
+    // 评估故障转移条件
+    pub fn should_failover(health: &SupplierHealth, config: &FailoverConfig) -> bool {
+        // 条件1: 连续失败次数超过阈值
+        if health.consecutive_failures >= config.max_consecutive_failures as i64 {
+            return true;
+        }
+
+        // 条件2: 响应时间超过阈值且成功率低
+        if health.response_time > config.max_response_time_ms as i64
+            && health.uptime_percentage < config.min_success_rate
+        {
+            return true;
+        }
+
+        // 条件3: 成功率过低
+        if health.uptime_percentage < config.min_success_rate - 10.0 {
+            return true;
+        }
+
+        // 条件4: 完全不健康
+        if !health.is_healthy {
+            return true;
+        }
+
+        false
+    }
+
+    /// 计算供应商综合评分
+    pub fn score(supplier_id: i64, health: &SupplierHealth, config: &FailoverConfig) -> f64 {
+        Self::breakdown(supplier_id, health, config).total
+    }
+
+    /// 按配置的权重计算各项得分，每项得分在 0 到该项权重之间
+    pub fn breakdown(
+        supplier_id: i64,
+        health: &SupplierHealth,
+        config: &FailoverConfig,
+    ) -> ScoreBreakdown {
+        let weights = &config.scoring;
+
+        // 成功率
+        let success_rate =
+            (health.uptime_percentage / 100.0).clamp(0.0, 1.0) * weights.success_rate;
+
+        // 响应时间 - 不超过目标响应时间得满分，超出部分按比例扣分，最多扣完
+        let latency = if health.response_time > 0 {
+            let target_time =
+                (config.max_response_time_ms as f64 * weights.target_latency_factor).max(1.0);
+            if health.response_time as f64 <= target_time {
+                weights.latency
+            } else {
+                let overrun = ((health.response_time as f64 - target_time) / target_time).min(2.0);
+                (weights.latency * (1.0 - overrun / 2.0)).max(0.0)
+            }
+        } else {
+            weights.latency / 2.0 // 没有数据时给一半分
+        };
+
+        // 连续失败 - 失败次数越少得分越高
+        let failure_ratio = if config.max_consecutive_failures > 0 {
+            health.consecutive_failures as f64 / config.max_consecutive_failures as f64
+        } else {
+            0.0
+        };
+        let failures = weights.failures * (1.0 - failure_ratio.min(1.0));
+
+        // 稳定性 - 基于总请求数和失败数的比例
+        let stability = if health.total_requests > 0 {
+            let failure_ratio = health.failed_requests as f64 / health.total_requests as f64;
+            (1.0 - failure_ratio).clamp(0.0, 1.0) * weights.stability
+        } else {
+            weights.stability / 2.0
+        };
+
+        // 成本 - 相对成本不高于基准得满分，越贵得分越低
+        let cost = weights.cost * (1.0 / weights.cost_of(supplier_id)).min(1.0);
+
+        let total = success_rate + latency + failures + stability + cost;
+        ScoreBreakdown {
+            success_rate: round2(success_rate),
+            latency: round2(latency),
+            failures: round2(failures),
+            stability: round2(stability),
+            cost: round2(cost),
+            total: round2(total),
+        }
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::failover::{order_by_chain, FailoverChainEntry};
+    use crate::models::supplier::HealthStatus;
+    use chrono::Utc;
+
+    fn supplier(id: i64) -> Supplier {
+        Supplier {
+            id: Some(id),
+            r#type: "claude".to_string(),
+            name: format!("supplier-{}", id),
+            base_url: "https://api.example.com".to_string(),
+            auth_token: "token".to_string(),
+            timeout_ms: None,
+            auto_update: None,
+            opus_model: None,
+            sonnet_model: None,
+            haiku_model: None,
+            is_active: Some(0),
+            sort_order: Some(id),
+            is_healthy: None,
+            last_check_time: None,
+            response_time: None,
+            consecutive_failures: None,
+            uptime_percentage: None,
+            total_requests: None,
+            failed_requests: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn health(id: i64, response_time: i64, uptime_percentage: f64) -> SupplierHealth {
+        SupplierHealth {
+            supplier_id: id,
+            is_healthy: true,
+            last_check_time: Utc::now(),
+            response_time,
+            consecutive_failures: 0,
+            uptime_percentage,
+            total_requests: 10,
+            failed_requests: 0,
+            status: HealthStatus::Healthy,
+            error_message: None,
+            circuit_state: Default::default(),
+        }
+    }
+
+    fn ids(suppliers: &[Supplier]) -> Vec<i64> {
+        suppliers
+            .iter()
+            .filter_map(|supplier| supplier.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_next_in_chain_wraps_and_skips_excluded() {
+        let ordered = vec![
+            (supplier(1), false),
+            (supplier(2), false),
+            (supplier(3), true),
+            (supplier(4), false),
+        ];
+
+        let next = FailoverPlanner::candidates(ordered.clone(), 2, FailoverStrategy::NextInChain);
+        assert_eq!(ids(&next), vec![4, 1]);
+
+        let scored = FailoverPlanner::candidates(ordered, 2, FailoverStrategy::BestScore);
+        assert_eq!(ids(&scored), vec![1, 4]);
+    }
+
+    #[test]
+    fn test_unlisted_suppliers_are_never_candidates_once_chain_is_set() {
+        let entries: Vec<FailoverChainEntry> = [1, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(position, supplier_id)| FailoverChainEntry {
+                id: None,
+                supplier_type: "claude".to_string(),
+                supplier_id,
+                position: position as i64,
+                is_excluded: Some(0),
+                created_at: None,
+                updated_at: None,
+            })
+            .collect();
+        let suppliers = || vec![supplier(1), supplier(2), supplier(3)];
+
+        // 新添加的供应商 3 不在链中，任何策略下都不会成为备用
+        for strategy in [
+            FailoverStrategy::NextInChain,
+            FailoverStrategy::BestScore,
+            FailoverStrategy::LowestLatency,
+        ] {
+            let candidates =
+                FailoverPlanner::candidates(order_by_chain(suppliers(), &entries), 1, strategy);
+            assert_eq!(ids(&candidates), vec![2]);
+        }
+
+        let unconfigured = FailoverPlanner::candidates(
+            order_by_chain(suppliers(), &[]),
+            1,
+            FailoverStrategy::BestScore,
+        );
+        assert_eq!(ids(&unconfigured), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_pick_by_strategy() {
+        let config = FailoverConfig::default();
+        let healthy = || {
+            vec![
+                (supplier(1), health(1, 4000, 90.0)),
+                (supplier(2), health(2, 800, 99.0)),
+                (supplier(3), health(3, 300, 60.0)),
+            ]
+        };
+
+        let pick = |strategy| {
+            FailoverPlanner::pick(strategy, healthy(), &config)
+                .and_then(|(supplier, _)| supplier.id)
+        };
+
+        assert_eq!(pick(FailoverStrategy::NextInChain), Some(1));
+        assert_eq!(pick(FailoverStrategy::BestScore), Some(2));
+        assert_eq!(pick(FailoverStrategy::LowestLatency), Some(3));
+    }
+
+    #[test]
+    fn test_breakdown_uses_configured_weights() {
+        let mut config = FailoverConfig::default();
+        let h = health(1, 800, 100.0);
+
+        let default_breakdown = FailoverPlanner::breakdown(1, &h, &config);
+        assert_eq!(default_breakdown.success_rate, 40.0);
+        assert_eq!(default_breakdown.latency, 30.0);
+        assert_eq!(default_breakdown.total, 100.0);
+
+        // 目标响应时间收紧到 500ms 后，800ms 只能拿到部分延迟分
+        config.scoring.target_latency_factor = 0.1;
+        let strict = FailoverPlanner::breakdown(1, &h, &config);
+        assert!(strict.latency < 30.0 && strict.latency > 0.0);
+
+        // 成本权重让更贵的供应商得分更低
+        config.scoring.cost = 20.0;
+        config.scoring.supplier_costs.insert(2, 4.0);
+        let cheap = FailoverPlanner::breakdown(1, &h, &config);
+        let expensive = FailoverPlanner::breakdown(2, &h, &config);
+        assert_eq!(cheap.cost, 20.0);
+        assert_eq!(expensive.cost, 5.0);
+        assert!(cheap.total > expensive.total);
+    }
+
+    #[test]
+    fn test_best_score_respects_cost_weight() {
+        let mut config = FailoverConfig::default();
+        config.scoring.cost = 50.0;
+        config.scoring.supplier_costs.insert(2, 10.0);
+
+        let healthy = vec![
+            (supplier(1), health(1, 3000, 97.0)),
+            (supplier(2), health(2, 800, 99.0)),
+        ];
+        let picked = FailoverPlanner::pick(FailoverStrategy::BestScore, healthy, &config)
+            .and_then(|(supplier, _)| supplier.id);
+        assert_eq!(picked, Some(1));
+    }
+}
@@ -0,0 +1,294 @@
+use crate::models::hook::{CreateSwitchRecordRequest, HookExecutionResult, SwitchHook};
+use crate::t;
+use sqlx::SqlitePool;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+/// 钩子输出保存的最大字节数，避免把大量日志写入数据库
+const MAX_HOOK_OUTPUT_BYTES: usize = 16 * 1024;
+
+/// 描述一次切换的上下文，会以环境变量的形式传给钩子
+#[derive(Debug, Clone, Default)]
+pub struct SwitchContext {
+    pub switch_type: String, // "supplier" or "work_mode"
+    pub supplier_type: Option<String>,
+    pub from_supplier_id: Option<i64>,
+    pub from_supplier_name: Option<String>,
+    pub to_supplier_id: Option<i64>,
+    pub to_supplier_name: Option<String>,
+    pub from_mode: Option<String>,
+    pub to_mode: Option<String>,
+    pub backup_id: Option<i64>,
+}
+
+impl SwitchContext {
+    /// 生成钩子进程的环境变量
+    pub fn env_vars(&self, stage: &str) -> Vec<(&'static str, String)> {
+        let optional = |value: &Option<String>| value.clone().unwrap_or_default();
+        let optional_id = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+
+        vec![
+            ("AI_TOOLS_HOOK_STAGE", stage.to_string()),
+            ("AI_TOOLS_SWITCH_TYPE", self.switch_type.clone()),
+            ("AI_TOOLS_SUPPLIER_TYPE", optional(&self.supplier_type)),
+            (
+                "AI_TOOLS_FROM_SUPPLIER_ID",
+                optional_id(self.from_supplier_id),
+            ),
+            (
+                "AI_TOOLS_FROM_SUPPLIER_NAME",
+                optional(&self.from_supplier_name),
+            ),
+            ("AI_TOOLS_TO_SUPPLIER_ID", optional_id(self.to_supplier_id)),
+            (
+                "AI_TOOLS_TO_SUPPLIER_NAME",
+                optional(&self.to_supplier_name),
+            ),
+            ("AI_TOOLS_FROM_MODE", optional(&self.from_mode)),
+            ("AI_TOOLS_TO_MODE", optional(&self.to_mode)),
+            ("AI_TOOLS_BACKUP_ID", optional_id(self.backup_id)),
+        ]
+    }
+
+    /// 生成切换记录
+    pub fn to_record(
+        &self,
+        success: bool,
+        message: &str,
+        hook_results: Vec<HookExecutionResult>,
+    ) -> CreateSwitchRecordRequest {
+        CreateSwitchRecordRequest {
+            switch_type: self.switch_type.clone(),
+            supplier_type: self.supplier_type.clone(),
+            from_supplier_id: self.from_supplier_id,
+            to_supplier_id: self.to_supplier_id,
+            from_mode: self.from_mode.clone(),
+            to_mode: self.to_mode.clone(),
+            backup_id: self.backup_id,
+            success,
+            message: Some(message.to_string()),
+            hook_results,
+        }
+    }
+
+    /// 钩子所属的作用域（作用域类型, 作用域值）
+    fn scope(&self) -> Option<(&'static str, String)> {
+        match self.switch_type.as_str() {
+            "supplier" => self
+                .supplier_type
+                .clone()
+                .map(|supplier_type| ("supplier_type", supplier_type)),
+            "work_mode" => self.to_mode.clone().map(|mode| ("work_mode", mode)),
+            _ => None,
+        }
+    }
+}
+
+pub struct HookRunner;
+
+impl HookRunner {
+    /// 执行某个阶段的全部钩子。
+    ///
+    /// 前置钩子遇到第一个失败就停止，后续钩子不再执行；后置钩子总是全部执行。
+    pub async fn run_stage(
+        pool: &SqlitePool,
+        context: &SwitchContext,
+        stage: &str,
+    ) -> Result<Vec<HookExecutionResult>, sqlx::Error> {
+        let (scope_type, scope_value) = match context.scope() {
+            Some(scope) => scope,
+            None => return Ok(Vec::new()),
+        };
+
+        let hooks = SwitchHook::get_enabled(pool, scope_type, &scope_value, stage).await?;
+        let mut results = Vec::with_capacity(hooks.len());
+
+        for hook in &hooks {
+            let result = Self::run_hook(hook, context).await;
+            let failed = !result.success;
+            results.push(result);
+
+            if failed && stage == "pre" {
+                break;
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// 执行单个钩子
+    pub async fn run_hook(hook: &SwitchHook, context: &SwitchContext) -> HookExecutionResult {
+        let start = Instant::now();
+        let mut result = HookExecutionResult {
+            hook_id: hook.id,
+            name: hook.name.clone(),
+            stage: hook.stage.clone(),
+            command: hook.command.clone(),
+            success: false,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: String::new(),
+            duration_ms: 0,
+            timed_out: false,
+            error: None,
+        };
+
+        let mut command = Self::shell_command(&hook.command);
+        command
+            .envs(context.env_vars(&hook.stage))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let child = match command.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                result.error = Some(t!("hook.spawn_failed", error = e));
+                result.duration_ms = start.elapsed().as_millis() as i64;
+                return result;
+            }
+        };
+
+        let timeout = Duration::from_millis(hook.timeout_ms());
+        match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => {
+                result.exit_code = output.status.code();
+                result.success = output.status.success();
+                result.stdout = truncate_output(&output.stdout);
+                result.stderr = truncate_output(&output.stderr);
+                if !result.success {
+                    result.error = Some(match result.exit_code {
+                        Some(code) => t!("hook.exit_code", code = code),
+                        None => t!("hook.killed_by_signal"),
+                    });
+                }
+            }
+            Ok(Err(e)) => {
+                result.error = Some(t!("hook.wait_failed", error = e));
+            }
+            Err(_) => {
+                // 超时后 future 被丢弃，kill_on_drop 会终止子进程
+                result.timed_out = true;
+                result.error = Some(t!("hook.timed_out", timeout_ms = hook.timeout_ms()));
+            }
+        }
+
+        result.duration_ms = start.elapsed().as_millis() as i64;
+        result
+    }
+
+    /// 通过系统 shell 执行命令，便于在钩子中使用管道和重定向
+    fn shell_command(script: &str) -> Command {
+        if cfg!(target_os = "windows") {
+            let mut command = Command::new("cmd");
+            command.arg("/C").arg(script);
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(script);
+            command
+        }
+    }
+
+    /// 把失败的钩子结果整理成一行错误描述
+    pub fn describe_failure(result: &HookExecutionResult) -> String {
+        let mut message = format!(
+            "{}: {}",
+            result.name,
+            result.error.clone().unwrap_or_else(|| t!("hook.failed"))
+        );
+
+        let stderr = result.stderr.trim();
+        if !stderr.is_empty() {
+            message.push_str(&format!(" ({})", stderr.lines().last().unwrap_or(stderr)));
+        }
+
+        message
+    }
+}
+
+fn truncate_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    if text.len() <= MAX_HOOK_OUTPUT_BYTES {
+        return text.into_owned();
+    }
+
+    let mut end = MAX_HOOK_OUTPUT_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n...{}", &text[..end], t!("hook.output_truncated"))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn hook(stage: &str, command: &str, timeout_ms: Option<i64>) -> SwitchHook {
+        SwitchHook {
+            id: Some(1),
+            scope_type: "supplier_type".to_string(),
+            scope_value: "claude".to_string(),
+            stage: stage.to_string(),
+            name: "test-hook".to_string(),
+            command: command.to_string(),
+            timeout_ms,
+            is_enabled: Some(1),
+            sort_order: Some(0),
+            created_at: None,
+            updated_at: None,
+        }
+    }
+
+    fn context() -> SwitchContext {
+        SwitchContext {
+            switch_type: "supplier".to_string(),
+            supplier_type: Some("claude".to_string()),
+            from_supplier_id: Some(1),
+            from_supplier_name: Some("old".to_string()),
+            to_supplier_id: Some(2),
+            to_supplier_name: Some("new".to_string()),
+            backup_id: Some(42),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hook_receives_switch_environment() {
+        let result = HookRunner::run_hook(
+            &hook(
+                "post",
+                "echo \"$AI_TOOLS_HOOK_STAGE $AI_TOOLS_FROM_SUPPLIER_NAME->$AI_TOOLS_TO_SUPPLIER_NAME $AI_TOOLS_BACKUP_ID\"",
+                None,
+            ),
+            &context(),
+        )
+        .await;
+
+        assert!(result.success);
+        assert_eq!(result.exit_code, Some(0));
+        assert_eq!(result.stdout.trim(), "post old->new 42");
+    }
+
+    #[tokio::test]
+    async fn test_failing_hook_reports_exit_code_and_stderr() {
+        let result =
+            HookRunner::run_hook(&hook("pre", "echo broken >&2; exit 3", None), &context()).await;
+
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(3));
+        assert_eq!(result.stderr.trim(), "broken");
+        assert!(HookRunner::describe_failure(&result).contains("broken"));
+    }
+
+    #[tokio::test]
+    async fn test_hook_times_out() {
+        let result = HookRunner::run_hook(&hook("pre", "sleep 5", Some(100)), &context()).await;
+
+        assert!(!result.success);
+        assert!(result.timed_out);
+        assert!(result.duration_ms < 5000);
+    }
+}
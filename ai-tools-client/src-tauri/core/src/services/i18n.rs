@@ -0,0 +1,368 @@
+// 后端消息的本地化，文案按消息ID保存在 locales 目录下的消息目录中
+
+use crate::models::config::AppState;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// 语言设置在 app_state 中的键
+const LOCALE_STATE_KEY: &str = "locale";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+    #[serde(rename = "en-US")]
+    EnUs,
+}
+
+impl Locale {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Locale::ZhCn => "zh-CN",
+            Locale::EnUs => "en-US",
+        }
+    }
+
+    /// 解析语言标识，只比较主语言，例如 "en"、"en-GB" 都视为 en-US
+    pub fn parse(value: &str) -> Option<Self> {
+        let language = value.split(['-', '_']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "zh" => Some(Locale::ZhCn),
+            "en" => Some(Locale::EnUs),
+            _ => None,
+        }
+    }
+
+    fn catalog_source(&self) -> &'static str {
+        match self {
+            Locale::ZhCn => include_str!("../../locales/zh-CN.json"),
+            Locale::EnUs => include_str!("../../locales/en-US.json"),
+        }
+    }
+
+    fn catalog(&self) -> &'static HashMap<String, String> {
+        static ZH_CN: OnceLock<HashMap<String, String>> = OnceLock::new();
+        static EN_US: OnceLock<HashMap<String, String>> = OnceLock::new();
+        let cell = match self {
+            Locale::ZhCn => &ZH_CN,
+            Locale::EnUs => &EN_US,
+        };
+        cell.get_or_init(|| {
+            serde_json::from_str(self.catalog_source()).expect("消息目录不是有效的JSON")
+        })
+    }
+
+    /// 读取保存的语言，未保存过或无法识别时使用默认语言
+    pub async fn load(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let locale = AppState::get(pool, LOCALE_STATE_KEY)
+            .await?
+            .and_then(|state| Locale::parse(&state.value))
+            .unwrap_or_default();
+
+        Ok(locale)
+    }
+
+    pub async fn save(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        AppState::set(pool, LOCALE_STATE_KEY, self.as_str()).await?;
+        Ok(())
+    }
+}
+
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// 当前使用的语言，启动时从 app_state 读取，切换语言时更新
+pub fn current_locale() -> Locale {
+    match CURRENT_LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::EnUs,
+        _ => Locale::ZhCn,
+    }
+}
+
+pub fn set_current_locale(locale: Locale) {
+    let value = match locale {
+        Locale::ZhCn => 0,
+        Locale::EnUs => 1,
+    };
+    CURRENT_LOCALE.store(value, Ordering::Relaxed);
+}
+
+/// 按消息ID取得指定语言的文案并替换 {name} 形式的参数。
+/// 文案只扫描一遍，参数值中出现的 {name} 原样保留，不会被再次替换。
+/// 缺少译文时使用中文文案，中文也没有时返回消息ID本身
+pub fn translate(locale: Locale, id: &str, args: &[(&str, String)]) -> String {
+    let template = locale
+        .catalog()
+        .get(id)
+        .or_else(|| Locale::ZhCn.catalog().get(id))
+        .map(String::as_str)
+        .unwrap_or(id);
+
+    let mut message = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[1..end];
+            args.iter()
+                .find(|(arg, _)| *arg == name)
+                .map(|(_, value)| (end, value))
+        });
+        match value {
+            Some((end, value)) => {
+                message.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            // 不是参数的花括号原样保留，例如 ${NAME}
+            None => {
+                message.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    message.push_str(rest);
+    message
+}
+
+/// 取得当前语言的文案，例如 `t!("error.db_busy")`、`t!("mode.switched", mode = name)`
+#[macro_export]
+macro_rules! t {
+    ($id:expr) => {
+        $crate::services::i18n::translate($crate::services::i18n::current_locale(), $id, &[])
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::services::i18n::translate(
+            $crate::services::i18n::current_locale(),
+            $id,
+            &[$((stringify!($name), $value.to_string())),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+
+    fn placeholders(template: &str) -> HashSet<String> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_have_same_ids_and_placeholders() {
+        let zh_cn = Locale::ZhCn.catalog();
+        let en_us = Locale::EnUs.catalog();
+        assert!(!zh_cn.is_empty());
+
+        for (id, template) in zh_cn {
+            let translated = en_us
+                .get(id)
+                .unwrap_or_else(|| panic!("en-US 缺少消息 {}", id));
+            assert_eq!(
+                placeholders(template),
+                placeholders(translated),
+                "消息 {} 的参数不一致",
+                id
+            );
+        }
+        for id in en_us.keys() {
+            assert!(zh_cn.contains_key(id), "zh-CN 缺少消息 {}", id);
+        }
+    }
+
+    /// 桌面应用、命令行工具和核心库的全部源文件
+    fn source_files() -> Vec<(PathBuf, String)> {
+        fn visit(dir: &Path, files: &mut Vec<(PathBuf, String)>) {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    visit(&path, files);
+                } else if path.extension().is_some_and(|ext| ext == "rs")
+                    && !path.ends_with("i18n.rs")
+                {
+                    let source = std::fs::read_to_string(&path).unwrap();
+                    files.push((path, source));
+                }
+            }
+        }
+
+        let mut files = Vec::new();
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        for dir in ["src", "../src", "../cli/src"] {
+            visit(&manifest_dir.join(dir), &mut files);
+        }
+        files
+    }
+
+    /// 代码中使用的消息ID都必须在消息目录中，包括桌面应用和命令行工具
+    #[test]
+    fn test_all_used_message_ids_exist() {
+        let mut ids = Vec::new();
+        for (path, source) in source_files() {
+            let mut rest = source.as_str();
+            while let Some(start) = rest.find("t!(") {
+                let standalone = rest[..start]
+                    .chars()
+                    .last()
+                    .is_none_or(|c| !c.is_alphanumeric() && c != '_' && c != '!');
+                rest = &rest[start + 3..];
+                // 消息ID可能在下一行，例如参数较多时
+                if let Some(literal) = rest.trim_start().strip_prefix('"') {
+                    if standalone {
+                        if let Some((id, _)) = literal.split_once('"') {
+                            ids.push((id.to_string(), path.display().to_string()));
+                        }
+                    }
+                }
+            }
+        }
+        assert!(!ids.is_empty());
+
+        let catalog = Locale::ZhCn.catalog();
+        for (id, file) in ids {
+            assert!(
+                catalog.contains_key(&id),
+                "{} 使用了不存在的消息 {}",
+                file,
+                id
+            );
+        }
+    }
+
+    /// 取出调用的参数部分，跳过字符串中的括号
+    fn call_arguments(source: &str) -> &str {
+        let mut depth = 1;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (index, c) in source.char_indices() {
+            if in_string {
+                match c {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return &source[..index];
+                    }
+                }
+                _ => {}
+            }
+        }
+        source
+    }
+
+    /// 错误消息必须通过消息ID翻译，anyhow!、AppError 和 ApiResponse::error 的参数中不能直接写中文
+    #[test]
+    fn test_error_messages_use_message_ids() {
+        const CALLS: [&str; 6] = [
+            "anyhow!(",
+            "bail!(",
+            "AppError::",
+            "ApiResponse::error(",
+            ".context(",
+            ".with_context(",
+        ];
+        let is_cjk = |c: char| ('\u{4e00}'..='\u{9fff}').contains(&c);
+
+        let mut found = Vec::new();
+        for (path, source) in source_files() {
+            // 测试代码中的断言消息不会返回给用户
+            let end = ["#[cfg(test)]", "#[cfg(all(test"]
+                .iter()
+                .filter_map(|marker| source.find(marker))
+                .min()
+                .unwrap_or(source.len());
+            let code = &source[..end];
+
+            for call in CALLS {
+                for (start, _) in code.match_indices(call) {
+                    let mut rest = &code[start + call.len()..];
+                    if call == "AppError::" {
+                        let name_len = rest
+                            .find(|c: char| !c.is_alphanumeric() && c != '_')
+                            .unwrap_or(rest.len());
+                        match rest[name_len..].strip_prefix('(') {
+                            Some(arguments) => rest = arguments,
+                            None => continue,
+                        }
+                    }
+
+                    let arguments = call_arguments(rest);
+                    let mut literals = arguments.split('"').skip(1).step_by(2);
+                    if literals.any(|literal| literal.chars().any(is_cjk)) {
+                        let line = code[..start].lines().count();
+                        found.push(format!("{}:{}", path.display(), line));
+                    }
+                }
+            }
+        }
+
+        assert!(found.is_empty(), "以下位置直接写了中文消息: {:?}", found);
+    }
+
+    #[test]
+    fn test_translate_with_arguments_and_fallback() {
+        assert_eq!(
+            translate(
+                Locale::ZhCn,
+                "error.not_found",
+                &[("resource", "供应商".to_string())]
+            ),
+            "供应商不存在"
+        );
+        assert_eq!(
+            translate(
+                Locale::EnUs,
+                "error.not_found",
+                &[("resource", "Supplier".to_string())]
+            ),
+            "Supplier not found"
+        );
+        assert_eq!(translate(Locale::EnUs, "missing.id", &[]), "missing.id");
+    }
+
+    #[test]
+    fn test_translate_does_not_expand_placeholders_in_values() {
+        assert_eq!(
+            translate(
+                Locale::ZhCn,
+                "command.field_ignored",
+                &[("id", "{field}".to_string()), ("field", "env".to_string())]
+            ),
+            "服务器 '{field}': 命令行无法设置字段 'env'，已忽略"
+        );
+        // 文案中不是参数的花括号保持不变
+        assert_eq!(
+            translate(
+                Locale::ZhCn,
+                "mcp.args_secret",
+                &[("id", "memory".to_string())]
+            ),
+            "服务器 'memory': args 中疑似包含硬编码的密钥，建议改用 ${NAME} 参数"
+        );
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("en-GB"), Some(Locale::EnUs));
+        assert_eq!(Locale::parse("zh_TW"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("fr-FR"), None);
+    }
+}
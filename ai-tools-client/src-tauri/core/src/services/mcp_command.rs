@@ -0,0 +1,595 @@
+use crate::models::mcp_template::CreateMcpTemplateRequest;
+use crate::services::config_importer::detect_platform;
+use crate::services::mcp_validator::TRANSPORT_TYPES;
+use crate::services::template_converter::{claude_servers, ConversionResult, TemplateConverter};
+use crate::t;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// `claude mcp add` / `codex mcp add` 命令行的解析结果
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpCommandParseResult {
+    pub server_id: String,
+    /// 命令所属的客户端：claude / codex
+    pub cli: String,
+    /// claude 的 `--scope`，模板本身不区分作用域，仅供参考
+    pub scope: Option<String>,
+    /// Claude 与 Codex 两种模板
+    pub templates: Vec<CreateMcpTemplateRequest>,
+    pub warnings: Vec<String>,
+}
+
+/// 命令行中的服务器定义
+#[derive(Debug, Default)]
+struct CommandServer {
+    transport: Option<String>,
+    command: Option<String>,
+    args: Vec<String>,
+    url: Option<String>,
+    env: BTreeMap<String, String>,
+    headers: BTreeMap<String, String>,
+}
+
+/// MCP 服务器安装命令与模板之间的转换
+pub struct McpCommandLine;
+
+impl McpCommandLine {
+    /// 解析一行 `claude mcp add ...` 或 `codex mcp add ...`，生成两种AI类型的模板
+    pub fn parse(line: &str) -> Result<McpCommandParseResult, String> {
+        let tokens = split_command_line(line)?;
+        let cli = match tokens.first().map(|program| program_name(program)) {
+            Some(cli) if cli == "claude" || cli == "codex" => cli,
+            _ => return Err(t!("command.unsupported")),
+        };
+        if tokens.get(1).map(String::as_str) != Some("mcp")
+            || tokens.get(2).map(String::as_str) != Some("add")
+        {
+            return Err(t!("command.only_mcp_add", cli = cli));
+        }
+
+        let mut server = CommandServer::default();
+        let mut scope = None;
+        let mut warnings = Vec::new();
+        let mut positionals = Vec::new();
+        let mut after_separator = false;
+
+        let mut index = 3;
+        while index < tokens.len() {
+            let token = &tokens[index];
+            index += 1;
+
+            if after_separator || !token.starts_with('-') || token == "-" {
+                positionals.push(token.clone());
+                continue;
+            }
+            if token == "--" {
+                after_separator = true;
+                continue;
+            }
+
+            // 支持 `--name value` 和 `--name=value` 两种写法
+            let (option, inline_value) = match token.split_once('=') {
+                Some((option, value)) if option.starts_with("--") => {
+                    (option.to_string(), Some(value.to_string()))
+                }
+                _ => (token.clone(), None),
+            };
+            let mut take_value = || -> Result<String, String> {
+                if let Some(value) = inline_value.clone() {
+                    return Ok(value);
+                }
+                match tokens.get(index) {
+                    Some(value) => {
+                        index += 1;
+                        Ok(value.clone())
+                    }
+                    None => Err(t!("command.option_missing_value", option = option)),
+                }
+            };
+
+            match (cli.as_str(), option.as_str()) {
+                ("claude", "-s" | "--scope") => scope = Some(take_value()?),
+                ("claude", "-t" | "--transport") => server.transport = Some(take_value()?),
+                ("claude", "-H" | "--header") => {
+                    let header = take_value()?;
+                    let (name, value) = header
+                        .split_once(':')
+                        .ok_or_else(|| t!("command.header_invalid", header = header))?;
+                    server
+                        .headers
+                        .insert(name.trim().to_string(), value.trim().to_string());
+                }
+                (_, "-e" | "--env") => {
+                    let (name, value) = parse_env(&take_value()?)?;
+                    server.env.insert(name, value);
+                    // claude 的 -e 可以连续跟多个 KEY=VALUE
+                    if cli == "claude" && inline_value.is_none() {
+                        while let Some(next) = tokens.get(index).filter(|t| is_env_assignment(t)) {
+                            let (name, value) = parse_env(next)?;
+                            server.env.insert(name, value);
+                            index += 1;
+                        }
+                    }
+                }
+                ("codex", "--url") => {
+                    server.url = Some(take_value()?);
+                    server.transport = Some("http".to_string());
+                }
+                (_, option) => warnings.push(t!("command.option_ignored", option = option)),
+            }
+        }
+
+        if positionals.is_empty() {
+            return Err(t!("command.name_missing"));
+        }
+        let server_id = positionals.remove(0);
+
+        match server.transport.as_deref() {
+            Some(transport) if !TRANSPORT_TYPES.contains(&transport) => {
+                return Err(t!("command.transport_unsupported", transport = transport));
+            }
+            Some("sse") | Some("http") if server.url.is_none() => {
+                if positionals.is_empty() {
+                    return Err(t!("command.url_missing"));
+                }
+                server.url = Some(positionals.remove(0));
+                if !positionals.is_empty() {
+                    warnings.push(t!("command.remote_args_ignored"));
+                    positionals.clear();
+                }
+            }
+            Some("sse") | Some("http") => {}
+            _ => {
+                if positionals.is_empty() {
+                    return Err(t!("command.command_missing"));
+                }
+                let command = positionals.remove(0);
+                if server.transport.is_none()
+                    && positionals.is_empty()
+                    && (command.starts_with("http://") || command.starts_with("https://"))
+                {
+                    warnings.push(t!("command.transport_inferred"));
+                    server.transport = Some("http".to_string());
+                    server.url = Some(command);
+                } else {
+                    server.transport = Some("stdio".to_string());
+                    server.command = Some(command);
+                    server.args = positionals;
+                }
+            }
+        }
+
+        let definition = server.to_claude();
+        let platform_type = detect_platform(&definition).to_string();
+        let mut root = Map::new();
+        root.insert(server_id.clone(), Value::Object(definition));
+        let claude_content =
+            serde_json::to_string_pretty(&Value::Object(root)).map_err(|e| e.to_string())?;
+
+        let codex = TemplateConverter::claude_to_codex(&claude_content, &server_id)?;
+        warnings.extend(codex.warnings);
+
+        let templates = vec![("claude", claude_content), ("codex", codex.content)]
+            .into_iter()
+            .map(|(ai_type, config_content)| CreateMcpTemplateRequest {
+                name: server_id.clone(),
+                version: None,
+                ai_type: ai_type.to_string(),
+                platform_type: platform_type.clone(),
+                config_content,
+                description: None,
+                category: None,
+                tags: None,
+                parameters: None,
+                platform_overrides: None,
+            })
+            .collect();
+
+        Ok(McpCommandParseResult {
+            server_id,
+            cli,
+            scope,
+            templates,
+            warnings,
+        })
+    }
+
+    /// 把模板内容导出为 cli 对应的安装命令，每个服务器一行
+    pub fn export(
+        content: &str,
+        ai_type: &str,
+        cli: &str,
+        fallback_id: &str,
+    ) -> Result<ConversionResult, String> {
+        if cli != "claude" && cli != "codex" {
+            return Err(t!("command.cli_unsupported", cli = cli));
+        }
+
+        // 统一转换为 Claude 写法再生成命令
+        let mut warnings = Vec::new();
+        let claude_content = match ai_type {
+            "claude" => content.to_string(),
+            "codex" => {
+                let converted = TemplateConverter::codex_to_claude(content)?;
+                warnings.extend(converted.warnings);
+                converted.content
+            }
+            other => return Err(t!("template.unsupported_ai_type", ai_type = other)),
+        };
+        let root: Value = serde_json::from_str(&claude_content)
+            .map_err(|e| t!("mcp.claude_json_invalid", error = e))?;
+
+        let mut lines = Vec::new();
+        for (id, server) in claude_servers(root, fallback_id)? {
+            let server = CommandServer::from_claude(&id, &server, &mut warnings)?;
+            let words = if cli == "claude" {
+                server.claude_words(&id)
+            } else {
+                server.codex_words(&id, &mut warnings)
+            };
+            lines.push(
+                words
+                    .iter()
+                    .map(|word| quote(word))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            );
+        }
+
+        Ok(ConversionResult {
+            content: lines.join("\n"),
+            warnings,
+        })
+    }
+}
+
+impl CommandServer {
+    fn to_claude(&self) -> Map<String, Value> {
+        let mut server = Map::new();
+        if let Some(transport) = &self.transport {
+            server.insert("type".to_string(), Value::from(transport.clone()));
+        }
+        if let Some(command) = &self.command {
+            server.insert("command".to_string(), Value::from(command.clone()));
+            server.insert("args".to_string(), Value::from(self.args.clone()));
+        }
+        if let Some(url) = &self.url {
+            server.insert("url".to_string(), Value::from(url.clone()));
+        }
+        if !self.headers.is_empty() {
+            server.insert("headers".to_string(), string_map(&self.headers));
+        }
+        if !self.env.is_empty() {
+            server.insert("env".to_string(), string_map(&self.env));
+        }
+        server
+    }
+
+    fn from_claude(
+        id: &str,
+        server: &Map<String, Value>,
+        warnings: &mut Vec<String>,
+    ) -> Result<Self, String> {
+        let mut result = CommandServer::default();
+        for (key, value) in server {
+            match key.as_str() {
+                "type" => result.transport = value.as_str().map(|s| s.to_string()),
+                "command" => result.command = value.as_str().map(|s| s.to_string()),
+                "args" => {
+                    result.args = value
+                        .as_array()
+                        .map(|args| {
+                            args.iter()
+                                .filter_map(|arg| arg.as_str().map(|s| s.to_string()))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                }
+                "url" => result.url = value.as_str().map(|s| s.to_string()),
+                "env" => result.env = to_string_map(value),
+                "headers" => result.headers = to_string_map(value),
+                other => warnings.push(t!("command.field_ignored", id = id, field = other)),
+            }
+        }
+
+        if result.command.is_none() && result.url.is_none() {
+            return Err(t!("command.command_or_url_missing", id = id));
+        }
+        Ok(result)
+    }
+
+    /// `claude mcp add --transport <type> <id> [--env K=V]... [--header 'K: V']... (<url> | -- <command> [args]...)`
+    fn claude_words(&self, id: &str) -> Vec<String> {
+        let transport = self.transport.clone().unwrap_or_else(|| {
+            if self.url.is_some() {
+                "http".to_string()
+            } else {
+                "stdio".to_string()
+            }
+        });
+        let mut words = vec![
+            "claude".to_string(),
+            "mcp".to_string(),
+            "add".to_string(),
+            "--transport".to_string(),
+            transport,
+            id.to_string(),
+        ];
+
+        match (&self.url, &self.command) {
+            (Some(url), _) => {
+                words.push(url.clone());
+                for (name, value) in &self.headers {
+                    words.push("--header".to_string());
+                    words.push(format!("{}: {}", name, value));
+                }
+                self.push_env(&mut words);
+            }
+            (None, Some(command)) => {
+                self.push_env(&mut words);
+                words.push("--".to_string());
+                words.push(command.clone());
+                words.extend(self.args.iter().cloned());
+            }
+            (None, None) => {}
+        }
+        words
+    }
+
+    /// `codex mcp add <id> [--env K=V]... (--url <url> | -- <command> [args]...)`
+    fn codex_words(&self, id: &str, warnings: &mut Vec<String>) -> Vec<String> {
+        let mut words = vec![
+            "codex".to_string(),
+            "mcp".to_string(),
+            "add".to_string(),
+            id.to_string(),
+        ];
+        self.push_env(&mut words);
+
+        match (&self.url, &self.command) {
+            (Some(url), _) => {
+                if self.transport.as_deref() == Some("sse") {
+                    warnings.push(t!("command.sse_exported_as_http", id = id));
+                }
+                if !self.headers.is_empty() {
+                    warnings.push(t!("command.headers_ignored", id = id));
+                }
+                words.push("--url".to_string());
+                words.push(url.clone());
+            }
+            (None, Some(command)) => {
+                words.push("--".to_string());
+                words.push(command.clone());
+                words.extend(self.args.iter().cloned());
+            }
+            (None, None) => {}
+        }
+        words
+    }
+
+    fn push_env(&self, words: &mut Vec<String>) {
+        for (name, value) in &self.env {
+            words.push("--env".to_string());
+            words.push(format!("{}={}", name, value));
+        }
+    }
+}
+
+fn string_map(map: &BTreeMap<String, String>) -> Value {
+    Value::Object(
+        map.iter()
+            .map(|(key, value)| (key.clone(), Value::from(value.clone())))
+            .collect(),
+    )
+}
+
+fn to_string_map(value: &Value) -> BTreeMap<String, String> {
+    value
+        .as_object()
+        .map(|map| {
+            map.iter()
+                .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 命令名，去掉路径和 Windows 后缀
+fn program_name(program: &str) -> String {
+    let name = program.rsplit(['/', '\\']).next().unwrap_or(program);
+    let lower = name.to_lowercase();
+    lower
+        .strip_suffix(".cmd")
+        .or_else(|| lower.strip_suffix(".exe"))
+        .unwrap_or(&lower)
+        .to_string()
+}
+
+fn is_env_assignment(token: &str) -> bool {
+    match token.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+fn parse_env(assignment: &str) -> Result<(String, String), String> {
+    if !is_env_assignment(assignment) {
+        return Err(t!("command.env_invalid", assignment = assignment));
+    }
+    let (name, value) = assignment.split_once('=').unwrap_or_default();
+    Ok((name.to_string(), value.to_string()))
+}
+
+/// 按 POSIX shell 规则拆分命令行：支持单引号、双引号、反斜杠转义和行尾续行
+fn split_command_line(line: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_token = false;
+    let mut chars = line.trim().chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => return Err(t!("command.single_quote_unclosed")),
+                    }
+                }
+            }
+            '"' => {
+                in_token = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => return Err(t!("command.double_quote_unclosed")),
+                        },
+                        Some(c) => current.push(c),
+                        None => return Err(t!("command.double_quote_unclosed")),
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                // 行尾的反斜杠表示续行
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(c) => {
+                    in_token = true;
+                    current.push(c);
+                }
+                None => {}
+            },
+            c if c.is_whitespace() => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                in_token = true;
+                current.push(c);
+            }
+        }
+    }
+
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+/// 生成可直接粘贴到 shell 的参数，含特殊字符时使用单引号
+fn quote(word: &str) -> String {
+    let safe = !word.is_empty()
+        && word.chars().all(|c| {
+            c.is_ascii_alphanumeric()
+                || matches!(c, '-' | '_' | '.' | '/' | ':' | '=' | '@' | ',' | '+' | '%')
+        });
+    if safe {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_claude_stdio_command() {
+        let result = McpCommandLine::parse(
+            "claude mcp add -s user context7 -e API_KEY=\"abc 123\" DEBUG=1 -- npx -y @upstash/context7-mcp",
+        )
+        .unwrap();
+
+        assert_eq!(result.server_id, "context7");
+        assert_eq!(result.cli, "claude");
+        assert_eq!(result.scope.as_deref(), Some("user"));
+        assert_eq!(result.templates.len(), 2);
+
+        let claude: Value = serde_json::from_str(&result.templates[0].config_content).unwrap();
+        let server = &claude["context7"];
+        assert_eq!(server["type"], "stdio");
+        assert_eq!(server["command"], "npx");
+        assert_eq!(
+            server["args"],
+            serde_json::json!(["-y", "@upstash/context7-mcp"])
+        );
+        assert_eq!(server["env"]["API_KEY"], "abc 123");
+        assert_eq!(server["env"]["DEBUG"], "1");
+        assert_eq!(result.templates[0].platform_type, "any");
+
+        let codex: toml::Value = toml::from_str(&result.templates[1].config_content).unwrap();
+        assert_eq!(
+            codex["mcp_servers"]["context7"]["command"].as_str(),
+            Some("npx")
+        );
+    }
+
+    #[test]
+    fn test_parse_remote_commands() {
+        let result = McpCommandLine::parse(
+            "claude mcp add --transport http linear https://mcp.linear.app/mcp \\\n  -H 'Authorization: Bearer x'",
+        )
+        .unwrap();
+        let claude: Value = serde_json::from_str(&result.templates[0].config_content).unwrap();
+        assert_eq!(claude["linear"]["url"], "https://mcp.linear.app/mcp");
+        assert_eq!(claude["linear"]["headers"]["Authorization"], "Bearer x");
+
+        let result =
+            McpCommandLine::parse("codex mcp add docs --url=https://mcp.example.com/mcp").unwrap();
+        let codex: toml::Value = toml::from_str(&result.templates[1].config_content).unwrap();
+        assert_eq!(codex["mcp_servers"]["docs"]["type"].as_str(), Some("http"));
+
+        assert!(McpCommandLine::parse("claude mcp add --transport sse only-name").is_err());
+        assert!(McpCommandLine::parse("npm install foo").is_err());
+        assert!(McpCommandLine::parse("claude mcp add 'broken").is_err());
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let content = r#"{
+  "exa": {
+    "type": "stdio",
+    "command": "npx",
+    "args": ["-y", "exa-mcp-server"],
+    "env": {"EXA_API_KEY": "${EXA_API_KEY}"},
+    "timeout": 30000
+  }
+}"#;
+
+        let exported = McpCommandLine::export(content, "claude", "claude", "exa").unwrap();
+        assert_eq!(
+            exported.content,
+            "claude mcp add --transport stdio exa --env 'EXA_API_KEY=${EXA_API_KEY}' -- npx -y exa-mcp-server"
+        );
+        assert!(exported.warnings.iter().any(|w| w.contains("timeout")));
+
+        let parsed = McpCommandLine::parse(&exported.content).unwrap();
+        let claude: Value = serde_json::from_str(&parsed.templates[0].config_content).unwrap();
+        assert_eq!(claude["exa"]["env"]["EXA_API_KEY"], "${EXA_API_KEY}");
+
+        let codex = McpCommandLine::export(content, "claude", "codex", "exa").unwrap();
+        assert_eq!(
+            codex.content,
+            "codex mcp add exa --env 'EXA_API_KEY=${EXA_API_KEY}' -- npx -y exa-mcp-server"
+        );
+    }
+}
@@ -0,0 +1,777 @@
+use crate::services::template_converter::{claude_servers, ServerEntry, TemplateConverter};
+use crate::t;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{ChildStdin, ChildStdout, Command};
+
+pub const DEFAULT_HANDSHAKE_TIMEOUT_MS: u64 = 15_000;
+pub const MAX_HANDSHAKE_TIMEOUT_MS: u64 = 120_000;
+
+const PROTOCOL_VERSION: &str = "2025-06-18";
+/// 只保留 stderr 末尾的内容
+const MAX_STDERR_BYTES: usize = 16 * 1024;
+/// tools/list 分页的上限，防止服务器一直返回 nextCursor
+const MAX_TOOL_PAGES: usize = 20;
+
+/// 服务器提供的工具
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpToolInfo {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// 握手测试结果，失败时 error 说明原因，stdio 服务器附带 stderr 输出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpProbeResult {
+    pub server_id: String,
+    pub success: bool,
+    pub server_name: Option<String>,
+    pub server_version: Option<String>,
+    pub protocol_version: Option<String>,
+    pub tools: Vec<McpToolInfo>,
+    pub error: Option<String>,
+    pub stderr: Option<String>,
+    pub elapsed_ms: i64,
+}
+
+#[derive(Debug, Default)]
+struct Handshake {
+    server_name: Option<String>,
+    server_version: Option<String>,
+    protocol_version: Option<String>,
+    tools: Vec<McpToolInfo>,
+}
+
+/// 启动或连接 MCP 服务器，完成 `initialize` 握手并读取 `tools/list`
+pub struct McpProbe;
+
+impl McpProbe {
+    /// 取出配置中的服务器定义，Codex 配置先转换为 Claude 写法
+    pub fn servers_from_config(
+        content: &str,
+        ai_type: &str,
+        fallback_id: &str,
+    ) -> Result<Vec<ServerEntry>, String> {
+        let content = match ai_type {
+            "claude" => content.to_string(),
+            "codex" => TemplateConverter::codex_to_claude(content)?.content,
+            other => return Err(t!("template.unsupported_ai_type", ai_type = other)),
+        };
+        let root: Value =
+            serde_json::from_str(&content).map_err(|e| t!("mcp.claude_json_invalid", error = e))?;
+        claude_servers(root, fallback_id)
+    }
+
+    /// 测试单个服务器，整个过程（含启动进程）受 timeout 限制
+    pub async fn probe(
+        server_id: &str,
+        server: &Map<String, Value>,
+        timeout: Duration,
+    ) -> McpProbeResult {
+        let start = Instant::now();
+        let stderr = Arc::new(Mutex::new(String::new()));
+
+        let transport = server
+            .get("type")
+            .and_then(Value::as_str)
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| {
+                if server.contains_key("url") {
+                    "http".to_string()
+                } else {
+                    "stdio".to_string()
+                }
+            });
+
+        let outcome = match transport.as_str() {
+            "stdio" => probe_stdio(server, timeout, stderr.clone()).await,
+            "http" | "sse" => {
+                let session = RemoteSession::new(server, transport == "sse");
+                match session {
+                    Ok(mut session) => {
+                        match tokio::time::timeout(timeout, session.handshake()).await {
+                            Ok(outcome) => outcome,
+                            Err(_) => Err(timeout_error(timeout)),
+                        }
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+            other => Err(t!("command.transport_unsupported", transport = other)),
+        };
+
+        let stderr = stderr
+            .lock()
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default();
+        let elapsed_ms = start.elapsed().as_millis() as i64;
+
+        match outcome {
+            Ok(handshake) => McpProbeResult {
+                server_id: server_id.to_string(),
+                success: true,
+                server_name: handshake.server_name,
+                server_version: handshake.server_version,
+                protocol_version: handshake.protocol_version,
+                tools: handshake.tools,
+                error: None,
+                stderr: None,
+                elapsed_ms,
+            },
+            Err(error) => McpProbeResult {
+                server_id: server_id.to_string(),
+                success: false,
+                server_name: None,
+                server_version: None,
+                protocol_version: None,
+                tools: Vec::new(),
+                error: Some(error),
+                stderr: if stderr.is_empty() {
+                    None
+                } else {
+                    Some(stderr)
+                },
+                elapsed_ms,
+            },
+        }
+    }
+}
+
+fn timeout_error(timeout: Duration) -> String {
+    t!("handshake.timeout", timeout_ms = timeout.as_millis())
+}
+
+fn initialize_params() -> Value {
+    json!({
+        "protocolVersion": PROTOCOL_VERSION,
+        "capabilities": {},
+        "clientInfo": {
+            "name": "ai-tools-client",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+    })
+}
+
+fn request_message(id: i64, method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+}
+
+fn notification_message(method: &str) -> Value {
+    json!({"jsonrpc": "2.0", "method": method})
+}
+
+/// 从消息（或批量消息）中找出指定 id 的响应
+fn match_response(message: &Value, id: i64) -> Option<Result<Value, String>> {
+    if let Some(batch) = message.as_array() {
+        return batch.iter().find_map(|item| match_response(item, id));
+    }
+    if message.get("id").and_then(Value::as_i64) != Some(id) || message.get("method").is_some() {
+        return None;
+    }
+
+    if let Some(error) = message.get("error") {
+        let text = error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| error.to_string());
+        return Some(Err(t!("handshake.server_error", error = text)));
+    }
+    Some(Ok(message.get("result").cloned().unwrap_or(Value::Null)))
+}
+
+fn parse_initialize(result: &Value, handshake: &mut Handshake) {
+    let info = result.get("serverInfo");
+    let text = |value: Option<&Value>| value.and_then(Value::as_str).map(|s| s.to_string());
+    handshake.server_name = text(info.and_then(|info| info.get("name")));
+    handshake.server_version = text(info.and_then(|info| info.get("version")));
+    handshake.protocol_version = text(result.get("protocolVersion"));
+}
+
+/// 读取一页工具，返回下一页的游标
+fn parse_tools(result: &Value, handshake: &mut Handshake) -> Option<String> {
+    if let Some(tools) = result.get("tools").and_then(Value::as_array) {
+        for tool in tools {
+            if let Some(name) = tool.get("name").and_then(Value::as_str) {
+                handshake.tools.push(McpToolInfo {
+                    name: name.to_string(),
+                    description: tool
+                        .get("description")
+                        .and_then(Value::as_str)
+                        .map(|s| s.to_string()),
+                });
+            }
+        }
+    }
+    result
+        .get("nextCursor")
+        .and_then(Value::as_str)
+        .map(|s| s.to_string())
+}
+
+fn tools_params(cursor: &Option<String>) -> Value {
+    match cursor {
+        Some(cursor) => json!({ "cursor": cursor }),
+        None => json!({}),
+    }
+}
+
+fn string_pairs(value: Option<&Value>) -> Vec<(String, String)> {
+    value
+        .and_then(Value::as_object)
+        .map(|map| {
+            map.iter()
+                .filter_map(|(key, value)| value.as_str().map(|v| (key.clone(), v.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// stdio 传输：每行一条 JSON-RPC 消息
+
+async fn probe_stdio(
+    server: &Map<String, Value>,
+    timeout: Duration,
+    stderr: Arc<Mutex<String>>,
+) -> Result<Handshake, String> {
+    let command = server
+        .get("command")
+        .and_then(Value::as_str)
+        .ok_or_else(|| t!("handshake.command_missing"))?;
+    let args: Vec<String> = server
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| arg.as_str().map(|s| s.to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut child = Command::new(command)
+        .args(&args)
+        .envs(string_pairs(server.get("env")))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| t!("handshake.spawn_failed", command = command, error = e))?;
+
+    let stderr_task = child.stderr.take().map(|mut pipe| {
+        let stderr = stderr.clone();
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 4096];
+            while let Ok(read) = pipe.read(&mut buffer).await {
+                if read == 0 {
+                    break;
+                }
+                if let Ok(mut text) = stderr.lock() {
+                    text.push_str(&String::from_utf8_lossy(&buffer[..read]));
+                    if text.len() > MAX_STDERR_BYTES {
+                        let mut cut = text.len() - MAX_STDERR_BYTES;
+                        while !text.is_char_boundary(cut) {
+                            cut += 1;
+                        }
+                        text.drain(..cut);
+                    }
+                }
+            }
+        })
+    });
+
+    let mut session = match (child.stdin.take(), child.stdout.take()) {
+        (Some(stdin), Some(stdout)) => StdioSession {
+            stdin,
+            lines: BufReader::new(stdout).lines(),
+        },
+        _ => return Err(t!("handshake.stdio_unavailable")),
+    };
+
+    let outcome = match tokio::time::timeout(timeout, session.handshake()).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(timeout_error(timeout)),
+    };
+
+    drop(session);
+    let _ = child.kill().await;
+    // 子进程退出后 stderr 很快关闭；孙进程可能仍占用管道，最多再等一会
+    if let Some(task) = stderr_task {
+        let _ = tokio::time::timeout(Duration::from_millis(500), task).await;
+    }
+
+    outcome
+}
+
+struct StdioSession {
+    stdin: ChildStdin,
+    lines: Lines<BufReader<ChildStdout>>,
+}
+
+impl StdioSession {
+    async fn send(&mut self, message: &Value) -> Result<(), String> {
+        let mut line = message.to_string();
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| t!("handshake.write_failed", error = e))?;
+        self.stdin
+            .flush()
+            .await
+            .map_err(|e| t!("handshake.write_failed", error = e))
+    }
+
+    async fn request(&mut self, id: i64, method: &str, params: Value) -> Result<Value, String> {
+        self.send(&request_message(id, method, params)).await?;
+        loop {
+            let line = self
+                .lines
+                .next_line()
+                .await
+                .map_err(|e| t!("handshake.read_failed", error = e))?
+                .ok_or_else(|| t!("handshake.exited"))?;
+
+            // 跳过日志输出、通知和服务器发起的请求
+            if let Ok(message) = serde_json::from_str::<Value>(&line) {
+                if let Some(response) = match_response(&message, id) {
+                    return response;
+                }
+            }
+        }
+    }
+
+    async fn handshake(&mut self) -> Result<Handshake, String> {
+        let mut handshake = Handshake::default();
+        let result = self.request(1, "initialize", initialize_params()).await?;
+        parse_initialize(&result, &mut handshake);
+        self.send(&notification_message("notifications/initialized"))
+            .await?;
+
+        let mut cursor = None;
+        for page in 0..MAX_TOOL_PAGES {
+            let result = self
+                .request(2 + page as i64, "tools/list", tools_params(&cursor))
+                .await?;
+            cursor = parse_tools(&result, &mut handshake);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(handshake)
+    }
+}
+
+// 远程传输：Streamable HTTP，以及旧版 SSE（GET 事件流 + endpoint 事件给出的 POST 地址）
+
+struct RemoteSession {
+    client: reqwest::Client,
+    url: String,
+    headers: Vec<(String, String)>,
+    legacy_sse: bool,
+    session_id: Option<String>,
+    /// 旧版 SSE 的事件流和消息地址
+    stream: Option<(reqwest::Response, SseBuffer)>,
+    endpoint: Option<String>,
+}
+
+impl RemoteSession {
+    fn new(server: &Map<String, Value>, legacy_sse: bool) -> Result<Self, String> {
+        let url = server
+            .get("url")
+            .and_then(Value::as_str)
+            .ok_or_else(|| t!("handshake.url_missing"))?
+            .to_string();
+        let client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| t!("probe.client_create_failed", error = e))?;
+
+        Ok(Self {
+            client,
+            url,
+            headers: string_pairs(server.get("headers")),
+            legacy_sse,
+            session_id: None,
+            stream: None,
+            endpoint: None,
+        })
+    }
+
+    fn post(&self, url: &str, message: &Value) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json, text/event-stream")
+            .header("MCP-Protocol-Version", PROTOCOL_VERSION)
+            .body(message.to_string());
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        if let Some(session_id) = &self.session_id {
+            request = request.header("Mcp-Session-Id", session_id);
+        }
+        request
+    }
+
+    async fn connect_sse(&mut self) -> Result<(), String> {
+        let mut request = self
+            .client
+            .get(&self.url)
+            .header("Accept", "text/event-stream");
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+        let response = request
+            .send()
+            .await
+            .map_err(|e| t!("handshake.sse_connect_failed", error = e))?;
+        if !response.status().is_success() {
+            return Err(t!(
+                "handshake.sse_connect_failed",
+                error = format!("HTTP {}", response.status().as_u16())
+            ));
+        }
+
+        let mut stream = (response, SseBuffer::default());
+        loop {
+            let (event, data) = next_sse_event(&mut stream).await?;
+            if event == "endpoint" {
+                let base = url::Url::parse(&self.url).map_err(|e| e.to_string())?;
+                let endpoint = base
+                    .join(data.trim())
+                    .map_err(|e| t!("handshake.endpoint_invalid", error = e))?;
+                self.endpoint = Some(endpoint.to_string());
+                self.stream = Some(stream);
+                return Ok(());
+            }
+        }
+    }
+
+    async fn send(&mut self, message: &Value, id: Option<i64>) -> Result<Option<Value>, String> {
+        let url = self.endpoint.clone().unwrap_or_else(|| self.url.clone());
+        let mut response = self
+            .post(&url, message)
+            .send()
+            .await
+            .map_err(|e| t!("handshake.request_failed", error = e))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let body: String = body.chars().take(300).collect();
+            return Err(format!("HTTP {}: {}", status.as_u16(), body.trim()));
+        }
+        if let Some(session_id) = response
+            .headers()
+            .get("mcp-session-id")
+            .and_then(|value| value.to_str().ok())
+        {
+            self.session_id = Some(session_id.to_string());
+        }
+
+        let id = match id {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        // 旧版 SSE 的响应从事件流返回
+        if self.legacy_sse {
+            let stream = self
+                .stream
+                .as_mut()
+                .ok_or_else(|| t!("handshake.sse_not_connected"))?;
+            loop {
+                let (_, data) = next_sse_event(stream).await?;
+                if let Ok(message) = serde_json::from_str::<Value>(&data) {
+                    if let Some(result) = match_response(&message, id) {
+                        return result.map(Some);
+                    }
+                }
+            }
+        }
+
+        let is_stream = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.starts_with("text/event-stream"))
+            .unwrap_or(false);
+
+        if is_stream {
+            let mut buffer = SseBuffer::default();
+            loop {
+                while let Some((_, data)) = buffer.next_event() {
+                    if let Ok(message) = serde_json::from_str::<Value>(&data) {
+                        if let Some(result) = match_response(&message, id) {
+                            return result.map(Some);
+                        }
+                    }
+                }
+                match response.chunk().await {
+                    Ok(Some(chunk)) => buffer.push(&chunk),
+                    Ok(None) => return Err(t!("handshake.stream_closed")),
+                    Err(e) => return Err(t!("handshake.stream_read_failed", error = e)),
+                }
+            }
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| t!("handshake.response_read_failed", error = e))?;
+        let message: Value = serde_json::from_str(&body)
+            .map_err(|e| t!("handshake.response_json_invalid", error = e))?;
+        match match_response(&message, id) {
+            Some(result) => result.map(Some),
+            None => Err(t!("handshake.result_missing")),
+        }
+    }
+
+    async fn request(&mut self, id: i64, method: &str, params: Value) -> Result<Value, String> {
+        self.send(&request_message(id, method, params), Some(id))
+            .await
+            .map(|result| result.unwrap_or(Value::Null))
+    }
+
+    async fn handshake(&mut self) -> Result<Handshake, String> {
+        if self.legacy_sse {
+            self.connect_sse().await?;
+        }
+
+        let mut handshake = Handshake::default();
+        let result = self.request(1, "initialize", initialize_params()).await?;
+        parse_initialize(&result, &mut handshake);
+        self.send(&notification_message("notifications/initialized"), None)
+            .await?;
+
+        let mut cursor = None;
+        for page in 0..MAX_TOOL_PAGES {
+            let result = self
+                .request(2 + page as i64, "tools/list", tools_params(&cursor))
+                .await?;
+            cursor = parse_tools(&result, &mut handshake);
+            if cursor.is_none() {
+                break;
+            }
+        }
+        Ok(handshake)
+    }
+}
+
+async fn next_sse_event(
+    stream: &mut (reqwest::Response, SseBuffer),
+) -> Result<(String, String), String> {
+    loop {
+        if let Some(event) = stream.1.next_event() {
+            return Ok(event);
+        }
+        match stream.0.chunk().await {
+            Ok(Some(chunk)) => stream.1.push(&chunk),
+            Ok(None) => return Err(t!("handshake.sse_closed")),
+            Err(e) => return Err(t!("handshake.sse_read_failed", error = e)),
+        }
+    }
+}
+
+/// 按空行切分 SSE 事件，返回（事件名, 数据）
+#[derive(Debug, Default)]
+struct SseBuffer {
+    text: String,
+}
+
+impl SseBuffer {
+    fn push(&mut self, chunk: &[u8]) {
+        self.text
+            .push_str(&String::from_utf8_lossy(chunk).replace("\r\n", "\n"));
+    }
+
+    fn next_event(&mut self) -> Option<(String, String)> {
+        loop {
+            let end = self.text.find("\n\n")?;
+            let block: String = self.text.drain(..end + 2).collect();
+
+            let mut event = "message".to_string();
+            let mut data = Vec::new();
+            for line in block.lines() {
+                if let Some(value) = line.strip_prefix("event:") {
+                    event = value.trim().to_string();
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    data.push(value.strip_prefix(' ').unwrap_or(value).to_string());
+                }
+            }
+            // 只有注释或 id 的块不是事件
+            if !data.is_empty() {
+                return Some((event, data.join("\n")));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+    use tokio::net::TcpListener;
+
+    /// 最小的 stdio MCP 服务器：回应 initialize 和 tools/list，其余消息忽略
+    const FAKE_SERVER: &str = r#"echo "fake server starting" >&2
+while IFS= read -r line; do
+  id=$(echo "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      echo 'not json: log line'
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"protocolVersion\":\"2025-06-18\",\"capabilities\":{\"tools\":{}},\"serverInfo\":{\"name\":\"fake-server\",\"version\":\"0.3.1\"}}}"
+      ;;
+    *'"method":"tools/list"'*)
+      echo "{\"jsonrpc\":\"2.0\",\"id\":$id,\"result\":{\"tools\":[{\"name\":\"echo\",\"description\":\"Echo text\",\"inputSchema\":{\"type\":\"object\"}},{\"name\":\"$FAKE_TOOL\"}]}}"
+      ;;
+  esac
+done
+"#;
+
+    fn stdio_server(script: &str, env: Value) -> (tempfile::TempDir, Map<String, Value>) {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("server.sh");
+        std::fs::write(&path, script).unwrap();
+        let server = json!({
+            "type": "stdio",
+            "command": "sh",
+            "args": [path.to_string_lossy()],
+            "env": env,
+        });
+        (dir, server.as_object().unwrap().clone())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_handshake_lists_tools() {
+        let (_dir, server) = stdio_server(FAKE_SERVER, json!({"FAKE_TOOL": "add"}));
+
+        let result = McpProbe::probe("fake", &server, Duration::from_secs(10)).await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.server_name.as_deref(), Some("fake-server"));
+        assert_eq!(result.server_version.as_deref(), Some("0.3.1"));
+        assert_eq!(result.protocol_version.as_deref(), Some("2025-06-18"));
+        assert_eq!(
+            result.tools,
+            vec![
+                McpToolInfo {
+                    name: "echo".to_string(),
+                    description: Some("Echo text".to_string()),
+                },
+                McpToolInfo {
+                    name: "add".to_string(),
+                    description: None,
+                },
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_stdio_failures_report_stderr_and_timeout() {
+        let (_dir, server) = stdio_server("echo 'Error: missing API key' >&2\nexit 1\n", json!({}));
+        let result = McpProbe::probe("broken", &server, Duration::from_secs(10)).await;
+        // 进程已退出，可能在写入或读取时发现
+        assert!(!result.success);
+        assert!(result.error.is_some());
+        assert_eq!(result.stderr.as_deref(), Some("Error: missing API key"));
+
+        let (_dir, server) = stdio_server("sleep 30\n", json!({}));
+        let result = McpProbe::probe("slow", &server, Duration::from_millis(300)).await;
+        assert!(!result.success);
+        assert!(result.error.unwrap().contains("超时"));
+        assert!(result.elapsed_ms < 5000);
+
+        let missing = json!({"command": "definitely-not-a-real-mcp-command"});
+        let result = McpProbe::probe(
+            "missing",
+            missing.as_object().unwrap(),
+            Duration::from_secs(5),
+        )
+        .await;
+        assert!(result.error.unwrap().contains("启动命令"));
+    }
+
+    #[tokio::test]
+    async fn test_http_handshake_with_session_and_event_stream() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(_) => return,
+                };
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 4096];
+                // 读取完整的请求头和正文
+                loop {
+                    let read = socket.read(&mut buffer).await.unwrap_or(0);
+                    if read == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    if let Some(end) = text.find("\r\n\r\n") {
+                        let length = text[..end]
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length:")
+                                    .map(|v| v.trim().parse::<usize>().unwrap_or(0))
+                            })
+                            .unwrap_or(0);
+                        if request.len() >= end + 4 + length {
+                            break;
+                        }
+                    }
+                }
+
+                let text = String::from_utf8_lossy(&request).to_string();
+                let has_session = text.to_lowercase().contains("mcp-session-id: s-1");
+                let response = if text.contains("\"method\":\"initialize\"") {
+                    let body = r#"{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2025-06-18","serverInfo":{"name":"remote","version":"2.0.0"}}}"#;
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nMcp-Session-Id: s-1\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else if text.contains("\"method\":\"tools/list\"") && has_session {
+                    let body = "event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"tools\":[{\"name\":\"search\"}]}}\n\n";
+                    format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                } else if has_session {
+                    "HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    "HTTP/1.1 400 Bad Request\r\nContent-Length: 10\r\nConnection: close\r\n\r\nno session".to_string()
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            }
+        });
+
+        let server = json!({"type": "http", "url": url});
+        let result = McpProbe::probe(
+            "remote",
+            server.as_object().unwrap(),
+            Duration::from_secs(10),
+        )
+        .await;
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.server_name.as_deref(), Some("remote"));
+        assert_eq!(result.tools.len(), 1);
+        assert_eq!(result.tools[0].name, "search");
+    }
+}
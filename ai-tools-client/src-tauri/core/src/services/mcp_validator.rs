@@ -0,0 +1,502 @@
+use crate::t;
+use serde_json::{Map, Value};
+
+/// 支持的传输类型
+pub const TRANSPORT_TYPES: [&str; 3] = ["stdio", "sse", "http"];
+
+/// 两种客户端配置中允许出现的服务器字段
+const KNOWN_FIELDS: [&str; 15] = [
+    "type",
+    "command",
+    "args",
+    "env",
+    "cwd",
+    "url",
+    "headers",
+    "http_headers",
+    "env_http_headers",
+    "bearer_token_env_var",
+    "timeout",
+    "startup_timeout_ms",
+    "startup_timeout_sec",
+    "tool_timeout_sec",
+    "enabled",
+];
+
+/// MCP 服务器定义的结构校验，返回（错误, 警告）
+pub struct McpServerValidator;
+
+impl McpServerValidator {
+    /// 校验 Claude JSON 配置。
+    ///
+    /// 支持三种写法：以服务器ID为键的 `{"context7": {...}}`，单个服务器定义
+    /// `{"type": "stdio", "command": ...}`，以及和转换、导入一样的 `{"mcpServers": {...}}` 包装。
+    pub fn validate_claude(content: &Value, platform_type: &str) -> (Vec<String>, Vec<String>) {
+        let mut report = Report::default();
+
+        let root = match content.as_object() {
+            Some(root) => root,
+            None => {
+                report.error(t!("mcp.claude_not_object"));
+                return report.finish();
+            }
+        };
+
+        // 去掉 mcpServers 包装，和其并列的字段写入时会被丢弃
+        let root = match root.get("mcpServers") {
+            Some(Value::Object(servers)) => {
+                for key in root.keys().filter(|key| key.as_str() != "mcpServers") {
+                    report.warn(t!("mcp.wrapper_extra_key", key = key));
+                }
+                servers
+            }
+            Some(_) => {
+                report.error(t!("mcp.wrapper_not_object"));
+                return report.finish();
+            }
+            None => root,
+        };
+
+        if is_server_definition(root) {
+            validate_server("(default)", root, platform_type, &mut report);
+        } else if root.is_empty() {
+            report.error(t!("mcp.no_servers"));
+        } else {
+            for (id, server) in root {
+                validate_server_entry(id, server, platform_type, &mut report);
+            }
+        }
+
+        report.finish()
+    }
+
+    /// 校验 Codex TOML 配置，内容只能由 `[mcp_servers.<id>]` 表组成
+    pub fn validate_codex(
+        content: &toml::Value,
+        platform_type: &str,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut report = Report::default();
+
+        let content = match serde_json::to_value(content) {
+            Ok(content) => content,
+            Err(e) => {
+                report.error(t!("mcp.codex_parse_failed", error = e));
+                return report.finish();
+            }
+        };
+        let root = content.as_object().cloned().unwrap_or_default();
+
+        for key in root.keys().filter(|key| key.as_str() != "mcp_servers") {
+            report.error(t!("mcp.codex_extra_key", key = key));
+        }
+
+        match root.get("mcp_servers").and_then(Value::as_object) {
+            Some(servers) if !servers.is_empty() => {
+                for (id, server) in servers {
+                    validate_server_entry(id, server, platform_type, &mut report);
+                }
+            }
+            Some(_) => report.error(t!("mcp.no_servers")),
+            None => report.error(t!("mcp.codex_missing_table")),
+        }
+
+        report.finish()
+    }
+}
+
+#[derive(Default)]
+struct Report {
+    errors: Vec<String>,
+    warnings: Vec<String>,
+}
+
+impl Report {
+    fn error(&mut self, message: String) {
+        self.errors.push(message);
+    }
+
+    fn warn(&mut self, message: String) {
+        self.warnings.push(message);
+    }
+
+    fn finish(self) -> (Vec<String>, Vec<String>) {
+        (self.errors, self.warnings)
+    }
+}
+
+fn is_server_definition(object: &Map<String, Value>) -> bool {
+    ["type", "command", "url"]
+        .iter()
+        .any(|key| object.contains_key(*key))
+}
+
+fn validate_server_entry(id: &str, server: &Value, platform_type: &str, report: &mut Report) {
+    if id.is_empty()
+        || !id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        report.error(t!("mcp.server_id_invalid", id = id));
+    }
+
+    match server.as_object() {
+        Some(server) => validate_server(id, server, platform_type, report),
+        None => report.error(t!("mcp.server_not_object", id = id)),
+    }
+}
+
+fn validate_server(
+    id: &str,
+    server: &Map<String, Value>,
+    platform_type: &str,
+    report: &mut Report,
+) {
+    for key in server.keys() {
+        if !KNOWN_FIELDS.contains(&key.as_str()) {
+            report.warn(t!("mcp.unknown_field", id = id, field = key));
+        }
+    }
+
+    // 传输类型，未声明时根据 command / url 推断
+    let transport = match server.get("type") {
+        Some(Value::String(transport)) => {
+            if !TRANSPORT_TYPES.contains(&transport.as_str()) {
+                report.error(t!("mcp.type_invalid", id = id, transport = transport));
+                return;
+            }
+            transport.clone()
+        }
+        Some(_) => {
+            report.error(t!("mcp.type_not_string", id = id));
+            return;
+        }
+        None if server.contains_key("command") => "stdio".to_string(),
+        None if server.contains_key("url") => "http".to_string(),
+        None => {
+            report.error(t!("mcp.command_or_url_required", id = id));
+            return;
+        }
+    };
+
+    if transport == "stdio" {
+        validate_stdio(id, server, platform_type, report);
+    } else {
+        validate_remote(id, server, report);
+    }
+
+    if let Some(env) = server.get("env") {
+        check_string_map(id, "env", env, report);
+    }
+
+    if let Some(cwd) = server.get("cwd") {
+        if !cwd.is_string() {
+            report.error(t!("mcp.cwd_not_string", id = id));
+        }
+    }
+
+    if let Some(enabled) = server.get("enabled") {
+        if !enabled.is_boolean() {
+            report.error(t!("mcp.enabled_not_bool", id = id));
+        }
+    }
+
+    for field in ["timeout", "startup_timeout_ms"] {
+        if let Some(value) = server.get(field) {
+            if !value.as_u64().map(|ms| ms > 0).unwrap_or(false) {
+                report.error(t!("mcp.field_not_positive_integer", id = id, field = field));
+            }
+        }
+    }
+
+    for field in ["startup_timeout_sec", "tool_timeout_sec"] {
+        if let Some(value) = server.get(field) {
+            if !value.as_f64().map(|sec| sec > 0.0).unwrap_or(false) {
+                report.error(t!("mcp.field_not_positive", id = id, field = field));
+            }
+        }
+    }
+}
+
+fn validate_stdio(id: &str, server: &Map<String, Value>, platform_type: &str, report: &mut Report) {
+    let command = match server.get("command") {
+        Some(Value::String(command)) if !command.trim().is_empty() => command.clone(),
+        Some(Value::String(_)) | None => {
+            report.error(t!("mcp.stdio_command_required", id = id));
+            return;
+        }
+        Some(_) => {
+            report.error(t!("mcp.command_not_string", id = id));
+            return;
+        }
+    };
+
+    let args: Vec<String> = match server.get("args") {
+        None => Vec::new(),
+        Some(Value::Array(items)) => {
+            let strings: Vec<String> = items
+                .iter()
+                .filter_map(|item| item.as_str().map(|s| s.to_string()))
+                .collect();
+            if strings.len() != items.len() {
+                report.error(t!("mcp.args_not_strings", id = id));
+            }
+            strings
+        }
+        Some(_) => {
+            report.error(t!("mcp.args_not_strings", id = id));
+            Vec::new()
+        }
+    };
+
+    if server.contains_key("url") {
+        report.warn(t!("mcp.stdio_ignores_url", id = id));
+    }
+
+    if std::iter::once(&command)
+        .chain(args.iter())
+        .any(|arg| arg.contains("@latest"))
+    {
+        report.warn(t!("mcp.unpinned_latest", id = id));
+    }
+
+    let program = command.trim().to_lowercase();
+    if (platform_type == "unix" || platform_type == "any")
+        && (program == "cmd" || program == "cmd.exe")
+        && args
+            .first()
+            .map(|arg| arg.eq_ignore_ascii_case("/c"))
+            .unwrap_or(false)
+    {
+        report.warn(t!("mcp.cmd_wrapper_on_unix", id = id));
+    }
+
+    if args.iter().any(|arg| looks_like_secret(arg)) {
+        report.warn(t!("mcp.args_secret", id = id));
+    }
+}
+
+fn validate_remote(id: &str, server: &Map<String, Value>, report: &mut Report) {
+    match server.get("url") {
+        Some(Value::String(url)) => {
+            // 含占位符的地址在渲染前无法校验格式
+            if !url.contains("${") {
+                match url::Url::parse(url) {
+                    Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => {
+                        let has_secret = parsed
+                            .query_pairs()
+                            .any(|(_, value)| looks_like_secret(&value));
+                        if has_secret {
+                            report.warn(t!("mcp.url_secret", id = id));
+                        }
+                    }
+                    _ => report.error(t!("mcp.url_invalid", id = id)),
+                }
+            }
+        }
+        Some(_) => report.error(t!("mcp.url_not_string", id = id)),
+        None => report.error(t!("mcp.remote_url_required", id = id)),
+    }
+
+    if server.contains_key("command") {
+        report.warn(t!("mcp.remote_ignores_command", id = id));
+    }
+
+    for field in ["headers", "http_headers", "env_http_headers"] {
+        if let Some(headers) = server.get(field) {
+            check_string_map(id, field, headers, report);
+        }
+    }
+}
+
+fn check_string_map(id: &str, field: &str, value: &Value, report: &mut Report) {
+    match value.as_object() {
+        Some(map) => {
+            if map.values().any(|value| !value.is_string()) {
+                report.error(t!("mcp.map_values_not_strings", id = id, field = field));
+            }
+            let secrets: Vec<&String> = map
+                .iter()
+                .filter(|(_, value)| value.as_str().map(looks_like_secret).unwrap_or(false))
+                .map(|(key, _)| key)
+                .collect();
+            if !secrets.is_empty() {
+                report.warn(t!(
+                    "mcp.map_secret",
+                    id = id,
+                    field = field,
+                    keys = secrets
+                        .iter()
+                        .map(|key| key.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+            }
+        }
+        None => report.error(t!("mcp.map_not_object", id = id, field = field)),
+    }
+}
+
+/// 粗略判断字符串是否像 API Key：UUID、常见密钥前缀或较长的随机串
+fn looks_like_secret(value: &str) -> bool {
+    let value = value.trim();
+    if value.contains("${") {
+        return false;
+    }
+
+    let value = value.strip_prefix("Bearer ").unwrap_or(value);
+
+    let is_uuid = value.len() == 36
+        && value.chars().enumerate().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if is_uuid {
+        return true;
+    }
+
+    const PREFIXES: [&str; 8] = [
+        "sk-",
+        "sk_",
+        "ghp_",
+        "gho_",
+        "github_pat_",
+        "xoxb-",
+        "AKIA",
+        "AIza",
+    ];
+    if value.len() >= 20 && PREFIXES.iter().any(|prefix| value.starts_with(prefix)) {
+        return true;
+    }
+
+    value.len() >= 32
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        && value.chars().any(|c| c.is_ascii_digit())
+        && value.chars().any(|c| c.is_ascii_alphabetic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claude(content: &str, platform_type: &str) -> (Vec<String>, Vec<String>) {
+        McpServerValidator::validate_claude(&serde_json::from_str(content).unwrap(), platform_type)
+    }
+
+    fn codex(content: &str) -> (Vec<String>, Vec<String>) {
+        McpServerValidator::validate_codex(&toml::from_str(content).unwrap(), "unix")
+    }
+
+    #[test]
+    fn test_claude_accepts_keyed_and_bare_forms() {
+        let (errors, warnings) = claude(
+            r#"{"context7": {"type": "stdio", "command": "npx", "args": ["-y", "@upstash/context7-mcp@1.0.0"], "env": {}}}"#,
+            "unix",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        let (errors, _) = claude(
+            r#"{"type": "http", "url": "https://mcp.example.com/mcp", "headers": {"X-Team": "dev"}}"#,
+            "unix",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+    }
+
+    #[test]
+    fn test_claude_accepts_mcp_servers_wrapper() {
+        let server =
+            r#"{"type": "stdio", "command": "npx", "args": ["-y", "@upstash/context7-mcp@1.0.0"]}"#;
+
+        // 包装和不包装的同一份配置得到相同的结果
+        let bare = claude(&format!(r#"{{"context7": {}}}"#, server), "unix");
+        let wrapped = claude(
+            &format!(r#"{{"mcpServers": {{"context7": {}}}}}"#, server),
+            "unix",
+        );
+        assert!(bare.0.is_empty(), "{:?}", bare.0);
+        assert_eq!(wrapped, bare);
+
+        // 包装内的服务器照常校验
+        let (errors, _) = claude(r#"{"mcpServers": {"context7": {"args": []}}}"#, "unix");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("context7"));
+
+        let (errors, _) = claude(r#"{"mcpServers": []}"#, "unix");
+        assert!(!errors.is_empty());
+        let (errors, _) = claude(r#"{"mcpServers": {}}"#, "unix");
+        assert!(!errors.is_empty());
+
+        let (errors, warnings) = claude(
+            &format!(
+                r#"{{"numStartups": 3, "mcpServers": {{"context7": {}}}}}"#,
+                server
+            ),
+            "unix",
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("numStartups"));
+    }
+
+    #[test]
+    fn test_claude_rejects_invalid_servers() {
+        let (errors, _) = claude(r#"{"foo": 1}"#, "unix");
+        assert!(!errors.is_empty());
+
+        let (errors, _) = claude(
+            r#"{"a": {"type": "websocket", "url": "wss://x"}, "b": {"type": "stdio", "args": [1]}, "c": {"url": "ftp://x", "env": {"N": 1}}}"#,
+            "unix",
+        );
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("'a'") && e.contains("type")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("'b'") && e.contains("command")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("'c'") && e.contains("url")));
+        assert!(errors
+            .iter()
+            .any(|e| e.contains("'c'") && e.contains("env")));
+    }
+
+    #[test]
+    fn test_codex_requires_mcp_servers_tables() {
+        let (errors, _) = codex(
+            r#"model = "gpt-5"
+[mcp_servers.fetch]
+command = "uvx"
+args = ["mcp-server-fetch"]
+startup_timeout_ms = "slow""#,
+        );
+        assert!(errors.iter().any(|e| e.contains("model")));
+        assert!(errors.iter().any(|e| e.contains("startup_timeout_ms")));
+
+        let (errors, _) = codex(r#"command = "npx""#);
+        assert!(errors.iter().any(|e| e.contains("mcp_servers")));
+    }
+
+    #[test]
+    fn test_warnings_for_suspicious_values() {
+        let (errors, warnings) = codex(
+            r#"[mcp_servers.exa]
+command = "cmd"
+args = ["/c", "npx", "-y", "@smithery/cli@latest", "run", "exa", "--key", "3f2b8c1e-5a7d-4d2e-9c11-0e6f2a9b7c44"]"#,
+        );
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert!(warnings.iter().any(|w| w.contains("@latest")));
+        assert!(warnings.iter().any(|w| w.contains("cmd /c")));
+        assert!(warnings.iter().any(|w| w.contains("密钥")));
+
+        // 使用参数占位符时不提示
+        let (_, warnings) = codex(
+            r#"[mcp_servers.exa]
+command = "npx"
+args = ["exa", "--key", "${EXA_API_KEY}"]"#,
+        );
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+}
@@ -0,0 +1,24 @@
+// 服务模块声明
+pub mod backup;
+pub mod builtin_templates;
+pub mod config_importer;
+pub mod config_service;
+pub mod config_writer;
+pub mod data_dir;
+pub mod database;
+pub mod failover;
+pub mod file_ops;
+pub mod hooks;
+pub mod i18n;
+pub mod mcp_command;
+pub mod mcp_probe;
+pub mod mcp_validator;
+pub mod mode_service;
+pub mod platform;
+pub mod platform_transform;
+pub mod secret_store;
+pub mod supplier_service;
+pub mod template_converter;
+pub mod template_renderer;
+pub mod template_service;
+pub mod text_diff;
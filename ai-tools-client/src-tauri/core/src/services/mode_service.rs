@@ -0,0 +1,610 @@
+use crate::models::config::{AppState as ConfigAppState, ConfigHistory};
+use crate::models::hook::SwitchRecord;
+use crate::models::mcp_template::{McpTemplate, McpTemplateRevision};
+use crate::models::mode::{
+    CreateWorkModeRequest, UpdateWorkModeRequest, WorkModeConfig, WorkModeStatus,
+    WorkModeSwitchRequest, WorkModeSwitchResult,
+};
+use crate::models::supplier::Supplier;
+use crate::models::{ApiResponse, AppError, ResultExt};
+use crate::services::config_writer::{ConfigFileSnapshot, ConfigPaths, ConfigWriter};
+use crate::services::hooks::{HookRunner, SwitchContext};
+use crate::services::platform::Platform;
+use crate::services::secret_store::SecretCipher;
+use crate::services::template_converter::server_id;
+use crate::t;
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// 没有保存当前模式时使用的默认模式
+pub const DEFAULT_WORK_MODE: &str = "claude_only";
+
+/// 工作模式的切换、状态查询、回滚和模式配置的编辑，桌面应用和命令行工具共用
+#[derive(Clone)]
+pub struct WorkModeService {
+    pool: SqlitePool,
+    data_dir: PathBuf,
+    paths: Option<ConfigPaths>,
+    /// 是否有正在进行的切换，同一个 AppCore 取得的服务共用
+    switching: Arc<AtomicBool>,
+}
+
+/// 切换期间持有，结束（包括提前返回）时清除切换标记
+struct SwitchGuard(Arc<AtomicBool>);
+
+impl SwitchGuard {
+    fn acquire(switching: &Arc<AtomicBool>) -> Option<Self> {
+        switching
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .ok()
+            .map(|_| Self(switching.clone()))
+    }
+}
+
+impl Drop for SwitchGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+impl WorkModeService {
+    pub fn new(pool: SqlitePool, data_dir: impl Into<PathBuf>, switching: Arc<AtomicBool>) -> Self {
+        Self {
+            pool,
+            data_dir: data_dir.into(),
+            paths: None,
+            switching,
+        }
+    }
+
+    /// 使用指定的配置文件位置，默认使用当前用户主目录下的配置
+    pub fn with_config_paths(mut self, paths: ConfigPaths) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    fn writer(&self) -> Result<ConfigWriter, AppError> {
+        let paths = match &self.paths {
+            Some(paths) => paths.clone(),
+            None => ConfigPaths::detect().context(t!("config.locate_failed"))?,
+        };
+        Ok(ConfigWriter::new(paths))
+    }
+
+    /// 切换工作模式：验证供应商和模板、执行钩子、写入配置文件，再保存并激活模式配置。
+    /// 写入配置文件或保存模式失败时恢复切换前的配置文件和激活的供应商。
+    /// 同一时间只允许一次切换，切换期间 status 的 is_transitioning 为 true
+    pub async fn switch(
+        &self,
+        request: WorkModeSwitchRequest,
+    ) -> Result<ApiResponse<WorkModeSwitchResult>, AppError> {
+        let _switching = match SwitchGuard::acquire(&self.switching) {
+            Some(guard) => guard,
+            None => return Ok(AppError::precondition(t!("mode.switch_in_progress")).into()),
+        };
+        let pool = &self.pool;
+
+        let mut steps_completed = Vec::new();
+        let mut backup_id = None;
+
+        // 步骤1: 验证供应商存在
+        steps_completed.push(t!("mode.step_validate_suppliers"));
+        let mut suppliers = Vec::new();
+        if let Some(claude_id) = request.claude_supplier_id {
+            match Supplier::get_by_id(pool, claude_id)
+                .await
+                .context(t!("supplier.claude_query_failed"))?
+            {
+                Some(supplier) => suppliers.push(supplier),
+                None => return Ok(AppError::not_found(t!("resource.claude_supplier")).into()),
+            }
+        }
+        if let Some(codex_id) = request.codex_supplier_id {
+            match Supplier::get_by_id(pool, codex_id)
+                .await
+                .context(t!("supplier.codex_query_failed"))?
+            {
+                Some(supplier) => suppliers.push(supplier),
+                None => return Ok(AppError::not_found(t!("resource.codex_supplier")).into()),
+            }
+        }
+
+        // 步骤2: 验证MCP模板存在
+        steps_completed.push(t!("mode.step_validate_templates"));
+        let mut templates = Vec::new();
+        if let Some(template_ids) = &request.mcp_template_ids {
+            for &template_id in template_ids {
+                match McpTemplate::get_by_id(pool, template_id)
+                    .await
+                    .context(t!("template.query_failed"))?
+                {
+                    Some(template) => templates.push(template),
+                    None => {
+                        return Ok(AppError::not_found_with_id(
+                            t!("resource.mcp_template"),
+                            template_id,
+                        )
+                        .into())
+                    }
+                }
+            }
+        }
+        let pins = request
+            .pinned_template_revisions
+            .clone()
+            .unwrap_or_default();
+        if let Some(error) = check_pinned_revisions(pool, &pins).await? {
+            return Ok(error.into());
+        }
+        let mut pinned_templates = Vec::with_capacity(templates.len());
+        for template in templates {
+            // 固定的修订已经检查过，这里不会缺失
+            if let Some(template) = pinned_template(pool, template, &pins).await? {
+                pinned_templates.push(template);
+            }
+        }
+        let templates = pinned_templates;
+
+        // 记录模式涉及的配置文件，用于备份和失败时恢复
+        let writer = self.writer()?;
+        let snapshots = snapshot_configs(&writer, &suppliers, &templates)?;
+        let cipher = if templates.is_empty() {
+            None
+        } else {
+            Some(self.secret_cipher()?)
+        };
+
+        // 步骤3: 创建备份（如果需要）
+        if request.create_backup {
+            steps_completed.push(t!("mode.step_backup"));
+            backup_id = self.backup_configs(&request, &snapshots).await?;
+        }
+
+        let from_mode = ConfigAppState::get(pool, "current_mode")
+            .await
+            .context(t!("mode.current_load_failed"))?
+            .map(|state| state.value);
+        let context = SwitchContext {
+            switch_type: "work_mode".to_string(),
+            from_mode,
+            to_mode: Some(request.target_mode.clone()),
+            backup_id,
+            ..Default::default()
+        };
+
+        // 执行前置钩子，任一失败都会中止切换
+        steps_completed.push(t!("mode.step_pre_hooks"));
+        let mut hook_results = HookRunner::run_stage(pool, &context, "pre")
+            .await
+            .context(t!("hook.pre_load_failed"))?;
+
+        if let Some(failed_hook) = hook_results.iter().find(|result| !result.success) {
+            let message = t!(
+                "switch.pre_hook_failed",
+                reason = HookRunner::describe_failure(failed_hook)
+            );
+            SwitchRecord::create(pool, context.to_record(false, &message, hook_results))
+                .await
+                .context(t!("switch.record_save_failed"))?;
+
+            return Ok(AppError::precondition(message).into());
+        }
+
+        let mut previous_active = Vec::new();
+        for supplier in &suppliers {
+            let active = Supplier::get_active(pool, &supplier.r#type)
+                .await
+                .context(t!("supplier.active_load_failed"))?;
+            previous_active.push((supplier.id.unwrap_or_default(), active));
+        }
+
+        // 步骤4: 写入供应商和MCP模板配置，并设置模式的供应商为激活状态
+        let applied = self
+            .apply_configs(&writer, &suppliers, &templates, cipher.as_ref())
+            .await;
+        if let Err(error) = applied {
+            let message = self
+                .restore_configs(&writer, &snapshots, &previous_active, error)
+                .await;
+            SwitchRecord::create(pool, context.to_record(false, &message, hook_results))
+                .await
+                .context(t!("switch.record_save_failed"))?;
+
+            return Ok(AppError::internal(message).into());
+        }
+        steps_completed.push(t!("mode.step_apply"));
+
+        // 步骤5: 保存新的工作模式配置，模式配置和当前模式在同一个事务中更新
+        let config_request = CreateWorkModeRequest {
+            mode_name: request.target_mode.clone(),
+            active_claude_supplier_id: request.claude_supplier_id,
+            active_codex_supplier_id: request.codex_supplier_id,
+            mcp_template_ids: request.mcp_template_ids,
+            pinned_template_revisions: request.pinned_template_revisions,
+        };
+        if let Err(e) = WorkModeConfig::save_and_activate(pool, config_request).await {
+            let error = format!("{}: {}", t!("mode.save_failed"), e);
+            let message = self
+                .restore_configs(&writer, &snapshots, &previous_active, error)
+                .await;
+            SwitchRecord::create(pool, context.to_record(false, &message, hook_results))
+                .await
+                .context(t!("switch.record_save_failed"))?;
+
+            return Ok(AppError::internal(message).into());
+        }
+        steps_completed.push(t!("mode.step_save"));
+
+        // 步骤6: 执行后置钩子，失败只记录不回滚
+        steps_completed.push(t!("mode.step_post_hooks"));
+        let post_results = HookRunner::run_stage(pool, &context, "post")
+            .await
+            .context(t!("hook.post_load_failed"))?;
+        hook_results.extend(post_results);
+
+        let message = t!("mode.switched", mode = request.target_mode);
+        SwitchRecord::create(
+            pool,
+            context.to_record(true, &message, hook_results.clone()),
+        )
+        .await
+        .context(t!("switch.record_save_failed"))?;
+        tracing::info!(
+            from_mode = ?context.from_mode,
+            to_mode = %request.target_mode,
+            "工作模式切换完成"
+        );
+
+        Ok(ApiResponse::success(WorkModeSwitchResult {
+            success: true,
+            message,
+            backup_id,
+            applied_at: Some(chrono::Utc::now()),
+            steps_completed,
+            hook_results,
+        }))
+    }
+
+    fn secret_cipher(&self) -> Result<SecretCipher, AppError> {
+        SecretCipher::for_data_dir(&self.data_dir).context(t!("template.secret_key_load_failed"))
+    }
+
+    /// 备份模式涉及的配置文件，每个文件一条备份记录，返回第一条的ID传给钩子
+    async fn backup_configs(
+        &self,
+        request: &WorkModeSwitchRequest,
+        snapshots: &[ConfigFileSnapshot],
+    ) -> Result<Option<i64>, AppError> {
+        let description = t!("mode.backup_description", mode = request.target_mode);
+
+        let mut backup_id = None;
+        for snapshot in snapshots {
+            let history = ConfigHistory::create(
+                &self.pool,
+                &snapshot.config_type,
+                &snapshot.path.to_string_lossy(),
+                snapshot.content.as_deref().unwrap_or_default(),
+                "backup",
+                Some(&description),
+            )
+            .await
+            .context(t!("config.backup_failed"))?;
+            backup_id = backup_id.or(history.id);
+        }
+
+        Ok(backup_id)
+    }
+
+    /// 写入供应商配置并确认能够正确解析，设置供应商为激活状态，再写入渲染后的MCP模板。
+    /// 失败时返回错误消息
+    async fn apply_configs(
+        &self,
+        writer: &ConfigWriter,
+        suppliers: &[Supplier],
+        templates: &[McpTemplate],
+        cipher: Option<&SecretCipher>,
+    ) -> Result<(), String> {
+        for supplier in suppliers {
+            writer
+                .apply_supplier(supplier)
+                .and_then(|_| writer.verify_supplier(supplier))
+                .map_err(|e| t!("switch.verify_failed", error = format!("{:#}", e)))?;
+            match Supplier::set_active(&self.pool, supplier.id.unwrap_or_default(), true).await {
+                Ok(true) => {}
+                Ok(false) => return Err(t!("switch.set_active_failed")),
+                Err(e) => return Err(t!("switch.set_active_failed_with", error = e)),
+            }
+        }
+
+        // 没有模板时不需要读取密钥
+        let Some(cipher) = cipher else {
+            return Ok(());
+        };
+        let platform_type = Platform::get_platform_type();
+        let home_dir = self.paths.as_ref().map(|paths| paths.home.as_path());
+        for template in templates {
+            let rendered = template
+                .render(&self.pool, cipher, &platform_type, home_dir, HashMap::new())
+                .await
+                .map_err(|e| {
+                    t!(
+                        "mode.template_apply_failed",
+                        name = template.name,
+                        error = e
+                    )
+                })?;
+            writer
+                .apply_mcp_servers(&template.ai_type, &rendered, &server_id(&template.name))
+                .map_err(|e| {
+                    t!(
+                        "mode.template_apply_failed",
+                        name = template.name,
+                        error = format!("{:#}", e)
+                    )
+                })?;
+            if let Some(template_id) = template.id {
+                if let Err(e) = McpTemplate::increment_usage_count(&self.pool, template_id).await {
+                    tracing::warn!(template_id, "更新模板使用次数失败: {:?}", e);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 恢复切换前的配置文件和各类型激活的供应商，返回写入失败时的提示消息
+    async fn restore_configs(
+        &self,
+        writer: &ConfigWriter,
+        snapshots: &[ConfigFileSnapshot],
+        previous_active: &[(i64, Option<Supplier>)],
+        error: String,
+    ) -> String {
+        let mut restored = writer
+            .restore(snapshots)
+            .map_err(|e| format!("{}: {:#}", t!("switch.restore_files_failed"), e));
+        for (target_id, previous) in previous_active {
+            if restored.is_err() {
+                break;
+            }
+            let (id, is_active) = match previous.as_ref().and_then(|supplier| supplier.id) {
+                Some(id) => (id, true),
+                None => (*target_id, false),
+            };
+            restored = Supplier::set_active(&self.pool, id, is_active)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("{}: {}", t!("switch.restore_active_failed"), e));
+        }
+
+        match restored {
+            Ok(()) => t!("mode.apply_failed_rolled_back", error = error),
+            Err(rollback_error) => t!(
+                "mode.apply_failed_rollback_failed",
+                error = error,
+                rollback_error = rollback_error
+            ),
+        }
+    }
+
+    /// 按已保存的模式配置切换，供应商和模板沿用该模式上次的设置
+    pub async fn switch_to_saved(
+        &self,
+        mode_name: &str,
+    ) -> Result<ApiResponse<WorkModeSwitchResult>, AppError> {
+        let config = match WorkModeConfig::get_by_name(&self.pool, mode_name)
+            .await
+            .context(t!("mode.load_failed"))?
+        {
+            Some(config) => config,
+            None => {
+                return Ok(AppError::not_found_with_id(t!("resource.work_mode"), mode_name).into())
+            }
+        };
+
+        let pins = config.get_pinned_revisions();
+        self.switch(WorkModeSwitchRequest {
+            target_mode: config.mode_name.clone(),
+            claude_supplier_id: config.active_claude_supplier_id,
+            codex_supplier_id: config.active_codex_supplier_id,
+            mcp_template_ids: Some(config.get_mcp_template_ids()),
+            pinned_template_revisions: (!pins.is_empty()).then_some(pins),
+            create_backup: false,
+        })
+        .await
+    }
+
+    /// 回到最近一次成功切换之前的工作模式
+    pub async fn rollback(&self) -> Result<ApiResponse<WorkModeSwitchResult>, AppError> {
+        let records = SwitchRecord::get_recent(&self.pool, Some("work_mode"), 50)
+            .await
+            .context(t!("switch.history_load_failed"))?;
+
+        let previous_mode = records
+            .into_iter()
+            .find(|record| record.success != 0)
+            .and_then(|record| record.from_mode);
+
+        match previous_mode {
+            Some(mode) => self.switch_to_saved(&mode).await,
+            None => Ok(AppError::precondition(t!("mode.rollback_unavailable")).into()),
+        }
+    }
+
+    /// 更新模式配置，固定的模板修订必须存在
+    pub async fn update(
+        &self,
+        request: UpdateWorkModeRequest,
+    ) -> Result<ApiResponse<Option<WorkModeConfig>>, AppError> {
+        if let Some(pins) = &request.pinned_template_revisions {
+            if let Some(error) = check_pinned_revisions(&self.pool, pins).await? {
+                return Ok(error.into());
+            }
+        }
+
+        let config = WorkModeConfig::update(&self.pool, request)
+            .await
+            .context(t!("mode.update_failed"))?;
+
+        Ok(ApiResponse::success(config))
+    }
+
+    /// 工作模式使用的MCP模板，固定了修订的模板返回对应修订的内容
+    pub async fn templates(
+        &self,
+        mode_name: &str,
+    ) -> Result<ApiResponse<Vec<McpTemplate>>, AppError> {
+        let pool = &self.pool;
+
+        let config = match WorkModeConfig::get_by_name(pool, mode_name)
+            .await
+            .context(t!("mode.load_failed"))?
+        {
+            Some(config) => config,
+            None => {
+                return Ok(AppError::not_found_with_id(t!("resource.work_mode"), mode_name).into())
+            }
+        };
+
+        let pins = config.get_pinned_revisions();
+        let mut templates = Vec::new();
+        for template_id in config.get_mcp_template_ids() {
+            let template = match McpTemplate::get_by_id(pool, template_id)
+                .await
+                .context(t!("template.query_failed"))?
+            {
+                Some(template) => template,
+                None => continue,
+            };
+
+            match pinned_template(pool, template, &pins).await? {
+                Some(template) => templates.push(template),
+                None => {
+                    return Ok(AppError::not_found_with_id(
+                        t!("resource.pinned_revision", template_id = template_id),
+                        pins[&template_id],
+                    )
+                    .into())
+                }
+            }
+        }
+
+        Ok(ApiResponse::success(templates))
+    }
+
+    /// 当前工作模式、激活的供应商和模式使用的MCP模板
+    pub async fn status(&self) -> Result<WorkModeStatus, AppError> {
+        let pool = &self.pool;
+
+        let current_mode = ConfigAppState::get(pool, "current_mode")
+            .await
+            .context(t!("mode.current_load_failed"))?
+            .map(|state| state.value)
+            .unwrap_or_else(|| DEFAULT_WORK_MODE.to_string());
+
+        let active_claude_supplier = Supplier::get_active(pool, "claude")
+            .await
+            .context(t!("supplier.claude_active_load_failed"))?
+            .map(|s| s.name);
+
+        let active_codex_supplier = Supplier::get_active(pool, "codex")
+            .await
+            .context(t!("supplier.codex_active_load_failed"))?
+            .map(|s| s.name);
+
+        let mut active_mcp_templates = Vec::new();
+        if let Some(config) = WorkModeConfig::get_by_name(pool, &current_mode)
+            .await
+            .context(t!("mode.load_failed"))?
+        {
+            for template_id in config.get_mcp_template_ids() {
+                if let Some(template) = McpTemplate::get_by_id(pool, template_id)
+                    .await
+                    .context(t!("template.query_failed"))?
+                {
+                    active_mcp_templates.push(template.name);
+                }
+            }
+        }
+
+        let last_switch_time = SwitchRecord::get_recent(pool, Some("work_mode"), 1)
+            .await
+            .context(t!("switch.history_load_failed"))?
+            .into_iter()
+            .next()
+            .and_then(|record| record.created_at);
+
+        Ok(WorkModeStatus {
+            current_mode,
+            is_transitioning: self.switching.load(Ordering::Acquire),
+            last_switch_time,
+            active_claude_supplier,
+            active_codex_supplier,
+            active_mcp_templates,
+        })
+    }
+}
+
+/// 模板固定了修订时返回该修订的内容，修订不存在时返回 None；未固定时返回模板本身
+async fn pinned_template(
+    pool: &SqlitePool,
+    template: McpTemplate,
+    pins: &HashMap<i64, i64>,
+) -> Result<Option<McpTemplate>, AppError> {
+    let Some(&revision) = template.id.and_then(|id| pins.get(&id)) else {
+        return Ok(Some(template));
+    };
+    let pinned = McpTemplateRevision::get(pool, template.id.unwrap_or_default(), revision)
+        .await
+        .context(t!("template.revision_query_failed"))?;
+
+    Ok(pinned.map(|pinned| pinned.apply_to(&template)))
+}
+
+/// 模式涉及的供应商配置文件和MCP配置文件，同一个文件只记录一次
+fn snapshot_configs(
+    writer: &ConfigWriter,
+    suppliers: &[Supplier],
+    templates: &[McpTemplate],
+) -> Result<Vec<ConfigFileSnapshot>, AppError> {
+    let mut snapshots: Vec<ConfigFileSnapshot> = Vec::new();
+    for supplier in suppliers {
+        let files = writer
+            .snapshot(&supplier.r#type)
+            .context(t!("switch.snapshot_failed"))?;
+        snapshots.extend(files);
+    }
+    for template in templates {
+        let files = writer
+            .snapshot_mcp(&template.ai_type)
+            .context(t!("switch.snapshot_failed"))?;
+        snapshots.extend(files);
+    }
+
+    let mut seen = HashSet::new();
+    snapshots.retain(|snapshot| seen.insert(snapshot.path.clone()));
+    Ok(snapshots)
+}
+
+/// 检查固定的模板修订是否存在，返回第一个不存在的修订对应的错误
+pub async fn check_pinned_revisions(
+    pool: &SqlitePool,
+    pins: &HashMap<i64, i64>,
+) -> Result<Option<AppError>, AppError> {
+    for (&template_id, &revision) in pins {
+        if McpTemplateRevision::get(pool, template_id, revision)
+            .await
+            .context(t!("template.revision_query_failed"))?
+            .is_none()
+        {
+            return Ok(Some(AppError::not_found_with_id(
+                t!("resource.template_revision", template_id = template_id),
+                revision,
+            )));
+        }
+    }
+    Ok(None)
+}
@@ -1,3 +1,4 @@
+use crate::t;
 use anyhow::Result;
 
 pub struct Platform;
@@ -29,7 +30,7 @@ impl Platform {
     /// 获取用户主目录
     pub fn get_home_dir() -> Result<String> {
         let home_dir = dirs::home_dir()
-            .ok_or_else(|| anyhow::anyhow!("无法获取用户主目录"))?
+            .ok_or_else(|| anyhow::anyhow!(t!("platform.home_dir_unavailable")))?
             .to_string_lossy()
             .to_string();
         Ok(home_dir)
@@ -38,7 +39,7 @@ impl Platform {
     /// 获取应用数据目录
     pub fn get_app_data_dir() -> Result<String> {
         let app_data_dir = dirs::data_dir()
-            .ok_or_else(|| anyhow::anyhow!("无法获取应用数据目录"))?
+            .ok_or_else(|| anyhow::anyhow!(t!("platform.data_dir_unavailable")))?
             .join("ai-tools-client")
             .to_string_lossy()
             .to_string();
@@ -48,7 +49,7 @@ impl Platform {
     /// 获取配置目录
     pub fn get_config_dir() -> Result<String> {
         let config_dir = dirs::config_dir()
-            .ok_or_else(|| anyhow::anyhow!("无法获取配置目录"))?
+            .ok_or_else(|| anyhow::anyhow!(t!("platform.config_dir_unavailable")))?
             .join("ai-tools-client")
             .to_string_lossy()
             .to_string();
@@ -0,0 +1,276 @@
+use crate::t;
+use serde_json::{Map, Value};
+
+/// 在 Windows 上需要通过 `cmd /c` 启动的命令（npm 系列在 Windows 上是 .cmd 脚本）
+const WRAPPED_COMMANDS: [&str; 7] = ["npx", "npm", "pnpm", "yarn", "bunx", "uvx", "uv"];
+
+/// 值为路径列表、需要转换分隔符的环境变量
+const PATH_LIST_ENV: [&str; 4] = ["PATH", "NODE_PATH", "PYTHONPATH", "CLASSPATH"];
+
+/// unix 与 windows 之间的模板配置转换，platform_type 为 `any` 的模板按 unix 写法保存
+pub struct PlatformTransformer;
+
+impl PlatformTransformer {
+    /// 把配置从 from 平台的写法转换为 to 平台的写法，没有需要调整的地方时原样返回
+    pub fn transform(content: &str, ai_type: &str, from: &str, to: &str) -> Result<String, String> {
+        let from = if from == "any" { "unix" } else { from };
+        if from == to {
+            return Ok(content.to_string());
+        }
+        if !matches!(to, "unix" | "windows") {
+            return Err(t!("platform.target_unsupported", platform = to));
+        }
+
+        match ai_type {
+            "claude" => {
+                let mut root: Value = serde_json::from_str(content)
+                    .map_err(|e| t!("mcp.claude_json_invalid", error = e))?;
+                let changed = match root.as_object_mut() {
+                    Some(root) if is_server_definition(root) => transform_server(root, to),
+                    Some(root) => transform_servers(root, to),
+                    None => false,
+                };
+
+                if !changed {
+                    return Ok(content.to_string());
+                }
+                serde_json::to_string_pretty(&root).map_err(|e| e.to_string())
+            }
+            "codex" => {
+                let root: toml::Value =
+                    toml::from_str(content).map_err(|e| t!("mcp.codex_toml_invalid", error = e))?;
+                let mut root = serde_json::to_value(root).map_err(|e| e.to_string())?;
+                let changed = match root.get_mut("mcp_servers").and_then(Value::as_object_mut) {
+                    Some(servers) => transform_servers(servers, to),
+                    None => false,
+                };
+
+                if !changed {
+                    return Ok(content.to_string());
+                }
+                toml::to_string(&root)
+                    .map(|content| content.trim_end().to_string())
+                    .map_err(|e| e.to_string())
+            }
+            other => Err(t!("template.unsupported_ai_type", ai_type = other)),
+        }
+    }
+}
+
+fn is_server_definition(object: &Map<String, Value>) -> bool {
+    ["type", "command", "url"]
+        .iter()
+        .any(|key| object.contains_key(*key))
+}
+
+/// 转换以服务器ID为键的所有定义，返回是否有改动
+fn transform_servers(servers: &mut Map<String, Value>, to: &str) -> bool {
+    let mut changed = false;
+    for server in servers.values_mut().filter_map(Value::as_object_mut) {
+        changed |= transform_server(server, to);
+    }
+    changed
+}
+
+/// 转换单个服务器定义，返回是否有改动
+fn transform_server(server: &mut Map<String, Value>, to: &str) -> bool {
+    let mut changed = false;
+
+    if let Some(command) = server.get("command").and_then(Value::as_str) {
+        let command = command.to_string();
+        let mut args: Vec<Value> = server
+            .get("args")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+
+        let rewritten = if to == "windows" {
+            to_windows_command(&command, &mut args)
+        } else {
+            to_unix_command(&command, &mut args)
+        };
+
+        if let Some(new_command) = rewritten {
+            server.insert("command".to_string(), Value::String(new_command));
+            server.insert("args".to_string(), Value::Array(args));
+            changed = true;
+        }
+    }
+
+    if let Some(env) = server.get_mut("env").and_then(Value::as_object_mut) {
+        let (from_separator, to_separator) = if to == "windows" {
+            (':', ';')
+        } else {
+            (';', ':')
+        };
+        for (key, value) in env.iter_mut() {
+            if !PATH_LIST_ENV.contains(&key.to_uppercase().as_str()) {
+                continue;
+            }
+            if let Some(text) = value.as_str() {
+                let converted = convert_path_list(text, from_separator, to_separator);
+                if converted != text {
+                    *value = Value::String(converted);
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    changed
+}
+
+/// `npx ...` -> `cmd /c npx ...`，绝对路径的包管理器命令只保留命令名
+fn to_windows_command(command: &str, args: &mut Vec<Value>) -> Option<String> {
+    let program = command.rsplit('/').next().unwrap_or(command);
+    if !WRAPPED_COMMANDS.contains(&program) {
+        return None;
+    }
+
+    args.splice(
+        0..0,
+        [
+            Value::String("/c".to_string()),
+            Value::String(program.to_string()),
+        ],
+    );
+    Some("cmd".to_string())
+}
+
+/// `cmd /c npx ...` -> `npx ...`，并去掉 .cmd / .exe 后缀
+fn to_unix_command(command: &str, args: &mut Vec<Value>) -> Option<String> {
+    let lower = command.to_lowercase();
+    let is_cmd = lower == "cmd" || lower == "cmd.exe";
+    let has_switch = args
+        .first()
+        .and_then(Value::as_str)
+        .map(|arg| arg.eq_ignore_ascii_case("/c"))
+        .unwrap_or(false);
+
+    if is_cmd && has_switch && args.len() >= 2 {
+        args.remove(0);
+        let program = match args.remove(0) {
+            Value::String(program) => program,
+            _ => return None,
+        };
+        return Some(strip_windows_suffix(&program));
+    }
+
+    let stripped = strip_windows_suffix(command);
+    if stripped != command {
+        Some(stripped)
+    } else {
+        None
+    }
+}
+
+fn strip_windows_suffix(command: &str) -> String {
+    let lower = command.to_lowercase();
+    for suffix in [".cmd", ".exe", ".bat"] {
+        if lower.ends_with(suffix) {
+            return command[..command.len() - suffix.len()].to_string();
+        }
+    }
+    command.to_string()
+}
+
+/// 转换路径列表分隔符，保留 Windows 盘符（如 `C:\`）和 `${NAME}` 占位符中的冒号
+fn convert_path_list(value: &str, from: char, to: char) -> String {
+    if from == ':' {
+        let mut result = String::with_capacity(value.len());
+        let chars: Vec<char> = value.chars().collect();
+        let mut in_placeholder = false;
+        for (index, &c) in chars.iter().enumerate() {
+            if c == '$' && chars.get(index + 1) == Some(&'{') {
+                in_placeholder = true;
+            } else if c == '}' {
+                in_placeholder = false;
+            }
+
+            let is_drive = index > 0
+                && chars[index - 1].is_ascii_alphabetic()
+                && (index == 1 || chars[index - 2] == ';' || chars[index - 2] == ':')
+                && matches!(chars.get(index + 1), Some('\\') | Some('/'));
+            if c == ':' && !in_placeholder && !is_drive {
+                result.push(to);
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    } else {
+        value.replace(from, &to.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_unix_to_windows_wraps_npx() {
+        let content = r#"{
+  "context7": {
+    "type": "stdio",
+    "command": "npx",
+    "args": ["-y", "@upstash/context7-mcp"],
+    "env": {"NODE_PATH": "/usr/lib/node:${workspace_dir}/node_modules"}
+  }
+}"#;
+
+        let windows = PlatformTransformer::transform(content, "claude", "any", "windows").unwrap();
+        let parsed: Value = serde_json::from_str(&windows).unwrap();
+        assert_eq!(parsed["context7"]["command"], "cmd");
+        assert_eq!(
+            parsed["context7"]["args"],
+            serde_json::json!(["/c", "npx", "-y", "@upstash/context7-mcp"])
+        );
+        assert_eq!(
+            parsed["context7"]["env"]["NODE_PATH"],
+            "/usr/lib/node;${workspace_dir}/node_modules"
+        );
+
+        // 反向转换回到原来的写法
+        let unix = PlatformTransformer::transform(&windows, "claude", "windows", "unix").unwrap();
+        let parsed: Value = serde_json::from_str(&unix).unwrap();
+        assert_eq!(parsed["context7"]["command"], "npx");
+        assert_eq!(parsed["context7"]["args"][0], "-y");
+    }
+
+    #[test]
+    fn test_codex_transform_and_passthrough() {
+        let content = r#"[mcp_servers.serena]
+command = "/usr/local/bin/uvx"
+args = ["serena"]"#;
+
+        let windows = PlatformTransformer::transform(content, "codex", "unix", "windows").unwrap();
+        let parsed: toml::Value = toml::from_str(&windows).unwrap();
+        let server = &parsed["mcp_servers"]["serena"];
+        assert_eq!(server["command"].as_str(), Some("cmd"));
+        assert_eq!(server["args"][1].as_str(), Some("uvx"));
+
+        // 无需调整的配置保持原样
+        let remote = r#"[mcp_servers.docs]
+url = "https://mcp.example.com/mcp""#;
+        assert_eq!(
+            PlatformTransformer::transform(remote, "codex", "unix", "windows").unwrap(),
+            remote
+        );
+    }
+
+    #[test]
+    fn test_windows_paths_keep_drive_letters() {
+        assert_eq!(
+            convert_path_list("C:\\tools;D:\\bin", ';', ':'),
+            "C:\\tools:D:\\bin"
+        );
+        assert_eq!(
+            convert_path_list("C:\\tools:D:\\bin", ':', ';'),
+            "C:\\tools;D:\\bin"
+        );
+        assert_eq!(
+            to_unix_command("npx.cmd", &mut Vec::new()),
+            Some("npx".to_string())
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::t;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+
+/// 数据目录中的密钥文件名
+pub const KEY_FILE_NAME: &str = "template-secrets.key";
+
+const CIPHER_PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// 模板密钥参数的加解密，密钥保存在应用数据目录下，不进入数据库
+pub struct SecretCipher {
+    cipher: Aes256Gcm,
+}
+
+impl SecretCipher {
+    /// 读取密钥文件，不存在时生成新的随机密钥
+    pub fn load_or_create(key_path: &Path) -> Result<Self> {
+        let key_bytes = if key_path.exists() {
+            let bytes = fs::read(key_path)
+                .with_context(|| t!("secret.key_read_failed", path = key_path.display()))?;
+            if bytes.len() != KEY_LEN {
+                return Err(anyhow!(t!(
+                    "secret.key_corrupted",
+                    path = key_path.display()
+                )));
+            }
+            bytes
+        } else {
+            if let Some(parent) = key_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let key = Aes256Gcm::generate_key(OsRng);
+            fs::write(key_path, key.as_slice())
+                .with_context(|| t!("secret.key_write_failed", path = key_path.display()))?;
+            restrict_permissions(key_path)?;
+            key.to_vec()
+        };
+
+        Ok(Self::from_key(&key_bytes))
+    }
+
+    /// 读取数据目录中的密钥文件，不存在时生成
+    pub fn for_data_dir(data_dir: &Path) -> Result<Self> {
+        Self::load_or_create(&data_dir.join(KEY_FILE_NAME))
+    }
+
+    fn from_key(key_bytes: &[u8]) -> Self {
+        let key = Key::<Aes256Gcm>::from_slice(key_bytes);
+        Self {
+            cipher: Aes256Gcm::new(key),
+        }
+    }
+
+    /// 加密，输出格式为 "v1:" + base64(nonce || ciphertext)
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| anyhow!(t!("secret.encrypt_failed")))?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", CIPHER_PREFIX, STANDARD.encode(payload)))
+    }
+
+    /// 解密 encrypt 的输出
+    pub fn decrypt(&self, encrypted: &str) -> Result<String> {
+        let encoded = encrypted
+            .strip_prefix(CIPHER_PREFIX)
+            .ok_or_else(|| anyhow!(t!("secret.format_unsupported")))?;
+        let payload = STANDARD
+            .decode(encoded)
+            .with_context(|| t!("secret.base64_invalid"))?;
+        if payload.len() <= NONCE_LEN {
+            return Err(anyhow!(t!("secret.length_invalid")));
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!(t!("secret.decrypt_failed")))?;
+
+        String::from_utf8(plaintext).with_context(|| t!("secret.utf8_invalid"))
+    }
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_encrypt_round_trip_with_persisted_key() {
+        let dir = tempdir().unwrap();
+        let key_path = dir.path().join("secret.key");
+
+        let cipher = SecretCipher::load_or_create(&key_path).unwrap();
+        let encrypted = cipher.encrypt("sk-example-secret").unwrap();
+        assert!(encrypted.starts_with(CIPHER_PREFIX));
+        assert!(!encrypted.contains("sk-example"));
+
+        // 重新加载同一个密钥文件后仍可解密
+        let reloaded = SecretCipher::load_or_create(&key_path).unwrap();
+        assert_eq!(reloaded.decrypt(&encrypted).unwrap(), "sk-example-secret");
+
+        let other = SecretCipher::load_or_create(&dir.path().join("other.key")).unwrap();
+        assert!(other.decrypt(&encrypted).is_err());
+    }
+}
@@ -0,0 +1,1143 @@
+use crate::models::circuit_breaker::{CircuitBreaker, CircuitState, CircuitStateChange};
+use crate::models::config::ConfigHistory;
+use crate::models::failover::{
+    order_by_chain, FailoverChainEntry, FailoverChainEntryRequest, FailoverChainItem,
+    FailoverStrategy, SupplierScoreExplanation,
+};
+use crate::models::hook::SwitchRecord;
+use crate::models::supplier::{
+    ConnectionTestResult, CreateSupplierRequest, FailoverConfig, HealthStatus, Supplier,
+    SupplierHealth, SupplierSwitchRequest, SupplierSwitchResult, SwitchReason,
+    UpdateSupplierRequest,
+};
+use crate::models::{ApiResponse, AppError, ResultExt};
+use crate::services::config_writer::{ConfigFileSnapshot, ConfigPaths, ConfigWriter};
+use crate::services::failover::FailoverPlanner;
+use crate::services::hooks::{HookRunner, SwitchContext};
+use crate::t;
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+/// 同时进行健康检查的供应商数量上限
+pub const HEALTH_CHECK_CONCURRENCY: usize = 4;
+
+type CircuitListener = Arc<dyn Fn(CircuitStateChange) + Send + Sync>;
+
+/// 供应商管理、健康检查、切换和故障转移。命令只负责参数和事件，业务流程都在这里，
+/// 各步骤之间互相调用时直接使用同一个服务，不再经过命令层
+#[derive(Clone)]
+pub struct SupplierService {
+    pool: SqlitePool,
+    paths: Option<ConfigPaths>,
+    on_circuit_change: Option<CircuitListener>,
+}
+
+impl SupplierService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self {
+            pool,
+            paths: None,
+            on_circuit_change: None,
+        }
+    }
+
+    /// 使用指定的配置文件位置，默认使用当前用户主目录下的配置
+    pub fn with_config_paths(mut self, paths: ConfigPaths) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    /// 熔断器状态发生变化时调用
+    pub fn with_circuit_listener(
+        mut self,
+        listener: impl Fn(CircuitStateChange) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_circuit_change = Some(Arc::new(listener));
+        self
+    }
+
+    fn writer(&self) -> Result<ConfigWriter, AppError> {
+        let paths = match &self.paths {
+            Some(paths) => paths.clone(),
+            None => ConfigPaths::detect().context(t!("config.locate_failed"))?,
+        };
+        Ok(ConfigWriter::new(paths))
+    }
+
+    /// 验证并创建供应商
+    pub async fn create(
+        &self,
+        request: CreateSupplierRequest,
+    ) -> Result<ApiResponse<Supplier>, AppError> {
+        if let Err(e) = Supplier::from_request(&request).validate() {
+            return Ok(e.into());
+        }
+
+        let supplier = Supplier::create(&self.pool, request)
+            .await
+            .context(t!("supplier.create_failed"))?;
+
+        Ok(ApiResponse::success(supplier))
+    }
+
+    pub async fn update(
+        &self,
+        request: UpdateSupplierRequest,
+    ) -> Result<ApiResponse<Option<Supplier>>, AppError> {
+        if Supplier::get_by_id(&self.pool, request.id)
+            .await
+            .context(t!("supplier.query_failed"))?
+            .is_none()
+        {
+            return Ok(AppError::not_found(t!("resource.supplier")).into());
+        }
+
+        let supplier = Supplier::update(&self.pool, request)
+            .await
+            .context(t!("supplier.update_failed"))?;
+
+        Ok(ApiResponse::success(supplier))
+    }
+
+    /// 只修改激活标记，不写入配置文件；需要写入配置文件时使用 switch
+    pub async fn set_active(
+        &self,
+        supplier_id: i64,
+        is_active: bool,
+    ) -> Result<ApiResponse<bool>, AppError> {
+        if Supplier::get_by_id(&self.pool, supplier_id)
+            .await
+            .context(t!("supplier.query_failed"))?
+            .is_none()
+        {
+            return Ok(AppError::not_found(t!("resource.supplier")).into());
+        }
+
+        let success = Supplier::set_active(&self.pool, supplier_id, is_active)
+            .await
+            .context(t!("supplier.set_active_failed"))?;
+
+        Ok(ApiResponse::success(success))
+    }
+
+    pub async fn test_connection(
+        &self,
+        supplier_id: i64,
+    ) -> Result<ApiResponse<ConnectionTestResult>, AppError> {
+        match Supplier::get_by_id(&self.pool, supplier_id)
+            .await
+            .context(t!("supplier.load_failed"))?
+        {
+            Some(supplier) => Ok(ApiResponse::success(supplier.test_connection().await)),
+            None => Ok(AppError::not_found_with_id(t!("resource.supplier"), supplier_id).into()),
+        }
+    }
+
+    /// 各类型的供应商数量和当前激活的供应商
+    pub async fn stats(&self) -> Result<serde_json::Value, AppError> {
+        let pool = &self.pool;
+
+        let claude_count = Supplier::get_by_type(pool, "claude")
+            .await
+            .context(t!("supplier.claude_list_failed"))?
+            .len() as i64;
+
+        let codex_count = Supplier::get_by_type(pool, "codex")
+            .await
+            .context(t!("supplier.codex_list_failed"))?
+            .len() as i64;
+
+        let active_claude = Supplier::get_active(pool, "claude")
+            .await
+            .context(t!("supplier.claude_active_load_failed"))?;
+
+        let active_codex = Supplier::get_active(pool, "codex")
+            .await
+            .context(t!("supplier.codex_active_load_failed"))?;
+
+        Ok(serde_json::json!({
+            "claude": claude_count,
+            "codex": codex_count,
+            "total": claude_count + codex_count,
+            "active_claude": active_claude.map(|s| s.name),
+            "active_codex": active_codex.map(|s| s.name)
+        }))
+    }
+
+    /// 按故障转移链排列的供应商，不在链中的排在后面，配置了链时标记为排除
+    pub async fn failover_chain(
+        &self,
+        supplier_type: &str,
+    ) -> Result<Vec<FailoverChainItem>, AppError> {
+        let suppliers = Supplier::get_by_type(&self.pool, supplier_type)
+            .await
+            .context(t!("supplier.list_failed"))?;
+        let chain = FailoverChainEntry::get_by_type(&self.pool, supplier_type)
+            .await
+            .context(t!("failover.chain_load_failed"))?;
+
+        Ok(order_by_chain(suppliers, &chain)
+            .into_iter()
+            .enumerate()
+            .map(|(position, (supplier, is_excluded))| FailoverChainItem {
+                supplier_id: supplier.id.unwrap_or_default(),
+                supplier_name: supplier.name,
+                position: position as i64,
+                is_excluded,
+                is_active: supplier.is_active.unwrap_or(0) == 1,
+            })
+            .collect())
+    }
+
+    /// 替换故障转移链，链中的供应商必须属于该类型且不能重复
+    pub async fn set_failover_chain(
+        &self,
+        supplier_type: &str,
+        entries: &[FailoverChainEntryRequest],
+    ) -> Result<ApiResponse<Vec<FailoverChainEntry>>, AppError> {
+        let suppliers = Supplier::get_by_type(&self.pool, supplier_type)
+            .await
+            .context(t!("supplier.list_failed"))?;
+
+        let mut seen = HashSet::new();
+        for entry in entries {
+            if !seen.insert(entry.supplier_id) {
+                return Ok(AppError::validation(t!(
+                    "failover.chain_duplicate",
+                    id = entry.supplier_id
+                ))
+                .into());
+            }
+            if !suppliers
+                .iter()
+                .any(|supplier| supplier.id == Some(entry.supplier_id))
+            {
+                return Ok(AppError::validation(t!(
+                    "failover.chain_unknown_supplier",
+                    id = entry.supplier_id,
+                    supplier_type = supplier_type
+                ))
+                .into());
+            }
+        }
+
+        let chain = FailoverChainEntry::replace_for_type(&self.pool, supplier_type, entries)
+            .await
+            .context(t!("failover.chain_save_failed"))?;
+
+        Ok(ApiResponse::success(chain))
+    }
+
+    /// 探测该类型的所有供应商，说明每个供应商的得分以及自动故障转移会选中哪一个。
+    /// 只用于展示，不改变熔断器状态
+    pub async fn explain_scores(
+        &self,
+        supplier_type: &str,
+    ) -> Result<Vec<SupplierScoreExplanation>, AppError> {
+        let pool = &self.pool;
+
+        let config = FailoverConfig::load(pool, supplier_type)
+            .await
+            .context(t!("failover.config_load_failed"))?;
+        let suppliers = Supplier::get_by_type(pool, supplier_type)
+            .await
+            .context(t!("supplier.list_failed"))?;
+        let chain = FailoverChainEntry::get_by_type(pool, supplier_type)
+            .await
+            .context(t!("failover.chain_load_failed"))?;
+        let current_id = suppliers
+            .iter()
+            .find(|supplier| supplier.is_active.unwrap_or(0) == 1)
+            .and_then(|supplier| supplier.id)
+            .unwrap_or_default();
+
+        let ordered = order_by_chain(suppliers, &chain);
+        let mut explanations = Vec::with_capacity(ordered.len());
+        let mut healths = HashMap::new();
+
+        // 和健康检查一样并发探测，结果按故障转移链的顺序返回
+        let previews = {
+            let config = config.clone();
+            let suppliers = ordered
+                .iter()
+                .map(|(supplier, _)| supplier.clone())
+                .collect();
+            self.probe_bounded(suppliers, move |service, supplier: Supplier| {
+                let config = config.clone();
+                async move { service.preview_health(&supplier, &config).await }
+            })
+            .await?
+        };
+
+        for (position, ((supplier, is_excluded), health)) in
+            ordered.iter().zip(previews).enumerate()
+        {
+            let supplier_id = supplier.id.unwrap_or_default();
+
+            let mut explanation = SupplierScoreExplanation {
+                supplier_id,
+                supplier_name: supplier.name.clone(),
+                position: position as i64,
+                is_current: supplier_id == current_id,
+                is_excluded: *is_excluded,
+                is_healthy: false,
+                response_time: 0,
+                relative_cost: config.scoring.cost_of(supplier_id),
+                breakdown: Default::default(),
+                selected: false,
+                error_message: None,
+            };
+
+            explanation.is_healthy = health.is_healthy;
+            explanation.response_time = health.response_time;
+            explanation.breakdown = FailoverPlanner::breakdown(supplier_id, &health, &config);
+            explanation.error_message = health.error_message.clone();
+            healths.insert(supplier_id, health);
+
+            explanations.push(explanation);
+        }
+
+        // 用和自动故障转移相同的规则标出会被选中的供应商
+        let healthy_candidates = FailoverPlanner::candidates(ordered, current_id, config.strategy)
+            .into_iter()
+            .filter_map(|supplier| {
+                let health = healths.get(&supplier.id.unwrap_or_default())?.clone();
+                if health.is_healthy {
+                    Some((supplier, health))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if let Some((selected, _)) =
+            FailoverPlanner::pick(config.strategy, healthy_candidates, &config)
+        {
+            for explanation in explanations.iter_mut() {
+                explanation.selected = Some(explanation.supplier_id) == selected.id;
+            }
+        }
+
+        Ok(explanations)
+    }
+
+    /// 探测单个供应商并更新熔断器，供应商不存在时返回 None
+    pub async fn check_health(&self, supplier_id: i64) -> Result<Option<SupplierHealth>, AppError> {
+        let supplier = match Supplier::get_by_id(&self.pool, supplier_id)
+            .await
+            .context(t!("supplier.load_failed"))?
+        {
+            Some(supplier) => supplier,
+            None => return Ok(None),
+        };
+
+        let config = FailoverConfig::load(&self.pool, &supplier.r#type)
+            .await
+            .context(t!("failover.config_load_failed"))?;
+        let now = Utc::now();
+
+        // 熔断中或半开试探次数已用完时不发起探测。申请许可和记录结果各在一个写事务中完成，
+        // 探测期间不持有写锁，同一供应商的并发检查不会丢失彼此的结果
+        let (previous_state, breaker, acquired) =
+            CircuitBreaker::update(&self.pool, supplier_id, |breaker| {
+                breaker.try_acquire(&config, now)
+            })
+            .await
+            .context(t!("circuit.save_failed"))?;
+        self.notify_circuit_change(&supplier, previous_state, &breaker);
+
+        let (is_healthy, response_time, error_message, breaker) = if acquired {
+            let connection_result = supplier.test_connection().await;
+            let success = connection_result.success;
+            let (previous_state, breaker, _) =
+                CircuitBreaker::update(&self.pool, supplier_id, |breaker| {
+                    if success {
+                        breaker.record_success(&config);
+                    } else {
+                        breaker.record_failure(&config, Utc::now());
+                    }
+                })
+                .await
+                .context(t!("circuit.save_failed"))?;
+            self.notify_circuit_change(&supplier, previous_state, &breaker);
+            (
+                success,
+                connection_result.response_time.unwrap_or(0),
+                connection_result.error,
+                breaker,
+            )
+        } else {
+            (false, 0, Some(t!("supplier.circuit_open_skipped")), breaker)
+        };
+
+        Ok(Some(health_report(
+            supplier_id,
+            now,
+            is_healthy,
+            response_time,
+            error_message,
+            &breaker,
+        )))
+    }
+
+    /// 只读地探测供应商，用于说明评分。熔断器只在内存副本上模拟，
+    /// 不占用半开试探次数，不保存熔断器，也不发送状态变化通知
+    async fn preview_health(
+        &self,
+        supplier: &Supplier,
+        config: &FailoverConfig,
+    ) -> Result<SupplierHealth, AppError> {
+        let supplier_id = supplier.id.unwrap_or_default();
+        let mut breaker = CircuitBreaker::get(&self.pool, supplier_id)
+            .await
+            .context(t!("circuit.load_failed"))?;
+        let now = Utc::now();
+
+        let (is_healthy, response_time, error_message) = if breaker.try_acquire(config, now) {
+            let connection_result = supplier.test_connection().await;
+            if connection_result.success {
+                breaker.record_success(config);
+            } else {
+                breaker.record_failure(config, Utc::now());
+            }
+            (
+                connection_result.success,
+                connection_result.response_time.unwrap_or(0),
+                connection_result.error,
+            )
+        } else {
+            (false, 0, Some(t!("supplier.circuit_open_skipped")))
+        };
+
+        Ok(health_report(
+            supplier_id,
+            now,
+            is_healthy,
+            response_time,
+            error_message,
+            &breaker,
+        ))
+    }
+
+    /// 并发检查全部供应商，同时进行的探测不超过 HEALTH_CHECK_CONCURRENCY 个，结果按供应商顺序返回
+    pub async fn check_all_health(&self) -> Result<Vec<SupplierHealth>, AppError> {
+        let suppliers = Supplier::get_all(&self.pool)
+            .await
+            .context(t!("supplier.list_failed"))?;
+        let ids: Vec<i64> = suppliers
+            .iter()
+            .filter_map(|supplier| supplier.id)
+            .collect();
+
+        let results = self.check_health_bounded(ids).await?;
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    async fn check_health_bounded(
+        &self,
+        ids: Vec<i64>,
+    ) -> Result<Vec<Option<SupplierHealth>>, AppError> {
+        self.probe_bounded(
+            ids,
+            |service, id| async move { service.check_health(id).await },
+        )
+        .await
+    }
+
+    /// 对每一项并发执行探测，同时进行的探测不超过 HEALTH_CHECK_CONCURRENCY 个，结果按输入顺序返回
+    async fn probe_bounded<I, T, F, Fut>(&self, items: Vec<I>, probe: F) -> Result<Vec<T>, AppError>
+    where
+        I: Send + 'static,
+        T: Send + 'static,
+        F: Fn(SupplierService, I) -> Fut,
+        Fut: Future<Output = Result<T, AppError>> + Send + 'static,
+    {
+        let semaphore = Arc::new(Semaphore::new(HEALTH_CHECK_CONCURRENCY));
+        let mut tasks = JoinSet::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let probing = probe(self.clone(), item);
+            let semaphore = semaphore.clone();
+            tasks.spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                (index, probing.await)
+            });
+        }
+
+        let mut results = Vec::new();
+        while let Some(joined) = tasks.join_next().await {
+            let (index, result) =
+                joined.map_err(|e| AppError::internal(t!("health.task_panicked", error = e)))?;
+            results.push((index, result?));
+        }
+        results.sort_by_key(|(index, _)| *index);
+
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    /// 切换供应商：探测目标、备份配置、执行钩子、写入配置文件，失败时按请求回滚
+    pub async fn switch(
+        &self,
+        request: SupplierSwitchRequest,
+    ) -> Result<ApiResponse<SupplierSwitchResult>, AppError> {
+        let pool = &self.pool;
+
+        // 验证供应商存在
+        let from_supplier = Supplier::get_by_id(pool, request.from_supplier_id)
+            .await
+            .context(t!("switch.source_query_failed"))?;
+
+        let to_supplier = Supplier::get_by_id(pool, request.to_supplier_id)
+            .await
+            .context(t!("switch.target_query_failed"))?;
+
+        let (from_supplier, to_supplier) = match (from_supplier, to_supplier) {
+            (Some(from_supplier), Some(to_supplier)) => (from_supplier, to_supplier),
+            _ => return Ok(AppError::not_found(t!("resource.supplier")).into()),
+        };
+
+        if from_supplier.r#type != to_supplier.r#type {
+            return Ok(AppError::validation(t!("switch.type_mismatch")).into());
+        }
+
+        let switch_time = Utc::now();
+        let mut context = SwitchContext {
+            switch_type: "supplier".to_string(),
+            supplier_type: Some(to_supplier.r#type.clone()),
+            from_supplier_id: from_supplier.id,
+            from_supplier_name: Some(from_supplier.name.clone()),
+            to_supplier_id: to_supplier.id,
+            to_supplier_name: Some(to_supplier.name.clone()),
+            ..Default::default()
+        };
+
+        // 切换前检查目标供应商是否可用，force 时跳过
+        if !request.force {
+            let probe = to_supplier.test_connection().await;
+            if !probe.success {
+                let reason = probe.error.unwrap_or_else(|| t!("common.unknown_error"));
+                let message = if probe.auth_error {
+                    t!("switch.target_auth_failed", reason = reason)
+                } else {
+                    t!("switch.target_unavailable", reason = reason)
+                };
+                SwitchRecord::create(pool, context.to_record(false, &message, Vec::new()))
+                    .await
+                    .context(t!("switch.record_save_failed"))?;
+
+                return Ok(AppError::precondition(message).into());
+            }
+        }
+
+        // 记录当前配置文件，失败时用于回滚
+        let writer = self.writer()?;
+        let snapshots = writer
+            .snapshot(&to_supplier.r#type)
+            .context(t!("switch.snapshot_failed"))?;
+
+        if request.create_backup {
+            let description = t!("switch.backup_description", name = to_supplier.name);
+            for snapshot in &snapshots {
+                let history = ConfigHistory::create(
+                    pool,
+                    &snapshot.config_type,
+                    &snapshot.path.to_string_lossy(),
+                    snapshot.content.as_deref().unwrap_or_default(),
+                    "backup",
+                    Some(&description),
+                )
+                .await
+                .context(t!("config.backup_failed"))?;
+
+                if context.backup_id.is_none() {
+                    context.backup_id = history.id;
+                }
+            }
+        }
+
+        // 执行前置钩子，任一失败都会中止切换
+        let mut hook_results = HookRunner::run_stage(pool, &context, "pre")
+            .await
+            .context(t!("hook.pre_load_failed"))?;
+
+        if let Some(failed_hook) = hook_results.iter().find(|result| !result.success) {
+            let message = t!(
+                "switch.pre_hook_failed",
+                reason = HookRunner::describe_failure(failed_hook)
+            );
+            SwitchRecord::create(pool, context.to_record(false, &message, hook_results))
+                .await
+                .context(t!("switch.record_save_failed"))?;
+
+            return Ok(AppError::precondition(message).into());
+        }
+
+        let previous_active = Supplier::get_active(pool, &to_supplier.r#type)
+            .await
+            .context(t!("supplier.active_load_failed"))?;
+
+        // 写入配置文件并确认能够正确解析，再设置目标供应商为激活状态
+        let apply_result = match writer
+            .apply_supplier(&to_supplier)
+            .and_then(|_| writer.verify_supplier(&to_supplier))
+        {
+            Ok(()) => match Supplier::set_active(pool, request.to_supplier_id, true).await {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(t!("switch.set_active_failed")),
+                Err(e) => Err(t!("switch.set_active_failed_with", error = e)),
+            },
+            Err(e) => Err(t!("switch.verify_failed", error = format!("{:#}", e))),
+        };
+
+        if let Err(error) = apply_result {
+            let message = if request.rollback_on_failure {
+                match self
+                    .rollback_switch(
+                        &writer,
+                        &snapshots,
+                        previous_active.as_ref(),
+                        request.to_supplier_id,
+                    )
+                    .await
+                {
+                    Ok(()) => t!("switch.failed_rolled_back", error = error),
+                    Err(rollback_error) => t!(
+                        "switch.failed_rollback_failed",
+                        error = error,
+                        rollback_error = rollback_error
+                    ),
+                }
+            } else {
+                t!("switch.failed", error = error)
+            };
+
+            SwitchRecord::create(pool, context.to_record(false, &message, hook_results))
+                .await
+                .context(t!("switch.record_save_failed"))?;
+
+            return Ok(AppError::internal(message).into());
+        }
+
+        // 执行后置钩子，失败只记录不回滚
+        let post_results = HookRunner::run_stage(pool, &context, "post")
+            .await
+            .context(t!("hook.post_load_failed"))?;
+        hook_results.extend(post_results);
+
+        let message = t!(
+            "switch.succeeded",
+            from = request.from_supplier_id,
+            to = request.to_supplier_id
+        );
+        SwitchRecord::create(
+            pool,
+            context.to_record(true, &message, hook_results.clone()),
+        )
+        .await
+        .context(t!("switch.record_save_failed"))?;
+        tracing::info!(
+            from_supplier_id = request.from_supplier_id,
+            to_supplier_id = request.to_supplier_id,
+            reason = ?request.switch_reason,
+            "供应商切换完成"
+        );
+
+        Ok(ApiResponse::success(SupplierSwitchResult {
+            success: true,
+            message,
+            from_supplier_id: request.from_supplier_id,
+            to_supplier_id: request.to_supplier_id,
+            switch_time,
+            rollback_available: context.backup_id.is_some(),
+            backup_id: context.backup_id,
+            error: None,
+            hook_results,
+        }))
+    }
+
+    /// 从同类型当前激活的供应商手动切换到指定供应商，没有激活的供应商时直接启用目标
+    pub async fn switch_to(
+        &self,
+        supplier_id: i64,
+        force: bool,
+        create_backup: bool,
+    ) -> Result<ApiResponse<SupplierSwitchResult>, AppError> {
+        let target = match Supplier::get_by_id(&self.pool, supplier_id)
+            .await
+            .context(t!("switch.target_query_failed"))?
+        {
+            Some(target) => target,
+            None => {
+                return Ok(AppError::not_found_with_id(t!("resource.supplier"), supplier_id).into())
+            }
+        };
+        let from_supplier_id = Supplier::get_active(&self.pool, &target.r#type)
+            .await
+            .context(t!("supplier.active_load_failed"))?
+            .and_then(|supplier| supplier.id)
+            .unwrap_or(supplier_id);
+
+        self.switch(SupplierSwitchRequest {
+            from_supplier_id,
+            to_supplier_id: supplier_id,
+            switch_reason: SwitchReason::Manual,
+            create_backup,
+            rollback_on_failure: true,
+            force,
+        })
+        .await
+    }
+
+    /// 恢复切换前的配置文件和激活状态
+    async fn rollback_switch(
+        &self,
+        writer: &ConfigWriter,
+        snapshots: &[ConfigFileSnapshot],
+        previous_active: Option<&Supplier>,
+        to_supplier_id: i64,
+    ) -> Result<(), AppError> {
+        writer
+            .restore(snapshots)
+            .context(t!("switch.restore_files_failed"))?;
+
+        let (id, is_active) = match previous_active.and_then(|supplier| supplier.id) {
+            Some(id) => (id, true),
+            None => (to_supplier_id, false),
+        };
+        Supplier::set_active(&self.pool, id, is_active)
+            .await
+            .context(t!("switch.restore_active_failed"))?;
+
+        Ok(())
+    }
+
+    /// 当前供应商不健康时按故障转移链挑选健康的备用供应商并切换
+    pub async fn auto_failover(
+        &self,
+        supplier_type: &str,
+    ) -> Result<ApiResponse<SupplierSwitchResult>, AppError> {
+        let pool = &self.pool;
+
+        // 获取故障转移配置
+        let config = FailoverConfig::load(pool, supplier_type)
+            .await
+            .context(t!("failover.config_load_failed"))?;
+
+        // 如果故障转移未启用，直接返回
+        if !config.enabled {
+            return Ok(AppError::precondition(t!("failover.disabled")).into());
+        }
+
+        // 获取当前激活的供应商
+        let current_supplier = match Supplier::get_active(pool, supplier_type)
+            .await
+            .context(t!("supplier.active_load_failed"))?
+        {
+            Some(supplier) => supplier,
+            None => return Ok(AppError::precondition(t!("failover.no_active_supplier")).into()),
+        };
+        let current_id = current_supplier.id.unwrap_or_default();
+
+        // 检查当前供应商健康状态
+        let health = match self.check_health(current_id).await? {
+            Some(health) => health,
+            None => return Ok(AppError::precondition(t!("failover.health_unavailable")).into()),
+        };
+
+        if !FailoverPlanner::should_failover(&health, &config) {
+            return Ok(AppError::precondition(t!("failover.current_healthy")).into());
+        }
+
+        // 按故障转移链排列备用供应商，配置了链时只在链中未排除的供应商之间转移
+        let suppliers = Supplier::get_by_type(pool, supplier_type)
+            .await
+            .context(t!("failover.candidates_load_failed"))?;
+        let chain = FailoverChainEntry::get_by_type(pool, supplier_type)
+            .await
+            .context(t!("failover.chain_load_failed"))?;
+        let candidates = FailoverPlanner::candidates(
+            order_by_chain(suppliers, &chain),
+            current_id,
+            config.strategy,
+        );
+
+        // 熔断中的供应商直接跳过
+        let breakers = CircuitBreaker::get_by_type(pool, supplier_type)
+            .await
+            .context(t!("circuit.load_failed"))?;
+        let now = Utc::now();
+        let candidates: Vec<Supplier> = candidates
+            .into_iter()
+            .filter(|supplier| {
+                supplier.id.is_some_and(|id| {
+                    !breakers.iter().any(|breaker| {
+                        breaker.supplier_id == id && breaker.is_blocking(&config, now)
+                    })
+                })
+            })
+            .collect();
+
+        let mut healthy_candidates = Vec::new();
+        if config.strategy == FailoverStrategy::NextInChain {
+            // 按链顺序时找到第一个健康的即可，不必探测整条链
+            for supplier in candidates {
+                let id = supplier.id.unwrap_or_default();
+                if let Ok(Some(health)) = self.check_health(id).await {
+                    if health.is_healthy {
+                        healthy_candidates.push((supplier, health));
+                        break;
+                    }
+                }
+            }
+        } else {
+            let ids = candidates
+                .iter()
+                .map(|supplier| supplier.id.unwrap_or_default())
+                .collect();
+            let results = self.check_health_bounded(ids).await?;
+            healthy_candidates = candidates
+                .into_iter()
+                .zip(results)
+                .filter_map(|(supplier, health)| {
+                    health
+                        .filter(|health| health.is_healthy)
+                        .map(|health| (supplier, health))
+                })
+                .collect();
+        }
+
+        // 如果找到目标，执行切换
+        let target = match FailoverPlanner::pick(config.strategy, healthy_candidates, &config) {
+            Some((target, _)) => target,
+            None => return Ok(AppError::precondition(t!("failover.no_healthy_candidate")).into()),
+        };
+
+        tracing::info!(
+            supplier_id = target.id,
+            strategy = ?config.strategy,
+            "选择备用供应商: {}",
+            target.name
+        );
+
+        self.switch(SupplierSwitchRequest {
+            from_supplier_id: current_id,
+            to_supplier_id: target.id.unwrap_or_default(),
+            switch_reason: SwitchReason::AutoFailover,
+            create_backup: true,
+            rollback_on_failure: config.auto_rollback,
+            force: false,
+        })
+        .await
+    }
+
+    /// 熔断器状态发生变化时通知监听者
+    fn notify_circuit_change(
+        &self,
+        supplier: &Supplier,
+        previous_state: CircuitState,
+        breaker: &CircuitBreaker,
+    ) {
+        let current_state = breaker.circuit_state();
+        if previous_state == current_state {
+            return;
+        }
+
+        if let Some(listener) = &self.on_circuit_change {
+            listener(CircuitStateChange {
+                supplier_id: breaker.supplier_id,
+                supplier_name: supplier.name.clone(),
+                from: previous_state,
+                to: current_state,
+                consecutive_failures: breaker.consecutive_failures,
+                changed_at: Utc::now(),
+            });
+        }
+    }
+}
+
+/// 根据探测结果和熔断器状态汇总健康状态
+fn health_report(
+    supplier_id: i64,
+    checked_at: DateTime<Utc>,
+    is_healthy: bool,
+    response_time: i64,
+    error_message: Option<String>,
+    breaker: &CircuitBreaker,
+) -> SupplierHealth {
+    let circuit_state = breaker.circuit_state();
+    let status = match (is_healthy, circuit_state) {
+        (_, CircuitState::Open) => HealthStatus::Unhealthy,
+        (true, CircuitState::Closed) => HealthStatus::Healthy,
+        _ => HealthStatus::Degraded,
+    };
+
+    SupplierHealth {
+        supplier_id,
+        is_healthy,
+        last_check_time: checked_at,
+        response_time,
+        consecutive_failures: breaker.consecutive_failures,
+        uptime_percentage: if is_healthy { 100.0 } else { 0.0 }, // 简化计算
+        total_requests: 1,
+        failed_requests: if is_healthy { 0 } else { 1 },
+        status,
+        error_message,
+        circuit_state,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::supplier::CreateSupplierRequest;
+    use crate::services::database::Database;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    struct TestDb {
+        _dir: TempDir,
+        pool: SqlitePool,
+    }
+
+    async fn test_db() -> TestDb {
+        let dir = TempDir::new().unwrap();
+        let db_url = format!("sqlite://{}", dir.path().join("test.db").to_string_lossy());
+        Database::new(&db_url).await.unwrap();
+        let pool = SqlitePool::connect(&db_url).await.unwrap();
+        TestDb { _dir: dir, pool }
+    }
+
+    /// 每个请求都延迟响应的本地服务，记录同时处理的最大请求数
+    async fn slow_server(active: Arc<AtomicUsize>, peak: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let active = active.clone();
+                let peak = peak.clone();
+                tokio::spawn(async move {
+                    let current = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(current, Ordering::SeqCst);
+                    let mut buffer = [0u8; 1024];
+                    let _ = socket.read(&mut buffer).await;
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    let _ = socket
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+                        )
+                        .await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    /// 端口释放后连接会被立即拒绝，每次探测都失败
+    async fn unreachable_supplier(pool: &SqlitePool, name: &str) -> i64 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        Supplier::create(
+            pool,
+            CreateSupplierRequest {
+                r#type: "claude".to_string(),
+                name: name.to_string(),
+                base_url,
+                auth_token: "token".to_string(),
+                timeout_ms: None,
+                auto_update: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+            },
+        )
+        .await
+        .unwrap()
+        .id
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_all_health_is_bounded_and_ordered() {
+        let db = test_db().await;
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let base_url = slow_server(active, peak.clone()).await;
+
+        let mut ids = Vec::new();
+        for index in 0..HEALTH_CHECK_CONCURRENCY * 2 {
+            let supplier = Supplier::create(
+                &db.pool,
+                CreateSupplierRequest {
+                    r#type: "claude".to_string(),
+                    name: format!("供应商{}", index),
+                    base_url: base_url.clone(),
+                    auth_token: "token".to_string(),
+                    timeout_ms: None,
+                    auto_update: None,
+                    opus_model: None,
+                    sonnet_model: None,
+                    haiku_model: None,
+                },
+            )
+            .await
+            .unwrap();
+            ids.push(supplier.id.unwrap());
+        }
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        let service = SupplierService::new(db.pool.clone())
+            .with_circuit_listener(move |change| recorded.lock().unwrap().push(change));
+
+        let results = service.check_all_health().await.unwrap();
+        assert_eq!(results.len(), ids.len());
+        assert!(results.iter().all(|health| health.is_healthy));
+        assert_eq!(
+            results
+                .iter()
+                .map(|health| health.supplier_id)
+                .collect::<Vec<_>>(),
+            Supplier::get_all(&db.pool)
+                .await
+                .unwrap()
+                .iter()
+                .filter_map(|supplier| supplier.id)
+                .collect::<Vec<_>>()
+        );
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1, "健康检查没有并发执行");
+        assert!(peak <= HEALTH_CHECK_CONCURRENCY);
+        // 全部探测成功，熔断器保持关闭
+        assert!(changes.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_explain_scores_probes_concurrently_in_chain_order() {
+        let db = test_db().await;
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let base_url = slow_server(active, peak.clone()).await;
+
+        for index in 0..HEALTH_CHECK_CONCURRENCY * 2 {
+            Supplier::create(
+                &db.pool,
+                CreateSupplierRequest {
+                    r#type: "claude".to_string(),
+                    name: format!("供应商{}", index),
+                    base_url: base_url.clone(),
+                    auth_token: "token".to_string(),
+                    timeout_ms: None,
+                    auto_update: None,
+                    opus_model: None,
+                    sonnet_model: None,
+                    haiku_model: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let service = SupplierService::new(db.pool.clone());
+        let explanations = service.explain_scores("claude").await.unwrap();
+        assert!(explanations
+            .iter()
+            .all(|explanation| explanation.is_healthy));
+        assert_eq!(
+            explanations
+                .iter()
+                .map(|explanation| explanation.supplier_id)
+                .collect::<Vec<_>>(),
+            Supplier::get_by_type(&db.pool, "claude")
+                .await
+                .unwrap()
+                .iter()
+                .filter_map(|supplier| supplier.id)
+                .collect::<Vec<_>>()
+        );
+
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1, "说明评分时没有并发探测");
+        assert!(peak <= HEALTH_CHECK_CONCURRENCY);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_checks_of_one_supplier_keep_every_failure() {
+        let db = test_db().await;
+        let supplier_id = unreachable_supplier(&db.pool, "不可用").await;
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        let service = SupplierService::new(db.pool.clone())
+            .with_circuit_listener(move |change| recorded.lock().unwrap().push(change));
+
+        // 同时检查的次数等于默认的连续失败阈值，每次失败都要计入
+        let threshold = FailoverConfig::default().max_consecutive_failures as usize;
+        let mut tasks = JoinSet::new();
+        for _ in 0..threshold {
+            let service = service.clone();
+            tasks.spawn(async move { service.check_health(supplier_id).await });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            let health = joined.unwrap().unwrap().unwrap();
+            assert!(!health.is_healthy);
+        }
+
+        let breaker = CircuitBreaker::get(&db.pool, supplier_id).await.unwrap();
+        assert_eq!(breaker.consecutive_failures, threshold as i64);
+        assert_eq!(breaker.circuit_state(), CircuitState::Open);
+
+        let changes = changes.lock().unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to, CircuitState::Open);
+    }
+
+    #[tokio::test]
+    async fn test_explain_scores_leaves_circuit_breakers_untouched() {
+        let db = test_db().await;
+        let failing = unreachable_supplier(&db.pool, "不可用").await;
+        let probing = unreachable_supplier(&db.pool, "试探中").await;
+
+        // 半开的熔断器只剩一次试探机会
+        let mut half_open = CircuitBreaker::new(probing);
+        half_open.state = CircuitState::HalfOpen.as_str().to_string();
+        half_open.save(&db.pool).await.unwrap();
+
+        let changes = Arc::new(Mutex::new(Vec::new()));
+        let recorded = changes.clone();
+        let service = SupplierService::new(db.pool.clone())
+            .with_circuit_listener(move |change| recorded.lock().unwrap().push(change));
+
+        // 反复说明评分也不会累计失败次数而熔断
+        let threshold = FailoverConfig::default().max_consecutive_failures;
+        for _ in 0..threshold {
+            let explanations = service.explain_scores("claude").await.unwrap();
+            assert_eq!(explanations.len(), 2);
+            assert!(explanations
+                .iter()
+                .all(|explanation| !explanation.is_healthy));
+        }
+
+        let breaker = CircuitBreaker::get(&db.pool, failing).await.unwrap();
+        assert_eq!(breaker.circuit_state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures, 0);
+
+        let breaker = CircuitBreaker::get(&db.pool, probing).await.unwrap();
+        assert_eq!(breaker.circuit_state(), CircuitState::HalfOpen);
+        assert_eq!(breaker.half_open_probes, 0);
+
+        assert!(changes.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,321 @@
+use crate::t;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// 配置转换结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionResult {
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+/// Claude 服务器定义，字段顺序即输出顺序
+#[derive(Debug, Default, Serialize)]
+struct ClaudeServer {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    transport: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    headers: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    timeout: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env: Option<Value>,
+}
+
+/// Codex 服务器定义，字段顺序即输出顺序
+#[derive(Debug, Default, Serialize)]
+struct CodexServer {
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    transport: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    args: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    startup_timeout_ms: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_headers: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env: Option<Value>,
+}
+
+/// 服务器ID与定义
+pub(crate) type ServerEntry = (String, Map<String, Value>);
+
+#[derive(Debug, Serialize)]
+struct CodexConfig {
+    mcp_servers: BTreeMap<String, CodexServer>,
+}
+
+/// Claude JSON 与 Codex TOML 之间的 MCP 配置转换
+pub struct TemplateConverter;
+
+impl TemplateConverter {
+    /// 按 AI 类型转换配置内容，fallback_id 用于没有服务器ID的单个 Claude 定义
+    pub fn convert(
+        content: &str,
+        from_ai_type: &str,
+        to_ai_type: &str,
+        fallback_id: &str,
+    ) -> Result<ConversionResult, String> {
+        match (from_ai_type, to_ai_type) {
+            ("claude", "codex") => Self::claude_to_codex(content, fallback_id),
+            ("codex", "claude") => Self::codex_to_claude(content),
+            (from, to) if from == to => Err(t!("convert.same_type")),
+            (from, to) => Err(t!("convert.unsupported", from = from, to = to)),
+        }
+    }
+
+    /// Claude `mcpServers` 片段转换为 `[mcp_servers.<id>]` 表
+    pub fn claude_to_codex(content: &str, fallback_id: &str) -> Result<ConversionResult, String> {
+        let root: Value =
+            serde_json::from_str(content).map_err(|e| t!("mcp.claude_json_invalid", error = e))?;
+        let servers = claude_servers(root, fallback_id)?;
+
+        let mut warnings = Vec::new();
+        let mut converted = BTreeMap::new();
+
+        for (id, server) in servers {
+            let mut target = CodexServer::default();
+            for (key, value) in server {
+                match key.as_str() {
+                    "type" => {
+                        let transport = value.as_str().unwrap_or_default().to_string();
+                        if transport == "sse" {
+                            warnings.push(t!("convert.sse_to_http", id = id));
+                            target.transport = Some("http".to_string());
+                        } else {
+                            target.transport = Some(transport);
+                        }
+                    }
+                    "command" => target.command = Some(value),
+                    "args" => target.args = Some(value),
+                    "url" => target.url = Some(value),
+                    "headers" => target.http_headers = Some(value),
+                    "timeout" => target.startup_timeout_ms = Some(value),
+                    "env" => target.env = non_empty(value),
+                    other => warnings.push(unsupported(&id, "Codex", other)),
+                }
+            }
+            converted.insert(id, target);
+        }
+
+        let content = toml::to_string(&CodexConfig {
+            mcp_servers: converted,
+        })
+        .map_err(|e| t!("mcp.codex_generate_failed", error = e))?;
+
+        Ok(ConversionResult {
+            content: content.trim_end().to_string(),
+            warnings,
+        })
+    }
+
+    /// `[mcp_servers.<id>]` 表转换为以服务器ID为键的 Claude JSON
+    pub fn codex_to_claude(content: &str) -> Result<ConversionResult, String> {
+        let root: toml::Value =
+            toml::from_str(content).map_err(|e| t!("mcp.codex_toml_invalid", error = e))?;
+        let root = serde_json::to_value(root).map_err(|e| e.to_string())?;
+
+        let servers = match root.get("mcp_servers").and_then(Value::as_object) {
+            Some(servers) if !servers.is_empty() => servers.clone(),
+            _ => return Err(t!("convert.codex_missing_table")),
+        };
+
+        let mut warnings = Vec::new();
+        let mut converted = BTreeMap::new();
+
+        for (id, server) in servers {
+            let server = match server {
+                Value::Object(server) => server,
+                _ => return Err(t!("convert.server_not_table", id = id)),
+            };
+
+            let mut target = ClaudeServer::default();
+            for (key, value) in server {
+                match key.as_str() {
+                    "type" => target.transport = value.as_str().map(|s| s.to_string()),
+                    "command" => target.command = Some(value),
+                    "args" => target.args = Some(value),
+                    "url" => target.url = Some(value),
+                    "http_headers" => target.headers = Some(value),
+                    "startup_timeout_ms" => target.timeout = Some(value),
+                    "startup_timeout_sec" => {
+                        target.timeout = value
+                            .as_f64()
+                            .map(|sec| Value::from((sec * 1000.0).round() as u64))
+                    }
+                    "env" => target.env = non_empty(value),
+                    "enabled" if value.as_bool() == Some(true) => {}
+                    other => warnings.push(unsupported(&id, "Claude", other)),
+                }
+            }
+
+            // Claude 需要显式的传输类型
+            if target.transport.is_none() {
+                target.transport = Some(if target.url.is_some() {
+                    "http".to_string()
+                } else {
+                    "stdio".to_string()
+                });
+            }
+            converted.insert(id, target);
+        }
+
+        let content = serde_json::to_string_pretty(&converted)
+            .map_err(|e| t!("mcp.claude_generate_failed", error = e))?;
+
+        Ok(ConversionResult { content, warnings })
+    }
+}
+
+/// 取出 Claude 配置中的服务器定义，支持 `{"mcpServers": {...}}`、以ID为键和单个定义三种写法
+pub(crate) fn claude_servers(root: Value, fallback_id: &str) -> Result<Vec<ServerEntry>, String> {
+    let mut root = match root {
+        Value::Object(root) => root,
+        _ => return Err(t!("mcp.claude_not_object")),
+    };
+
+    if let Some(Value::Object(servers)) = root.remove("mcpServers") {
+        root = servers;
+    }
+
+    if ["type", "command", "url"]
+        .iter()
+        .any(|key| root.contains_key(*key))
+    {
+        return Ok(vec![(server_id(fallback_id), root)]);
+    }
+
+    if root.is_empty() {
+        return Err(t!("mcp.no_servers"));
+    }
+
+    root.into_iter()
+        .map(|(id, server)| match server {
+            Value::Object(server) => Ok((id, server)),
+            _ => Err(t!("mcp.server_not_object", id = id)),
+        })
+        .collect()
+}
+
+/// 由模板名称生成服务器ID，例如 "Sequential Thinking" -> "sequential-thinking"
+pub fn server_id(name: &str) -> String {
+    let id = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if id.is_empty() {
+        "server".to_string()
+    } else {
+        id
+    }
+}
+
+fn non_empty(value: Value) -> Option<Value> {
+    match &value {
+        Value::Object(map) if map.is_empty() => None,
+        _ => Some(value),
+    }
+}
+
+fn unsupported(id: &str, target: &str, field: &str) -> String {
+    t!(
+        "convert.field_unsupported",
+        id = id,
+        target = target,
+        field = field
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claude_to_codex_and_back() {
+        let claude = r#"{
+  "mcpServers": {
+    "memory": {
+      "type": "stdio",
+      "command": "npx",
+      "args": ["-y", "@modelcontextprotocol/server-memory"],
+      "env": {"MEMORY_FILE": "${workspace_dir}/memory.json"},
+      "timeout": 20000
+    }
+  }
+}"#;
+
+        let codex = TemplateConverter::claude_to_codex(claude, "unused").unwrap();
+        assert!(codex.warnings.is_empty());
+        let parsed: toml::Value = toml::from_str(&codex.content).unwrap();
+        let server = &parsed["mcp_servers"]["memory"];
+        assert_eq!(server["command"].as_str(), Some("npx"));
+        assert_eq!(server["startup_timeout_ms"].as_integer(), Some(20000));
+        assert_eq!(
+            server["env"]["MEMORY_FILE"].as_str(),
+            Some("${workspace_dir}/memory.json")
+        );
+
+        let back = TemplateConverter::codex_to_claude(&codex.content).unwrap();
+        let parsed: Value = serde_json::from_str(&back.content).unwrap();
+        assert_eq!(parsed["memory"]["type"], "stdio");
+        assert_eq!(
+            parsed["memory"]["args"][1],
+            "@modelcontextprotocol/server-memory"
+        );
+        assert_eq!(parsed["memory"]["timeout"], 20000);
+    }
+
+    #[test]
+    fn test_bare_claude_definition_uses_fallback_id() {
+        let result = TemplateConverter::claude_to_codex(
+            r#"{"type": "sse", "url": "https://mcp.example.com/sse", "headers": {"X-Team": "dev"}}"#,
+            "Example Server",
+        )
+        .unwrap();
+
+        let parsed: toml::Value = toml::from_str(&result.content).unwrap();
+        let server = &parsed["mcp_servers"]["example-server"];
+        assert_eq!(server["type"].as_str(), Some("http"));
+        assert_eq!(server["http_headers"]["X-Team"].as_str(), Some("dev"));
+        assert!(result.warnings.iter().any(|w| w.contains("SSE")));
+    }
+
+    #[test]
+    fn test_codex_only_fields_are_reported() {
+        let result = TemplateConverter::codex_to_claude(
+            r#"[mcp_servers.serena]
+command = "uvx"
+args = ["serena"]
+startup_timeout_sec = 15
+tool_timeout_sec = 60
+cwd = "/tmp""#,
+        )
+        .unwrap();
+
+        let parsed: Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(parsed["serena"]["timeout"], 15000);
+        assert_eq!(result.warnings.len(), 2);
+        assert!(result
+            .warnings
+            .iter()
+            .any(|w| w.contains("tool_timeout_sec")));
+    }
+}
@@ -0,0 +1,254 @@
+use crate::models::mcp_template::TemplateParameter;
+use crate::t;
+use std::collections::{HashMap, HashSet};
+
+/// 无需在参数定义中声明、由客户端在应用模板时提供的变量
+pub const BUILTIN_VARIABLES: [&str; 2] = ["workspace_dir", "home_dir"];
+
+/// 模板中的占位符片段
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// 渲染 config_content 中的 `${NAME}` 占位符，`$${` 表示字面量 `${`
+pub struct TemplateRenderer;
+
+impl TemplateRenderer {
+    /// 提取模板中出现的占位符名称（去重，保持出现顺序）
+    pub fn placeholders(content: &str) -> Result<Vec<String>, String> {
+        let mut seen = HashSet::new();
+        let mut names = Vec::new();
+
+        for segment in Self::parse(content)? {
+            if let Segment::Placeholder(name) = segment {
+                if seen.insert(name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// 渲染模板。
+    ///
+    /// 取值优先级：传入的值 > 参数默认值；必填参数或未声明的变量缺少取值时报错。
+    /// 值会按目标格式转义，占位符应写在字符串字面量中。
+    pub fn render(
+        content: &str,
+        ai_type: &str,
+        parameters: &[TemplateParameter],
+        values: &HashMap<String, String>,
+    ) -> Result<String, String> {
+        let mut rendered = String::with_capacity(content.len());
+        let mut missing = Vec::new();
+
+        for segment in Self::parse(content)? {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Placeholder(name) => {
+                    let parameter = parameters.iter().find(|p| p.name == name);
+                    let value = values
+                        .get(name)
+                        .cloned()
+                        .or_else(|| parameter.and_then(|p| p.default.clone()));
+
+                    match value {
+                        Some(value) => rendered.push_str(&Self::escape(&value, ai_type)),
+                        None => {
+                            if !missing.iter().any(|m| m == name) {
+                                missing.push(name.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(t!("render.missing_parameters", names = missing.join(", ")));
+        }
+
+        Ok(rendered)
+    }
+
+    /// 检查参数定义与模板内容是否一致，返回（错误, 警告）
+    pub fn check(content: &str, parameters: &[TemplateParameter]) -> (Vec<String>, Vec<String>) {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        let placeholders = match Self::placeholders(content) {
+            Ok(placeholders) => placeholders,
+            Err(e) => {
+                errors.push(e);
+                return (errors, warnings);
+            }
+        };
+
+        let mut names = HashSet::new();
+        for parameter in parameters {
+            if !is_valid_name(&parameter.name) {
+                errors.push(t!("render.parameter_name_invalid", name = parameter.name));
+            }
+            if !names.insert(parameter.name.as_str()) {
+                errors.push(t!("render.parameter_duplicated", name = parameter.name));
+            }
+            if parameter.secret && parameter.default.is_some() {
+                errors.push(t!("render.secret_default", name = parameter.name));
+            }
+            if !placeholders.contains(&parameter.name) {
+                warnings.push(t!("render.parameter_unused", name = parameter.name));
+            }
+        }
+
+        for placeholder in &placeholders {
+            if !names.contains(placeholder.as_str())
+                && !BUILTIN_VARIABLES.contains(&placeholder.as_str())
+            {
+                warnings.push(t!("render.variable_undeclared", name = placeholder));
+            }
+        }
+
+        (errors, warnings)
+    }
+
+    /// 用占位值代替所有变量，便于在未填参数时检查配置格式
+    pub fn render_for_validation(content: &str) -> Result<String, String> {
+        let mut rendered = String::with_capacity(content.len());
+        for segment in Self::parse(content)? {
+            match segment {
+                Segment::Text(text) => rendered.push_str(text),
+                Segment::Placeholder(_) => rendered.push('0'),
+            }
+        }
+        Ok(rendered)
+    }
+
+    fn parse(content: &str) -> Result<Vec<Segment<'_>>, String> {
+        let mut segments = Vec::new();
+        let mut rest = content;
+
+        while let Some(start) = rest.find("${") {
+            // `$${` 转义为字面量 `${`
+            if start > 0 && rest.as_bytes()[start - 1] == b'$' {
+                segments.push(Segment::Text(&rest[..start - 1]));
+                segments.push(Segment::Text("${"));
+                rest = &rest[start + 2..];
+                continue;
+            }
+
+            segments.push(Segment::Text(&rest[..start]));
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| t!("render.placeholder_unclosed"))?;
+            let name = after[..end].trim();
+            if !is_valid_name(name) {
+                return Err(t!("render.placeholder_name_invalid", name = name));
+            }
+
+            segments.push(Segment::Placeholder(name));
+            rest = &after[end + 1..];
+        }
+
+        segments.push(Segment::Text(rest));
+        Ok(segments)
+    }
+
+    /// 按 JSON / TOML 基本字符串的规则转义（两者的转义规则兼容）
+    fn escape(value: &str, ai_type: &str) -> String {
+        match ai_type {
+            "claude" | "codex" => {
+                let quoted = serde_json::to_string(value).unwrap_or_default();
+                quoted[1..quoted.len() - 1].to_string()
+            }
+            _ => value.to_string(),
+        }
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameter(
+        name: &str,
+        required: bool,
+        secret: bool,
+        default: Option<&str>,
+    ) -> TemplateParameter {
+        TemplateParameter {
+            name: name.to_string(),
+            description: None,
+            required,
+            secret,
+            default: default.map(|d| d.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render_fills_values_and_defaults() {
+        let content = r#"args = ["--key", "${EXA_API_KEY}", "--dir", "${workspace_dir}", "--lang", "${LANG}"]"#;
+        let parameters = vec![
+            parameter("EXA_API_KEY", true, true, None),
+            parameter("LANG", false, false, Some("zh")),
+        ];
+        let values = HashMap::from([
+            ("EXA_API_KEY".to_string(), "secret\"key".to_string()),
+            ("workspace_dir".to_string(), "/work".to_string()),
+        ]);
+
+        let rendered = TemplateRenderer::render(content, "codex", &parameters, &values).unwrap();
+        let parsed: toml::Table = rendered.parse().unwrap();
+        let args = parsed["args"].as_array().unwrap();
+        assert_eq!(args[1].as_str(), Some("secret\"key"));
+        assert_eq!(args[3].as_str(), Some("/work"));
+        assert_eq!(args[5].as_str(), Some("zh"));
+    }
+
+    #[test]
+    fn test_render_reports_missing_values() {
+        let parameters = vec![parameter("TOKEN", true, true, None)];
+        let err = TemplateRenderer::render(
+            r#"{"env": {"TOKEN": "${TOKEN}", "DIR": "${workspace_dir}"}}"#,
+            "claude",
+            &parameters,
+            &HashMap::new(),
+        )
+        .unwrap_err();
+
+        assert!(err.contains("TOKEN"));
+        assert!(err.contains("workspace_dir"));
+    }
+
+    #[test]
+    fn test_escaped_placeholder_and_check() {
+        assert_eq!(
+            TemplateRenderer::placeholders("cost $${HOME} ${A} ${A}").unwrap(),
+            vec!["A".to_string()]
+        );
+        assert!(TemplateRenderer::placeholders("${broken").is_err());
+
+        let (errors, warnings) = TemplateRenderer::check(
+            r#"{"key": "${KEY}", "other": "${UNDECLARED}"}"#,
+            &[
+                parameter("KEY", true, true, Some("leaked")),
+                parameter("UNUSED", false, false, None),
+            ],
+        );
+        assert_eq!(errors.len(), 1);
+        assert_eq!(warnings.len(), 2);
+    }
+}
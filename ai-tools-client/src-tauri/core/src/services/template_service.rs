@@ -0,0 +1,620 @@
+use crate::models::mcp_template::{
+    compare_versions, CreateMcpTemplateRequest, McpTemplate, McpTemplateConversion,
+    McpTemplatePage, McpTemplateQuery, McpTemplateRevision, McpTemplateRevisionDiff,
+    McpTemplateTools, McpTemplateValue, McpTemplateValueView, UpdateMcpTemplateRequest,
+    TEMPLATE_SORT_OPTIONS,
+};
+use crate::models::{ApiResponse, AppError, ResultExt};
+use crate::services::config_writer::ConfigPaths;
+use crate::services::mcp_command::McpCommandLine;
+use crate::services::mcp_probe::{
+    McpProbe, McpProbeResult, DEFAULT_HANDSHAKE_TIMEOUT_MS, MAX_HANDSHAKE_TIMEOUT_MS,
+};
+use crate::services::platform::Platform;
+use crate::services::secret_store::SecretCipher;
+use crate::services::template_converter::{ConversionResult, TemplateConverter};
+use crate::t;
+use sqlx::SqlitePool;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// MCP模板的编辑、参数、渲染、转换和握手测试，桌面应用和命令行工具共用。
+/// 只读取一条记录的查询直接使用模型
+#[derive(Clone)]
+pub struct TemplateService {
+    pool: SqlitePool,
+    data_dir: PathBuf,
+    paths: Option<ConfigPaths>,
+}
+
+impl TemplateService {
+    /// data_dir 是保存模板密钥文件的应用数据目录
+    pub fn new(pool: SqlitePool, data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            pool,
+            data_dir: data_dir.into(),
+            paths: None,
+        }
+    }
+
+    /// 使用指定配置文件位置中的主目录渲染 ${home_dir}，默认使用当前用户的主目录
+    pub fn with_config_paths(mut self, paths: ConfigPaths) -> Self {
+        self.paths = Some(paths);
+        self
+    }
+
+    fn home_dir(&self) -> Option<&Path> {
+        self.paths.as_ref().map(|paths| paths.home.as_path())
+    }
+
+    fn cipher(&self) -> Result<SecretCipher, AppError> {
+        SecretCipher::for_data_dir(&self.data_dir).context(t!("template.secret_key_load_failed"))
+    }
+
+    async fn find(&self, template_id: i64) -> Result<Option<McpTemplate>, AppError> {
+        McpTemplate::get_by_id(&self.pool, template_id)
+            .await
+            .context(t!("template.query_failed"))
+    }
+
+    pub async fn search(
+        &self,
+        query: McpTemplateQuery,
+    ) -> Result<ApiResponse<McpTemplatePage>, AppError> {
+        if let Some(sort_by) = &query.sort_by {
+            if !TEMPLATE_SORT_OPTIONS.contains(&sort_by.as_str()) {
+                return Ok(AppError::validation(t!(
+                    "template.unsupported_sort",
+                    sort_by = sort_by,
+                    options = TEMPLATE_SORT_OPTIONS.join(", ")
+                ))
+                .into());
+            }
+        }
+
+        let page = McpTemplate::search(&self.pool, &query)
+            .await
+            .context(t!("template.load_failed"))?;
+
+        Ok(ApiResponse::success(page))
+    }
+
+    /// 验证并创建模板
+    pub async fn create(
+        &self,
+        request: CreateMcpTemplateRequest,
+    ) -> Result<ApiResponse<McpTemplate>, AppError> {
+        let template = McpTemplate::from_request(&request);
+
+        if let Some(e) = template.validate_config().to_error() {
+            return Ok(e.context(t!("template.validation_failed")).into());
+        }
+
+        let created = McpTemplate::create(&self.pool, request)
+            .await
+            .context(t!("template.create_failed"))?;
+
+        Ok(ApiResponse::success(created))
+    }
+
+    /// 更新模板，内置模板只读；未指定版本号时按改动自动升级
+    pub async fn update(
+        &self,
+        mut request: UpdateMcpTemplateRequest,
+    ) -> Result<ApiResponse<Option<McpTemplate>>, AppError> {
+        let existing = match self.find(request.id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        if existing.is_builtin() {
+            return Ok(AppError::precondition(t!("template.builtin_readonly")).into());
+        }
+
+        // 未指定版本号（或原样提交当前版本号）时按改动自动升级，手动指定的版本号不能低于当前版本
+        match request
+            .version
+            .as_deref()
+            .map(|version| compare_versions(version, &existing.version))
+        {
+            Some(Ordering::Equal) => request.version = None,
+            Some(Ordering::Less) => {
+                return Ok(AppError::validation(t!(
+                    "template.version_too_low",
+                    version = existing.version
+                ))
+                .into())
+            }
+            _ => {}
+        }
+
+        // 用更新后的模板进行验证
+        if let Some(e) = existing.apply_update(&request).validate_config().to_error() {
+            return Ok(e.context(t!("template.validation_failed")).into());
+        }
+
+        let updated = McpTemplate::update(&self.pool, request)
+            .await
+            .context(t!("template.update_failed"))?;
+
+        Ok(ApiResponse::success(updated))
+    }
+
+    /// 删除模板，内置模板不能删除
+    pub async fn delete(&self, template_id: i64) -> Result<ApiResponse<bool>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        if template.is_builtin() {
+            return Ok(AppError::precondition(t!("template.builtin_undeletable")).into());
+        }
+
+        let deleted = McpTemplate::delete(&self.pool, template_id)
+            .await
+            .context(t!("template.delete_failed"))?;
+
+        if deleted {
+            Ok(ApiResponse::success(true))
+        } else {
+            Ok(AppError::internal(t!("template.delete_no_rows")).into())
+        }
+    }
+
+    /// 以新名称复制模板，新名称不能与已有模板重复
+    pub async fn clone_template(
+        &self,
+        template_id: i64,
+        new_name: &str,
+    ) -> Result<ApiResponse<Option<McpTemplate>>, AppError> {
+        if self.find(template_id).await?.is_none() {
+            return Ok(AppError::not_found(t!("resource.source_template")).into());
+        }
+
+        let existing_with_new_name = sqlx::query("SELECT id FROM mcp_templates WHERE name = ?")
+            .bind(new_name)
+            .fetch_optional(&self.pool)
+            .await
+            .context(t!("template.name_check_failed"))?;
+
+        if existing_with_new_name.is_some() {
+            return Ok(AppError::conflict(t!("template.name_taken")).into());
+        }
+
+        let cloned = McpTemplate::clone_template(&self.pool, template_id, new_name)
+            .await
+            .context(t!("template.clone_failed"))?;
+
+        Ok(ApiResponse::success(cloned))
+    }
+
+    /// 按内置/自定义、AI类型和平台统计模板数量
+    pub async fn stats(&self) -> Result<serde_json::Value, AppError> {
+        let pool = &self.pool;
+
+        let total_count =
+            sqlx::query_scalar::<_, Option<i64>>("SELECT COUNT(*) FROM mcp_templates")
+                .fetch_one(pool)
+                .await
+                .context(t!("template.stats_total_failed"))?
+                .unwrap_or(0);
+
+        let builtin_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE is_builtin = 1",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_builtin_failed"))?
+        .unwrap_or(0);
+
+        let custom_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE is_builtin = 0",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_custom_failed"))?
+        .unwrap_or(0);
+
+        let claude_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE ai_type = 'claude'",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_claude_failed"))?
+        .unwrap_or(0);
+
+        let codex_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE ai_type = 'codex'",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_codex_failed"))?
+        .unwrap_or(0);
+
+        let unix_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE platform_type = 'unix'",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_unix_failed"))?
+        .unwrap_or(0);
+
+        let windows_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE platform_type = 'windows'",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_windows_failed"))?
+        .unwrap_or(0);
+
+        let any_platform_count = sqlx::query_scalar::<_, Option<i64>>(
+            "SELECT COUNT(*) FROM mcp_templates WHERE platform_type = 'any'",
+        )
+        .fetch_one(pool)
+        .await
+        .context(t!("template.stats_any_failed"))?
+        .unwrap_or(0);
+
+        Ok(serde_json::json!({
+            "total": total_count,
+            "builtin": builtin_count,
+            "custom": custom_count,
+            "claude": claude_count,
+            "codex": codex_count,
+            "unix": unix_count,
+            "windows": windows_count,
+            "any": any_platform_count
+        }))
+    }
+
+    /// 保存模板参数值，密钥参数加密保存；返回的视图中密钥参数不包含明文
+    pub async fn set_values(
+        &self,
+        template_id: i64,
+        values: HashMap<String, String>,
+    ) -> Result<ApiResponse<Vec<McpTemplateValueView>>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        let parameters = match template.get_parameters() {
+            Ok(parameters) => parameters,
+            Err(e) => return Ok(AppError::internal(e).into()),
+        };
+
+        let cipher = self.cipher()?;
+        if let Err(e) =
+            McpTemplateValue::set_values(&self.pool, &cipher, template_id, &parameters, values)
+                .await
+        {
+            return Ok(e.into());
+        }
+
+        self.values(template_id).await
+    }
+
+    pub async fn values(
+        &self,
+        template_id: i64,
+    ) -> Result<ApiResponse<Vec<McpTemplateValueView>>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        let parameters = match template.get_parameters() {
+            Ok(parameters) => parameters,
+            Err(e) => return Ok(AppError::internal(e).into()),
+        };
+
+        let rows = McpTemplateValue::get_by_template(&self.pool, template_id)
+            .await
+            .context(t!("template.values_load_failed"))?;
+
+        Ok(ApiResponse::success(McpTemplateValue::to_views(
+            &rows,
+            &parameters,
+        )))
+    }
+
+    /// 渲染模板配置，values 中的值优先于已保存的值，且不会被保存；
+    /// platform_type 默认为当前平台
+    pub async fn render(
+        &self,
+        template_id: i64,
+        values: HashMap<String, String>,
+        platform_type: Option<String>,
+    ) -> Result<ApiResponse<String>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        let platform_type = platform_type.unwrap_or_else(Platform::get_platform_type);
+        let cipher = self.cipher()?;
+        match template
+            .render(&self.pool, &cipher, &platform_type, self.home_dir(), values)
+            .await
+        {
+            Ok(rendered) => Ok(ApiResponse::success(rendered)),
+            Err(e) => Ok(AppError::validation(e)
+                .context(t!("template.render_failed"))
+                .into()),
+        }
+    }
+
+    /// 为模板生成另一种AI类型的对应模板，名称、版本、平台和参数保持不变
+    pub async fn create_counterpart(
+        &self,
+        template_id: i64,
+    ) -> Result<ApiResponse<McpTemplateConversion>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        let target_ai_type = match template.ai_type.as_str() {
+            "claude" => "codex",
+            "codex" => "claude",
+            other => {
+                return Ok(AppError::validation(t!(
+                    "template.unsupported_ai_type",
+                    ai_type = other
+                ))
+                .into())
+            }
+        };
+
+        let conversion = match TemplateConverter::convert(
+            &template.config_content,
+            &template.ai_type,
+            target_ai_type,
+            &template.name,
+        ) {
+            Ok(conversion) => conversion,
+            Err(e) => {
+                return Ok(AppError::validation(e.to_string())
+                    .context(t!("template.convert_failed"))
+                    .into())
+            }
+        };
+
+        let existing = sqlx::query(
+            "SELECT id FROM mcp_templates WHERE name = ? AND version = ? AND ai_type = ? AND platform_type = ?",
+        )
+        .bind(&template.name)
+        .bind(&template.version)
+        .bind(target_ai_type)
+        .bind(&template.platform_type)
+        .fetch_optional(&self.pool)
+        .await
+        .context(t!("template.name_check_failed"))?;
+
+        if existing.is_some() {
+            return Ok(AppError::conflict(t!(
+                "template.counterpart_exists",
+                ai_type = target_ai_type,
+                name = template.name,
+                version = template.version
+            ))
+            .into());
+        }
+
+        let mut warnings = conversion.warnings;
+
+        // 平台覆盖配置一并转换
+        let mut platform_overrides = HashMap::new();
+        for (platform, content) in template.get_platform_overrides().unwrap_or_default() {
+            match TemplateConverter::convert(
+                &content,
+                &template.ai_type,
+                target_ai_type,
+                &template.name,
+            ) {
+                Ok(converted) => {
+                    warnings.extend(converted.warnings);
+                    platform_overrides.insert(platform, converted.content);
+                }
+                Err(e) => warnings.push(t!(
+                    "template.override_convert_skipped",
+                    platform = platform,
+                    error = e
+                )),
+            }
+        }
+
+        let request = CreateMcpTemplateRequest {
+            name: template.name.clone(),
+            version: Some(template.version.clone()),
+            ai_type: target_ai_type.to_string(),
+            platform_type: template.platform_type.clone(),
+            config_content: conversion.content,
+            description: template.description.clone(),
+            category: template.category.clone(),
+            tags: Some(template.get_tags()),
+            parameters: template.get_parameters().ok(),
+            platform_overrides: if platform_overrides.is_empty() {
+                None
+            } else {
+                Some(platform_overrides)
+            },
+        };
+
+        let created = McpTemplate::create(&self.pool, request)
+            .await
+            .context(t!("template.counterpart_create_failed"))?;
+
+        warnings.extend(created.validate_config().warnings);
+
+        Ok(ApiResponse::success(McpTemplateConversion {
+            template: created,
+            warnings,
+        }))
+    }
+
+    /// 把模板导出为安装命令，cli 默认与模板的AI类型相同，指定 platform_type 时导出该平台的配置
+    pub async fn export_command(
+        &self,
+        template_id: i64,
+        cli: Option<String>,
+        platform_type: Option<String>,
+    ) -> Result<ApiResponse<ConversionResult>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        let content = match platform_type {
+            Some(platform_type) => match template.content_for_platform(&platform_type) {
+                Ok(content) => content,
+                Err(e) => {
+                    return Ok(AppError::validation(e)
+                        .context(t!("template.platform_content_failed"))
+                        .into())
+                }
+            },
+            None => template.config_content.clone(),
+        };
+        let cli = cli.unwrap_or_else(|| template.ai_type.clone());
+
+        match McpCommandLine::export(&content, &template.ai_type, &cli, &template.name) {
+            Ok(result) => Ok(ApiResponse::success(result)),
+            Err(e) => Ok(AppError::validation(e.to_string())
+                .context(t!("template.command_export_failed"))
+                .into()),
+        }
+    }
+
+    /// 启动或连接模板中的服务器，完成 MCP 握手并读取工具列表；成功时缓存发现的工具。
+    /// server_id 为空时测试第一个服务器，握手失败时返回的结果中 success 为 false
+    pub async fn test(
+        &self,
+        template_id: i64,
+        server_id: Option<String>,
+        values: HashMap<String, String>,
+        timeout_ms: Option<u64>,
+    ) -> Result<ApiResponse<McpProbeResult>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+
+        let cipher = self.cipher()?;
+        let rendered = match template
+            .render(
+                &self.pool,
+                &cipher,
+                &Platform::get_platform_type(),
+                self.home_dir(),
+                values,
+            )
+            .await
+        {
+            Ok(rendered) => rendered,
+            Err(e) => {
+                return Ok(AppError::validation(e)
+                    .context(t!("template.render_failed"))
+                    .into())
+            }
+        };
+
+        let servers =
+            match McpProbe::servers_from_config(&rendered, &template.ai_type, &template.name) {
+                Ok(servers) => servers,
+                Err(e) => {
+                    return Ok(AppError::validation(e.to_string())
+                        .context(t!("template.servers_read_failed"))
+                        .into())
+                }
+            };
+        let (id, server) = match server_id {
+            Some(server_id) => match servers.into_iter().find(|(id, _)| *id == server_id) {
+                Some(server) => server,
+                None => {
+                    return Ok(AppError::not_found_with_id(
+                        t!("resource.template_server"),
+                        server_id,
+                    )
+                    .into())
+                }
+            },
+            None => match servers.into_iter().next() {
+                Some(server) => server,
+                None => return Ok(AppError::precondition(t!("template.no_servers")).into()),
+            },
+        };
+
+        let timeout_ms = timeout_ms
+            .filter(|ms| *ms > 0)
+            .unwrap_or(DEFAULT_HANDSHAKE_TIMEOUT_MS)
+            .min(MAX_HANDSHAKE_TIMEOUT_MS);
+        let result = McpProbe::probe(&id, &server, Duration::from_millis(timeout_ms)).await;
+
+        if result.success {
+            McpTemplateTools::save(&self.pool, template_id, &result)
+                .await
+                .context(t!("template.tools_save_failed"))?;
+        }
+
+        Ok(ApiResponse::success(result))
+    }
+
+    /// 比较两个修订，to_revision 为空时与最新修订比较
+    pub async fn diff_revisions(
+        &self,
+        template_id: i64,
+        from_revision: i64,
+        to_revision: Option<i64>,
+    ) -> Result<ApiResponse<McpTemplateRevisionDiff>, AppError> {
+        let revisions = McpTemplateRevision::get_by_template(&self.pool, template_id)
+            .await
+            .context(t!("template.revisions_load_failed"))?;
+
+        let from = revisions.iter().find(|r| r.revision == from_revision);
+        let to = match to_revision {
+            Some(to_revision) => revisions.iter().find(|r| r.revision == to_revision),
+            None => revisions.first(),
+        };
+
+        match (from, to) {
+            (Some(from), Some(to)) => Ok(ApiResponse::success(from.diff(to))),
+            _ => Ok(AppError::not_found(t!("resource.revision")).into()),
+        }
+    }
+
+    /// 把模板恢复到指定修订，恢复后生成新的修订并升级版本号
+    pub async fn revert(
+        &self,
+        template_id: i64,
+        revision: i64,
+        note: Option<String>,
+    ) -> Result<ApiResponse<McpTemplate>, AppError> {
+        let template = match self.find(template_id).await? {
+            Some(template) => template,
+            None => return Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        };
+        if template.is_builtin() {
+            return Ok(AppError::precondition(t!("template.builtin_readonly")).into());
+        }
+
+        let revision = match McpTemplateRevision::get(&self.pool, template_id, revision)
+            .await
+            .context(t!("template.revision_query_failed"))?
+        {
+            Some(revision) => revision,
+            None => return Ok(AppError::not_found(t!("resource.revision")).into()),
+        };
+
+        match McpTemplate::revert_to(&self.pool, &revision, note)
+            .await
+            .context(t!("template.revert_failed"))?
+        {
+            Some(template) => Ok(ApiResponse::success(template)),
+            None => Ok(AppError::not_found(t!("resource.mcp_template")).into()),
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use serde::{Deserialize, Serialize};
+
+/// 超过该行数时不再计算最长公共子序列，直接按整体替换输出
+const MAX_DIFF_LINES: usize = 5000;
+
+/// 按行比较的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextDiff {
+    pub added: usize,
+    pub removed: usize,
+    /// unified diff 格式的文本，内容相同时为空
+    pub unified: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// 生成 unified diff，context 为每处改动前后保留的上下文行数
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> TextDiff {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&old_lines, &new_lines);
+
+    let added = ops.iter().filter(|(op, _, _)| *op == Op::Insert).count();
+    let removed = ops.iter().filter(|(op, _, _)| *op == Op::Delete).count();
+    if added == 0 && removed == 0 {
+        return TextDiff {
+            added,
+            removed,
+            unified: String::new(),
+        };
+    }
+
+    let mut unified = format!("--- {}\n+++ {}\n", old_label, new_label);
+
+    // 把相距不超过 2 * context 的改动合并到同一个区块
+    let changes: Vec<usize> = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _, _))| *op != Op::Equal)
+        .map(|(index, _)| index)
+        .collect();
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &index in &changes {
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let slice = &ops[start..end];
+        let old_start = slice
+            .iter()
+            .find(|(op, _, _)| *op != Op::Insert)
+            .map(|(_, old_index, _)| old_index + 1)
+            .unwrap_or_else(|| {
+                ops[..start]
+                    .iter()
+                    .filter(|(op, _, _)| *op != Op::Insert)
+                    .count()
+            });
+        let new_start = slice
+            .iter()
+            .find(|(op, _, _)| *op != Op::Delete)
+            .map(|(_, _, new_index)| new_index + 1)
+            .unwrap_or_else(|| {
+                ops[..start]
+                    .iter()
+                    .filter(|(op, _, _)| *op != Op::Delete)
+                    .count()
+            });
+        let old_count = slice.iter().filter(|(op, _, _)| *op != Op::Insert).count();
+        let new_count = slice.iter().filter(|(op, _, _)| *op != Op::Delete).count();
+
+        unified.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for (op, old_index, new_index) in slice {
+            let (prefix, line) = match op {
+                Op::Equal => (' ', old_lines[*old_index]),
+                Op::Delete => ('-', old_lines[*old_index]),
+                Op::Insert => ('+', new_lines[*new_index]),
+            };
+            unified.push(prefix);
+            unified.push_str(line);
+            unified.push('\n');
+        }
+    }
+
+    TextDiff {
+        added,
+        removed,
+        unified,
+    }
+}
+
+/// 基于最长公共子序列的编辑序列，元素为（操作, 旧行号, 新行号）
+fn diff_ops(old: &[&str], new: &[&str]) -> Vec<(Op, usize, usize)> {
+    // 去掉相同的首尾行，缩小需要比较的范围
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut ops: Vec<(Op, usize, usize)> = (0..prefix).map(|i| (Op::Equal, i, i)).collect();
+
+    if old_mid.len() > MAX_DIFF_LINES || new_mid.len() > MAX_DIFF_LINES {
+        ops.extend((0..old_mid.len()).map(|i| (Op::Delete, prefix + i, prefix)));
+        ops.extend((0..new_mid.len()).map(|j| (Op::Insert, prefix + old_mid.len(), prefix + j)));
+    } else {
+        let (n, m) = (old_mid.len(), new_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if old_mid[i] == new_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n || j < m {
+            if i < n && j < m && old_mid[i] == new_mid[j] {
+                ops.push((Op::Equal, prefix + i, prefix + j));
+                i += 1;
+                j += 1;
+            } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+                // 删除行排在新增行之前
+                ops.push((Op::Delete, prefix + i, prefix + j));
+                i += 1;
+            } else {
+                ops.push((Op::Insert, prefix + i, prefix + j));
+                j += 1;
+            }
+        }
+    }
+
+    let old_tail = old.len() - suffix;
+    let new_tail = new.len() - suffix;
+    ops.extend((0..suffix).map(|k| (Op::Equal, old_tail + k, new_tail + k)));
+    ops
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_with_context() {
+        let old = "{\n  \"memory\": {\n    \"command\": \"npx\",\n    \"args\": [\"-y\", \"server-memory\"]\n  }\n}";
+        let new = "{\n  \"memory\": {\n    \"command\": \"npx\",\n    \"args\": [\"-y\", \"server-memory@1.2.0\"],\n    \"timeout\": 30000\n  }\n}";
+
+        let diff = unified_diff(old, new, "r1", "r2", 1);
+        assert_eq!(diff.added, 2);
+        assert_eq!(diff.removed, 1);
+        assert_eq!(
+            diff.unified,
+            "--- r1\n+++ r2\n@@ -3,3 +3,4 @@\n     \"command\": \"npx\",\n-    \"args\": [\"-y\", \"server-memory\"]\n+    \"args\": [\"-y\", \"server-memory@1.2.0\"],\n+    \"timeout\": 30000\n   }\n"
+        );
+
+        let same = unified_diff(old, old, "r1", "r1", 3);
+        assert!(same.unified.is_empty());
+
+        // 相距较远的改动分成两个区块
+        let diff = unified_diff(
+            "a\nb\nc\nd\ne\nf\ng",
+            "A\nb\nc\nd\ne\nf\nG",
+            "old",
+            "new",
+            1,
+        );
+        assert_eq!(diff.unified.matches("@@ -").count(), 2);
+    }
+}
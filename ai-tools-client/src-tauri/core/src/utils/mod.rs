@@ -1,4 +1,2 @@
 // 工具模块声明
 pub mod logger;
-pub mod validator;
-pub mod file_utils;
\ No newline at end of file
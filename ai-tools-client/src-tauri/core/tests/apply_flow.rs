@@ -0,0 +1,174 @@
+// MCP模板从创建、填写参数到写入用户配置的完整流程，配置文件写入临时主目录
+
+mod common;
+
+use ai_tools_core::models::mcp_template::{
+    CreateMcpTemplateRequest, McpTemplate, TemplateParameter,
+};
+use ai_tools_core::models::ErrorCode;
+use common::{expect_data, read_json, write_file, TestEnv};
+use std::collections::HashMap;
+use std::fs;
+
+fn template_request(ai_type: &str, config_content: &str) -> CreateMcpTemplateRequest {
+    CreateMcpTemplateRequest {
+        name: "Fetch".to_string(),
+        version: None,
+        ai_type: ai_type.to_string(),
+        platform_type: "any".to_string(),
+        config_content: config_content.to_string(),
+        description: None,
+        category: None,
+        tags: None,
+        parameters: None,
+        platform_overrides: None,
+    }
+}
+
+#[tokio::test]
+async fn test_apply_claude_template_merges_user_config_and_restores_backup() {
+    let env = TestEnv::new().await;
+    let original = r#"{"numStartups":3,"mcpServers":{"old":{"command":"old"}}}"#;
+    write_file(&env.paths.claude_user, original);
+
+    let template = expect_data(
+        env.core
+            .templates()
+            .create(template_request(
+                "claude",
+                r#"{"fetch":{"command":"uvx","args":["mcp-server-fetch","--root","${home_dir}"]}}"#,
+            ))
+            .await
+            .unwrap(),
+    );
+    let template_id = template.id.unwrap();
+
+    let cipher = env.core.secret_cipher().unwrap();
+    let result = expect_data(
+        env.core
+            .config()
+            .apply_template(template_id, &cipher, "unix", HashMap::new())
+            .await
+            .unwrap(),
+    );
+    assert_eq!(result.server_ids, vec!["fetch".to_string()]);
+    assert_eq!(
+        result.config_path,
+        env.paths.claude_user.to_string_lossy().to_string()
+    );
+
+    let config = read_json(&env.paths.claude_user);
+    assert_eq!(config["numStartups"], 3);
+    assert_eq!(config["mcpServers"]["old"]["command"], "old");
+    assert_eq!(config["mcpServers"]["fetch"]["command"], "uvx");
+    // ${home_dir} 渲染为临时主目录，而不是当前用户的主目录
+    assert_eq!(
+        config["mcpServers"]["fetch"]["args"][2],
+        env.paths.home.to_string_lossy().as_ref()
+    );
+
+    let template = McpTemplate::get_by_id(env.core.pool(), template_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(template.usage_count, Some(1));
+
+    // 写入前的备份可以恢复
+    let backup_id = result.backup_id.expect("写入前应备份配置文件");
+    expect_data(env.core.config().restore_backup(backup_id).await.unwrap());
+    assert_eq!(
+        fs::read_to_string(&env.paths.claude_user).unwrap(),
+        original
+    );
+}
+
+#[tokio::test]
+async fn test_apply_codex_template_renders_saved_secret() {
+    let env = TestEnv::new().await;
+    let templates = env.core.templates();
+
+    let mut request = template_request(
+        "codex",
+        "[mcp_servers.exa]\ncommand = \"npx\"\nargs = [\"-y\", \"exa-mcp\", \"--key\", \"${EXA_API_KEY}\"]\n",
+    );
+    request.parameters = Some(vec![TemplateParameter {
+        name: "EXA_API_KEY".to_string(),
+        description: None,
+        required: true,
+        secret: true,
+        default: None,
+    }]);
+    let template_id = expect_data(templates.create(request).await.unwrap())
+        .id
+        .unwrap();
+
+    let cipher = env.core.secret_cipher().unwrap();
+
+    // 缺少必填参数时拒绝写入，配置文件保持不存在
+    let response = env
+        .core
+        .config()
+        .apply_template(template_id, &cipher, "unix", HashMap::new())
+        .await
+        .unwrap();
+    assert_eq!(response.code, Some(ErrorCode::ValidationFailed));
+    assert!(!env.paths.codex_config.exists());
+
+    expect_data(
+        templates
+            .set_values(
+                template_id,
+                HashMap::from([("EXA_API_KEY".to_string(), "sk-exa".to_string())]),
+            )
+            .await
+            .unwrap(),
+    );
+
+    let result = expect_data(
+        env.core
+            .config()
+            .apply_template(template_id, &cipher, "unix", HashMap::new())
+            .await
+            .unwrap(),
+    );
+    assert_eq!(result.server_ids, vec!["exa".to_string()]);
+
+    let config: toml::Table = fs::read_to_string(&env.paths.codex_config)
+        .unwrap()
+        .parse()
+        .unwrap();
+    let args = config["mcp_servers"]["exa"]["args"].as_array().unwrap();
+    assert_eq!(args.last().and_then(|arg| arg.as_str()), Some("sk-exa"));
+
+    // 密钥只以密文保存在数据库中，重新打开核心后仍可解密
+    let reopened = ai_tools_core::AppCore::new(env.core.pool().clone(), env.core.data_dir());
+    let rendered = expect_data(
+        reopened
+            .templates()
+            .render(template_id, HashMap::new(), Some("unix".to_string()))
+            .await
+            .unwrap(),
+    );
+    assert!(rendered.contains("sk-exa"));
+}
+
+#[tokio::test]
+async fn test_builtin_templates_are_read_only() {
+    let env = TestEnv::new().await;
+    env.core.prepare().await;
+
+    let builtin = McpTemplate::get_all(env.core.pool())
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|template| template.is_builtin())
+        .expect("准备时应同步内置模板");
+
+    let response = env
+        .core
+        .templates()
+        .delete(builtin.id.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.code, Some(ErrorCode::PreconditionFailed));
+}
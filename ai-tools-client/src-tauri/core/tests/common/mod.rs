@@ -0,0 +1,68 @@
+// 集成测试的公共环境：临时数据目录和临时主目录，不读写当前用户的配置
+#![allow(dead_code)]
+
+use ai_tools_core::models::supplier::{CreateSupplierRequest, Supplier};
+use ai_tools_core::models::ApiResponse;
+use ai_tools_core::services::config_writer::ConfigPaths;
+use ai_tools_core::AppCore;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+pub struct TestEnv {
+    pub core: AppCore,
+    pub paths: ConfigPaths,
+    _dir: TempDir,
+}
+
+impl TestEnv {
+    pub async fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = ConfigPaths::from_home(&dir.path().join("home"));
+        let core = AppCore::open(&dir.path().join("data"))
+            .await
+            .unwrap()
+            .with_config_paths(paths.clone());
+
+        Self {
+            core,
+            paths,
+            _dir: dir,
+        }
+    }
+
+    pub async fn create_supplier(&self, supplier_type: &str, name: &str) -> Supplier {
+        self.core
+            .suppliers()
+            .create(CreateSupplierRequest {
+                r#type: supplier_type.to_string(),
+                name: name.to_string(),
+                base_url: format!("https://{}.example.com", name),
+                auth_token: format!("sk-{}", name),
+                timeout_ms: None,
+                auto_update: None,
+                opus_model: None,
+                sonnet_model: None,
+                haiku_model: None,
+            })
+            .await
+            .unwrap()
+            .data
+            .unwrap()
+    }
+}
+
+/// 取出成功响应的数据，失败时带上响应中的消息
+pub fn expect_data<T>(response: ApiResponse<T>) -> T {
+    assert!(response.success, "请求失败: {:?}", response.message);
+    response.data.unwrap()
+}
+
+pub fn write_file(path: &Path, content: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+pub fn read_json(path: &Path) -> serde_json::Value {
+    serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+}
@@ -0,0 +1,406 @@
+// 供应商和工作模式的完整切换流程，配置文件写入临时主目录
+
+mod common;
+
+use ai_tools_core::models::config::ConfigHistory;
+use ai_tools_core::models::hook::{CreateSwitchHookRequest, SwitchHook, SwitchRecord};
+use ai_tools_core::models::mcp_template::{CreateMcpTemplateRequest, TemplateParameter};
+use ai_tools_core::models::mode::WorkModeSwitchRequest;
+use ai_tools_core::models::supplier::Supplier;
+use ai_tools_core::models::ErrorCode;
+use common::{expect_data, read_json, write_file, TestEnv};
+use std::fs;
+
+#[tokio::test]
+async fn test_supplier_switch_writes_settings_and_restores_backup() {
+    let env = TestEnv::new().await;
+    let pool = env.core.pool();
+    let original = r#"{"theme":"dark","env":{"ANTHROPIC_BASE_URL":"https://old.example.com"}}"#;
+    write_file(&env.paths.claude_settings, original);
+
+    let first = env.create_supplier("claude", "first").await;
+    let second = env.create_supplier("claude", "second").await;
+
+    // 写入前备份原配置，其他配置项保持不变
+    let result = expect_data(
+        env.core
+            .suppliers()
+            .switch_to(first.id.unwrap(), true, true)
+            .await
+            .unwrap(),
+    );
+    let backup_id = result.backup_id.expect("切换前应备份配置文件");
+
+    let settings = read_json(&env.paths.claude_settings);
+    assert_eq!(settings["theme"], "dark");
+    assert_eq!(
+        settings["env"]["ANTHROPIC_BASE_URL"],
+        "https://first.example.com"
+    );
+    assert_eq!(settings["env"]["ANTHROPIC_AUTH_TOKEN"], "sk-first");
+
+    // 再切换时从当前激活的供应商切换过去
+    let result = expect_data(
+        env.core
+            .suppliers()
+            .switch_to(second.id.unwrap(), true, true)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(result.from_supplier_id, first.id.unwrap());
+    assert_eq!(
+        read_json(&env.paths.claude_settings)["env"]["ANTHROPIC_BASE_URL"],
+        "https://second.example.com"
+    );
+
+    let active = Supplier::get_active(pool, "claude").await.unwrap().unwrap();
+    assert_eq!(active.id, second.id);
+
+    let records = SwitchRecord::get_recent(pool, Some("supplier"), 10)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 2);
+    assert!(records.iter().all(|record| record.success != 0));
+
+    // 恢复第一次切换前的备份，文件回到原样并记录一条恢复历史
+    let restored = expect_data(env.core.config().restore_backup(backup_id).await.unwrap());
+    assert_eq!(restored.operation_type, "restore");
+    assert_eq!(
+        fs::read_to_string(&env.paths.claude_settings).unwrap(),
+        original
+    );
+}
+
+#[tokio::test]
+async fn test_codex_switch_writes_config_and_auth() {
+    let env = TestEnv::new().await;
+    write_file(&env.paths.codex_config, "model = \"gpt-5-codex\"\n");
+
+    let supplier = env.create_supplier("codex", "relay").await;
+    expect_data(
+        env.core
+            .suppliers()
+            .switch_to(supplier.id.unwrap(), true, false)
+            .await
+            .unwrap(),
+    );
+
+    let config: toml::Table = fs::read_to_string(&env.paths.codex_config)
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(config["model"].as_str(), Some("gpt-5-codex"));
+    assert_eq!(config["model_provider"].as_str(), Some("relay"));
+    assert_eq!(
+        config["model_providers"]["relay"]["base_url"].as_str(),
+        Some("https://relay.example.com")
+    );
+    assert!(env.paths.codex_auth.exists());
+
+    // 不要求备份时不产生备份记录
+    let backups = ConfigHistory::get_recent(env.core.pool(), 10)
+        .await
+        .unwrap();
+    assert!(backups.is_empty());
+}
+
+#[tokio::test]
+async fn test_failed_pre_hook_aborts_supplier_switch() {
+    let env = TestEnv::new().await;
+    let pool = env.core.pool();
+    let supplier = env.create_supplier("claude", "hooked").await;
+
+    SwitchHook::create(
+        pool,
+        CreateSwitchHookRequest {
+            scope_type: "supplier_type".to_string(),
+            scope_value: "claude".to_string(),
+            stage: "pre".to_string(),
+            name: "block".to_string(),
+            command: "exit 3".to_string(),
+            timeout_ms: None,
+            is_enabled: Some(true),
+            sort_order: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let response = env
+        .core
+        .suppliers()
+        .switch_to(supplier.id.unwrap(), true, true)
+        .await
+        .unwrap();
+    assert!(!response.success);
+    assert_eq!(response.code, Some(ErrorCode::PreconditionFailed));
+
+    // 配置文件没有写入，失败的切换有记录
+    assert!(!env.paths.claude_settings.exists());
+    let records = SwitchRecord::get_recent(pool, Some("supplier"), 10)
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].success, 0);
+}
+
+#[tokio::test]
+async fn test_work_mode_switch_status_and_rollback() {
+    let env = TestEnv::new().await;
+    let claude = env.create_supplier("claude", "claude-relay").await;
+    let codex = env.create_supplier("codex", "codex-relay").await;
+    let modes = env.core.modes();
+
+    let switch = |target_mode: &str| WorkModeSwitchRequest {
+        target_mode: target_mode.to_string(),
+        claude_supplier_id: claude.id,
+        codex_supplier_id: (target_mode == "claude_codex")
+            .then_some(codex.id)
+            .flatten(),
+        mcp_template_ids: None,
+        pinned_template_revisions: None,
+        create_backup: false,
+    };
+
+    expect_data(modes.switch(switch("claude_only")).await.unwrap());
+    expect_data(modes.switch(switch("claude_codex")).await.unwrap());
+
+    let status = modes.status().await.unwrap();
+    assert_eq!(status.current_mode, "claude_codex");
+    assert!(status.last_switch_time.is_some());
+    assert!(!status.is_transitioning);
+    assert_eq!(status.active_codex_supplier.as_deref(), Some("codex-relay"));
+    assert!(env.paths.codex_auth.exists());
+
+    // 回滚到上一次切换之前的模式，使用该模式保存的配置
+    let result = expect_data(modes.rollback().await.unwrap());
+    assert!(result.success);
+    assert_eq!(modes.status().await.unwrap().current_mode, "claude_only");
+
+    // 切换到不存在的供应商时拒绝
+    let mut request = switch("claude_only");
+    request.claude_supplier_id = Some(9999);
+    let response = modes.switch(request).await.unwrap();
+    assert_eq!(response.code, Some(ErrorCode::NotFound));
+}
+
+#[tokio::test]
+async fn test_work_mode_hooks_receive_backup_id() {
+    let env = TestEnv::new().await;
+    let pool = env.core.pool();
+    write_file(&env.paths.claude_settings, r#"{"theme":"dark"}"#);
+    let claude = env.create_supplier("claude", "claude-relay").await;
+
+    SwitchHook::create(
+        pool,
+        CreateSwitchHookRequest {
+            scope_type: "work_mode".to_string(),
+            scope_value: "claude_only".to_string(),
+            stage: "pre".to_string(),
+            name: "backup-id".to_string(),
+            command: "echo \"$AI_TOOLS_BACKUP_ID\"".to_string(),
+            timeout_ms: None,
+            is_enabled: Some(true),
+            sort_order: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let result = expect_data(
+        env.core
+            .modes()
+            .switch(WorkModeSwitchRequest {
+                target_mode: "claude_only".to_string(),
+                claude_supplier_id: claude.id,
+                codex_supplier_id: None,
+                mcp_template_ids: None,
+                pinned_template_revisions: None,
+                create_backup: true,
+            })
+            .await
+            .unwrap(),
+    );
+
+    // 切换前备份了模式涉及的配置文件，钩子收到的正是这条备份
+    let backup_id = result.backup_id.expect("切换前应备份配置文件");
+    assert_eq!(result.hook_results[0].stdout.trim(), backup_id.to_string());
+
+    let backup = ConfigHistory::get_by_id(pool, backup_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        backup.config_path,
+        env.paths.claude_settings.to_string_lossy().as_ref()
+    );
+    assert_eq!(backup.backup_content, r#"{"theme":"dark"}"#);
+
+    let records = SwitchRecord::get_recent(pool, Some("work_mode"), 10)
+        .await
+        .unwrap();
+    assert_eq!(records[0].backup_id, Some(backup_id));
+}
+
+fn claude_template(name: &str, config_content: &str) -> CreateMcpTemplateRequest {
+    CreateMcpTemplateRequest {
+        name: name.to_string(),
+        version: None,
+        ai_type: "claude".to_string(),
+        platform_type: "any".to_string(),
+        config_content: config_content.to_string(),
+        description: None,
+        category: None,
+        tags: None,
+        parameters: None,
+        platform_overrides: None,
+    }
+}
+
+#[tokio::test]
+async fn test_work_mode_switch_applies_configs_and_restores_on_failure() {
+    let env = TestEnv::new().await;
+    let pool = env.core.pool();
+    write_file(&env.paths.claude_settings, r#"{"theme":"dark"}"#);
+    let relay = env.create_supplier("claude", "claude-relay").await;
+    let other = env.create_supplier("claude", "other").await;
+    let templates = env.core.templates();
+    let fetch = expect_data(
+        templates
+            .create(claude_template(
+                "Fetch",
+                r#"{"fetch":{"command":"uvx","args":["mcp-server-fetch"]}}"#,
+            ))
+            .await
+            .unwrap(),
+    );
+
+    let result = expect_data(
+        env.core
+            .modes()
+            .switch(WorkModeSwitchRequest {
+                target_mode: "claude_only".to_string(),
+                claude_supplier_id: relay.id,
+                codex_supplier_id: None,
+                mcp_template_ids: Some(vec![fetch.id.unwrap()]),
+                pinned_template_revisions: None,
+                create_backup: false,
+            })
+            .await
+            .unwrap(),
+    );
+    assert!(result.success);
+
+    // 供应商配置和MCP服务器都写入了配置文件，模式的供应商被激活
+    let settings = read_json(&env.paths.claude_settings);
+    assert_eq!(settings["theme"], "dark");
+    assert_eq!(
+        settings["env"]["ANTHROPIC_BASE_URL"],
+        "https://claude-relay.example.com"
+    );
+    let user_config = read_json(&env.paths.claude_user);
+    assert_eq!(user_config["mcpServers"]["fetch"]["command"], "uvx");
+    let status = env.core.modes().status().await.unwrap();
+    assert_eq!(
+        status.active_claude_supplier.as_deref(),
+        Some("claude-relay")
+    );
+    assert_eq!(status.active_mcp_templates, vec!["Fetch".to_string()]);
+
+    // 模板缺少必填参数时写入失败，已写入的供应商配置和激活状态都恢复原样
+    let mut request = claude_template(
+        "Exa",
+        r#"{"exa":{"command":"npx","args":["exa-mcp","--key","${EXA_API_KEY}"]}}"#,
+    );
+    request.parameters = Some(vec![TemplateParameter {
+        name: "EXA_API_KEY".to_string(),
+        description: None,
+        required: true,
+        secret: true,
+        default: None,
+    }]);
+    let exa = expect_data(templates.create(request).await.unwrap());
+    let settings_before = fs::read_to_string(&env.paths.claude_settings).unwrap();
+    let user_config_before = fs::read_to_string(&env.paths.claude_user).unwrap();
+
+    let response = env
+        .core
+        .modes()
+        .switch(WorkModeSwitchRequest {
+            target_mode: "claude_exa".to_string(),
+            claude_supplier_id: other.id,
+            codex_supplier_id: None,
+            mcp_template_ids: Some(vec![exa.id.unwrap()]),
+            pinned_template_revisions: None,
+            create_backup: false,
+        })
+        .await
+        .unwrap();
+    assert!(!response.success);
+    assert_eq!(response.code, Some(ErrorCode::Internal));
+    assert_eq!(
+        fs::read_to_string(&env.paths.claude_settings).unwrap(),
+        settings_before
+    );
+    assert_eq!(
+        fs::read_to_string(&env.paths.claude_user).unwrap(),
+        user_config_before
+    );
+
+    let status = env.core.modes().status().await.unwrap();
+    assert_eq!(status.current_mode, "claude_only");
+    assert_eq!(
+        status.active_claude_supplier.as_deref(),
+        Some("claude-relay")
+    );
+    assert!(!status.is_transitioning);
+    let records = SwitchRecord::get_recent(pool, Some("work_mode"), 1)
+        .await
+        .unwrap();
+    assert_eq!(records[0].success, 0);
+}
+
+#[tokio::test]
+async fn test_work_mode_switch_in_progress_rejects_another_switch() {
+    let env = TestEnv::new().await;
+    let claude = env.create_supplier("claude", "claude-relay").await;
+    SwitchHook::create(
+        env.core.pool(),
+        CreateSwitchHookRequest {
+            scope_type: "work_mode".to_string(),
+            scope_value: "claude_only".to_string(),
+            stage: "pre".to_string(),
+            name: "slow".to_string(),
+            command: "sleep 1".to_string(),
+            timeout_ms: None,
+            is_enabled: Some(true),
+            sort_order: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let request = WorkModeSwitchRequest {
+        target_mode: "claude_only".to_string(),
+        claude_supplier_id: claude.id,
+        codex_supplier_id: None,
+        mcp_template_ids: None,
+        pinned_template_revisions: None,
+        create_backup: false,
+    };
+    let modes = env.core.modes();
+    let switching = modes.switch(request.clone());
+    let check = async {
+        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+        let status = env.core.modes().status().await.unwrap();
+        let response = env.core.modes().switch(request.clone()).await.unwrap();
+        (status, response)
+    };
+    let (result, (status, response)) = tokio::join!(switching, check);
+
+    // 切换期间状态显示正在切换，另一次切换被拒绝
+    assert!(status.is_transitioning);
+    assert_eq!(response.code, Some(ErrorCode::PreconditionFailed));
+    expect_data(result.unwrap());
+    assert!(!env.core.modes().status().await.unwrap().is_transitioning);
+}
@@ -1,13 +1,15 @@
 use crate::models::config::{
     AppState as ConfigAppState, ConfigHistory, UpdateWorkModeRequest, WorkModeConfig,
 };
-use crate::models::mcp_template::McpTemplate;
-use crate::models::supplier::Supplier;
-use crate::models::ApiResponse;
-use anyhow::Result;
-use std::sync::Arc;
+use crate::models::{ApiResponse, AppError, CommandResult, ResultExt};
+use crate::services::backup::{
+    DataExport, DataImportMode, DataImportResult, SnapshotInfo, SnapshotPolicy, SnapshotStore,
+};
+use crate::services::data_dir::{move_data_dir, DataDirResolver, MoveDataDirResult, StorageStatus};
+use crate::services::i18n::{set_current_locale, Locale};
+use crate::t;
+use crate::utils::logger::{self, LogEntry, LogLevel, LogSettings};
 use tauri::State;
-use tokio::sync::Mutex;
 
 // 使用相同的应用状态
 use crate::commands::supplier::AppState;
@@ -15,14 +17,15 @@ use crate::commands::supplier::AppState;
 // 配置相关命令
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn backup_config(
     state: State<'_, AppState>,
     config_type: String,
     config_path: String,
     content: String,
     description: Option<String>,
-) -> Result<ApiResponse<ConfigHistory>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<ConfigHistory> {
+    let pool = state.core.pool().clone();
 
     let backup = ConfigHistory::create(
         &pool,
@@ -33,99 +36,78 @@ pub async fn backup_config(
         description.as_deref(),
     )
     .await
-    .map_err(|e| format!("创建配置备份失败: {}", e))?;
+    .context(t!("config.backup_create_failed"))?;
 
     Ok(ApiResponse::success(backup))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_config_history(
     state: State<'_, AppState>,
     config_type: String,
     limit: Option<i64>,
-) -> Result<ApiResponse<Vec<ConfigHistory>>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<Vec<ConfigHistory>> {
+    let pool = state.core.pool().clone();
 
     let history = ConfigHistory::get_by_type(&pool, &config_type, limit)
         .await
-        .map_err(|e| format!("获取配置历史失败: {}", e))?;
+        .context(t!("config.history_load_failed"))?;
 
     Ok(ApiResponse::success(history))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_latest_config_backup(
     state: State<'_, AppState>,
     config_type: String,
-) -> Result<ApiResponse<Option<ConfigHistory>>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<Option<ConfigHistory>> {
+    let pool = state.core.pool().clone();
 
     let backup = ConfigHistory::get_latest(&pool, &config_type)
         .await
-        .map_err(|e| format!("获取最新配置备份失败: {}", e))?;
+        .context(t!("config.latest_backup_load_failed"))?;
 
     Ok(ApiResponse::success(backup))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn restore_config_from_backup(
     state: State<'_, AppState>,
     backup_id: i64,
-) -> Result<ApiResponse<ConfigHistory>, String> {
-    let pool = state.db_pool.lock().await;
-
-    // 获取备份记录
-    let backup = ConfigHistory::get_by_id(&pool, backup_id)
-        .await
-        .map_err(|e| format!("获取备份记录失败: {}", e))?;
-
-    if let Some(backup_record) = backup {
-        // 创建恢复历史记录
-        let restore_history = ConfigHistory::create(
-            &pool,
-            &backup_record.config_type,
-            &backup_record.config_path,
-            &backup_record.backup_content,
-            "restore",
-            Some(&format!("从备份ID {} 恢复", backup_id)),
-        )
-        .await
-        .map_err(|e| format!("创建恢复历史记录失败: {}", e))?;
-
-        // TODO: 实现实际的配置文件恢复逻辑
-        // 这里需要根据配置类型和路径来恢复文件
-
-        Ok(ApiResponse::success(restore_history))
-    } else {
-        Ok(ApiResponse::error("备份记录不存在".to_string()))
-    }
+) -> CommandResult<ConfigHistory> {
+    state.core.config().restore_backup(backup_id).await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn cleanup_old_config_history(
     state: State<'_, AppState>,
     config_type: String,
     keep_count: i64,
-) -> Result<ApiResponse<i64>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<i64> {
+    let pool = state.core.pool().clone();
 
     let deleted_count = ConfigHistory::cleanup_old(&pool, &config_type, keep_count)
         .await
-        .map_err(|e| format!("清理配置历史失败: {}", e))?;
+        .context(t!("config.history_cleanup_failed"))?;
 
     Ok(ApiResponse::success(deleted_count))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn delete_config_history(
     state: State<'_, AppState>,
     backup_id: i64,
-) -> Result<ApiResponse<bool>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<bool> {
+    let pool = state.core.pool().clone();
 
     let deleted = ConfigHistory::delete(&pool, backup_id)
         .await
-        .map_err(|e| format!("删除配置历史失败: {}", e))?;
+        .context(t!("config.history_delete_failed"))?;
 
     Ok(ApiResponse::success(deleted))
 }
@@ -133,41 +115,41 @@ pub async fn delete_config_history(
 // 工作模式相关命令
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_work_mode_config(
     state: State<'_, AppState>,
     mode_name: String,
-) -> Result<ApiResponse<Option<WorkModeConfig>>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<Option<WorkModeConfig>> {
+    let pool = state.core.pool().clone();
 
     let config = WorkModeConfig::get_by_mode(&pool, &mode_name)
         .await
-        .map_err(|e| format!("获取工作模式配置失败: {}", e))?;
+        .context(t!("mode.load_failed"))?;
 
     Ok(ApiResponse::success(config))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_all_work_mode_configs(
     state: State<'_, AppState>,
-) -> Result<ApiResponse<Vec<WorkModeConfig>>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<Vec<WorkModeConfig>> {
+    let pool = state.core.pool().clone();
 
     let configs = WorkModeConfig::get_all(&pool)
         .await
-        .map_err(|e| format!("获取所有工作模式配置失败: {}", e))?;
+        .context(t!("mode.list_failed"))?;
 
     Ok(ApiResponse::success(configs))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn update_work_mode_config(
     state: State<'_, AppState>,
     request: UpdateWorkModeRequest,
-) -> Result<ApiResponse<WorkModeConfig>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+) -> CommandResult<WorkModeConfig> {
+    let pool = state.core.pool().clone();
 
     // 验证工作模式名称
     let temp_config = WorkModeConfig {
@@ -184,17 +166,17 @@ pub async fn update_work_mode_config(
     };
 
     if let Err(e) = temp_config.validate() {
-        return Ok(ApiResponse::error(e));
+        return Ok(e.into());
     }
 
     let updated_config = WorkModeConfig::update(&pool, request.clone())
         .await
-        .map_err(|e| format!("更新工作模式配置失败: {}", e))?;
+        .context(t!("mode.update_failed"))?;
 
     // 同时更新应用状态中的当前模式
     ConfigAppState::set_current_mode(&pool, &request.mode_name)
         .await
-        .map_err(|e| format!("更新当前模式失败: {}", e))?;
+        .context(t!("mode.current_update_failed"))?;
 
     Ok(ApiResponse::success(updated_config))
 }
@@ -202,71 +184,69 @@ pub async fn update_work_mode_config(
 // 应用状态相关命令
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_app_state(
     state: State<'_, AppState>,
     key: String,
-) -> Result<ApiResponse<Option<ConfigAppState>>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<Option<ConfigAppState>> {
+    let pool = state.core.pool().clone();
 
     let state = ConfigAppState::get(&pool, &key)
         .await
-        .map_err(|e| format!("获取应用状态失败: {}", e))?;
+        .context(t!("config.app_state_load_failed"))?;
 
     Ok(ApiResponse::success(state))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn set_app_state(
     state: State<'_, AppState>,
     key: String,
     value: String,
-) -> Result<ApiResponse<ConfigAppState>, String> {
-    let pool = state.db_pool.lock().await;
+) -> CommandResult<ConfigAppState> {
+    let pool = state.core.pool().clone();
 
     let state = ConfigAppState::set(&pool, &key, &value)
         .await
-        .map_err(|e| format!("设置应用状态失败: {}", e))?;
+        .context(t!("config.app_state_save_failed"))?;
 
     Ok(ApiResponse::success(state))
 }
 
 #[tauri::command]
-pub async fn get_current_mode(state: State<'_, AppState>) -> Result<ApiResponse<String>, String> {
-    let pool = state.db_pool.lock().await;
+#[tracing::instrument(skip_all, err)]
+pub async fn get_current_mode(state: State<'_, AppState>) -> CommandResult<String> {
+    let pool = state.core.pool().clone();
 
     let current_mode = ConfigAppState::get_current_mode(&pool)
         .await
-        .map_err(|e| format!("获取当前模式失败: {}", e))?;
+        .context(t!("mode.current_load_failed"))?;
 
     Ok(ApiResponse::success(current_mode))
 }
 
 #[tauri::command]
-pub async fn get_all_app_states(
-    state: State<'_, AppState>,
-) -> Result<ApiResponse<Vec<ConfigAppState>>, String> {
-    let pool = state.db_pool.lock().await;
+#[tracing::instrument(skip_all, err)]
+pub async fn get_all_app_states(state: State<'_, AppState>) -> CommandResult<Vec<ConfigAppState>> {
+    let pool = state.core.pool().clone();
 
     let states = ConfigAppState::get_all(&pool)
         .await
-        .map_err(|e| format!("获取所有应用状态失败: {}", e))?;
+        .context(t!("config.app_states_load_failed"))?;
 
     Ok(ApiResponse::success(states))
 }
 
 #[tauri::command]
-pub async fn get_database_stats(
-    state: State<'_, AppState>,
-) -> Result<ApiResponse<serde_json::Value>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+#[tracing::instrument(skip_all, err)]
+pub async fn get_database_stats(state: State<'_, AppState>) -> CommandResult<serde_json::Value> {
+    let pool = state.core.pool().clone();
 
     // 获取数据库统计信息
     let stats = crate::services::database::Database::get_db_stats(&pool)
         .await
-        .map_err(|e| format!("获取数据库统计失败: {}", e))?;
+        .context(t!("database.stats_load_failed"))?;
 
     Ok(ApiResponse::success(
         serde_json::to_value(&stats).unwrap_or_default(),
@@ -274,54 +254,278 @@ pub async fn get_database_stats(
 }
 
 #[tauri::command]
-pub async fn test_database_connection(
-    state: State<'_, AppState>,
-) -> Result<ApiResponse<bool>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+#[tracing::instrument(skip_all, err)]
+pub async fn test_database_connection(state: State<'_, AppState>) -> CommandResult<bool> {
+    let pool = state.core.pool().clone();
 
     let result = crate::services::database::Database::test_db_connection(&pool)
         .await
-        .map_err(|e| format!("数据库连接测试失败: {}", e))?;
+        .context(t!("database.connection_test_failed"))?;
 
     Ok(ApiResponse::success(result))
 }
 
 #[tauri::command]
-pub async fn export_all_data(
+#[tracing::instrument(skip_all, err)]
+pub async fn get_schema_versions(
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<crate::services::database::SchemaVersion>> {
+    let pool = state.core.pool().clone();
+
+    let versions = crate::services::database::Database::get_schema_versions(&pool)
+        .await
+        .context(t!("database.schema_versions_load_failed"))?;
+
+    Ok(ApiResponse::success(versions))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn export_all_data(state: State<'_, AppState>) -> CommandResult<DataExport> {
+    let pool = state.core.pool().clone();
+
+    let export_data = DataExport::collect(&pool)
+        .await
+        .context(t!("backup.export_failed"))?;
+
+    Ok(ApiResponse::success(export_data))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn import_all_data(
     state: State<'_, AppState>,
-) -> Result<ApiResponse<serde_json::Value>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
+    data: serde_json::Value,
+    mode: Option<DataImportMode>,
+) -> CommandResult<DataImportResult> {
+    let export_data: DataExport = match serde_json::from_value(data) {
+        Ok(export_data) => export_data,
+        Err(e) => {
+            return Ok(AppError::validation(t!("backup.invalid_export_file", error = e)).into())
+        }
     };
+    if let Err(e) = export_data.verify() {
+        return Ok(AppError::validation(e.to_string()).into());
+    }
+
+    let pool = state.core.pool().clone();
 
-    // 获取所有数据
-    let suppliers = Supplier::get_all(&pool)
+    let result = export_data
+        .import(&pool, mode.unwrap_or_default())
         .await
-        .map_err(|e| format!("导出供应商数据失败: {}", e))?;
+        .context(t!("backup.import_failed"))?;
+
+    Ok(ApiResponse::success(result))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn create_database_snapshot(state: State<'_, AppState>) -> CommandResult<SnapshotInfo> {
+    let pool = state.core.pool().clone();
 
-    let templates = McpTemplate::get_all(&pool)
+    let store = SnapshotStore::new(state.core.data_dir());
+    let snapshot = store
+        .create(&pool)
         .await
-        .map_err(|e| format!("导出MCP模板数据失败: {}", e))?;
+        .context(t!("backup.snapshot_create_failed"))?;
 
-    let work_modes = WorkModeConfig::get_all(&pool)
+    let policy = SnapshotPolicy::load(&pool)
         .await
-        .map_err(|e| format!("导出工作模式数据失败: {}", e))?;
+        .context(t!("backup.policy_load_failed"))?;
+    store
+        .prune(policy.keep)
+        .context(t!("backup.snapshot_prune_failed"))?;
+
+    Ok(ApiResponse::success(snapshot))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn list_database_snapshots(
+    state: State<'_, AppState>,
+) -> CommandResult<Vec<SnapshotInfo>> {
+    let snapshots = SnapshotStore::new(state.core.data_dir())
+        .list()
+        .context(t!("backup.snapshots_load_failed"))?;
+
+    Ok(ApiResponse::success(snapshots))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_snapshot_policy(state: State<'_, AppState>) -> CommandResult<SnapshotPolicy> {
+    let pool = state.core.pool().clone();
 
-    let app_states = ConfigAppState::get_all(&pool)
+    let policy = SnapshotPolicy::load(&pool)
         .await
-        .map_err(|e| format!("导出应用状态数据失败: {}", e))?;
+        .context(t!("backup.policy_load_failed"))?;
 
-    let export_data = serde_json::json!({
-        "suppliers": suppliers,
-        "mcp_templates": templates,
-        "work_modes": work_modes,
-        "app_states": app_states,
-        "export_time": chrono::Utc::now().to_rfc3339()
-    });
+    Ok(ApiResponse::success(policy))
+}
 
-    Ok(ApiResponse::success(export_data))
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn save_snapshot_policy(
+    state: State<'_, AppState>,
+    policy: SnapshotPolicy,
+) -> CommandResult<SnapshotPolicy> {
+    if let Err(e) = policy.validate() {
+        return Ok(e.into());
+    }
+
+    let pool = state.core.pool().clone();
+
+    policy
+        .save(&pool)
+        .await
+        .context(t!("backup.policy_save_failed"))?;
+
+    Ok(ApiResponse::success(policy))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_storage_status(state: State<'_, AppState>) -> CommandResult<StorageStatus> {
+    let mut status = state.storage.clone();
+    // 迁移数据目录后连接池已关闭，提示用户重启
+    if let Some(pending) =
+        DataDirResolver::load_saved().filter(|saved| saved != state.core.data_dir())
+    {
+        status.set_pending(&pending.to_string_lossy(), state.core.pool().is_closed());
+    }
+
+    Ok(ApiResponse::success(status))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn move_data_directory(
+    state: State<'_, AppState>,
+    target_dir: String,
+) -> CommandResult<MoveDataDirResult> {
+    let target_dir = target_dir.trim();
+    if target_dir.is_empty() {
+        return Ok(AppError::validation(t!("storage.target_empty")).into());
+    }
+    let target = std::path::PathBuf::from(target_dir);
+    if !target.is_absolute() {
+        return Ok(AppError::validation(t!("storage.target_not_absolute")).into());
+    }
+
+    let pool = state.core.pool().clone();
+
+    let result = match move_data_dir(
+        &pool,
+        state.core.data_dir(),
+        &target,
+        DataDirResolver::save_location,
+    )
+    .await
+    .context(t!("storage.move_failed"))
+    {
+        Ok(result) => result,
+        Err(e) => return Ok(e.into()),
+    };
+
+    Ok(ApiResponse::success(result))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_locale(state: State<'_, AppState>) -> CommandResult<String> {
+    let pool = state.core.pool().clone();
+    let locale = Locale::load(&pool)
+        .await
+        .context(t!("locale.load_failed"))?;
+
+    Ok(ApiResponse::success(locale.as_str().to_string()))
+}
+
+/// 切换后端消息使用的语言，之后的响应消息和验证错误都使用新语言
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn set_locale(state: State<'_, AppState>, locale: String) -> CommandResult<String> {
+    let locale = match Locale::parse(&locale) {
+        Some(locale) => locale,
+        None => return Ok(AppError::validation(t!("locale.unsupported", locale = locale)).into()),
+    };
+
+    let pool = state.core.pool().clone();
+    locale.save(&pool).await.context(t!("locale.save_failed"))?;
+    set_current_locale(locale);
+
+    Ok(ApiResponse::success(locale.as_str().to_string()))
+}
+
+/// 默认返回的日志条数
+const DEFAULT_RECENT_LOG_LIMIT: usize = 200;
+
+/// 单次最多返回的日志条数
+const MAX_RECENT_LOG_LIMIT: usize = 2000;
+
+fn log_settings(state: &AppState) -> LogSettings {
+    LogSettings {
+        level: logger::current_level(),
+        env_override: logger::env_override(),
+        log_dir: logger::log_dir(state.core.data_dir())
+            .to_string_lossy()
+            .to_string(),
+        max_files: logger::MAX_LOG_FILES,
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_log_settings(state: State<'_, AppState>) -> CommandResult<LogSettings> {
+    Ok(ApiResponse::success(log_settings(&state)))
+}
+
+/// 调整日志级别，立即生效并在下次启动时沿用
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn set_log_level(
+    state: State<'_, AppState>,
+    level: String,
+) -> CommandResult<LogSettings> {
+    let level = match LogLevel::parse(&level) {
+        Some(level) => level,
+        None => return Ok(AppError::validation(t!("log.level_invalid", level = level)).into()),
+    };
+
+    let pool = state.core.pool().clone();
+    level
+        .save(&pool)
+        .await
+        .context(t!("log.level_save_failed"))?;
+    logger::set_level(level).context(t!("log.level_apply_failed"))?;
+
+    Ok(ApiResponse::success(log_settings(&state)))
+}
+
+/// 读取最近的日志，供日志查看页面使用
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_recent_logs(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+    min_level: Option<String>,
+) -> CommandResult<Vec<LogEntry>> {
+    let min_level = match min_level.as_deref().filter(|level| !level.is_empty()) {
+        Some(level) => match LogLevel::parse(level) {
+            Some(level) => Some(level),
+            None => return Ok(AppError::validation(t!("log.level_invalid", level = level)).into()),
+        },
+        None => None,
+    };
+    let limit = limit
+        .unwrap_or(DEFAULT_RECENT_LOG_LIMIT)
+        .clamp(1, MAX_RECENT_LOG_LIMIT);
+
+    let log_dir = logger::log_dir(state.core.data_dir());
+    let entries = tokio::task::spawn_blocking(move || logger::recent(&log_dir, limit, min_level))
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))?
+        .context(t!("log.read_failed"))?;
+
+    Ok(ApiResponse::success(entries))
 }
@@ -0,0 +1,112 @@
+use crate::models::hook::{
+    CreateSwitchHookRequest, SwitchHook, SwitchRecord, UpdateSwitchHookRequest,
+};
+use crate::models::{ApiResponse, AppError, CommandResult, ResultExt};
+use crate::t;
+use tauri::State;
+
+use crate::commands::supplier::AppState;
+
+// 切换钩子相关命令
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn list_switch_hooks(
+    state: State<'_, AppState>,
+    scope_type: Option<String>,
+    scope_value: Option<String>,
+) -> CommandResult<Vec<SwitchHook>> {
+    let pool = state.core.pool().clone();
+
+    let hooks = match (scope_type, scope_value) {
+        (Some(scope_type), Some(scope_value)) => {
+            SwitchHook::get_by_scope(&pool, &scope_type, &scope_value)
+                .await
+                .context(t!("hook.list_failed"))?
+        }
+        _ => SwitchHook::get_all(&pool)
+            .await
+            .context(t!("hook.list_failed"))?,
+    };
+
+    Ok(ApiResponse::success(hooks))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn create_switch_hook(
+    state: State<'_, AppState>,
+    request: CreateSwitchHookRequest,
+) -> CommandResult<SwitchHook> {
+    let pool = state.core.pool().clone();
+
+    if let Err(e) = SwitchHook::from_request(&request).validate() {
+        return Ok(e.into());
+    }
+
+    let created_hook = SwitchHook::create(&pool, request)
+        .await
+        .context(t!("hook.create_failed"))?;
+
+    Ok(ApiResponse::success(created_hook))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn update_switch_hook(
+    state: State<'_, AppState>,
+    request: UpdateSwitchHookRequest,
+) -> CommandResult<Option<SwitchHook>> {
+    let pool = state.core.pool().clone();
+
+    let existing_hook = match SwitchHook::get_by_id(&pool, request.id)
+        .await
+        .context(t!("hook.query_failed"))?
+    {
+        Some(hook) => hook,
+        None => return Ok(AppError::not_found(t!("resource.switch_hook")).into()),
+    };
+
+    // 用更新后的钩子进行验证
+    if let Err(e) = existing_hook.apply_update(&request).validate() {
+        return Ok(e.into());
+    }
+
+    let updated_hook = SwitchHook::update(&pool, request)
+        .await
+        .context(t!("hook.update_failed"))?;
+
+    Ok(ApiResponse::success(updated_hook))
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn delete_switch_hook(state: State<'_, AppState>, id: i64) -> CommandResult<bool> {
+    let pool = state.core.pool().clone();
+
+    let deleted = SwitchHook::delete(&pool, id)
+        .await
+        .context(t!("hook.delete_failed"))?;
+
+    if deleted {
+        Ok(ApiResponse::success(true))
+    } else {
+        Ok(AppError::not_found(t!("resource.switch_hook")).into())
+    }
+}
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_switch_history(
+    state: State<'_, AppState>,
+    switch_type: Option<String>,
+    limit: Option<i64>,
+) -> CommandResult<Vec<SwitchRecord>> {
+    let pool = state.core.pool().clone();
+
+    let records = SwitchRecord::get_recent(&pool, switch_type.as_deref(), limit.unwrap_or(50))
+        .await
+        .context(t!("switch.history_load_failed"))?;
+
+    Ok(ApiResponse::success(records))
+}
@@ -1,12 +1,19 @@
 use crate::models::mcp_template::{
-    CreateMcpTemplateRequest, McpTemplate, McpTemplateCategory, McpTemplateValidationResult,
-    UpdateMcpTemplateRequest,
+    CreateMcpTemplateRequest, McpTemplate, McpTemplateCategory, McpTemplateConversion,
+    McpTemplatePage, McpTemplateQuery, McpTemplateRevision, McpTemplateRevisionDiff,
+    McpTemplateTools, McpTemplateValidationResult, McpTemplateValueView, UpdateMcpTemplateRequest,
 };
-use crate::models::ApiResponse;
-use anyhow::Result;
-use std::sync::Arc;
+use crate::models::{
+    ApiResponse, AppError, CommandResult, ConflictStrategy, ImportReport, ResultExt,
+};
+use crate::services::config_importer::{ConfigImporter, ImportPaths, McpImportPreview};
+use crate::services::mcp_command::{McpCommandLine, McpCommandParseResult};
+use crate::services::mcp_probe::McpProbeResult;
+use crate::services::template_converter::{ConversionResult, TemplateConverter};
+use crate::t;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use tauri::State;
-use tokio::sync::Mutex;
 
 // 使用与供应商模块相同的应用状态
 use crate::commands::supplier::AppState;
@@ -14,471 +21,429 @@ use crate::commands::supplier::AppState;
 // MCP模板相关命令
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn list_mcp_templates(
     state: State<'_, AppState>,
-    ai_type: Option<String>,
-    platform_type: Option<String>,
-) -> Result<ApiResponse<Vec<McpTemplate>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
-
-    let templates = match (ai_type, platform_type) {
-        (Some(ai_type), Some(platform_type)) => {
-            McpTemplate::get_by_filters(&pool, &ai_type, &platform_type)
-                .await
-                .map_err(|e| format!("获取MCP模板失败: {}", e))?
-        }
-        (Some(ai_type), None) => McpTemplate::get_by_ai_type(&pool, &ai_type)
-            .await
-            .map_err(|e| format!("获取MCP模板失败: {}", e))?,
-        (None, Some(platform_type)) => McpTemplate::get_by_platform_type(&pool, &platform_type)
-            .await
-            .map_err(|e| format!("获取MCP模板失败: {}", e))?,
-        (None, None) => McpTemplate::get_all(&pool)
-            .await
-            .map_err(|e| format!("获取MCP模板失败: {}", e))?,
-    };
-
-    Ok(ApiResponse::success(templates))
+    query: Option<McpTemplateQuery>,
+) -> CommandResult<McpTemplatePage> {
+    state
+        .core
+        .templates()
+        .search(query.unwrap_or_default())
+        .await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn create_mcp_template(
     state: State<'_, AppState>,
     request: CreateMcpTemplateRequest,
-) -> Result<ApiResponse<McpTemplate>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
-
-    // 验证请求
-    let template = McpTemplate {
-        id: None,
-        name: request.name.clone(),
-        version: request
-            .version
-            .clone()
-            .unwrap_or_else(|| "1.0.0".to_string()),
-        ai_type: request.ai_type.clone(),
-        platform_type: request.platform_type.clone(),
-        config_content: request.config_content.clone(),
-        description: request.description.clone(),
-        is_builtin: Some(0),
-        category: request.category.clone(),
-        tags: request
-            .tags
-            .as_ref()
-            .map(|tags| serde_json::to_string(tags).unwrap_or_default()),
-        usage_count: Some(0),
-        created_at: None,
-        updated_at: None,
-    };
-
-    let validation_result = template.validate_config();
-    if !validation_result.valid {
-        return Ok(ApiResponse::error(format!(
-            "模板验证失败: {}",
-            validation_result.errors.join("; ")
-        )));
-    }
-
-    let created_template = McpTemplate::create(&pool, request)
-        .await
-        .map_err(|e| format!("创建MCP模板失败: {}", e))?;
-
-    Ok(ApiResponse::success(created_template))
+) -> CommandResult<McpTemplate> {
+    state.core.templates().create(request).await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn update_mcp_template(
     state: State<'_, AppState>,
     request: UpdateMcpTemplateRequest,
-) -> Result<ApiResponse<Option<McpTemplate>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
-
-    // 检查模板是否存在
-    let existing_template = McpTemplate::get_by_id(&pool, request.id)
-        .await
-        .map_err(|e| format!("查询MCP模板失败: {}", e))?;
-
-    let existing_template = match existing_template {
-        Some(template) => template,
-        None => return Ok(ApiResponse::error("MCP模板不存在".to_string())),
-    };
-
-    // 如果是内置模板，不允许修改
-    if existing_template.is_builtin() {
-        return Ok(ApiResponse::error("内置模板不允许修改".to_string()));
-    }
-
-    // 构建更新后的模板进行验证
-    let updated_template_for_validation = McpTemplate {
-        id: Some(request.id),
-        name: request
-            .name
-            .clone()
-            .unwrap_or_else(|| existing_template.name.clone()),
-        version: request
-            .version
-            .clone()
-            .unwrap_or_else(|| existing_template.version.clone()),
-        ai_type: existing_template.ai_type.clone(),
-        platform_type: existing_template.platform_type.clone(),
-        config_content: request
-            .config_content
-            .clone()
-            .unwrap_or_else(|| existing_template.config_content.clone()),
-        description: request
-            .description
-            .clone()
-            .or_else(|| existing_template.description.clone()),
-        is_builtin: existing_template.is_builtin,
-        category: request
-            .category
-            .clone()
-            .or_else(|| existing_template.category.clone()),
-        tags: request
-            .tags
-            .as_ref()
-            .map(|tags| serde_json::to_string(tags).unwrap_or_default())
-            .or_else(|| existing_template.tags.clone()),
-        usage_count: existing_template.usage_count,
-        created_at: existing_template.created_at,
-        updated_at: existing_template.updated_at,
-    };
-
-    let validation_result = updated_template_for_validation.validate_config();
-    if !validation_result.valid {
-        return Ok(ApiResponse::error(format!(
-            "模板验证失败: {}",
-            validation_result.errors.join("; ")
-        )));
-    }
-
-    let updated_template = McpTemplate::update(&pool, request)
-        .await
-        .map_err(|e| format!("更新MCP模板失败: {}", e))?;
-
-    Ok(ApiResponse::success(updated_template))
+) -> CommandResult<Option<McpTemplate>> {
+    state.core.templates().update(request).await
 }
 
 #[tauri::command]
-pub async fn delete_mcp_template(
-    state: State<'_, AppState>,
-    id: i64,
-) -> Result<ApiResponse<bool>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
-
-    // 检查模板是否存在
-    let existing_template = McpTemplate::get_by_id(&pool, id)
-        .await
-        .map_err(|e| format!("查询MCP模板失败: {}", e))?;
-
-    if let Some(template) = existing_template {
-        // 如果是内置模板，不允许删除
-        if template.is_builtin() {
-            return Ok(ApiResponse::error("内置模板不允许删除".to_string()));
-        }
-
-        let deleted = McpTemplate::delete(&pool, id)
-            .await
-            .map_err(|e| format!("删除MCP模板失败: {}", e))?;
-
-        if deleted {
-            Ok(ApiResponse::success(true))
-        } else {
-            Ok(ApiResponse::error("删除失败".to_string()))
-        }
-    } else {
-        Ok(ApiResponse::error("MCP模板不存在".to_string()))
-    }
+#[tracing::instrument(skip_all, err)]
+pub async fn delete_mcp_template(state: State<'_, AppState>, id: i64) -> CommandResult<bool> {
+    state.core.templates().delete(id).await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_mcp_template_by_id(
     state: State<'_, AppState>,
     id: i64,
-) -> Result<ApiResponse<Option<McpTemplate>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+) -> CommandResult<Option<McpTemplate>> {
+    let pool = state.core.pool().clone();
 
     let template = McpTemplate::get_by_id(&pool, id)
         .await
-        .map_err(|e| format!("获取MCP模板失败: {}", e))?;
+        .context(t!("template.load_failed"))?;
 
     Ok(ApiResponse::success(template))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn validate_mcp_template(
     state: State<'_, AppState>,
     request: CreateMcpTemplateRequest,
-) -> Result<ApiResponse<McpTemplateValidationResult>, String> {
-    let template = McpTemplate {
-        id: None,
-        name: request.name.clone(),
-        version: request
-            .version
-            .clone()
-            .unwrap_or_else(|| "1.0.0".to_string()),
-        ai_type: request.ai_type.clone(),
-        platform_type: request.platform_type.clone(),
-        config_content: request.config_content.clone(),
-        description: request.description.clone(),
-        is_builtin: Some(0),
-        category: request.category.clone(),
-        tags: request
-            .tags
-            .as_ref()
-            .map(|tags| serde_json::to_string(tags).unwrap_or_default()),
-        usage_count: Some(0),
-        created_at: None,
-        updated_at: None,
-    };
+) -> CommandResult<McpTemplateValidationResult> {
+    let template = McpTemplate::from_request(&request);
 
     let validation_result = template.validate_config();
     Ok(ApiResponse::success(validation_result))
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_mcp_template_categories(
     state: State<'_, AppState>,
-) -> Result<ApiResponse<Vec<McpTemplateCategory>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+) -> CommandResult<Vec<McpTemplateCategory>> {
+    let pool = state.core.pool().clone();
 
-    let categories = McpTemplate::get_categories(&pool)
+    let categories = McpTemplateCategory::get_all(&pool)
         .await
-        .map_err(|e| format!("获取MCP模板分类失败: {}", e))?;
+        .context(t!("template.categories_load_failed"))?;
 
     Ok(ApiResponse::success(categories))
 }
 
 #[tauri::command]
-pub async fn increment_template_usage(
+#[tracing::instrument(skip_all, err)]
+pub async fn save_mcp_template_category(
     state: State<'_, AppState>,
-    id: i64,
-) -> Result<ApiResponse<bool>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+    name: String,
+    description: Option<String>,
+    sort_order: Option<i64>,
+) -> CommandResult<Vec<McpTemplateCategory>> {
+    let pool = state.core.pool().clone();
+
+    let name = name.trim();
+    if name.is_empty() {
+        return Ok(AppError::validation(t!("template.category_name_empty")).into());
+    }
 
-    let incremented = McpTemplate::increment_usage_count(&pool, id)
+    McpTemplateCategory::save(&pool, name, description.as_deref(), sort_order)
         .await
-        .map_err(|e| format!("增加使用计数失败: {}", e))?;
+        .context(t!("template.category_save_failed"))?;
 
-    Ok(ApiResponse::success(incremented))
+    let categories = McpTemplateCategory::get_all(&pool)
+        .await
+        .context(t!("template.categories_load_failed"))?;
+
+    Ok(ApiResponse::success(categories))
 }
 
 #[tauri::command]
-pub async fn clone_mcp_template(
+#[tracing::instrument(skip_all, err)]
+pub async fn delete_mcp_template_category(
     state: State<'_, AppState>,
-    id: i64,
-    new_name: String,
-) -> Result<ApiResponse<Option<McpTemplate>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+    name: String,
+) -> CommandResult<bool> {
+    let pool = state.core.pool().clone();
 
-    // 检查原模板是否存在
-    let existing_template = McpTemplate::get_by_id(&pool, id)
+    let deleted = McpTemplateCategory::delete(&pool, &name)
         .await
-        .map_err(|e| format!("查询MCP模板失败: {}", e))?;
+        .context(t!("template.category_delete_failed"))?;
 
-    if existing_template.is_none() {
-        return Ok(ApiResponse::error("原模板不存在".to_string()));
+    if deleted {
+        Ok(ApiResponse::success(true))
+    } else {
+        Ok(AppError::precondition(t!("template.category_in_use", name = name)).into())
     }
+}
 
-    // 检查新名称是否已存在
-    let existing_with_new_name = sqlx::query("SELECT id FROM mcp_templates WHERE name = ?")
-        .bind(&new_name)
-        .fetch_optional(&pool)
-        .await
-        .map_err(|e| format!("检查模板名称失败: {}", e))?;
-
-    if existing_with_new_name.is_some() {
-        return Ok(ApiResponse::error("模板名称已存在".to_string()));
-    }
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn increment_template_usage(state: State<'_, AppState>, id: i64) -> CommandResult<bool> {
+    let pool = state.core.pool().clone();
 
-    let cloned_template = McpTemplate::clone_template(&pool, id, &new_name)
+    let incremented = McpTemplate::increment_usage_count(&pool, id)
         .await
-        .map_err(|e| format!("克隆模板失败: {}", e))?;
+        .context(t!("template.usage_increment_failed"))?;
+
+    Ok(ApiResponse::success(incremented))
+}
 
-    Ok(ApiResponse::success(cloned_template))
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn clone_mcp_template(
+    state: State<'_, AppState>,
+    id: i64,
+    new_name: String,
+) -> CommandResult<Option<McpTemplate>> {
+    state.core.templates().clone_template(id, &new_name).await
 }
 
 #[tauri::command]
+#[tracing::instrument(skip_all, err)]
 pub async fn get_mcp_template_stats(
     state: State<'_, AppState>,
-) -> Result<ApiResponse<serde_json::Value>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+) -> CommandResult<serde_json::Value> {
+    let stats = state.core.templates().stats().await?;
 
-    // 获取总模板数
-    let total_count = sqlx::query_scalar::<_, Option<i64>>("SELECT COUNT(*) FROM mcp_templates")
-        .fetch_one(&pool)
-        .await
-        .map_err(|e| format!("获取模板总数失败: {}", e))?
-        .unwrap_or(0);
+    Ok(ApiResponse::success(stats))
+}
 
-    // 获取内置模板数
-    let builtin_count = sqlx::query_scalar::<_, Option<i64>>(
-        "SELECT COUNT(*) FROM mcp_templates WHERE is_builtin = 1",
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn import_mcp_templates(
+    state: State<'_, AppState>,
+    templates: Vec<CreateMcpTemplateRequest>,
+    strategy: Option<ConflictStrategy>,
+    dry_run: Option<bool>,
+) -> CommandResult<ImportReport> {
+    let pool = state.core.pool().clone();
+
+    let report = McpTemplate::import(
+        &pool,
+        templates,
+        strategy.unwrap_or_default(),
+        dry_run.unwrap_or(false),
     )
-    .fetch_one(&pool)
     .await
-    .map_err(|e| format!("获取内置模板数失败: {}", e))?
-    .unwrap_or(0);
+    .context(t!("template.import_failed"))?;
 
-    // 获取自定义模板数
-    let custom_count = sqlx::query_scalar::<_, Option<i64>>(
-        "SELECT COUNT(*) FROM mcp_templates WHERE is_builtin = 0",
-    )
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("获取自定义模板数失败: {}", e))?
-    .unwrap_or(0);
+    Ok(report.into_response())
+}
 
-    // 获取Claude模板数
-    let claude_count = sqlx::query_scalar::<_, Option<i64>>(
-        "SELECT COUNT(*) FROM mcp_templates WHERE ai_type = 'claude'",
-    )
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("获取Claude模板数失败: {}", e))?
-    .unwrap_or(0);
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn export_mcp_templates(state: State<'_, AppState>) -> CommandResult<Vec<McpTemplate>> {
+    let pool = state.core.pool().clone();
 
-    // 获取Codex模板数
-    let codex_count = sqlx::query_scalar::<_, Option<i64>>(
-        "SELECT COUNT(*) FROM mcp_templates WHERE ai_type = 'codex'",
-    )
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("获取Codex模板数失败: {}", e))?
-    .unwrap_or(0);
+    let templates = McpTemplate::get_all(&pool)
+        .await
+        .context(t!("template.export_failed"))?;
 
-    // 获取Unix平台模板数
-    let unix_count = sqlx::query_scalar::<_, Option<i64>>(
-        "SELECT COUNT(*) FROM mcp_templates WHERE platform_type = 'unix'",
-    )
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("获取Unix模板数失败: {}", e))?
-    .unwrap_or(0);
+    Ok(ApiResponse::success(templates))
+}
 
-    // 获取Windows平台模板数
-    let windows_count = sqlx::query_scalar::<_, Option<i64>>(
-        "SELECT COUNT(*) FROM mcp_templates WHERE platform_type = 'windows'",
-    )
-    .fetch_one(&pool)
-    .await
-    .map_err(|e| format!("获取Windows模板数失败: {}", e))?
-    .unwrap_or(0);
-
-    let stats = serde_json::json!({
-        "total": total_count,
-        "builtin": builtin_count,
-        "custom": custom_count,
-        "claude": claude_count,
-        "codex": codex_count,
-        "unix": unix_count,
-        "windows": windows_count
-    });
+// 模板参数相关命令
 
-    Ok(ApiResponse::success(stats))
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn set_mcp_template_values(
+    state: State<'_, AppState>,
+    template_id: i64,
+    values: HashMap<String, String>,
+) -> CommandResult<Vec<McpTemplateValueView>> {
+    state.core.templates().set_values(template_id, values).await
 }
 
 #[tauri::command]
-pub async fn import_mcp_templates(
+#[tracing::instrument(skip_all, err)]
+pub async fn get_mcp_template_values(
     state: State<'_, AppState>,
-    templates: Vec<CreateMcpTemplateRequest>,
-) -> Result<ApiResponse<Vec<McpTemplate>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
+    template_id: i64,
+) -> CommandResult<Vec<McpTemplateValueView>> {
+    state.core.templates().values(template_id).await
+}
+
+/// 渲染模板配置，values 中的值优先于已保存的值，且不会被保存；
+/// platform_type 默认为当前平台
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn render_mcp_template(
+    state: State<'_, AppState>,
+    template_id: i64,
+    values: Option<HashMap<String, String>>,
+    platform_type: Option<String>,
+) -> CommandResult<String> {
+    state
+        .core
+        .templates()
+        .render(template_id, values.unwrap_or_default(), platform_type)
+        .await
+}
+
+// 模板转换相关命令
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn convert_mcp_config(
+    content: String,
+    from_ai_type: String,
+    to_ai_type: String,
+    server_name: Option<String>,
+) -> CommandResult<ConversionResult> {
+    let fallback_id = server_name.unwrap_or_else(|| "server".to_string());
+    match TemplateConverter::convert(&content, &from_ai_type, &to_ai_type, &fallback_id) {
+        Ok(result) => Ok(ApiResponse::success(result)),
+        Err(e) => Ok(AppError::validation(e.to_string())
+            .context(t!("template.convert_failed"))
+            .into()),
+    }
+}
+
+/// 为模板生成另一种AI类型的对应模板，名称、版本、平台和参数保持不变
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn create_counterpart_template(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> CommandResult<McpTemplateConversion> {
+    state.core.templates().create_counterpart(template_id).await
+}
+
+// 从现有配置导入相关命令
+
+/// 扫描 Claude Code、Claude Desktop、Codex 和指定项目中的MCP服务器，只返回预览，
+/// 用户选中的候选项再通过 import_mcp_templates 导入
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn scan_mcp_import_sources(
+    state: State<'_, AppState>,
+    project_dirs: Option<Vec<String>>,
+) -> CommandResult<McpImportPreview> {
+    let pool = state.core.pool().clone();
+
+    let paths = match ImportPaths::detect() {
+        Ok(paths) => paths,
+        Err(e) => return Ok(AppError::from(e).into()),
     };
-    let mut created_templates = Vec::new();
-    let mut errors = Vec::new();
-
-    for request in templates.into_iter() {
-        // 验证模板
-        let template = McpTemplate {
-            id: None,
-            name: request.name.clone(),
-            version: request
-                .version
-                .clone()
-                .unwrap_or_else(|| "1.0.0".to_string()),
-            ai_type: request.ai_type.clone(),
-            platform_type: request.platform_type.clone(),
-            config_content: request.config_content.clone(),
-            description: request.description.clone(),
-            is_builtin: Some(0),
-            category: request.category.clone(),
-            tags: request
-                .tags
-                .as_ref()
-                .map(|tags| serde_json::to_string(tags).unwrap_or_default()),
-            usage_count: Some(0),
-            created_at: None,
-            updated_at: None,
-        };
-
-        let validation_result = template.validate_config();
-        if !validation_result.valid {
-            errors.push(format!(
-                "模板 '{}' 验证失败: {}",
-                template.name,
-                validation_result.errors.join("; ")
-            ));
-            continue;
+    let project_dirs: Vec<PathBuf> = project_dirs
+        .unwrap_or_default()
+        .into_iter()
+        .map(PathBuf::from)
+        .collect();
+
+    let mut preview = ConfigImporter::scan(&paths, &project_dirs);
+
+    let existing = McpTemplate::get_all(&pool)
+        .await
+        .context(t!("template.load_failed"))?;
+    ConfigImporter::mark_duplicates(&mut preview.candidates, &existing);
+
+    Ok(ApiResponse::success(preview))
+}
+
+// 安装命令相关命令
+
+/// 解析 `claude mcp add` / `codex mcp add` 命令行，返回验证过的两种AI类型的模板请求，不写入数据库
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn parse_mcp_add_command(command_line: String) -> CommandResult<McpCommandParseResult> {
+    let mut result = match McpCommandLine::parse(&command_line) {
+        Ok(result) => result,
+        Err(e) => {
+            return Ok(AppError::validation(e)
+                .context(t!("template.command_parse_failed"))
+                .into())
         }
+    };
 
-        match McpTemplate::create(&pool, request).await {
-            Ok(created) => created_templates.push(created),
-            Err(e) => errors.push(format!("导入模板 '{}' 失败: {}", template.name, e)),
+    for request in &result.templates {
+        let validation_result = McpTemplate::from_request(request).validate_config();
+        if let Some(e) = validation_result.to_error() {
+            return Ok(e
+                .context(t!(
+                    "template.ai_type_validation_failed",
+                    ai_type = request.ai_type
+                ))
+                .into());
+        }
+        for warning in validation_result.warnings {
+            if !result.warnings.contains(&warning) {
+                result.warnings.push(warning);
+            }
         }
     }
 
-    if !errors.is_empty() {
-        return Ok(ApiResponse::error(format!(
-            "导入过程中发生错误: {}",
-            errors.join("; ")
-        )));
-    }
+    Ok(ApiResponse::success(result))
+}
 
-    Ok(ApiResponse::success(created_templates))
+/// 把模板导出为安装命令，cli 默认与模板的AI类型相同，指定 platform_type 时导出该平台的配置
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn export_mcp_template_command(
+    state: State<'_, AppState>,
+    template_id: i64,
+    cli: Option<String>,
+    platform_type: Option<String>,
+) -> CommandResult<ConversionResult> {
+    state
+        .core
+        .templates()
+        .export_command(template_id, cli, platform_type)
+        .await
 }
 
+// 握手测试相关命令
+
+/// 启动或连接模板中的服务器，完成 MCP 握手并读取工具列表；成功时缓存发现的工具。
+/// server_id 为空时测试第一个服务器，握手失败时返回的结果中 success 为 false
 #[tauri::command]
-pub async fn export_mcp_templates(
+#[tracing::instrument(skip_all, err)]
+pub async fn test_mcp_template(
     state: State<'_, AppState>,
-) -> Result<ApiResponse<Vec<McpTemplate>>, String> {
-    let pool = {
-        let guard = state.db_pool.lock().await;
-        guard.clone()
-    };
+    template_id: i64,
+    server_id: Option<String>,
+    values: Option<HashMap<String, String>>,
+    timeout_ms: Option<u64>,
+) -> CommandResult<McpProbeResult> {
+    state
+        .core
+        .templates()
+        .test(
+            template_id,
+            server_id,
+            values.unwrap_or_default(),
+            timeout_ms,
+        )
+        .await
+}
 
-    let templates = McpTemplate::get_all(&pool)
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn get_mcp_template_tools(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> CommandResult<Vec<McpTemplateTools>> {
+    let pool = state.core.pool().clone();
+
+    let tools = McpTemplateTools::get_by_template(&pool, template_id)
         .await
-        .map_err(|e| format!("导出MCP模板失败: {}", e))?;
+        .context(t!("template.tools_load_failed"))?;
 
-    Ok(ApiResponse::success(templates))
+    Ok(ApiResponse::success(tools))
+}
+
+// 修订历史相关命令
+
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn list_mcp_template_revisions(
+    state: State<'_, AppState>,
+    template_id: i64,
+) -> CommandResult<Vec<McpTemplateRevision>> {
+    let pool = state.core.pool().clone();
+
+    let revisions = McpTemplateRevision::get_by_template(&pool, template_id)
+        .await
+        .context(t!("template.revisions_load_failed"))?;
+
+    Ok(ApiResponse::success(revisions))
+}
+
+/// 比较两个修订，to_revision 为空时与最新修订比较
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn diff_mcp_template_revisions(
+    state: State<'_, AppState>,
+    template_id: i64,
+    from_revision: i64,
+    to_revision: Option<i64>,
+) -> CommandResult<McpTemplateRevisionDiff> {
+    state
+        .core
+        .templates()
+        .diff_revisions(template_id, from_revision, to_revision)
+        .await
+}
+
+/// 把模板恢复到指定修订，恢复后生成新的修订并升级版本号
+#[tauri::command]
+#[tracing::instrument(skip_all, err)]
+pub async fn revert_mcp_template(
+    state: State<'_, AppState>,
+    template_id: i64,
+    revision: i64,
+    note: Option<String>,
+) -> CommandResult<McpTemplate> {
+    state
+        .core
+        .templates()
+        .revert(template_id, revision, note)
+        .await
 }
@@ -1,136 +1,67 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::path::{Path, PathBuf};
-use std::sync::Arc;
-use tokio::sync::Mutex;
-use url::Url;
-
 // Modules
 mod commands;
-mod models;
-mod services;
+
+// 模型和服务在 ai-tools-core 中，命令通过 crate:: 路径引用
+use ai_tools_core::{models, services, t, utils, AppCore};
 
 // Commands
 use commands::*;
 
 // 应用状态
 use commands::supplier::AppState;
-
-fn ensure_writable_dir(dir: &Path) -> bool {
-    if let Err(err) = std::fs::create_dir_all(dir) {
-        println!("无法创建目录 {:?}: {:?}", dir, err);
-        return false;
-    }
-
-    let test_file = dir.join(".write_test");
-    match std::fs::write(&test_file, "test") {
-        Ok(_) => {
-            let _ = std::fs::remove_file(&test_file);
-            true
-        }
-        Err(err) => {
-            println!("目录 {:?} 不可写: {:?}", dir, err);
-            false
-        }
-    }
-}
-
-/// 获取应用数据目录，优先使用用户主目录下的 .ai-tools
-fn get_app_data_dir() -> PathBuf {
-    // 优先级1: 用户主目录
-    if let Some(home_dir) = dirs::home_dir() {
-        let home_dir = home_dir.join(".ai-tools");
-        println!("尝试主目录路径: {:?}", home_dir);
-        if ensure_writable_dir(&home_dir) {
-            println!("使用主目录路径: {:?}", home_dir);
-            return home_dir;
-        }
-    }
-
-    let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-
-    // 优先级2: 项目目录 data
-    let data_dir = current_dir.join("data");
-    println!("尝试项目 data 目录: {:?}", data_dir);
-    if ensure_writable_dir(&data_dir) {
-        println!("使用项目 data 目录: {:?}", data_dir);
-        return data_dir;
-    }
-
-    // 优先级3: 系统数据目录
-    if let Some(data_dir) = dirs::data_dir() {
-        let app_dir = data_dir.join("ai-tools-client");
-        println!("尝试系统数据目录: {:?}", app_dir);
-        if ensure_writable_dir(&app_dir) {
-            println!("使用系统数据目录: {:?}", app_dir);
-            return app_dir;
-        }
-    }
-
-    // 优先级4: 临时目录
-    let temp_dir = std::env::temp_dir();
-    let app_dir = temp_dir.join("ai-tools-client");
-    println!("尝试临时目录: {:?}", app_dir);
-    if ensure_writable_dir(&app_dir) {
-        println!("使用临时目录: {:?}", app_dir);
-        return app_dir;
-    }
-
-    // 如果都失败，使用当前目录
-    println!("所有路径都失败，使用当前目录");
-    PathBuf::from(".")
-}
-
-/// 根据数据库文件路径生成 sqlx 可识别的 sqlite 连接字符串
-fn build_sqlite_url(db_path: &Path) -> String {
-    if db_path.is_absolute() {
-        if let Ok(url) = Url::from_file_path(db_path) {
-            return url.as_str().replacen("file://", "sqlite://", 1);
-        }
-    }
-
-    let normalized = db_path.to_string_lossy().replace('\\', "/");
-    format!("sqlite://{}", normalized)
-}
+use services::data_dir::{
+    build_sqlite_url, DataDirResolver, StorageHealth, StorageStatus, DB_FILE_NAME,
+};
 
 #[tokio::main]
 async fn main() {
-    // 初始化数据库 - 使用兼容 AppImage 的路径策略
-    let app_data_dir = get_app_data_dir();
-
-    let db_path = app_data_dir.join("ai-tools.db");
-    println!("数据库文件路径: {:?}", db_path);
+    // 初始化数据库 - 命令行参数和环境变量优先，其次是兼容 AppImage 的默认路径
+    let selection = DataDirResolver::from_environment().resolve();
+    let app_data_dir = selection.dir.clone();
+
+    // 日志写入数据目录，guard 需要保持到程序退出
+    let _log_guard = utils::logger::init(&utils::logger::log_dir(&app_data_dir));
+    tracing::info!(
+        source = ?selection.source,
+        fallback_reasons = ?selection.fallback_reasons,
+        "使用数据目录: {:?}",
+        app_data_dir
+    );
+
+    let db_path = app_data_dir.join(DB_FILE_NAME);
+    tracing::info!("数据库文件路径: {:?}", db_path);
 
     let db_url = build_sqlite_url(&db_path);
-    println!("数据库连接URL: {}", db_url);
+    tracing::debug!("数据库连接URL: {}", db_url);
 
     // 首先测试 SQLite 库是否正常工作
-    println!("测试内存数据库连接...");
+    tracing::debug!("测试内存数据库连接...");
     match services::database::Database::new("sqlite::memory:").await {
         Ok(_) => {
-            println!("内存数据库连接成功，SQLite 库正常");
+            tracing::debug!("内存数据库连接成功，SQLite 库正常");
         }
         Err(e) => {
-            println!("内存数据库连接失败: {:?}", e);
-            println!("可能是 SQLite 库兼容性问题");
+            tracing::error!("内存数据库连接失败，可能是 SQLite 库兼容性问题: {:?}", e);
         }
     }
 
     // 创建数据库连接池，仅使用经过验证的持久化路径
-    let database = match services::database::Database::new(&db_url).await {
+    let (database, db_error) = match services::database::Database::new(&db_url).await {
         Ok(db) => {
-            println!("持久化数据库初始化成功: {}", db_url);
-            db
+            tracing::info!("持久化数据库初始化成功: {}", db_url);
+            (db, None)
         }
         Err(file_db_err) => {
-            println!("持久化数据库初始化失败: {:?}", file_db_err);
-            println!("使用内存数据库作为最后备选方案（数据不会持久化）");
+            tracing::error!("持久化数据库初始化失败: {:?}", file_db_err);
+            tracing::warn!("使用内存数据库作为最后备选方案（数据不会持久化）");
 
             match services::database::Database::new("sqlite::memory:").await {
                 Ok(db) => {
-                    println!("内存数据库初始化成功");
-                    db
+                    tracing::info!("内存数据库初始化成功");
+                    (db, Some(file_db_err.to_string()))
                 }
                 Err(memory_err) => {
                     panic!(
@@ -142,9 +73,21 @@ async fn main() {
         }
     };
 
-    let app_state = AppState {
-        db_pool: Arc::new(Mutex::new(database.pool.clone())),
-    };
+    // 读取保存的语言和日志级别，同步内置MCP模板
+    let core = AppCore::new(database.pool.clone(), app_data_dir.clone());
+    core.prepare().await;
+
+    // 按快照策略定期备份数据库文件
+    tokio::spawn(
+        services::backup::SnapshotStore::new(&app_data_dir).run_periodically(database.pool.clone()),
+    );
+
+    let storage = StorageStatus::new(&selection, db_error);
+    if storage.health != StorageHealth::Ok {
+        tracing::warn!("数据存储状态异常: {}", storage.message);
+    }
+
+    let app_state = AppState { core, storage };
 
     tauri::Builder::default()
         .manage(app_state)
@@ -168,7 +111,16 @@ async fn main() {
             auto_failover,
             get_failover_config,
             update_failover_config,
+            get_failover_chain,
+            set_failover_chain,
+            explain_supplier_scores,
             get_supplier_switch_progress,
+            // Switch hook commands
+            list_switch_hooks,
+            create_switch_hook,
+            update_switch_hook,
+            delete_switch_hook,
+            get_switch_history,
             // MCP Template commands
             list_mcp_templates,
             create_mcp_template,
@@ -177,11 +129,26 @@ async fn main() {
             get_mcp_template_by_id,
             validate_mcp_template,
             get_mcp_template_categories,
+            save_mcp_template_category,
+            delete_mcp_template_category,
             increment_template_usage,
             clone_mcp_template,
             get_mcp_template_stats,
             import_mcp_templates,
             export_mcp_templates,
+            set_mcp_template_values,
+            get_mcp_template_values,
+            render_mcp_template,
+            convert_mcp_config,
+            create_counterpart_template,
+            scan_mcp_import_sources,
+            parse_mcp_add_command,
+            export_mcp_template_command,
+            test_mcp_template,
+            get_mcp_template_tools,
+            list_mcp_template_revisions,
+            diff_mcp_template_revisions,
+            revert_mcp_template,
             // Config commands
             backup_config,
             get_config_history,
@@ -193,6 +160,7 @@ async fn main() {
             get_work_mode_by_name,
             list_work_mode_configs,
             update_work_mode_by_id,
+            get_work_mode_templates,
             switch_work_mode,
             get_work_mode_status,
             rollback_work_mode,
@@ -204,7 +172,20 @@ async fn main() {
             // Database commands
             get_database_stats,
             test_database_connection,
+            get_schema_versions,
             export_all_data,
+            import_all_data,
+            create_database_snapshot,
+            list_database_snapshots,
+            get_snapshot_policy,
+            save_snapshot_policy,
+            get_storage_status,
+            move_data_directory,
+            get_locale,
+            set_locale,
+            get_log_settings,
+            set_log_level,
+            get_recent_logs,
         ])
         .setup(|_app| {
             // 数据库已在上面初始化
@@ -1,4 +0,0 @@
-// 服务模块声明
-pub mod database;
-pub mod file_ops;
-pub mod platform;
@@ -1,5 +1,7 @@
 import zhCN from './locales/zh-CN'
 import enUS from './locales/en-US'
+import { TauriHelper } from '@/utils/tauriHelper'
+import type { ApiResponse } from '@/types'
 
 // 简单的国际化实现
 export type Locale = 'zh-CN' | 'en-US'
@@ -30,6 +32,18 @@ class I18n {
     this.currentLocale = locale
     localStorage.setItem('locale', locale)
     document.documentElement.lang = locale
+    void this.syncBackendLocale()
+  }
+
+  // 让后端消息和验证错误使用相同的语言
+  async syncBackendLocale() {
+    try {
+      await TauriHelper.invokeTauri<ApiResponse<string>>('set_locale', {
+        locale: this.currentLocale
+      })
+    } catch (error) {
+      console.warn('同步后端语言失败:', error)
+    }
   }
 
   // 获取当前语言
@@ -10,6 +10,7 @@ import * as ElementPlusIconsVue from '@element-plus/icons-vue'
 
 import App from './App.vue'
 import router from './router'
+import { i18n } from './i18n'
 
 const app = createApp(App)
 
@@ -22,4 +23,7 @@ app.use(createPinia())
 app.use(router)
 app.use(ElementPlus)
 
+// 后端消息使用与界面相同的语言
+void i18n.syncBackendLocale()
+
 app.mount('#app')
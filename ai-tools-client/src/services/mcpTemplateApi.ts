@@ -4,7 +4,11 @@ import type {
   UpdateMcpTemplateRequest,
   McpTemplateValidationResult,
   McpTemplateCategory,
-  ApiResponse
+  McpTemplatePage,
+  McpTemplateQuery,
+  ApiResponse,
+  ConflictStrategy,
+  ImportReport
 } from '@/types'
 import '@/types/tauri'
 import { TauriHelper } from '@/utils/tauriHelper'
@@ -12,12 +16,17 @@ import { TauriHelper } from '@/utils/tauriHelper'
 class McpTemplateApiService {
   // 获取MCP模板列表
   async listMcpTemplates(aiType?: string, platformType?: string): Promise<McpTemplate[]> {
+    const page = await this.searchMcpTemplates({ ai_type: aiType, platform_type: platformType })
+    return page.items
+  }
+
+  // 按关键字、标签、分类等条件分页查询MCP模板
+  async searchMcpTemplates(query: McpTemplateQuery): Promise<McpTemplatePage> {
     try {
-      const result = await TauriHelper.invokeTauri<ApiResponse<McpTemplate[]>>('list_mcp_templates', {
-        aiType,
-        platformType
+      const result = await TauriHelper.invokeTauri<ApiResponse<McpTemplatePage>>('list_mcp_templates', {
+        query
       })
-      return result.data || []
+      return result.data || { items: [], total: 0, page: 1 }
     } catch (error) {
       console.error('获取MCP模板列表失败:', error)
       throw error
@@ -139,13 +148,19 @@ class McpTemplateApiService {
   }
 
   // 导入MCP模板
-  async importMcpTemplates(templates: CreateMcpTemplateRequest[]): Promise<McpTemplate[]> {
+  async importMcpTemplates(
+    templates: CreateMcpTemplateRequest[],
+    strategy: ConflictStrategy = 'skip',
+    dryRun = false
+  ): Promise<ImportReport> {
     try {
-      const result = await TauriHelper.invokeTauri<ApiResponse<McpTemplate[]>>('import_mcp_templates', {
-        templates
+      const result = await TauriHelper.invokeTauri<ApiResponse<ImportReport>>('import_mcp_templates', {
+        templates,
+        strategy,
+        dryRun
       })
-      if (result.success) {
-        return result.data!
+      if (result.success && result.data) {
+        return result.data
       } else {
         throw new Error(result.message || '导入MCP模板失败')
       }
@@ -155,6 +170,7 @@ class McpTemplateApiService {
     }
   }
 
+
   // 导出MCP模板
   async exportMcpTemplates(): Promise<McpTemplate[]> {
     try {
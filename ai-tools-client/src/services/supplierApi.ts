@@ -10,7 +10,9 @@ import type {
   FailoverConfig,
   SupplierPerformanceMetrics,
   SupplierSwitchHistory,
-  ApiResponse
+  ApiResponse,
+  ConflictStrategy,
+  ImportReport
 } from '@/types'
 import '@/types/tauri'
 import { TauriHelper } from '@/utils/tauriHelper'
@@ -138,13 +140,19 @@ class SupplierApiService {
   }
 
   // 导入供应商
-  async importSuppliers(suppliers: CreateSupplierRequest[]): Promise<Supplier[]> {
+  async importSuppliers(
+    suppliers: CreateSupplierRequest[],
+    strategy: ConflictStrategy = 'skip',
+    dryRun = false
+  ): Promise<ImportReport> {
     try {
-      const result = await TauriHelper.invokeTauri<ApiResponse<Supplier[]>>('import_suppliers', {
-        suppliers
+      const result = await TauriHelper.invokeTauri<ApiResponse<ImportReport>>('import_suppliers', {
+        suppliers,
+        strategy,
+        dryRun
       })
-      if (result.success) {
-        return result.data!
+      if (result.success && result.data) {
+        return result.data
       } else {
         throw new Error(result.message || '导入供应商失败')
       }
@@ -154,6 +162,7 @@ class SupplierApiService {
     }
   }
 
+
   // 导出供应商
   async exportSuppliers(): Promise<Supplier[]> {
     try {
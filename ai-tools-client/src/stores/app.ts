@@ -1,5 +1,7 @@
 import { defineStore } from 'pinia'
 import { ref, computed } from 'vue'
+import { TauriHelper } from '@/utils/tauriHelper'
+import type { ApiResponse, MoveDataDirResult, StorageStatus } from '@/types'
 
 export const useAppStore = defineStore('app', () => {
   // 状态
@@ -7,9 +9,13 @@ export const useAppStore = defineStore('app', () => {
   const error = ref<string | null>(null)
   const currentPlatform = ref<string>('')
   const isTauriApp = ref(false)
+  const storageStatus = ref<StorageStatus | null>(null)
 
   // 计算属性
   const isReady = computed(() => !isLoading.value && !error.value)
+  const storageWarning = computed(() =>
+    storageStatus.value && storageStatus.value.health !== 'ok' ? storageStatus.value : null
+  )
 
   // 操作
   const setLoading = (loading: boolean) => {
@@ -32,6 +38,25 @@ export const useAppStore = defineStore('app', () => {
     isTauriApp.value = isTauri
   }
 
+  // 读取数据存储状态，使用内存数据库或临时目录时需要在界面上提示
+  const loadStorageStatus = async () => {
+    try {
+      const result = await TauriHelper.invokeTauri<ApiResponse<StorageStatus>>('get_storage_status')
+      storageStatus.value = result.success && result.data ? result.data : null
+    } catch (error) {
+      console.warn('获取数据存储状态失败:', error)
+    }
+  }
+
+  // 迁移数据目录，完成后旧数据库停止写入，刷新存储状态以提示用户重启
+  const moveDataDirectory = async (targetDir: string) => {
+    const result = await TauriHelper.invokeTauri<ApiResponse<MoveDataDirResult>>('move_data_directory', {
+      targetDir,
+    })
+    await loadStorageStatus()
+    return result
+  }
+
   // 初始化应用
   const initializeApp = async () => {
     try {
@@ -44,6 +69,7 @@ export const useAppStore = defineStore('app', () => {
 
         // 获取平台信息 - 暂时使用浏览器平台信息
         setCurrentPlatform(navigator.platform)
+        await loadStorageStatus()
       } else {
         setTauriApp(false)
         setCurrentPlatform(navigator.platform)
@@ -61,9 +87,11 @@ export const useAppStore = defineStore('app', () => {
     error,
     currentPlatform,
     isTauriApp,
+    storageStatus,
 
     // 计算属性
     isReady,
+    storageWarning,
 
     // 操作
     setLoading,
@@ -72,5 +100,7 @@ export const useAppStore = defineStore('app', () => {
     setCurrentPlatform,
     setTauriApp,
     initializeApp,
+    loadStorageStatus,
+    moveDataDirectory,
   }
 })
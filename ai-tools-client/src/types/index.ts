@@ -3,8 +3,23 @@ export interface ApiResponse<T = unknown> {
   success: boolean
   data?: T
   message?: string
+  // 失败时的错误码，取值见 ErrorCode
+  code?: ErrorCode
+  // 失败时的结构化详情，例如 { errors: string[] } 或 { resource, id }
+  details?: Record<string, unknown>
 }
 
+// 后端返回的稳定错误码
+export type ErrorCode =
+  | 'NOT_FOUND'
+  | 'VALIDATION_FAILED'
+  | 'CONFLICT'
+  | 'PRECONDITION_FAILED'
+  | 'DB_BUSY'
+  | 'DB_ERROR'
+  | 'IO_ERROR'
+  | 'INTERNAL'
+
 // 分页相关
 export interface PaginationParams {
   page: number
@@ -27,6 +42,71 @@ export interface OperationResult {
 }
 
 // 配置相关类型
+// 导入冲突处理方式
+export type ConflictStrategy = 'skip' | 'overwrite' | 'rename' | 'bump_version'
+
+// 导入结果，逐项列出处理方式
+export interface ImportItemResult {
+  index: number
+  name: string
+  action: 'created' | 'updated' | 'skipped' | 'failed'
+  id?: number
+  message?: string
+}
+
+export interface ImportReport {
+  dry_run: boolean
+  committed: boolean
+  created: number
+  updated: number
+  skipped: number
+  failed: number
+  items: ImportItemResult[]
+}
+
+// 数据存储状态，critical 表示正在使用内存数据库
+export interface StorageStatus {
+  health: 'ok' | 'degraded' | 'critical'
+  message: string
+  data_dir: string
+  db_path: string
+  source: 'cli_arg' | 'env_var' | 'saved' | 'home' | 'project_data' | 'system_data' | 'temp' | 'current_dir'
+  fallback_reasons: string[]
+  in_memory: boolean
+  db_error?: string
+  pending_data_dir?: string
+  // 数据目录已迁移，当前数据库不再接受写入
+  restart_required: boolean
+  // 数据目录由命令行参数或环境变量指定，已保存的目录重启后不会生效
+  pending_overridden: boolean
+}
+
+export interface MoveDataDirResult {
+  from: string
+  to: string
+  copied_files: number
+  restart_required: boolean
+}
+
+export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error'
+
+export interface LogSettings {
+  level: LogLevel
+  // 由环境变量指定日志级别时，界面上的修改在重启后不会生效
+  env_override?: string
+  log_dir: string
+  max_files: number
+}
+
+export interface LogEntry {
+  timestamp?: string
+  level: string
+  target?: string
+  message: string
+  fields: Record<string, any>
+  spans: string[]
+}
+
 export type PlatformType = 'windows' | 'macos' | 'linux' | 'unix'
 export type AiType = 'claude' | 'codex'
 export type WorkMode = 'claude_only' | 'codex_only' | 'claude_codex'
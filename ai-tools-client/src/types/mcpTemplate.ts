@@ -56,4 +56,25 @@ export interface McpTemplateCategory {
   name: string
   count: number
   description?: string
+  sort_order: number
+}
+
+// MCP模板列表查询条件
+export interface McpTemplateQuery {
+  ai_type?: string
+  platform_type?: string
+  keyword?: string
+  tags?: string[]
+  category?: string
+  is_builtin?: boolean
+  sort_by?: 'default' | 'relevance' | 'usage' | 'recent' | 'name'
+  page?: number
+  page_size?: number
+}
+
+export interface McpTemplatePage {
+  items: McpTemplate[]
+  total: number
+  page: number
+  page_size?: number
 }
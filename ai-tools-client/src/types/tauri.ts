@@ -10,21 +10,23 @@ export interface TauriCommands {
   test_supplier_connection: (params: { supplier: any }) => Promise<any>
   validate_supplier_config: (params: { supplier: any }) => Promise<any>
   get_supplier_stats: () => Promise<any>
-  import_suppliers: (params: { suppliers: any[] }) => Promise<any>
+  import_suppliers: (params: { suppliers: any[]; strategy?: string; dryRun?: boolean }) => Promise<any>
   export_suppliers: () => Promise<any>
 
   // MCP模板相关命令
-  list_mcp_templates: (params?: { aiType?: string; platformType?: string }) => Promise<any>
+  list_mcp_templates: (params?: { query?: any }) => Promise<any>
   create_mcp_template: (params: any) => Promise<any>
   update_mcp_template: (params: any) => Promise<any>
   delete_mcp_template: (params: { id: number }) => Promise<any>
   get_mcp_template_by_id: (params: { id: number }) => Promise<any>
   validate_mcp_template: (params: any) => Promise<any>
   get_mcp_template_categories: () => Promise<any>
+  save_mcp_template_category: (params: { name: string; description?: string; sortOrder?: number }) => Promise<any>
+  delete_mcp_template_category: (params: { name: string }) => Promise<any>
   increment_template_usage: (params: { id: number }) => Promise<any>
   clone_mcp_template: (params: { id: number; newName: string }) => Promise<any>
   get_mcp_template_stats: () => Promise<any>
-  import_mcp_templates: (params: { templates: any[] }) => Promise<any>
+  import_mcp_templates: (params: { templates: any[]; strategy?: string; dryRun?: boolean }) => Promise<any>
   export_mcp_templates: () => Promise<any>
 
   // 配置相关命令
@@ -56,8 +58,21 @@ export interface TauriCommands {
 
   // 数据库相关命令
   get_database_stats: () => Promise<any>
+  get_schema_versions: () => Promise<any>
   test_database_connection: () => Promise<any>
   export_all_data: () => Promise<any>
+  import_all_data: (args: { data: any; mode?: 'replace' | 'merge' }) => Promise<any>
+  create_database_snapshot: () => Promise<any>
+  list_database_snapshots: () => Promise<any>
+  get_snapshot_policy: () => Promise<any>
+  save_snapshot_policy: (args: { policy: { enabled: boolean; interval_hours: number; keep: number } }) => Promise<any>
+  get_storage_status: () => Promise<any>
+  move_data_directory: (args: { targetDir: string }) => Promise<any>
+
+  // 日志相关命令
+  get_log_settings: () => Promise<any>
+  set_log_level: (args: { level: string }) => Promise<any>
+  get_recent_logs: (args: { limit?: number; minLevel?: string }) => Promise<any>
 
   // 配置文件应用命令
   apply_config_file: (params: {
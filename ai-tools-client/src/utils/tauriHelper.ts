@@ -1,4 +1,5 @@
 // Tauri环境检测和模拟数据工具
+import type { ApiResponse } from '@/types'
 export class TauriHelper {
   static isTauriEnvironment(): boolean {
     return typeof window !== 'undefined' && !!window.__TAURI__
@@ -9,8 +10,25 @@ export class TauriHelper {
       // 开发模式下的模拟数据
       return this.getMockData<T>(command, args)
     }
-    // 类型断言以绕过 TypeScript 检查
-    return (window.__TAURI__.invoke as any)(command, args) as Promise<T>
+    try {
+      // 类型断言以绕过 TypeScript 检查
+      return await ((window.__TAURI__.invoke as any)(command, args) as Promise<T>)
+    } catch (error) {
+      // 命令失败时后端返回与 ApiResponse 相同结构的错误，按普通的失败响应处理
+      if (this.isApiError(error)) {
+        return error as T
+      }
+      throw error
+    }
+  }
+
+  static isApiError(error: unknown): error is ApiResponse {
+    return (
+      typeof error === 'object' &&
+      error !== null &&
+      (error as ApiResponse).success === false &&
+      typeof (error as ApiResponse).code === 'string'
+    )
   }
 
   // 开发模式数据持久化
@@ -145,9 +163,10 @@ export class TauriHelper {
         this.saveToStorage(storageKey, templates)
       }
 
+      const items = templates as unknown as any[]
       return {
         success: true,
-        data: templates
+        data: { items, total: items.length, page: 1 }
       } as T
     }
 